@@ -0,0 +1,72 @@
+-- Roles are rows now instead of hard-coded strings. Built-in roles are
+-- marked is_system and cannot be deleted; `admin` cannot be edited either.
+CREATE TABLE roles (
+    name TEXT PRIMARY KEY,
+    description TEXT NOT NULL DEFAULT '',
+    is_system BOOLEAN NOT NULL DEFAULT FALSE,
+    created_at TIMESTAMPTZ NOT NULL DEFAULT now()
+);
+
+-- Must match `models::permission::Permission`
+CREATE TABLE permissions (
+    name TEXT PRIMARY KEY,
+    description TEXT NOT NULL
+);
+
+CREATE TABLE role_permissions (
+    role TEXT NOT NULL REFERENCES roles(name) ON UPDATE CASCADE ON DELETE CASCADE,
+    permission TEXT NOT NULL REFERENCES permissions(name) ON DELETE CASCADE,
+    PRIMARY KEY (role, permission)
+);
+
+INSERT INTO permissions (name, description) VALUES
+    ('tickets.create',        'Open new tickets'),
+    ('tickets.read_own',      'See tickets where you are the customer'),
+    ('tickets.read_assigned', 'See tickets assigned to you'),
+    ('tickets.read_all',      'See every ticket'),
+    ('tickets.update_own',    'Edit tickets where you are the customer'),
+    ('tickets.update',        'Edit any ticket you can see'),
+    ('tickets.delete',        'Delete tickets'),
+    ('tickets.assign',        'Assign tickets to agents'),
+    ('notes.read',            'Read internal notes and their comments'),
+    ('notes.write',           'Write internal notes and comments'),
+    ('kb.manage',             'Create, edit and delete knowledge base content'),
+    ('reports.view',          'View analytics and reports'),
+    ('users.manage',          'Invite staff, change user roles, manage lockouts and API keys'),
+    ('roles.manage',          'Define roles and their permissions'),
+    ('settings.manage',       'Change security settings such as mandatory 2FA');
+
+INSERT INTO roles (name, description, is_system) VALUES
+    ('user',  'Customer', TRUE),
+    ('agent', 'Support agent', TRUE),
+    ('admin', 'Administrator with every permission', TRUE);
+
+INSERT INTO role_permissions (role, permission) VALUES
+    ('user', 'tickets.create'),
+    ('user', 'tickets.read_own'),
+    ('user', 'tickets.update_own'),
+    ('agent', 'tickets.create'),
+    ('agent', 'tickets.read_assigned'),
+    ('agent', 'tickets.update'),
+    ('agent', 'tickets.assign'),
+    ('agent', 'notes.read'),
+    ('agent', 'notes.write'),
+    ('agent', 'kb.manage');
+
+INSERT INTO role_permissions (role, permission)
+SELECT 'admin', name FROM permissions;
+
+-- Keep any role strings already in use so the foreign keys below hold
+INSERT INTO roles (name, description)
+SELECT DISTINCT role, 'Imported' FROM users
+WHERE role NOT IN (SELECT name FROM roles);
+
+INSERT INTO roles (name, description)
+SELECT DISTINCT role, 'Imported' FROM invitations
+WHERE role NOT IN (SELECT name FROM roles);
+
+ALTER TABLE users
+    ADD CONSTRAINT users_role_fkey FOREIGN KEY (role) REFERENCES roles(name) ON UPDATE CASCADE;
+
+ALTER TABLE invitations
+    ADD CONSTRAINT invitations_role_fkey FOREIGN KEY (role) REFERENCES roles(name) ON UPDATE CASCADE;
@@ -0,0 +1,29 @@
+-- Links between local users and accounts at an OpenID Connect provider
+CREATE TABLE user_identities (
+    provider TEXT NOT NULL,
+    subject TEXT NOT NULL,
+    user_id UUID NOT NULL REFERENCES users(id) ON DELETE CASCADE,
+    email TEXT,
+    created_at TIMESTAMPTZ NOT NULL DEFAULT now(),
+    last_login_at TIMESTAMPTZ,
+    PRIMARY KEY (provider, subject)
+);
+
+CREATE INDEX idx_user_identities_user_id ON user_identities(user_id);
+
+-- One row per SSO login attempt. The browser carries `state` to the IdP and
+-- back; once the callback succeeds a single-use handoff code lets the
+-- frontend collect the session. Only SHA-256 digests of both are stored.
+CREATE TABLE oidc_logins (
+    id UUID PRIMARY KEY DEFAULT gen_random_uuid(),
+    provider TEXT NOT NULL,
+    state_hash TEXT NOT NULL UNIQUE,
+    nonce TEXT NOT NULL,
+    code_verifier TEXT NOT NULL,
+    created_at TIMESTAMPTZ NOT NULL DEFAULT now(),
+    expires_at TIMESTAMPTZ NOT NULL,
+    user_id UUID REFERENCES users(id) ON DELETE CASCADE,
+    handoff_hash TEXT UNIQUE,
+    handoff_expires_at TIMESTAMPTZ,
+    redeemed_at TIMESTAMPTZ
+);
@@ -0,0 +1,20 @@
+-- Self-registered accounts start unverified; tickets are only matched to a
+-- user by customer_email once they've proven they own that address.
+ALTER TABLE users ADD COLUMN email_verified_at TIMESTAMPTZ;
+
+-- Existing accounts predate verification and keep their tickets
+UPDATE users SET email_verified_at = COALESCE(created_at, now());
+
+-- Single-use verification links (SHA-256 digests only). `email` is the
+-- address the link was sent to, so a link can't verify a changed address.
+CREATE TABLE email_verification_tokens (
+    id UUID PRIMARY KEY DEFAULT gen_random_uuid(),
+    user_id UUID NOT NULL REFERENCES users(id) ON DELETE CASCADE,
+    email TEXT NOT NULL,
+    token_hash TEXT NOT NULL UNIQUE,
+    created_at TIMESTAMPTZ NOT NULL DEFAULT now(),
+    expires_at TIMESTAMPTZ NOT NULL,
+    used_at TIMESTAMPTZ
+);
+
+CREATE INDEX idx_email_verification_tokens_user_id ON email_verification_tokens(user_id);
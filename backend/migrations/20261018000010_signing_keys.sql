@@ -0,0 +1,15 @@
+-- Asymmetric keys for signing access tokens, identified by `kid`.
+-- One key signs at a time; retired keys are still published and accepted
+-- until every token they signed has expired. The private key has to be
+-- usable to sign, so like TOTP secrets it is stored as-is (PKCS#8 PEM).
+CREATE TABLE signing_keys (
+    kid TEXT PRIMARY KEY,
+    algorithm TEXT NOT NULL CHECK (algorithm IN ('RS256', 'EdDSA')),
+    private_key TEXT NOT NULL,
+    public_jwk TEXT NOT NULL,
+    created_at TIMESTAMPTZ NOT NULL DEFAULT now(),
+    retired_at TIMESTAMPTZ
+);
+
+-- At most one key is signing
+CREATE UNIQUE INDEX idx_signing_keys_active ON signing_keys ((true)) WHERE retired_at IS NULL;
@@ -0,0 +1,7 @@
+-- Self-service profile fields
+ALTER TABLE users
+    ADD COLUMN timezone TEXT NOT NULL DEFAULT 'UTC',
+    ADD COLUMN avatar_url TEXT,
+    -- Set when an admin scrubs the account's personal data; the row stays
+    -- so tickets, notes and messages keep their author
+    ADD COLUMN anonymized_at TIMESTAMPTZ;
@@ -0,0 +1,9 @@
+-- Where each login came from, so users can recognise and revoke their devices.
+-- `ip_address` and `user_agent` are captured when the session starts;
+-- `last_seen_at` is bumped (at most once a minute) while it is in use.
+ALTER TABLE sessions
+    ADD COLUMN user_agent TEXT,
+    ADD COLUMN ip_address TEXT,
+    ADD COLUMN last_seen_at TIMESTAMPTZ NOT NULL DEFAULT now();
+
+UPDATE sessions SET last_seen_at = created_at;
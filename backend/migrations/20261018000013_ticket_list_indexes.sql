@@ -0,0 +1,17 @@
+-- Ticket lists are paged with keyset cursors on (sort column, id), which
+-- needs the timestamps to be set. Rows from before the defaults existed
+-- get the other timestamp, or now().
+UPDATE tickets SET created_at = COALESCE(updated_at, now()) WHERE created_at IS NULL;
+UPDATE tickets SET updated_at = created_at WHERE updated_at IS NULL;
+
+ALTER TABLE tickets
+    ALTER COLUMN created_at SET NOT NULL,
+    ALTER COLUMN updated_at SET NOT NULL;
+
+-- One per sort order, plus the scopes the three list endpoints always apply
+CREATE INDEX idx_tickets_created_at ON tickets(created_at, id);
+CREATE INDEX idx_tickets_updated_at ON tickets(updated_at, id);
+CREATE INDEX idx_tickets_priority ON tickets(priority, id);
+CREATE INDEX idx_tickets_assigned_to ON tickets(assigned_to, created_at, id);
+CREATE INDEX idx_tickets_customer_email ON tickets(customer_email, created_at, id);
+CREATE INDEX idx_tickets_status ON tickets(status, created_at, id);
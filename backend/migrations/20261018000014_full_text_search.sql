@@ -0,0 +1,19 @@
+-- Full-text search over tickets, messages and notes. Expression indexes
+-- rather than stored columns; `search_service` repeats these exact
+-- expressions so the planner can use them. Ticket subjects weigh more
+-- than any body text.
+CREATE INDEX idx_tickets_search ON tickets USING GIN ((
+    setweight(to_tsvector('english'::regconfig, subject), 'A')
+    || to_tsvector('english'::regconfig, COALESCE(description, ''))
+));
+
+CREATE INDEX idx_messages_search ON messages USING GIN (
+    to_tsvector('english'::regconfig, content)
+);
+
+CREATE INDEX idx_notes_search ON notes USING GIN (
+    to_tsvector('english'::regconfig, content)
+);
+
+CREATE INDEX IF NOT EXISTS idx_messages_ticket_id ON messages(ticket_id);
+CREATE INDEX IF NOT EXISTS idx_notes_ticket_id ON notes(ticket_id);
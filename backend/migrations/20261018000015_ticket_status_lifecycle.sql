@@ -0,0 +1,20 @@
+-- Ticket lifecycle: New -> Open -> Pending (waiting on the customer) / OnHold
+-- -> Resolved -> Closed. Allowed transitions are enforced in ticket_service.
+-- InProgress tickets are being worked on, which is now just Open.
+
+ALTER TYPE ticket_status RENAME TO ticket_status_old;
+
+CREATE TYPE ticket_status AS ENUM ('New', 'Open', 'Pending', 'OnHold', 'Resolved', 'Closed');
+
+ALTER TABLE tickets ALTER COLUMN status DROP DEFAULT;
+
+ALTER TABLE tickets ALTER COLUMN status TYPE ticket_status USING (
+    CASE status::TEXT
+        WHEN 'InProgress' THEN 'Open'
+        ELSE status::TEXT
+    END
+)::ticket_status;
+
+ALTER TABLE tickets ALTER COLUMN status SET DEFAULT 'New';
+
+DROP TYPE ticket_status_old;
@@ -0,0 +1,14 @@
+-- Audit trail of ticket changes, one row per changed field. Values are stored
+-- as text (status and priority names, assignee ids). `actor_id` is NULL when
+-- nobody in particular made the change or the user has since been deleted.
+CREATE TABLE ticket_events (
+    id UUID PRIMARY KEY DEFAULT gen_random_uuid(),
+    ticket_id UUID NOT NULL REFERENCES tickets(id) ON DELETE CASCADE,
+    actor_id UUID REFERENCES users(id) ON DELETE SET NULL,
+    field TEXT NOT NULL,
+    old_value TEXT,
+    new_value TEXT,
+    created_at TIMESTAMPTZ NOT NULL DEFAULT now()
+);
+
+CREATE INDEX idx_ticket_events_ticket_id_created_at ON ticket_events(ticket_id, created_at);
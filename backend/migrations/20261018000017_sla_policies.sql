@@ -0,0 +1,37 @@
+-- Response and resolution targets per priority. A policy with a
+-- customer_domain applies to tickets from that organization's email domain
+-- and takes precedence over the domain-less default for the same priority.
+CREATE TABLE sla_policies (
+    id UUID PRIMARY KEY DEFAULT gen_random_uuid(),
+    name TEXT NOT NULL,
+    priority ticket_priority NOT NULL,
+    customer_domain TEXT CHECK (customer_domain = lower(customer_domain)),
+    first_response_minutes INT NOT NULL CHECK (first_response_minutes > 0),
+    resolution_minutes INT NOT NULL CHECK (resolution_minutes > 0),
+    created_at TIMESTAMPTZ NOT NULL DEFAULT now(),
+    updated_at TIMESTAMPTZ NOT NULL DEFAULT now()
+);
+
+CREATE UNIQUE INDEX idx_sla_policies_priority_domain
+    ON sla_policies(priority, COALESCE(customer_domain, ''));
+
+-- Each ticket's SLA clock. Due times are set from the policy when the ticket
+-- is created (or its priority changes) and pushed back by the time the clock
+-- spends stopped (`sla_paused_at` is set while the ticket is Pending, Resolved
+-- or Closed). The breach checker fills in the *_breached_at columns.
+ALTER TABLE tickets
+    ADD COLUMN sla_policy_id UUID REFERENCES sla_policies(id) ON DELETE SET NULL,
+    ADD COLUMN first_response_due_at TIMESTAMPTZ,
+    ADD COLUMN first_responded_at TIMESTAMPTZ,
+    ADD COLUMN first_response_breached_at TIMESTAMPTZ,
+    ADD COLUMN resolution_due_at TIMESTAMPTZ,
+    ADD COLUMN resolved_at TIMESTAMPTZ,
+    ADD COLUMN resolution_breached_at TIMESTAMPTZ,
+    ADD COLUMN sla_paused_at TIMESTAMPTZ,
+    ADD COLUMN sla_paused_seconds BIGINT NOT NULL DEFAULT 0;
+
+-- What the breach checker scans for
+CREATE INDEX idx_tickets_first_response_due ON tickets(first_response_due_at)
+    WHERE first_responded_at IS NULL AND first_response_breached_at IS NULL;
+CREATE INDEX idx_tickets_resolution_due ON tickets(resolution_due_at)
+    WHERE resolved_at IS NULL AND resolution_breached_at IS NULL;
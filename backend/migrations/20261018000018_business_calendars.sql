@@ -0,0 +1,33 @@
+-- Business-hour calendars for timers that should only run while the support
+-- desk is open. Hours are local to the calendar's IANA time zone; a shift
+-- that closes at or before it opens runs past midnight. A holiday cancels the
+-- shifts that open on that date.
+CREATE TABLE business_calendars (
+    id UUID PRIMARY KEY DEFAULT gen_random_uuid(),
+    name TEXT NOT NULL UNIQUE,
+    timezone TEXT NOT NULL,
+    created_at TIMESTAMPTZ NOT NULL DEFAULT now(),
+    updated_at TIMESTAMPTZ NOT NULL DEFAULT now()
+);
+
+CREATE TABLE business_hours (
+    id UUID PRIMARY KEY DEFAULT gen_random_uuid(),
+    calendar_id UUID NOT NULL REFERENCES business_calendars(id) ON DELETE CASCADE,
+    -- 0 = Monday
+    weekday SMALLINT NOT NULL CHECK (weekday BETWEEN 0 AND 6),
+    opens_at TIME NOT NULL,
+    closes_at TIME NOT NULL
+);
+
+CREATE INDEX idx_business_hours_calendar ON business_hours(calendar_id);
+
+CREATE TABLE business_holidays (
+    calendar_id UUID NOT NULL REFERENCES business_calendars(id) ON DELETE CASCADE,
+    date DATE NOT NULL,
+    name TEXT NOT NULL,
+    PRIMARY KEY (calendar_id, date)
+);
+
+-- SLA targets of a policy with a calendar count business time only. A
+-- calendar can't be deleted while a policy uses it.
+ALTER TABLE sla_policies ADD COLUMN calendar_id UUID REFERENCES business_calendars(id);
@@ -0,0 +1,25 @@
+-- Agents the assignment engine may hand tickets to. Only agents with a
+-- profile are routed to; `max_open_tickets` NULL means no limit.
+-- `last_assigned_at` is when routing last gave them a ticket (round-robin).
+CREATE TABLE agent_routing_profiles (
+    user_id UUID PRIMARY KEY REFERENCES users(id) ON DELETE CASCADE,
+    skills TEXT[] NOT NULL DEFAULT '{}',
+    max_open_tickets INT CHECK (max_open_tickets >= 0),
+    available BOOLEAN NOT NULL DEFAULT true,
+    last_assigned_at TIMESTAMPTZ,
+    updated_at TIMESTAMPTZ NOT NULL DEFAULT now()
+);
+
+-- Ticket tags share the knowledge base's tag names; the skills strategy
+-- matches them against agent skills
+CREATE TABLE ticket_tags (
+    ticket_id UUID NOT NULL REFERENCES tickets(id) ON DELETE CASCADE,
+    tag_id UUID NOT NULL REFERENCES tags(id) ON DELETE CASCADE,
+    PRIMARY KEY (ticket_id, tag_id)
+);
+
+CREATE INDEX idx_ticket_tags_tag ON ticket_tags(tag_id);
+
+-- Counting each agent's open tickets
+CREATE INDEX idx_tickets_assigned_open ON tickets(assigned_to)
+    WHERE status NOT IN ('Resolved', 'Closed');
@@ -0,0 +1,24 @@
+-- Groups of staff (e.g. Billing, Tier 2) with a shared ticket queue.
+-- Leads can manage their team's other members.
+CREATE TABLE teams (
+    id UUID PRIMARY KEY DEFAULT gen_random_uuid(),
+    name TEXT NOT NULL UNIQUE,
+    description TEXT,
+    created_at TIMESTAMPTZ NOT NULL DEFAULT now(),
+    updated_at TIMESTAMPTZ NOT NULL DEFAULT now()
+);
+
+CREATE TABLE team_members (
+    team_id UUID NOT NULL REFERENCES teams(id) ON DELETE CASCADE,
+    user_id UUID NOT NULL REFERENCES users(id) ON DELETE CASCADE,
+    is_lead BOOLEAN NOT NULL DEFAULT false,
+    added_at TIMESTAMPTZ NOT NULL DEFAULT now(),
+    PRIMARY KEY (team_id, user_id)
+);
+
+CREATE INDEX idx_team_members_user ON team_members(user_id);
+
+-- The queue a ticket waits in; routing only picks agents from this team
+ALTER TABLE tickets ADD COLUMN team_id UUID REFERENCES teams(id) ON DELETE SET NULL;
+
+CREATE INDEX idx_tickets_team_created ON tickets(team_id, created_at) WHERE team_id IS NOT NULL;
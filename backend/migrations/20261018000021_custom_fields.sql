@@ -0,0 +1,28 @@
+-- Admin-defined ticket fields (order number, product, plan tier, ...).
+-- `key` is the stable name used in the API, filters and exports.
+CREATE TABLE custom_fields (
+    id UUID PRIMARY KEY DEFAULT gen_random_uuid(),
+    key TEXT NOT NULL UNIQUE,
+    label TEXT NOT NULL,
+    field_type TEXT NOT NULL CHECK (field_type IN ('text', 'number', 'dropdown', 'date', 'checkbox')),
+    -- Choices for dropdowns; empty for every other type
+    options TEXT[] NOT NULL DEFAULT '{}',
+    -- A ticket can't be closed while this field is empty
+    required_on_close BOOLEAN NOT NULL DEFAULT false,
+    position INT NOT NULL DEFAULT 0,
+    created_at TIMESTAMPTZ NOT NULL DEFAULT now(),
+    updated_at TIMESTAMPTZ NOT NULL DEFAULT now()
+);
+
+-- Values are stored in a canonical text form per type (numbers without
+-- trailing zeros, dates as YYYY-MM-DD, checkboxes as true/false) so equality
+-- filters work on the raw column
+CREATE TABLE ticket_field_values (
+    ticket_id UUID NOT NULL REFERENCES tickets(id) ON DELETE CASCADE,
+    field_id UUID NOT NULL REFERENCES custom_fields(id) ON DELETE CASCADE,
+    value TEXT NOT NULL,
+    updated_at TIMESTAMPTZ NOT NULL DEFAULT now(),
+    PRIMARY KEY (ticket_id, field_id)
+);
+
+CREATE INDEX idx_ticket_field_values_lookup ON ticket_field_values(field_id, value);
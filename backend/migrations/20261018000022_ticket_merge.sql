@@ -0,0 +1,15 @@
+-- A duplicate ticket merged into another is closed and points at the ticket
+-- that now holds its conversation.
+ALTER TABLE tickets ADD COLUMN merged_into_id UUID REFERENCES tickets(id) ON DELETE SET NULL;
+
+CREATE INDEX idx_tickets_merged_into ON tickets(merged_into_id) WHERE merged_into_id IS NOT NULL;
+
+-- Users following a ticket besides its customer and assignee
+CREATE TABLE ticket_watchers (
+    ticket_id UUID NOT NULL REFERENCES tickets(id) ON DELETE CASCADE,
+    user_id UUID NOT NULL REFERENCES users(id) ON DELETE CASCADE,
+    added_at TIMESTAMPTZ NOT NULL DEFAULT now(),
+    PRIMARY KEY (ticket_id, user_id)
+);
+
+CREATE INDEX idx_ticket_watchers_user ON ticket_watchers(user_id);
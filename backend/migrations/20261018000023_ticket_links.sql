@@ -0,0 +1,18 @@
+-- Typed links between tickets, stored one way round: `ticket_id` has `kind`
+-- `linked_ticket_id`, e.g. its parent or the ticket it's blocked by. The other
+-- side (child, blocks, duplicated by) is derived when reading. `related` links
+-- are stored with the smaller id first.
+CREATE TABLE ticket_links (
+    id UUID PRIMARY KEY DEFAULT gen_random_uuid(),
+    ticket_id UUID NOT NULL REFERENCES tickets(id) ON DELETE CASCADE,
+    linked_ticket_id UUID NOT NULL REFERENCES tickets(id) ON DELETE CASCADE,
+    kind TEXT NOT NULL CHECK (kind IN ('parent', 'related', 'duplicate_of', 'blocked_by')),
+    created_by UUID REFERENCES users(id) ON DELETE SET NULL,
+    created_at TIMESTAMPTZ NOT NULL DEFAULT now(),
+    CHECK (ticket_id <> linked_ticket_id),
+    UNIQUE (ticket_id, linked_ticket_id, kind)
+);
+
+-- A ticket has at most one parent
+CREATE UNIQUE INDEX idx_ticket_links_one_parent ON ticket_links(ticket_id) WHERE kind = 'parent';
+CREATE INDEX idx_ticket_links_linked ON ticket_links(linked_ticket_id, kind);
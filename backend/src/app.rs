@@ -8,18 +8,31 @@ use uuid::Uuid;
 use crate::{
     config::AppConfig,
     db,
+    mailer,
     middleware::{
         auth_extension::require_auth,
         rate_limit::rate_limit_middleware,
     },
     routes::*,
+    services::{signing_key_service::KeyStore, sla_service},
     state::{AppState, SharedState},
+    utils::{oidc::OidcClient, password_policy::PasswordPolicy},
+};
+use crate::routes::{
+    agent_ticket_routes,
+    auth_routes::{public_auth_routes, protected_auth_routes},
+    invitation_routes::{public_invitation_routes, protected_invitation_routes},
+    kb_routes::{public_kb_routes, protected_kb_routes},
+    mfa_routes::{public_mfa_routes, protected_mfa_routes},
+    notification_routes::{public_notification_routes, protected_notification_routes},
+    signing_key_routes::{public_signing_key_routes, protected_signing_key_routes},
 };
-use crate::routes::{agent_ticket_routes, kb_routes::{public_kb_routes, protected_kb_routes}};
 
 pub async fn create_app() -> anyhow::Result<Router> {
     let config = AppConfig::from_env();
     let pool = db::connect_to_db(&config.database_url).await;
+    let mailer = mailer::from_config(&config)?;
+    let password_policy = PasswordPolicy::from_config(&config)?;
 
     // ✅ Initialize WebSocket channels map
     let ws_channels: Arc<RwLock<HashMap<Uuid, broadcast::Sender<String>>>> =
@@ -30,8 +43,26 @@ pub async fn create_app() -> anyhow::Result<Router> {
         db: pool,
         config: config.clone(),
         ws_channels,
+        mailer,
+        oidc: Arc::new(OidcClient::new()),
+        // Retired keys stay valid until every token they signed has expired
+        keys: Arc::new(KeyStore::new(
+            config.jwt_algorithm,
+            config.access_token_ttl() + chrono::Duration::minutes(1),
+            config.jwt_secret.clone(),
+        )),
+        password_policy: Arc::new(password_policy),
     });
 
+    // 🔑 Load (or create the first) token signing key now rather than on the first login
+    shared_state.keys.signing_key(&shared_state.db).await?;
+
+    // ⏰ Flag tickets that miss their SLA targets
+    sla_service::spawn_breach_checker(
+        shared_state.db.clone(),
+        std::time::Duration::from_secs(config.sla_check_interval_seconds.max(1)),
+    );
+
     // 🌍 Global CORS policy - allows all origins, methods, and headers
     let cors = CorsLayer::new()
         .allow_origin(Any)
@@ -50,15 +81,35 @@ pub async fn create_app() -> anyhow::Result<Router> {
         .merge(user_routes::routes(shared_state.clone()))
         .merge(comment_routes::routes(shared_state.clone()))
         .merge(agent_ticket_routes::routes(shared_state.clone()))
+        .merge(protected_invitation_routes(shared_state.clone()))
+        .merge(protected_mfa_routes(shared_state.clone()))
+        .merge(lockout_routes::routes(shared_state.clone()))
+        .merge(api_key_routes::routes(shared_state.clone()))
+        .merge(role_routes::routes(shared_state.clone()))
+        .merge(protected_notification_routes(shared_state.clone()))
+        .merge(protected_auth_routes(shared_state.clone()))
+        .merge(protected_signing_key_routes(shared_state.clone()))
+        .merge(session_routes::routes(shared_state.clone()))
+        .merge(search_routes::routes(shared_state.clone()))
+        .merge(sla_routes::routes(shared_state.clone()))
+        .merge(business_calendar_routes::routes(shared_state.clone()))
+        .merge(routing_routes::routes(shared_state.clone()))
+        .merge(team_routes::routes(shared_state.clone()))
+        .merge(custom_field_routes::routes(shared_state.clone()))
+        .merge(ticket_link_routes::routes(shared_state.clone()))
         .layer(middleware::from_fn_with_state(shared_state.clone(), require_auth))
         .layer(middleware::from_fn(rate_limit_middleware));
 
     // 🆓 Public routes (no authentication)
     let public_routes = Router::new()
-        .merge(auth_routes::routes(shared_state.clone()))
+        .merge(public_auth_routes(shared_state.clone()))
         .merge(ws_routes::routes(shared_state.clone())) // WebSocket has its own auth
         .merge(public_kb_routes(shared_state.clone())) // ✅ Updated
-        .merge(notification_routes::routes(shared_state.clone()));
+        .merge(public_invitation_routes(shared_state.clone()))
+        .merge(public_mfa_routes(shared_state.clone()))
+        .merge(public_notification_routes(shared_state.clone()))
+        .merge(oidc_routes::routes(shared_state.clone()))
+        .merge(public_signing_key_routes(shared_state.clone()));
     // 🛠️ Compose final app
     let app = Router::new()
         .merge(public_routes)
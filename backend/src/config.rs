@@ -1,22 +1,174 @@
 use std::env;
 
+use crate::models::signing_key::KeyAlgorithm;
+
 #[derive(Clone, Debug)]
 pub struct AppConfig {
     pub database_url: String,
-    pub jwt_secret: String,
+    /// Only verifies HS256 tokens issued before signing keys existed
+    pub jwt_secret: Option<String>,
+    /// Algorithm for newly generated signing keys (`JWT_ALGORITHM`)
+    pub jwt_algorithm: KeyAlgorithm,
     pub port: u16,
+    pub access_token_ttl_minutes: i64,
+    pub refresh_token_ttl_days: i64,
+    pub frontend_url: String,
+    pub mail_transport: String,
+    pub mail_from: String,
+    pub mail_outbox_dir: String,
+    pub smtp_host: Option<String>,
+    pub smtp_port: u16,
+    pub smtp_username: Option<String>,
+    pub smtp_password: Option<String>,
+    pub mfa_issuer: String,
+    /// Base URL this API is reachable at; OIDC redirect URIs are built from it
+    pub public_url: String,
+    pub oidc_providers: Vec<OidcProviderConfig>,
+    pub password_min_length: usize,
+    pub password_max_length: usize,
+    /// File of passwords nobody may use, one per line (`PASSWORD_BANNED_LIST`)
+    pub password_banned_list: Option<String>,
+    /// How often overdue tickets are checked for SLA breaches (`SLA_CHECK_INTERVAL_SECONDS`)
+    pub sla_check_interval_seconds: u64,
+}
+
+/// One OpenID Connect identity provider staff can sign in with.
+/// Configured as `OIDC_PROVIDERS=corp,...` plus `OIDC_CORP_*` variables.
+#[derive(Clone, Debug)]
+pub struct OidcProviderConfig {
+    /// Lower-case id used in URLs (`/auth/oidc/{name}/login`)
+    pub name: String,
+    pub issuer: String,
+    pub client_id: String,
+    pub client_secret: Option<String>,
+    pub scopes: String,
+    /// ID token claim holding groups or roles (string or array of strings)
+    pub role_claim: String,
+    /// Claim value -> role, first match wins
+    pub role_map: Vec<(String, String)>,
+    /// Role for users none of `role_map` matches; `None` refuses them
+    pub default_role: Option<String>,
+}
+
+impl OidcProviderConfig {
+    fn from_env(name: &str) -> Self {
+        let var = |key: &str| env::var(format!("OIDC_{}_{key}", name.to_uppercase())).ok();
+        let required = |key: &str| {
+            var(key).unwrap_or_else(|| panic!("OIDC_{}_{key} is required", name.to_uppercase()))
+        };
+
+        Self {
+            name: name.to_lowercase(),
+            issuer: required("ISSUER").trim_end_matches('/').to_string(),
+            client_id: required("CLIENT_ID"),
+            client_secret: var("CLIENT_SECRET"),
+            scopes: var("SCOPES").unwrap_or_else(|| "openid email profile".into()),
+            role_claim: var("ROLE_CLAIM").unwrap_or_else(|| "groups".into()),
+            role_map: parse_role_map(&var("ROLE_MAP").unwrap_or_default()),
+            default_role: var("DEFAULT_ROLE").filter(|r| !r.is_empty()),
+        }
+    }
+}
+
+/// Parse `"support-admins=admin, support=agent"` into ordered pairs
+fn parse_role_map(raw: &str) -> Vec<(String, String)> {
+    raw.split(',')
+        .filter_map(|pair| pair.split_once('='))
+        .map(|(claim, role)| (claim.trim().to_string(), role.trim().to_string()))
+        .filter(|(claim, role)| !claim.is_empty() && !role.is_empty())
+        .collect()
 }
 
 impl AppConfig {
     pub fn from_env() -> Self {
         Self {
             database_url: env::var("DATABASE_URL").expect("DATABASE_URL is required"),
-            jwt_secret: env::var("JWT_SECRET").expect("JWT_SECRET is required"),
+            jwt_secret: env::var("JWT_SECRET").ok().filter(|s| !s.is_empty()),
+            jwt_algorithm: env::var("JWT_ALGORITHM")
+                .unwrap_or_else(|_| "EdDSA".into())
+                .parse()
+                .expect("JWT_ALGORITHM must be RS256 or EdDSA"),
             port: env::var("PORT")
                 .unwrap_or_else(|_| "8000".into())
                 .parse()
                 .expect("PORT must be a number"),
+            access_token_ttl_minutes: env::var("ACCESS_TOKEN_TTL_MINUTES")
+                .unwrap_or_else(|_| "15".into())
+                .parse()
+                .expect("ACCESS_TOKEN_TTL_MINUTES must be a number"),
+            refresh_token_ttl_days: env::var("REFRESH_TOKEN_TTL_DAYS")
+                .unwrap_or_else(|_| "30".into())
+                .parse()
+                .expect("REFRESH_TOKEN_TTL_DAYS must be a number"),
+            frontend_url: env::var("FRONTEND_URL")
+                .unwrap_or_else(|_| "http://localhost:3000".into()),
+            mail_transport: env::var("MAIL_TRANSPORT").unwrap_or_else(|_| "file".into()),
+            mail_from: env::var("MAIL_FROM")
+                .unwrap_or_else(|_| "Support <support@localhost>".into()),
+            mail_outbox_dir: env::var("MAIL_OUTBOX_DIR").unwrap_or_else(|_| "outbox".into()),
+            smtp_host: env::var("SMTP_HOST").ok(),
+            smtp_port: env::var("SMTP_PORT")
+                .unwrap_or_else(|_| "587".into())
+                .parse()
+                .expect("SMTP_PORT must be a number"),
+            smtp_username: env::var("SMTP_USERNAME").ok(),
+            smtp_password: env::var("SMTP_PASSWORD").ok(),
+            mfa_issuer: env::var("MFA_ISSUER").unwrap_or_else(|_| "Customer Support".into()),
+            public_url: env::var("PUBLIC_URL")
+                .unwrap_or_else(|_| {
+                    format!("http://localhost:{}", env::var("PORT").unwrap_or_else(|_| "8000".into()))
+                })
+                .trim_end_matches('/')
+                .to_string(),
+            oidc_providers: env::var("OIDC_PROVIDERS")
+                .unwrap_or_default()
+                .split(',')
+                .map(str::trim)
+                .filter(|name| !name.is_empty())
+                .map(OidcProviderConfig::from_env)
+                .collect(),
+            password_min_length: env::var("PASSWORD_MIN_LENGTH")
+                .unwrap_or_else(|_| "10".into())
+                .parse()
+                .expect("PASSWORD_MIN_LENGTH must be a number"),
+            password_max_length: env::var("PASSWORD_MAX_LENGTH")
+                .unwrap_or_else(|_| "128".into())
+                .parse()
+                .expect("PASSWORD_MAX_LENGTH must be a number"),
+            password_banned_list: env::var("PASSWORD_BANNED_LIST").ok().filter(|p| !p.is_empty()),
+            sla_check_interval_seconds: env::var("SLA_CHECK_INTERVAL_SECONDS")
+                .unwrap_or_else(|_| "60".into())
+                .parse()
+                .expect("SLA_CHECK_INTERVAL_SECONDS must be a number"),
         }
     }
+
+    pub fn oidc_provider(&self, name: &str) -> Option<&OidcProviderConfig> {
+        self.oidc_providers.iter().find(|p| p.name == name)
+    }
+
+    pub fn access_token_ttl(&self) -> chrono::Duration {
+        chrono::Duration::minutes(self.access_token_ttl_minutes)
+    }
+
+    pub fn refresh_token_ttl(&self) -> chrono::Duration {
+        chrono::Duration::days(self.refresh_token_ttl_days)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn role_map_keeps_order_and_skips_junk() {
+        assert_eq!(
+            parse_role_map("support-admins=admin, support = agent,broken,=x,y="),
+            vec![
+                ("support-admins".to_string(), "admin".to_string()),
+                ("support".to_string(), "agent".to_string()),
+            ]
+        );
+        assert!(parse_role_map("").is_empty());
+    }
 }
- 
\ No newline at end of file
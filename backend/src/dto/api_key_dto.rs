@@ -0,0 +1,18 @@
+use serde::{Deserialize, Serialize};
+
+use crate::models::api_key::ApiKey;
+
+#[derive(Debug, Deserialize)]
+pub struct CreateApiKeyRequest {
+    pub name: String,
+    pub scopes: Vec<String>,
+    pub expires_in_days: Option<i64>, // omit for a key that never expires
+}
+
+/// Returned once on creation; `key` is not stored and cannot be fetched again
+#[derive(Debug, Serialize)]
+pub struct CreateApiKeyResponse {
+    #[serde(flatten)]
+    pub api_key: ApiKey,
+    pub key: String,
+}
@@ -0,0 +1,27 @@
+use chrono::NaiveDate;
+use serde::Deserialize;
+use validator::Validate;
+
+use crate::utils::business_time::Shift;
+
+/// Create a calendar, or replace all of one's settings
+#[derive(Debug, Deserialize, Validate)]
+pub struct BusinessCalendarRequest {
+    #[validate(length(min = 1, max = 100, message = "Name must be 1-100 characters"))]
+    pub name: String,
+    /// IANA time zone the hours are in, e.g. `Europe/Berlin`
+    pub timezone: String,
+    /// e.g. `{"weekday": "Mon", "opens_at": "09:00:00", "closes_at": "17:00:00"}`
+    #[serde(default)]
+    pub hours: Vec<Shift>,
+    #[serde(default)]
+    #[validate]
+    pub holidays: Vec<HolidayRequest>,
+}
+
+#[derive(Debug, Deserialize, Validate)]
+pub struct HolidayRequest {
+    pub date: NaiveDate,
+    #[validate(length(min = 1, max = 100, message = "Name must be 1-100 characters"))]
+    pub name: String,
+}
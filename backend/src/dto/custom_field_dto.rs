@@ -0,0 +1,35 @@
+use serde::Deserialize;
+use validator::Validate;
+
+use crate::models::custom_field::CustomFieldType;
+
+/// Define a new ticket field
+#[derive(Debug, Deserialize, Validate)]
+pub struct CustomFieldRequest {
+    /// e.g. `order_number`; can't be changed later
+    pub key: String,
+    #[validate(length(min = 1, max = 100, message = "Label must be 1-100 characters"))]
+    pub label: String,
+    /// `text`, `number`, `dropdown`, `date` or `checkbox`; can't be changed later
+    pub field_type: CustomFieldType,
+    /// Choices, for dropdowns only
+    #[serde(default)]
+    pub options: Vec<String>,
+    #[serde(default)]
+    pub required_on_close: bool,
+    #[serde(default)]
+    pub position: i32,
+}
+
+/// Replace a field's label, options and rules
+#[derive(Debug, Deserialize, Validate)]
+pub struct UpdateCustomFieldRequest {
+    #[validate(length(min = 1, max = 100, message = "Label must be 1-100 characters"))]
+    pub label: String,
+    #[serde(default)]
+    pub options: Vec<String>,
+    #[serde(default)]
+    pub required_on_close: bool,
+    #[serde(default)]
+    pub position: i32,
+}
@@ -0,0 +1,27 @@
+use serde::{Deserialize, Serialize};
+use validator::Validate;
+
+use crate::models::invitation::Invitation;
+
+#[derive(Debug, Deserialize, Validate)]
+pub struct CreateInvitationRequest {
+    #[validate(email)]
+    pub email: String,
+    pub role: String, // any role except the default customer role
+    pub expires_in_hours: Option<i64>,
+}
+
+/// Returned once on creation; `token` is not stored and cannot be fetched again
+#[derive(Debug, Serialize)]
+pub struct CreateInvitationResponse {
+    #[serde(flatten)]
+    pub invitation: Invitation,
+    pub token: String,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct AcceptInvitationRequest {
+    pub token: String,
+    pub name: String,
+    pub password: String,
+}
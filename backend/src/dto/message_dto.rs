@@ -4,9 +4,7 @@ use uuid::Uuid;
 #[derive(Debug, Deserialize)]
 pub struct CreateMessageRequest {
     pub ticket_id: Uuid,
-    pub sender_id: Option<Uuid>,
     pub content: String,
-    pub is_from_customer: bool,
     pub channel: Option<String>,
     pub in_reply_to: Option<Uuid>,
     pub subject: Option<String>,
@@ -0,0 +1,60 @@
+use serde::{Deserialize, Serialize};
+
+/// Returned by login when a second factor is still needed. `mfa_token` is
+/// exchanged at `/auth/mfa/verify`, or at `/auth/mfa/enroll*` when
+/// `enrollment_required` is set.
+#[derive(Debug, Serialize)]
+pub struct MfaChallengeResponse {
+    pub mfa_required: bool,
+    pub enrollment_required: bool,
+    pub mfa_token: String,
+    pub expires_in: i64, // seconds
+}
+
+#[derive(Debug, Deserialize)]
+pub struct MfaVerifyRequest {
+    pub mfa_token: String,
+    pub code: String, // TOTP code or recovery code
+}
+
+#[derive(Debug, Deserialize)]
+pub struct MfaEnrollRequest {
+    pub mfa_token: String,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct MfaCodeRequest {
+    pub code: String,
+}
+
+#[derive(Debug, Serialize)]
+pub struct TotpSetupResponse {
+    pub secret: String,
+    pub otpauth_uri: String,
+}
+
+/// Recovery codes are only ever shown here, right after they are generated
+#[derive(Debug, Serialize)]
+pub struct RecoveryCodesResponse {
+    pub recovery_codes: Vec<String>,
+}
+
+/// Tokens for a user who just finished mandatory enrollment, plus their recovery codes
+#[derive(Debug, Serialize)]
+pub struct MfaEnrollResponse {
+    pub token: String,
+    pub refresh_token: String,
+    pub recovery_codes: Vec<String>,
+}
+
+#[derive(Debug, Serialize)]
+pub struct MfaStatusResponse {
+    pub enabled: bool,
+    pub required: bool,
+    pub recovery_codes_remaining: i64,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct MfaSettings {
+    pub required_roles: Vec<String>,
+}
@@ -5,3 +5,18 @@ pub mod message_dto;
 pub mod attachment_dto;
 pub mod kb_dto;
 pub mod notification_dto;
+pub mod invitation_dto;
+pub mod mfa_dto;
+pub mod api_key_dto;
+
+pub mod role_dto;
+pub mod oidc_dto;
+pub mod signing_key_dto;
+pub mod user_dto;
+pub mod session_dto;
+pub mod search_dto;
+pub mod sla_dto;
+pub mod business_calendar_dto;
+pub mod routing_dto;
+pub mod team_dto;
+pub mod custom_field_dto;
\ No newline at end of file
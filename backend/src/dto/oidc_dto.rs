@@ -0,0 +1,21 @@
+use serde::{Deserialize, Serialize};
+
+#[derive(Debug, Serialize)]
+pub struct OidcProviderResponse {
+    pub name: String,
+    pub login_url: String,
+}
+
+/// What the provider sends back to the callback: `code` and `state` on
+/// success, `error` when the user cancelled or was refused
+#[derive(Debug, Deserialize)]
+pub struct OidcCallbackQuery {
+    pub code: Option<String>,
+    pub state: Option<String>,
+    pub error: Option<String>,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct OidcExchangeRequest {
+    pub code: String,
+}
@@ -0,0 +1,57 @@
+use serde::{Deserialize, Serialize};
+use validator::Validate;
+
+use crate::models::{
+    routing::{RoutingDecision, RoutingStrategy},
+    ticket::Ticket,
+};
+
+/// Turn automatic assignment on or off and pick its strategy
+#[derive(Debug, Deserialize)]
+pub struct RoutingSettingsRequest {
+    pub auto_assign: bool,
+    /// `round_robin`, `least_open` or `skills`
+    pub strategy: RoutingStrategy,
+}
+
+/// Create or replace an agent's routing profile
+#[derive(Debug, Deserialize, Validate)]
+pub struct AgentRoutingProfileRequest {
+    /// Tag names the agent handles, matched case-insensitively
+    #[serde(default)]
+    #[validate(length(max = 50, message = "At most 50 skills"))]
+    pub skills: Vec<String>,
+    /// Open tickets the agent can hold; omit for no limit
+    pub max_open_tickets: Option<i32>,
+    #[serde(default = "default_available")]
+    pub available: bool,
+}
+
+fn default_available() -> bool {
+    true
+}
+
+#[derive(Debug, Deserialize)]
+pub struct AvailabilityRequest {
+    pub available: bool,
+}
+
+/// `?strategy=` for a dry run; defaults to the configured strategy
+#[derive(Debug, Default, Deserialize)]
+pub struct RoutingPreviewQuery {
+    pub strategy: Option<RoutingStrategy>,
+}
+
+/// Reroute a ticket away from its current assignee
+#[derive(Debug, Default, Deserialize)]
+pub struct RouteTicketRequest {
+    /// Defaults to the configured strategy
+    pub strategy: Option<RoutingStrategy>,
+}
+
+#[derive(Debug, Serialize)]
+pub struct RouteTicketResponse {
+    /// The ticket after routing; unchanged when nobody was eligible
+    pub ticket: Ticket,
+    pub decision: RoutingDecision,
+}
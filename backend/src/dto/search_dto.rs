@@ -0,0 +1,20 @@
+use serde::{Deserialize, Serialize};
+
+use crate::models::search::SearchResult;
+
+/// `q` uses web search syntax: `"exact phrase"`, `or`, `-exclude`
+#[derive(Debug, Deserialize)]
+pub struct SearchQuery {
+    pub q: Option<String>,
+    pub page: Option<i64>,
+    pub per_page: Option<i64>,
+}
+
+#[derive(Debug, Serialize)]
+pub struct SearchResponse {
+    pub results: Vec<SearchResult>,
+    /// Matching tickets across all pages
+    pub total: i64,
+    pub page: i64,
+    pub per_page: i64,
+}
@@ -0,0 +1,11 @@
+use serde::Serialize;
+
+use crate::models::session::Session;
+
+#[derive(Debug, Serialize)]
+pub struct SessionResponse {
+    #[serde(flatten)]
+    pub session: Session,
+    /// The session making this request
+    pub current: bool,
+}
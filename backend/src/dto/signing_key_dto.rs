@@ -0,0 +1,9 @@
+use serde::Deserialize;
+
+use crate::models::signing_key::KeyAlgorithm;
+
+#[derive(Debug, Default, Deserialize)]
+pub struct RotateSigningKeyRequest {
+    /// Defaults to `JWT_ALGORITHM`
+    pub algorithm: Option<KeyAlgorithm>,
+}
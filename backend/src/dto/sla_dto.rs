@@ -0,0 +1,19 @@
+use serde::Deserialize;
+use uuid::Uuid;
+use validator::Validate;
+
+use crate::models::ticket::TicketPriority;
+
+/// Create a policy, or replace all of one's settings
+#[derive(Debug, Deserialize, Validate)]
+pub struct SlaPolicyRequest {
+    #[validate(length(min = 1, max = 100, message = "Name must be 1-100 characters"))]
+    pub name: String,
+    pub priority: TicketPriority,
+    /// Only tickets from this email domain; omit for the default policy
+    pub customer_domain: Option<String>,
+    pub first_response_minutes: i32,
+    pub resolution_minutes: i32,
+    /// Business calendar to count targets in; omit to count around the clock
+    pub calendar_id: Option<Uuid>,
+}
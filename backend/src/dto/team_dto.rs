@@ -0,0 +1,18 @@
+use serde::Deserialize;
+use validator::Validate;
+
+/// Create a team, or rename one
+#[derive(Debug, Deserialize, Validate)]
+pub struct TeamRequest {
+    #[validate(length(min = 1, max = 100, message = "Name must be 1-100 characters"))]
+    pub name: String,
+    #[validate(length(max = 500, message = "Description can be max 500 characters"))]
+    pub description: Option<String>,
+}
+
+/// Add someone to a team or change their lead flag
+#[derive(Debug, Default, Deserialize)]
+pub struct TeamMemberRequest {
+    #[serde(default)]
+    pub is_lead: bool,
+}
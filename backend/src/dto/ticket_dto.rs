@@ -1,8 +1,15 @@
+use std::collections::BTreeMap;
+
+use chrono::{DateTime, Utc};
 use serde::{Deserialize, Serialize};
+use serde_json::{Map, Value};
 use uuid::Uuid;
 use validator::Validate;
 
-use crate::models::ticket::{TicketPriority, TicketStatus};
+use crate::models::{
+    ticket::{Ticket, TicketPriority, TicketStatus},
+    ticket_link::{LinkKind, TicketLink},
+};
 
 /// DTO for creating a ticket
 #[derive(Debug, Deserialize, Serialize, Validate)]
@@ -17,6 +24,18 @@ pub struct CreateTicketRequest {
 
     #[validate(email)]
     pub customer_email: String,
+
+    /// Team queue to file the ticket in, e.g. Billing
+    pub team_id: Option<Uuid>,
+
+    /// Tag names, e.g. `["billing", "refund"]`; skill-based routing matches on them
+    #[serde(default)]
+    #[validate(length(max = 20, message = "At most 20 tags"))]
+    pub tags: Vec<String>,
+
+    /// Custom field values by key, e.g. `{"order_number": "A-123"}`
+    #[serde(default)]
+    pub custom_fields: Map<String, Value>,
 }
 
 /// DTO for updating a ticket
@@ -28,8 +47,86 @@ pub struct UpdateTicketRequest {
     #[validate(length(max = 500, message = "Description can be max 500 characters"))]
     pub description: Option<String>,
 
-    pub status: Option<TicketStatus>,     // Enum: Open, InProgress, Closed
+    pub status: Option<TicketStatus>,     // Enum: New, Open, Pending, OnHold, Resolved, Closed
     pub priority: Option<TicketPriority>, // Enum: Low, Medium, High
 
     pub assigned_to: Option<Uuid>,
+
+    /// With `status: Closed`, close the ticket's child tickets too
+    #[serde(default)]
+    pub close_children: bool,
+}
+
+/// Query string shared by `GET /tickets`, `/admin/tickets` and `/agent/tickets`
+#[derive(Debug, Default, Deserialize)]
+pub struct ListTicketsQuery {
+    /// Comma-separated, e.g. `New,Open`
+    pub status: Option<String>,
+    /// Comma-separated, e.g. `High,Medium`
+    pub priority: Option<String>,
+    pub assigned_to: Option<Uuid>,
+    /// `true` for tickets nobody is assigned to
+    pub unassigned: Option<bool>,
+    pub customer_email: Option<String>,
+    /// Comma-separated tag names; tickets must have all of them
+    pub tag: Option<String>,
+    /// Comma-separated custom field `key:value` pairs, e.g. `plan:Pro,seats:5`
+    pub fields: Option<String>,
+    /// RFC 3339 timestamps; `_after` is inclusive, `_before` exclusive
+    pub created_after: Option<DateTime<Utc>>,
+    pub created_before: Option<DateTime<Utc>>,
+    pub updated_after: Option<DateTime<Utc>>,
+    pub updated_before: Option<DateTime<Utc>>,
+    /// `created_at`, `updated_at` or `priority`, `-` prefix for descending.
+    /// Defaults to `-created_at`.
+    pub sort: Option<String>,
+    /// `next_cursor` from the previous page
+    pub cursor: Option<String>,
+    /// Page size, 1-100 (default 25)
+    pub limit: Option<i64>,
+}
+
+#[derive(Debug, Serialize)]
+pub struct TicketPageResponse {
+    pub tickets: Vec<Ticket>,
+    pub total: i64,
+    /// `None` on the last page
+    pub next_cursor: Option<String>,
+}
+
+/// A single ticket with its tags, custom field values and links
+#[derive(Debug, Serialize)]
+pub struct TicketDetailResponse {
+    #[serde(flatten)]
+    pub ticket: Ticket,
+    pub tags: Vec<String>,
+    /// Typed values by field key; empty fields are left out
+    pub custom_fields: BTreeMap<String, Value>,
+    /// Links to other tickets the caller can see
+    pub links: Vec<TicketLink>,
+}
+
+/// Replace a ticket's tags
+#[derive(Debug, Deserialize, Validate)]
+pub struct SetTicketTagsRequest {
+    #[validate(length(max = 20, message = "At most 20 tags"))]
+    pub tags: Vec<String>,
+}
+
+/// Move some of a ticket's messages into a new ticket
+#[derive(Debug, Deserialize, Validate)]
+pub struct SplitTicketRequest {
+    #[validate(length(min = 1, message = "Pick at least one message"))]
+    pub message_ids: Vec<Uuid>,
+
+    /// Subject of the new ticket; defaults to the original's
+    #[validate(length(min = 3, message = "Subject must be at least 3 characters"))]
+    pub subject: Option<String>,
+}
+
+/// Link the ticket to another, e.g. `{"kind": "child", "ticket_id": "..."}`
+#[derive(Debug, Deserialize)]
+pub struct CreateTicketLinkRequest {
+    pub kind: LinkKind,
+    pub ticket_id: Uuid,
 }
@@ -0,0 +1,67 @@
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use uuid::Uuid;
+use validator::Validate;
+
+use crate::models::user::UserSummary;
+
+/// `GET /me`: who you are and what you may do
+#[derive(Debug, Serialize)]
+pub struct ProfileResponse {
+    pub id: Uuid,
+    pub name: String,
+    pub email: String,
+    pub email_verified: bool,
+    pub role: String,
+    pub permissions: Vec<String>,
+    pub timezone: String,
+    pub avatar_url: Option<String>,
+    pub created_at: Option<DateTime<Utc>>,
+}
+
+/// Omitted fields are left unchanged; `avatar_url: ""` removes the avatar
+#[derive(Debug, Deserialize, Validate)]
+pub struct UpdateProfileRequest {
+    #[validate(length(min = 1, max = 100, message = "Name must be 1-100 characters"))]
+    pub name: Option<String>,
+    pub timezone: Option<String>,
+    pub avatar_url: Option<String>,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct ChangePasswordRequest {
+    pub current_password: String,
+    pub new_password: String,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct ListUsersQuery {
+    pub search: Option<String>,
+    pub role: Option<String>,
+    pub is_active: Option<bool>,
+    pub page: Option<i64>,
+    pub per_page: Option<i64>,
+}
+
+#[derive(Debug, Serialize)]
+pub struct UserListResponse {
+    pub users: Vec<UserSummary>,
+    pub total: i64,
+    pub page: i64,
+    pub per_page: i64,
+}
+
+/// Omitted fields are left unchanged. Role and status have their own endpoints.
+#[derive(Debug, Deserialize, Validate)]
+pub struct AdminUpdateUserRequest {
+    #[validate(length(min = 1, max = 100, message = "Name must be 1-100 characters"))]
+    pub name: Option<String>,
+    #[validate(email)]
+    pub email: Option<String>,
+    pub timezone: Option<String>,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct SetUserStatusRequest {
+    pub is_active: bool,
+}
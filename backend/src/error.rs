@@ -0,0 +1,45 @@
+use axum::{
+    http::StatusCode,
+    response::{IntoResponse, Response},
+    Json,
+};
+use serde_json::json;
+
+use crate::utils::password_policy::PasswordPolicyError;
+
+/// Error answer for handlers that need to tell the client more than a
+/// status code. Any `StatusCode` converts into it, so `?` keeps working.
+#[derive(Debug)]
+pub enum ApiError {
+    Status(StatusCode),
+    /// 400 with `{"error": code, "message": message}`
+    Invalid { code: &'static str, message: String },
+}
+
+impl From<StatusCode> for ApiError {
+    fn from(status: StatusCode) -> Self {
+        ApiError::Status(status)
+    }
+}
+
+impl From<PasswordPolicyError> for ApiError {
+    fn from(err: PasswordPolicyError) -> Self {
+        ApiError::Invalid {
+            code: err.code(),
+            message: err.to_string(),
+        }
+    }
+}
+
+impl IntoResponse for ApiError {
+    fn into_response(self) -> Response {
+        match self {
+            ApiError::Status(status) => status.into_response(),
+            ApiError::Invalid { code, message } => (
+                StatusCode::BAD_REQUEST,
+                Json(json!({ "error": code, "message": message })),
+            )
+                .into_response(),
+        }
+    }
+}
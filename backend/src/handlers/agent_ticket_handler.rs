@@ -9,10 +9,10 @@ use sqlx::{query, query_as};
 use uuid::Uuid;
 
 use crate::{
-    models::ticket::Ticket,
-    services::notification_services::notify_user,
+    middleware::auth::Principal,
+    models::{permission::Permission, ticket::Ticket},
+    services::{notification_services::notify_user, ticket_service::{self, ReplySide, TICKET_COLUMNS}},
     state::SharedState,
-    utils::jwt::Claims,
 };
 
 #[derive(serde::Deserialize)]
@@ -24,34 +24,24 @@ pub struct ReplyPayload {
 pub async fn list_or_reply_agent_tickets(
     Path(agent_id): Path<Uuid>,
     State(state): State<SharedState>,
+    principal: Principal,
     req: Request,
     Json(payload): Json<Option<ReplyPayload>>,
 ) -> Response {
-    let Some(claims) = req.extensions().get::<Claims>() else {
-        return (StatusCode::UNAUTHORIZED, "Missing authentication").into_response();
-    };
-
-    if claims.role != "agent" {
+    if !principal.has(Permission::TicketsReadAssigned) {
         return (StatusCode::FORBIDDEN, "Only agents can access this route").into_response();
     }
 
-    if claims.sub != agent_id {
+    if principal.id != agent_id {
         return (StatusCode::FORBIDDEN, "Agent ID mismatch").into_response();
     }
 
     let method = req.method();
 
     if method == Method::GET {
-        match query_as::<_, Ticket>(
-            r#"
-            SELECT id, subject, description,
-                   status, priority, assigned_to,
-                   created_at, updated_at,
-                   customer_email, user_id
-            FROM tickets
-            WHERE assigned_to = $1
-            "#,
-        )
+        match query_as::<_, Ticket>(&format!(
+            "SELECT {TICKET_COLUMNS} FROM tickets WHERE assigned_to = $1"
+        ))
         .bind(agent_id)
         .fetch_all(&state.db)
         .await
@@ -74,7 +64,7 @@ pub async fn list_or_reply_agent_tickets(
             "#,
             Uuid::new_v4(),
             reply.ticket_id,
-            claims.sub,
+            principal.id,
             reply.content,
             Utc::now()
         )
@@ -85,16 +75,13 @@ pub async fn list_or_reply_agent_tickets(
             return (StatusCode::INTERNAL_SERVER_ERROR, "Failed to send reply").into_response();
         }
 
-        let ticket = match query_as::<_, Ticket>(
-            r#"
-            SELECT id, subject, description,
-                   status, priority, assigned_to,
-                   created_at, updated_at,
-                   customer_email, user_id
-            FROM tickets
-            WHERE id = $1
-            "#,
-        )
+        if let Err(err) = ticket_service::record_reply(&state.db, reply.ticket_id, ReplySide::Staff, principal.id).await {
+            tracing::error!("Failed to record first response: {:?}", err);
+        }
+
+        let ticket = match query_as::<_, Ticket>(&format!(
+            "SELECT {TICKET_COLUMNS} FROM tickets WHERE id = $1"
+        ))
         .bind(reply.ticket_id)
         .fetch_one(&state.db)
         .await
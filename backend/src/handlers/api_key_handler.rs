@@ -0,0 +1,104 @@
+use axum::{
+    extract::{Path, State},
+    http::StatusCode,
+    Json,
+};
+use chrono::{Duration, Utc};
+use uuid::Uuid;
+
+use crate::{
+    dto::api_key_dto::{CreateApiKeyRequest, CreateApiKeyResponse},
+    middleware::auth::Principal,
+    models::api_key::{ApiKey, API_KEY_SCOPES},
+    services::api_key_service::{self, ApiKeyError},
+    state::SharedState,
+};
+
+fn api_key_error_status(err: ApiKeyError) -> StatusCode {
+    match err {
+        ApiKeyError::InvalidScope(_) | ApiKeyError::NoScopes => StatusCode::BAD_REQUEST,
+        ApiKeyError::NotFound => StatusCode::NOT_FOUND,
+        ApiKeyError::Database(err) => {
+            tracing::error!("DB error handling API keys: {:?}", err);
+            StatusCode::INTERNAL_SERVER_ERROR
+        }
+    }
+}
+
+/// POST /api-keys - Create an API key acting as the current user
+pub async fn create_api_key(
+    State(state): State<SharedState>,
+    principal: Principal,
+    Json(payload): Json<CreateApiKeyRequest>,
+) -> Result<Json<CreateApiKeyResponse>, StatusCode> {
+    let name = payload.name.trim();
+    if name.is_empty() {
+        return Err(StatusCode::BAD_REQUEST);
+    }
+
+    let expires_at = match payload.expires_in_days {
+        Some(days) if days <= 0 => return Err(StatusCode::BAD_REQUEST),
+        Some(days) => Some(Utc::now() + Duration::days(days)),
+        None => None,
+    };
+
+    let (api_key, key) =
+        api_key_service::create_api_key(&state.db, principal.id, name, &payload.scopes, expires_at)
+            .await
+            .map_err(api_key_error_status)?;
+
+    Ok(Json(CreateApiKeyResponse { api_key, key }))
+}
+
+/// GET /api-keys - The current user's API keys
+pub async fn list_my_api_keys(
+    State(state): State<SharedState>,
+    principal: Principal,
+) -> Result<Json<Vec<ApiKey>>, StatusCode> {
+    let keys = api_key_service::list_for_user(&state.db, principal.id)
+        .await
+        .map_err(api_key_error_status)?;
+
+    Ok(Json(keys))
+}
+
+/// GET /api-keys/scopes - Scopes that can be granted
+pub async fn list_scopes() -> Json<Vec<&'static str>> {
+    Json(API_KEY_SCOPES.to_vec())
+}
+
+/// DELETE /api-keys/{id} - Revoke one of the current user's keys
+pub async fn revoke_my_api_key(
+    State(state): State<SharedState>,
+    principal: Principal,
+    Path(key_id): Path<Uuid>,
+) -> Result<Json<ApiKey>, StatusCode> {
+    let key = api_key_service::revoke_api_key(&state.db, key_id, Some(principal.id))
+        .await
+        .map_err(api_key_error_status)?;
+
+    Ok(Json(key))
+}
+
+/// GET /admin/api-keys - Every API key
+pub async fn list_all_api_keys(
+    State(state): State<SharedState>,
+) -> Result<Json<Vec<ApiKey>>, StatusCode> {
+    let keys = api_key_service::list_all(&state.db)
+        .await
+        .map_err(api_key_error_status)?;
+
+    Ok(Json(keys))
+}
+
+/// DELETE /admin/api-keys/{id} - Revoke any API key
+pub async fn revoke_any_api_key(
+    State(state): State<SharedState>,
+    Path(key_id): Path<Uuid>,
+) -> Result<Json<ApiKey>, StatusCode> {
+    let key = api_key_service::revoke_api_key(&state.db, key_id, None)
+        .await
+        .map_err(api_key_error_status)?;
+
+    Ok(Json(key))
+}
@@ -1,18 +1,44 @@
-use axum::{extract::State, Json};
-use axum::http::StatusCode;
+use std::net::SocketAddr;
+
+use axum::{extract::{ConnectInfo, State}, Json};
+use axum::http::{HeaderMap, StatusCode};
+use chrono::Utc;
+use serde_json::{json, Value};
 use sqlx::query_as_unchecked;
 use uuid::Uuid;
 use crate::{
-    dto::auth_dto::{RegisterRequest, LoginRequest, AuthResponse},
+    dto::auth_dto::{
+        RegisterRequest, LoginRequest, AuthResponse, RefreshRequest, LogoutRequest,
+        ForgotPasswordRequest, ResetPasswordRequest, LoginResponse, VerifyEmailRequest,
+    },
+    dto::mfa_dto::MfaChallengeResponse,
+    error::ApiError,
+    mailer::Email,
+    middleware::{auth::Principal, client_info::client_info, rate_limit::get_real_ip},
+    models::login_attempt::LoginOutcome,
+    models::session::ClientInfo,
+    models::role::DEFAULT_ROLE,
     models::user::User,
+    services::email_verification_service::{
+        self, EmailVerificationError, VerificationRequest, VERIFICATION_TOKEN_TTL_HOURS,
+    },
+    services::login_throttle_service,
+    services::mfa_service::{self, ChallengePurpose, CHALLENGE_TTL_MINUTES},
+    services::settings_service,
+    services::password_reset_service::{self, PasswordResetError, RESET_TOKEN_TTL_MINUTES},
+    services::session_service::{self, SessionError},
+    services::user_service,
     state::SharedState,
-    utils::{hash::hash_password, hash::verify_password, jwt::create_token},
+    utils::{hash::{dummy_verify, hash_password, needs_rehash, verify_password}, jwt::create_token},
 };
 
 pub async fn register(
     State(state): State<SharedState>,
+    client: ClientInfo,
     Json(payload): Json<RegisterRequest>,
-) -> Result<Json<AuthResponse>, StatusCode> {
+) -> Result<Json<LoginResponse>, ApiError> {
+    state.password_policy.check(&payload.password)?;
+
     let uuid = Uuid::new_v4();
     let password_hash = hash_password(&payload.password)
         .map_err(|_| StatusCode::INTERNAL_SERVER_ERROR)?;
@@ -28,7 +54,7 @@ pub async fn register(
         payload.name,
         payload.email,
         password_hash,
-        payload.role  // ← USE ROLE FROM REQUEST
+        DEFAULT_ROLE
     )
     .fetch_one(&state.db)
     .await
@@ -37,21 +63,114 @@ pub async fn register(
         StatusCode::INTERNAL_SERVER_ERROR
     })?;
 
-    let token = create_token(
-        user.id,
-        user.email.clone(),
-        user.role.clone(),
-        &state.config.jwt_secret,
-    )
-    .map_err(|_| StatusCode::INTERNAL_SERVER_ERROR)?;
+    // The account works right away, but tickets filed under this address
+    // stay hidden until the link in this email is used
+    let request = email_verification_service::request_verification(&state.db, user.id)
+        .await
+        .map_err(|err| {
+            tracing::error!("Email verification DB error: {:?}", err);
+            StatusCode::INTERNAL_SERVER_ERROR
+        })?;
+    send_verification_email(&state, request);
 
-    Ok(Json(AuthResponse { token }))
+    Ok(Json(complete_login(&state, &user, &client).await?))
 }
 
+fn send_verification_email(state: &SharedState, request: VerificationRequest) {
+    let link = format!(
+        "{}/verify-email?token={}",
+        state.config.frontend_url.trim_end_matches('/'),
+        request.token
+    );
+    let email = Email {
+        to: request.email,
+        subject: "Verify your email address".into(),
+        body: format!(
+            "Hi {},\n\nPlease confirm this is your email address by opening the link below. \
+             It expires in {} hours.\n\n{}\n\n\
+             If you didn't create an account, you can ignore this email.",
+            request.name, VERIFICATION_TOKEN_TTL_HOURS, link
+        ),
+    };
+
+    let mailer = state.mailer.clone();
+    tokio::spawn(async move {
+        if let Err(err) = mailer.send(email).await {
+            tracing::error!("Failed to send verification email: {:?}", err);
+        }
+    });
+}
+
+/// POST /auth/verify-email - Confirm an email address with the emailed token
+pub async fn verify_email(
+    State(state): State<SharedState>,
+    Json(payload): Json<VerifyEmailRequest>,
+) -> Result<StatusCode, StatusCode> {
+    let user_id = email_verification_service::verify_email(&state.db, &payload.token)
+        .await
+        .map_err(|err| match err {
+            EmailVerificationError::Database(err) => {
+                tracing::error!("Verify email DB error: {:?}", err);
+                StatusCode::INTERNAL_SERVER_ERROR
+            }
+            _ => StatusCode::BAD_REQUEST,
+        })?;
+
+    tracing::info!("✅ Email verified for user {}", user_id);
+    Ok(StatusCode::NO_CONTENT)
+}
+
+/// POST /auth/verify-email/resend - Send the caller a new verification link
+pub async fn resend_verification(
+    State(state): State<SharedState>,
+    principal: Principal,
+) -> Result<StatusCode, StatusCode> {
+    let request = email_verification_service::request_verification(&state.db, principal.id)
+        .await
+        .map_err(|err| match err {
+            EmailVerificationError::AlreadyVerified => StatusCode::CONFLICT,
+            EmailVerificationError::TooSoon => StatusCode::TOO_MANY_REQUESTS,
+            EmailVerificationError::UserNotFound | EmailVerificationError::InvalidToken => {
+                StatusCode::NOT_FOUND
+            }
+            EmailVerificationError::Database(err) => {
+                tracing::error!("Email verification DB error: {:?}", err);
+                StatusCode::INTERNAL_SERVER_ERROR
+            }
+        })?;
+    send_verification_email(&state, request);
+
+    Ok(StatusCode::ACCEPTED)
+}
+
+/// POST /auth/login
+///
+/// Failed attempts are counted per account and per IP with exponential
+/// backoff; emails without an account are counted and locked the same way.
+/// While locked out or throttled the answer is the same 401 as for a wrong
+/// password, after the same password hashing work; only the audit log
+/// (`login_attempts`) records why.
 pub async fn login(
     State(state): State<SharedState>,
+    ConnectInfo(addr): ConnectInfo<SocketAddr>,
+    headers: HeaderMap,
     Json(payload): Json<LoginRequest>,
-) -> Result<Json<AuthResponse>, StatusCode> {
+) -> Result<Json<LoginResponse>, StatusCode> {
+    let ip = get_real_ip(&headers, addr.ip()).to_string();
+    let client = client_info(&headers, Some(addr));
+
+    let ip_blocked = login_throttle_service::ip_blocked_until(&state.db, &ip)
+        .await
+        .map_err(|err| {
+            tracing::error!("Login throttle DB error: {:?}", err);
+            StatusCode::INTERNAL_SERVER_ERROR
+        })?;
+
+    if ip_blocked.is_some() {
+        audit_login(&state, &payload.email, &ip, None, LoginOutcome::IpThrottled).await?;
+        return Err(StatusCode::UNAUTHORIZED);
+    }
+
     let user = query_as_unchecked!(
         User,
         "SELECT * FROM users WHERE email = $1",
@@ -66,27 +185,315 @@ pub async fn login(
 
     let user = match user {
         Some(u) => u,
-        None => return Err(StatusCode::UNAUTHORIZED),
+        None => {
+            dummy_verify(&payload.password);
+            let locked = login_throttle_service::unknown_email_blocked_until(&state.db, &payload.email)
+                .await
+                .map_err(|err| {
+                    tracing::error!("Login throttle DB error: {:?}", err);
+                    StatusCode::INTERNAL_SERVER_ERROR
+                })?;
+            let outcome = if locked.is_some() { LoginOutcome::AccountLocked } else { LoginOutcome::UnknownUser };
+            audit_login(&state, &payload.email, &ip, None, outcome).await?;
+            return Err(StatusCode::UNAUTHORIZED);
+        }
     };
 
+    if user.locked_until.is_some_and(|until| until > Utc::now()) {
+        dummy_verify(&payload.password);
+        audit_login(&state, &payload.email, &ip, Some(user.id), LoginOutcome::AccountLocked).await?;
+        return Err(StatusCode::UNAUTHORIZED);
+    }
+
     let valid = verify_password(&payload.password, &user.password_hash)
         .map_err(|_| StatusCode::INTERNAL_SERVER_ERROR)?;
 
     if !valid {
+        let locked_until = login_throttle_service::register_failure(&state.db, user.id)
+            .await
+            .map_err(|err| {
+                tracing::error!("Login throttle DB error: {:?}", err);
+                StatusCode::INTERNAL_SERVER_ERROR
+            })?;
+        if let Some(until) = locked_until {
+            tracing::warn!("🔒 Account {} locked until {} after failed logins", user.id, until);
+        }
+        audit_login(&state, &payload.email, &ip, Some(user.id), LoginOutcome::BadCredentials).await?;
         return Err(StatusCode::UNAUTHORIZED);
     }
 
     if !user.is_active {
+        audit_login(&state, &payload.email, &ip, Some(user.id), LoginOutcome::Disabled).await?;
         return Err(StatusCode::FORBIDDEN); // User is disabled
     }
 
-    let token = create_token(
+    upgrade_password_hash(&state, &user, &payload.password).await;
+
+    login_throttle_service::register_success(&state.db, user.id)
+        .await
+        .map_err(|err| {
+            tracing::error!("Login throttle DB error: {:?}", err);
+            StatusCode::INTERNAL_SERVER_ERROR
+        })?;
+    audit_login(&state, &payload.email, &ip, Some(user.id), LoginOutcome::Success).await?;
+
+    Ok(Json(complete_login(&state, &user, &client).await?))
+}
+
+/// Re-hash a password stored as bcrypt or with older Argon2 parameters,
+/// now that we have it in plain text. Failures are logged, not fatal.
+async fn upgrade_password_hash(state: &SharedState, user: &User, password: &str) {
+    if !needs_rehash(&user.password_hash) {
+        return;
+    }
+
+    let new_hash = match hash_password(password) {
+        Ok(hash) => hash,
+        Err(err) => {
+            tracing::error!("Password rehash failed: {:?}", err);
+            return;
+        }
+    };
+
+    match user_service::upgrade_password_hash(&state.db, user.id, &user.password_hash, &new_hash).await {
+        Ok(()) => tracing::info!("🔐 Upgraded password hash of user {}", user.id),
+        Err(err) => tracing::error!("Password hash upgrade DB error: {:?}", err),
+    }
+}
+
+async fn audit_login(
+    state: &SharedState,
+    email: &str,
+    ip: &str,
+    user_id: Option<Uuid>,
+    outcome: LoginOutcome,
+) -> Result<(), StatusCode> {
+    login_throttle_service::record_attempt(&state.db, email, user_id, ip, outcome)
+        .await
+        .map_err(|err| {
+            tracing::error!("Login audit DB error: {:?}", err);
+            StatusCode::INTERNAL_SERVER_ERROR
+        })
+}
+
+/// POST /auth/refresh - Rotate the refresh token and mint a new access token
+pub async fn refresh(
+    State(state): State<SharedState>,
+    Json(payload): Json<RefreshRequest>,
+) -> Result<Json<AuthResponse>, StatusCode> {
+    let issued = session_service::rotate_refresh_token(
+        &state.db,
+        &payload.refresh_token,
+        state.config.refresh_token_ttl(),
+    )
+    .await
+    .map_err(|err| match err {
+        SessionError::InvalidToken | SessionError::TokenReused | SessionError::NotFound => {
+            StatusCode::UNAUTHORIZED
+        }
+        SessionError::Database(err) => {
+            tracing::error!("Refresh DB error: {:?}", err);
+            StatusCode::INTERNAL_SERVER_ERROR
+        }
+    })?;
+
+    let user = query_as_unchecked!(
+        User,
+        "SELECT * FROM users WHERE id = $1",
+        issued.user_id
+    )
+    .fetch_optional(&state.db)
+    .await
+    .map_err(|err| {
+        tracing::error!("Refresh DB error: {:?}", err);
+        StatusCode::INTERNAL_SERVER_ERROR
+    })?
+    .ok_or(StatusCode::UNAUTHORIZED)?;
+
+    if !user.is_active {
+        return Err(StatusCode::FORBIDDEN);
+    }
+
+    let token = access_token(&state, &user, issued.session_id).await?;
+
+    Ok(Json(AuthResponse {
+        token,
+        refresh_token: issued.refresh_token,
+    }))
+}
+
+/// POST /auth/logout - Revoke the session behind a refresh token
+pub async fn logout(
+    State(state): State<SharedState>,
+    Json(payload): Json<LogoutRequest>,
+) -> Result<StatusCode, StatusCode> {
+    session_service::revoke_by_refresh_token(&state.db, &payload.refresh_token)
+        .await
+        .map_err(|err| {
+            tracing::error!("Logout DB error: {:?}", err);
+            StatusCode::INTERNAL_SERVER_ERROR
+        })?;
+
+    // Unknown or already revoked tokens are not an error: the client is logged out either way
+    Ok(StatusCode::NO_CONTENT)
+}
+
+/// POST /auth/forgot-password - Email a password reset link.
+/// Always answers 202 with the same body so it can't be used to probe for accounts.
+pub async fn forgot_password(
+    State(state): State<SharedState>,
+    Json(payload): Json<ForgotPasswordRequest>,
+) -> Result<(StatusCode, Json<Value>), StatusCode> {
+    let request = password_reset_service::request_reset(&state.db, payload.email.trim())
+        .await
+        .map_err(|err| {
+            tracing::error!("Forgot password DB error: {:?}", err);
+            StatusCode::INTERNAL_SERVER_ERROR
+        })?;
+
+    if let Some(request) = request {
+        let link = format!(
+            "{}/reset-password?token={}",
+            state.config.frontend_url.trim_end_matches('/'),
+            request.token
+        );
+        let email = Email {
+            to: request.email,
+            subject: "Reset your password".into(),
+            body: format!(
+                "Hi {},\n\nUse the link below to choose a new password. \
+                 It expires in {} minutes and can only be used once.\n\n{}\n\n\
+                 If you didn't ask for this, you can ignore this email.",
+                request.name, RESET_TOKEN_TTL_MINUTES, link
+            ),
+        };
+
+        // Send in the background so response time doesn't depend on whether the account exists
+        let mailer = state.mailer.clone();
+        tokio::spawn(async move {
+            if let Err(err) = mailer.send(email).await {
+                tracing::error!("Failed to send password reset email: {:?}", err);
+            }
+        });
+    }
+
+    Ok((
+        StatusCode::ACCEPTED,
+        Json(json!({
+            "message": "If an account exists for this email, a reset link has been sent."
+        })),
+    ))
+}
+
+/// POST /auth/reset-password - Set a new password with a reset token
+pub async fn reset_password(
+    State(state): State<SharedState>,
+    Json(payload): Json<ResetPasswordRequest>,
+) -> Result<StatusCode, ApiError> {
+    state.password_policy.check(&payload.new_password)?;
+
+    let password_hash = hash_password(&payload.new_password)
+        .map_err(|_| StatusCode::INTERNAL_SERVER_ERROR)?;
+
+    password_reset_service::reset_password(&state.db, &payload.token, &password_hash)
+        .await
+        .map_err(|err| match err {
+            PasswordResetError::InvalidToken => StatusCode::BAD_REQUEST,
+            PasswordResetError::Database(err) => {
+                tracing::error!("Reset password DB error: {:?}", err);
+                StatusCode::INTERNAL_SERVER_ERROR
+            }
+        })?;
+
+    Ok(StatusCode::NO_CONTENT)
+}
+
+/// Finish a login whose password (or invitation) check passed: hand out
+/// tokens, or an interim MFA token if a second factor is enabled or required
+pub(crate) async fn complete_login(
+    state: &SharedState,
+    user: &User,
+    client: &ClientInfo,
+) -> Result<LoginResponse, StatusCode> {
+    let mfa_enabled = mfa_service::is_enabled(&state.db, user.id)
+        .await
+        .map_err(|err| {
+            tracing::error!("MFA status DB error: {:?}", err);
+            StatusCode::INTERNAL_SERVER_ERROR
+        })?;
+
+    let purpose = if mfa_enabled {
+        Some(ChallengePurpose::Verify)
+    } else {
+        let required = settings_service::is_mfa_required_for(&state.db, &user.role)
+            .await
+            .map_err(|err| {
+                tracing::error!("MFA settings DB error: {:?}", err);
+                StatusCode::INTERNAL_SERVER_ERROR
+            })?;
+        required.then_some(ChallengePurpose::Enroll)
+    };
+
+    let Some(purpose) = purpose else {
+        return Ok(LoginResponse::Authenticated(issue_tokens(state, user, client).await?));
+    };
+
+    let mfa_token = mfa_service::create_challenge(&state.db, user.id, purpose)
+        .await
+        .map_err(|err| {
+            tracing::error!("MFA challenge DB error: {:?}", err);
+            StatusCode::INTERNAL_SERVER_ERROR
+        })?;
+
+    Ok(LoginResponse::MfaRequired(MfaChallengeResponse {
+        mfa_required: true,
+        enrollment_required: purpose == ChallengePurpose::Enroll,
+        mfa_token,
+        expires_in: CHALLENGE_TTL_MINUTES * 60,
+    }))
+}
+
+/// Sign a short-lived access token for `user` in session `session_id`
+async fn access_token(state: &SharedState, user: &User, session_id: Uuid) -> Result<String, StatusCode> {
+    let key = state.keys.signing_key(&state.db).await.map_err(|err| {
+        tracing::error!("Signing key error: {:?}", err);
+        StatusCode::INTERNAL_SERVER_ERROR
+    })?;
+
+    create_token(
         user.id,
         user.email.clone(),
-        user.role.clone(), // ✅ Fixed
-        &state.config.jwt_secret,
+        user.role.clone(),
+        session_id,
+        &key,
+        state.config.access_token_ttl(),
+    )
+    .map_err(|_| StatusCode::INTERNAL_SERVER_ERROR)
+}
+
+/// Start a new session for `user` on `client`'s device and return its
+/// access + refresh token pair
+pub(crate) async fn issue_tokens(
+    state: &SharedState,
+    user: &User,
+    client: &ClientInfo,
+) -> Result<AuthResponse, StatusCode> {
+    let issued = session_service::create_session(
+        &state.db,
+        user.id,
+        state.config.refresh_token_ttl(),
+        client,
     )
-    .map_err(|_| StatusCode::INTERNAL_SERVER_ERROR)?;
+    .await
+    .map_err(|err| {
+        tracing::error!("Session DB error: {:?}", err);
+        StatusCode::INTERNAL_SERVER_ERROR
+    })?;
+
+    let token = access_token(state, user, issued.session_id).await?;
 
-    Ok(Json(AuthResponse { token }))
+    Ok(AuthResponse {
+        token,
+        refresh_token: issued.refresh_token,
+    })
 }
@@ -0,0 +1,153 @@
+use axum::{
+    extract::{Path, State},
+    http::StatusCode,
+    Json,
+};
+use chrono::NaiveDate;
+use uuid::Uuid;
+use validator::Validate;
+
+use crate::{
+    dto::business_calendar_dto::{BusinessCalendarRequest, HolidayRequest},
+    middleware::auth::Principal,
+    models::business_calendar::{BusinessCalendar, Holiday},
+    services::business_calendar_service::{self, CalendarError, CalendarInput},
+    state::SharedState,
+};
+
+fn calendar_error_status(err: CalendarError) -> StatusCode {
+    match err {
+        CalendarError::NotFound => StatusCode::NOT_FOUND,
+        CalendarError::InvalidTimezone(_) | CalendarError::DuplicateHoliday(_) => StatusCode::BAD_REQUEST,
+        CalendarError::AlreadyExists | CalendarError::InUse => StatusCode::CONFLICT,
+        CalendarError::Database(err) => {
+            tracing::error!("DB error handling business calendars: {:?}", err);
+            StatusCode::INTERNAL_SERVER_ERROR
+        }
+    }
+}
+
+fn validated<T: Validate>(payload: T) -> Result<T, StatusCode> {
+    if let Err(errors) = payload.validate() {
+        tracing::warn!("Validation failed for business calendar: {:?}", errors);
+        return Err(StatusCode::BAD_REQUEST);
+    }
+
+    Ok(payload)
+}
+
+fn holiday(payload: HolidayRequest) -> Holiday {
+    Holiday {
+        date: payload.date,
+        name: payload.name,
+    }
+}
+
+fn calendar_input(payload: BusinessCalendarRequest) -> Result<CalendarInput, StatusCode> {
+    let payload = validated(payload)?;
+
+    Ok(CalendarInput {
+        name: payload.name,
+        timezone: payload.timezone,
+        hours: payload.hours,
+        holidays: payload.holidays.into_iter().map(holiday).collect(),
+    })
+}
+
+/// GET /admin/calendars
+pub async fn list_calendars(
+    State(state): State<SharedState>,
+) -> Result<Json<Vec<BusinessCalendar>>, StatusCode> {
+    let calendars = business_calendar_service::list_calendars(&state.db)
+        .await
+        .map_err(calendar_error_status)?;
+
+    Ok(Json(calendars))
+}
+
+/// GET /admin/calendars/{id}
+pub async fn get_calendar(
+    State(state): State<SharedState>,
+    Path(calendar_id): Path<Uuid>,
+) -> Result<Json<BusinessCalendar>, StatusCode> {
+    let calendar = business_calendar_service::get_calendar(&state.db, calendar_id)
+        .await
+        .map_err(calendar_error_status)?;
+
+    Ok(Json(calendar))
+}
+
+/// POST /admin/calendars - Weekly hours in a time zone, plus holidays
+pub async fn create_calendar(
+    State(state): State<SharedState>,
+    principal: Principal,
+    Json(payload): Json<BusinessCalendarRequest>,
+) -> Result<Json<BusinessCalendar>, StatusCode> {
+    let input = calendar_input(payload)?;
+    let calendar = business_calendar_service::create_calendar(&state.db, &input)
+        .await
+        .map_err(calendar_error_status)?;
+
+    tracing::info!("🗓️ Business calendar {} created by {}", calendar.id, principal.id);
+    Ok(Json(calendar))
+}
+
+/// PUT /admin/calendars/{id} - Replaces the hours and holidays too
+pub async fn update_calendar(
+    State(state): State<SharedState>,
+    principal: Principal,
+    Path(calendar_id): Path<Uuid>,
+    Json(payload): Json<BusinessCalendarRequest>,
+) -> Result<Json<BusinessCalendar>, StatusCode> {
+    let input = calendar_input(payload)?;
+    let calendar = business_calendar_service::update_calendar(&state.db, calendar_id, &input)
+        .await
+        .map_err(calendar_error_status)?;
+
+    tracing::info!("🗓️ Business calendar {} updated by {}", calendar_id, principal.id);
+    Ok(Json(calendar))
+}
+
+/// DELETE /admin/calendars/{id} - 409 while an SLA policy uses it
+pub async fn delete_calendar(
+    State(state): State<SharedState>,
+    principal: Principal,
+    Path(calendar_id): Path<Uuid>,
+) -> Result<StatusCode, StatusCode> {
+    business_calendar_service::delete_calendar(&state.db, calendar_id)
+        .await
+        .map_err(calendar_error_status)?;
+
+    tracing::info!("🗓️ Business calendar {} deleted by {}", calendar_id, principal.id);
+    Ok(StatusCode::NO_CONTENT)
+}
+
+/// POST /admin/calendars/{id}/holidays - Add a holiday (or rename the one on that date)
+pub async fn add_holiday(
+    State(state): State<SharedState>,
+    principal: Principal,
+    Path(calendar_id): Path<Uuid>,
+    Json(payload): Json<HolidayRequest>,
+) -> Result<Json<Holiday>, StatusCode> {
+    let holiday = holiday(validated(payload)?);
+    let holiday = business_calendar_service::add_holiday(&state.db, calendar_id, &holiday)
+        .await
+        .map_err(calendar_error_status)?;
+
+    tracing::info!("🗓️ Holiday {} added to calendar {} by {}", holiday.date, calendar_id, principal.id);
+    Ok(Json(holiday))
+}
+
+/// DELETE /admin/calendars/{id}/holidays/{date}
+pub async fn remove_holiday(
+    State(state): State<SharedState>,
+    principal: Principal,
+    Path((calendar_id, date)): Path<(Uuid, NaiveDate)>,
+) -> Result<StatusCode, StatusCode> {
+    business_calendar_service::remove_holiday(&state.db, calendar_id, date)
+        .await
+        .map_err(calendar_error_status)?;
+
+    tracing::info!("🗓️ Holiday {} removed from calendar {} by {}", date, calendar_id, principal.id);
+    Ok(StatusCode::NO_CONTENT)
+}
@@ -6,9 +6,9 @@ use serde::Deserialize;
 
 use crate::{
     dto::note_dto::CreateCommentRequest,
-    models::comment::Comment,
+    models::{comment::Comment, permission::Permission},
     state::SharedState,
-    middleware::auth::AuthUser, // ✅ Pull authenticated user from JWT
+    middleware::auth::Principal, // ✅ Caller and their permissions
 };
 
 #[derive(Deserialize)]
@@ -19,9 +19,11 @@ pub struct UpdateCommentRequest {
 // ✅ POST /comments - Create new comment (auth required)
 pub async fn add_comment(
     State(state): State<SharedState>,
-    AuthUser(user): AuthUser,                          // ✅ Extract user from token
+    principal: Principal,
     Json(payload): Json<CreateCommentRequest>,
 ) -> Result<Json<Comment>, StatusCode> {
+    principal.require(Permission::NotesWrite)?;
+
     let comment = query_as_unchecked!(
         Comment,
         r#"
@@ -30,7 +32,7 @@ pub async fn add_comment(
         RETURNING *
         "#,
         payload.note_id,
-        user.id,                                        // ✅ Use ID from token
+        principal.id,                                   // ✅ Use ID from token
         payload.content
     )
     .fetch_one(&state.db)
@@ -47,7 +49,10 @@ pub async fn add_comment(
 pub async fn get_comments_by_note(
     State(state): State<SharedState>,
     Path(note_id): Path<Uuid>,
+    principal: Principal,
 ) -> Result<Json<Vec<Comment>>, StatusCode> {
+    principal.require(Permission::NotesRead)?;
+
     let comments = query_as_unchecked!(
         Comment,
         r#"
@@ -71,7 +76,10 @@ pub async fn get_comments_by_note(
 pub async fn delete_comment(
     State(state): State<SharedState>,
     Path(comment_id): Path<Uuid>,
+    principal: Principal,
 ) -> Result<StatusCode, StatusCode> {
+    principal.require(Permission::NotesWrite)?;
+
     let result = sqlx::query!(
         r#"
         DELETE FROM comments
@@ -95,8 +103,11 @@ pub async fn delete_comment(
 pub async fn update_comment(
     State(state): State<SharedState>,
     Path(comment_id): Path<Uuid>,
+    principal: Principal,
     Json(payload): Json<UpdateCommentRequest>,
 ) -> Result<Json<Comment>, StatusCode> {
+    principal.require(Permission::NotesWrite)?;
+
     let comment = query_as_unchecked!(
         Comment,
         r#"
@@ -0,0 +1,112 @@
+use axum::{
+    extract::{Path, State},
+    http::StatusCode,
+    Json,
+};
+use uuid::Uuid;
+use validator::Validate;
+
+use crate::{
+    dto::custom_field_dto::{CustomFieldRequest, UpdateCustomFieldRequest},
+    middleware::auth::Principal,
+    models::custom_field::CustomField,
+    services::custom_field_service::{self, CustomFieldError, FieldInput, FieldUpdate},
+    state::SharedState,
+};
+
+pub(crate) fn custom_field_error_status(err: CustomFieldError) -> StatusCode {
+    match err {
+        CustomFieldError::NotFound => StatusCode::NOT_FOUND,
+        CustomFieldError::AlreadyExists => StatusCode::CONFLICT,
+        CustomFieldError::InvalidKey
+        | CustomFieldError::InvalidOptions
+        | CustomFieldError::UnknownField(_)
+        | CustomFieldError::InvalidValue { .. } => {
+            tracing::warn!("Rejected custom field input: {}", err);
+            StatusCode::BAD_REQUEST
+        }
+        CustomFieldError::Database(err) => {
+            tracing::error!("DB error handling custom fields: {:?}", err);
+            StatusCode::INTERNAL_SERVER_ERROR
+        }
+    }
+}
+
+fn validated<T: Validate>(payload: T) -> Result<T, StatusCode> {
+    if let Err(errors) = payload.validate() {
+        tracing::warn!("Validation failed for custom field: {:?}", errors);
+        return Err(StatusCode::BAD_REQUEST);
+    }
+
+    Ok(payload)
+}
+
+/// GET /custom-fields - Field definitions, for ticket forms and filters
+pub async fn list_fields(
+    State(state): State<SharedState>,
+) -> Result<Json<Vec<CustomField>>, StatusCode> {
+    let fields = custom_field_service::list_fields(&state.db)
+        .await
+        .map_err(custom_field_error_status)?;
+
+    Ok(Json(fields))
+}
+
+/// POST /admin/custom-fields
+pub async fn create_field(
+    State(state): State<SharedState>,
+    principal: Principal,
+    Json(payload): Json<CustomFieldRequest>,
+) -> Result<Json<CustomField>, StatusCode> {
+    let payload = validated(payload)?;
+    let input = FieldInput {
+        key: payload.key,
+        label: payload.label,
+        field_type: payload.field_type,
+        options: payload.options,
+        required_on_close: payload.required_on_close,
+        position: payload.position,
+    };
+    let field = custom_field_service::create_field(&state.db, &input)
+        .await
+        .map_err(custom_field_error_status)?;
+
+    tracing::info!("🏷️ Custom field {} created by {}", field.key, principal.id);
+    Ok(Json(field))
+}
+
+/// PUT /admin/custom-fields/{id} - Key and type stay as they are
+pub async fn update_field(
+    State(state): State<SharedState>,
+    principal: Principal,
+    Path(field_id): Path<Uuid>,
+    Json(payload): Json<UpdateCustomFieldRequest>,
+) -> Result<Json<CustomField>, StatusCode> {
+    let payload = validated(payload)?;
+    let input = FieldUpdate {
+        label: payload.label,
+        options: payload.options,
+        required_on_close: payload.required_on_close,
+        position: payload.position,
+    };
+    let field = custom_field_service::update_field(&state.db, field_id, &input)
+        .await
+        .map_err(custom_field_error_status)?;
+
+    tracing::info!("🏷️ Custom field {} updated by {}", field.key, principal.id);
+    Ok(Json(field))
+}
+
+/// DELETE /admin/custom-fields/{id} - Drops every ticket's value too
+pub async fn delete_field(
+    State(state): State<SharedState>,
+    principal: Principal,
+    Path(field_id): Path<Uuid>,
+) -> Result<StatusCode, StatusCode> {
+    custom_field_service::delete_field(&state.db, field_id)
+        .await
+        .map_err(custom_field_error_status)?;
+
+    tracing::info!("🏷️ Custom field {} deleted by {}", field_id, principal.id);
+    Ok(StatusCode::NO_CONTENT)
+}
@@ -0,0 +1,112 @@
+use axum::{
+    extract::{Path, State},
+    http::StatusCode,
+    Json,
+};
+use chrono::Duration;
+use uuid::Uuid;
+use validator::Validate;
+
+use crate::{
+    dto::{
+        auth_dto::LoginResponse,
+        invitation_dto::{AcceptInvitationRequest, CreateInvitationRequest, CreateInvitationResponse},
+    },
+    error::ApiError,
+    handlers::{auth_handler::complete_login, role_handler::ensure_can_grant_role},
+    middleware::auth::Principal,
+    models::{invitation::Invitation, session::ClientInfo},
+    services::invitation_service::{self, InvitationError, DEFAULT_EXPIRY_HOURS},
+    state::SharedState,
+    utils::hash::hash_password,
+};
+
+fn invitation_error_status(err: InvitationError) -> StatusCode {
+    match err {
+        InvitationError::InvalidRole => StatusCode::BAD_REQUEST,
+        InvitationError::EmailTaken => StatusCode::CONFLICT,
+        InvitationError::NotFound => StatusCode::NOT_FOUND,
+        InvitationError::InvalidToken => StatusCode::GONE,
+        InvitationError::Database(err) => {
+            tracing::error!("DB error handling invitation: {:?}", err);
+            StatusCode::INTERNAL_SERVER_ERROR
+        }
+    }
+}
+
+/// POST /admin/invitations - Invite a new staff member into any non-customer role
+pub async fn create_invitation(
+    State(state): State<SharedState>,
+    principal: Principal,
+    Json(payload): Json<CreateInvitationRequest>,
+) -> Result<Json<CreateInvitationResponse>, StatusCode> {
+    if let Err(errors) = payload.validate() {
+        tracing::warn!("Validation failed for create_invitation: {:?}", errors);
+        return Err(StatusCode::BAD_REQUEST);
+    }
+
+    let hours = payload.expires_in_hours.unwrap_or(DEFAULT_EXPIRY_HOURS);
+    if hours <= 0 {
+        return Err(StatusCode::BAD_REQUEST);
+    }
+
+    ensure_can_grant_role(&state, &principal, &payload.role).await?;
+
+    let (invitation, token) = invitation_service::create_invitation(
+        &state.db,
+        &payload.email,
+        &payload.role,
+        principal.id,
+        Duration::hours(hours),
+    )
+    .await
+    .map_err(invitation_error_status)?;
+
+    Ok(Json(CreateInvitationResponse { invitation, token }))
+}
+
+/// GET /admin/invitations - List all invitations
+pub async fn list_invitations(
+    State(state): State<SharedState>,
+) -> Result<Json<Vec<Invitation>>, StatusCode> {
+    let invitations = invitation_service::list_invitations(&state.db)
+        .await
+        .map_err(invitation_error_status)?;
+
+    Ok(Json(invitations))
+}
+
+/// DELETE /admin/invitations/{id} - Revoke a pending invitation
+pub async fn revoke_invitation(
+    State(state): State<SharedState>,
+    Path(invitation_id): Path<Uuid>,
+) -> Result<Json<Invitation>, StatusCode> {
+    let invitation = invitation_service::revoke_invitation(&state.db, invitation_id)
+        .await
+        .map_err(invitation_error_status)?;
+
+    Ok(Json(invitation))
+}
+
+/// POST /auth/invitations/accept - Create the invited account and log it in
+pub async fn accept_invitation(
+    State(state): State<SharedState>,
+    client: ClientInfo,
+    Json(payload): Json<AcceptInvitationRequest>,
+) -> Result<Json<LoginResponse>, ApiError> {
+    state.password_policy.check(&payload.password)?;
+
+    let password_hash = hash_password(&payload.password)
+        .map_err(|_| StatusCode::INTERNAL_SERVER_ERROR)?;
+
+    let user = invitation_service::accept_invitation(
+        &state.db,
+        &payload.token,
+        &payload.name,
+        &password_hash,
+    )
+    .await
+    .map_err(invitation_error_status)?;
+
+    Ok(Json(complete_login(&state, &user, &client).await?))
+}
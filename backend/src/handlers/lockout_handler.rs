@@ -0,0 +1,98 @@
+use axum::{
+    extract::{Path, Query, State},
+    http::StatusCode,
+    Json,
+};
+use serde::{Deserialize, Serialize};
+use uuid::Uuid;
+
+use crate::{
+    middleware::auth::Principal,
+    models::login_attempt::{LockedAccount, LoginAttempt, ThrottledIp},
+    services::login_throttle_service::{self, LoginThrottleError},
+    state::SharedState,
+};
+
+fn throttle_error_status(err: LoginThrottleError) -> StatusCode {
+    match err {
+        LoginThrottleError::UserNotFound => StatusCode::NOT_FOUND,
+        LoginThrottleError::Database(err) => {
+            tracing::error!("DB error handling lockouts: {:?}", err);
+            StatusCode::INTERNAL_SERVER_ERROR
+        }
+    }
+}
+
+#[derive(Debug, Serialize)]
+pub struct LockoutsResponse {
+    pub accounts: Vec<LockedAccount>,
+    pub ips: Vec<ThrottledIp>,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct LoginAttemptsQuery {
+    pub email: Option<String>,
+    pub ip: Option<String>,
+    pub limit: Option<i64>,
+}
+
+/// GET /admin/lockouts - Accounts and IPs currently locked out
+pub async fn list_lockouts(
+    State(state): State<SharedState>,
+) -> Result<Json<LockoutsResponse>, StatusCode> {
+    let accounts = login_throttle_service::locked_accounts(&state.db)
+        .await
+        .map_err(throttle_error_status)?;
+    let ips = login_throttle_service::throttled_ips(&state.db)
+        .await
+        .map_err(throttle_error_status)?;
+
+    Ok(Json(LockoutsResponse { accounts, ips }))
+}
+
+/// GET /admin/login-attempts?email=&ip=&limit= - Login audit log, newest first
+pub async fn list_login_attempts(
+    State(state): State<SharedState>,
+    Query(query): Query<LoginAttemptsQuery>,
+) -> Result<Json<Vec<LoginAttempt>>, StatusCode> {
+    let limit = query.limit.unwrap_or(100).clamp(1, 1000);
+
+    let attempts = login_throttle_service::recent_attempts(
+        &state.db,
+        query.email.as_deref(),
+        query.ip.as_deref(),
+        limit,
+    )
+    .await
+    .map_err(throttle_error_status)?;
+
+    Ok(Json(attempts))
+}
+
+/// DELETE /admin/lockouts/users/{id} - Unlock an account
+pub async fn clear_account_lockout(
+    State(state): State<SharedState>,
+    principal: Principal,
+    Path(user_id): Path<Uuid>,
+) -> Result<StatusCode, StatusCode> {
+    login_throttle_service::clear_account(&state.db, user_id)
+        .await
+        .map_err(throttle_error_status)?;
+
+    tracing::info!("🔓 Admin {} cleared lockout for user {}", principal.id, user_id);
+    Ok(StatusCode::NO_CONTENT)
+}
+
+/// DELETE /admin/lockouts/ips/{ip} - Stop throttling an IP
+pub async fn clear_ip_lockout(
+    State(state): State<SharedState>,
+    principal: Principal,
+    Path(ip): Path<String>,
+) -> Result<StatusCode, StatusCode> {
+    login_throttle_service::clear_ip(&state.db, &ip)
+        .await
+        .map_err(throttle_error_status)?;
+
+    tracing::info!("🔓 Admin {} cleared lockout for IP {}", principal.id, ip);
+    Ok(StatusCode::NO_CONTENT)
+}
@@ -9,16 +9,24 @@ use uuid::Uuid;
 
 use crate::{
     dto::message_dto::CreateMessageRequest,
+    handlers::ticket_handler::{fetch_visible, reply_side},
+    middleware::auth::Principal,
     models::message::{Message, MessageWithSender},
     state::SharedState,
-    services::collaboration_service::{get_messages_by_ticket},
+    services::{collaboration_service::get_messages_by_ticket, ticket_service::{self, ReplySide}},
 };
 
 /// POST /messages
+///
+/// The sender is the caller, and whether the message is from the customer
+/// follows from who the caller is on this ticket.
 pub async fn send_message(
     State(state): State<SharedState>,
+    principal: Principal,
     Json(payload): Json<CreateMessageRequest>,
 ) -> Result<Json<Message>, StatusCode> {
+    let ticket = fetch_visible(&state, &principal, payload.ticket_id).await?;
+    let side = reply_side(&principal, &ticket);
     let now = Utc::now();
 
     let message = query_as_unchecked!(
@@ -49,9 +57,9 @@ pub async fn send_message(
             message_id, external_sender_email, is_email, created_at
         "#,
         payload.ticket_id,
-        payload.sender_id,
+        principal.id,
         payload.content,
-        payload.is_from_customer,
+        side == ReplySide::Customer,
         payload.channel,
         payload.in_reply_to,
         payload.subject,
@@ -68,6 +76,11 @@ pub async fn send_message(
         StatusCode::INTERNAL_SERVER_ERROR
     })?;
 
+    // The message is saved either way
+    if let Err(err) = ticket_service::record_reply(&state.db, message.ticket_id, side, principal.id).await {
+        tracing::error!("Error updating ticket after reply: {:?}", err);
+    }
+
     // ✅ Broadcast via WebSocket to subscribed clients
     if let Some(tx) = state.ws_channels.read().await.get(&message.ticket_id) {
         if let Ok(msg_json) = serde_json::to_string(&message) {
@@ -82,7 +95,10 @@ pub async fn send_message(
 pub async fn get_messages(
     State(state): State<SharedState>,
     Path(ticket_id): Path<Uuid>,
+    principal: Principal,
 ) -> Result<Json<Vec<MessageWithSender>>, StatusCode> {
+    fetch_visible(&state, &principal, ticket_id).await?;
+
     let messages = get_messages_by_ticket(&state.db, ticket_id)
         .await
         .map_err(|err| {
@@ -0,0 +1,250 @@
+use axum::{extract::State, http::StatusCode, Json};
+use sqlx::query_as_unchecked;
+use uuid::Uuid;
+
+use crate::{
+    dto::{
+        auth_dto::AuthResponse,
+        mfa_dto::{
+            MfaCodeRequest, MfaEnrollRequest, MfaEnrollResponse, MfaSettings, MfaStatusResponse,
+            MfaVerifyRequest, RecoveryCodesResponse, TotpSetupResponse,
+        },
+    },
+    handlers::auth_handler::issue_tokens,
+    middleware::auth::Principal,
+    models::{session::ClientInfo, user::User},
+    services::{
+        mfa_service::{self, ChallengePurpose, MfaError, TotpSetup},
+        settings_service::{self, SettingsError},
+    },
+    state::SharedState,
+};
+
+fn mfa_error_status(err: MfaError) -> StatusCode {
+    match err {
+        MfaError::InvalidChallenge => StatusCode::UNAUTHORIZED,
+        MfaError::InvalidCode => StatusCode::BAD_REQUEST,
+        MfaError::AlreadyEnabled | MfaError::NotEnabled | MfaError::SetupNotStarted => {
+            StatusCode::CONFLICT
+        }
+        MfaError::Database(err) => {
+            tracing::error!("DB error handling MFA: {:?}", err);
+            StatusCode::INTERNAL_SERVER_ERROR
+        }
+    }
+}
+
+fn settings_error_status(err: SettingsError) -> StatusCode {
+    match err {
+        SettingsError::UnknownRole(_) => StatusCode::BAD_REQUEST,
+        SettingsError::Database(err) => {
+            tracing::error!("DB error handling settings: {:?}", err);
+            StatusCode::INTERNAL_SERVER_ERROR
+        }
+    }
+}
+
+impl From<TotpSetup> for TotpSetupResponse {
+    fn from(setup: TotpSetup) -> Self {
+        Self {
+            secret: setup.secret,
+            otpauth_uri: setup.otpauth_uri,
+        }
+    }
+}
+
+async fn load_active_user(state: &SharedState, user_id: Uuid) -> Result<User, StatusCode> {
+    let user = query_as_unchecked!(User, "SELECT * FROM users WHERE id = $1", user_id)
+        .fetch_optional(&state.db)
+        .await
+        .map_err(|err| {
+            tracing::error!("MFA user lookup DB error: {:?}", err);
+            StatusCode::INTERNAL_SERVER_ERROR
+        })?
+        .ok_or(StatusCode::UNAUTHORIZED)?;
+
+    if !user.is_active {
+        return Err(StatusCode::FORBIDDEN);
+    }
+
+    Ok(user)
+}
+
+// ---------------------------------------------------------------------------
+// Second login step (interim token from /auth/login)
+// ---------------------------------------------------------------------------
+
+/// POST /auth/mfa/verify - Exchange the interim token and a TOTP/recovery code for tokens
+pub async fn verify_login(
+    State(state): State<SharedState>,
+    client: ClientInfo,
+    Json(payload): Json<MfaVerifyRequest>,
+) -> Result<Json<AuthResponse>, StatusCode> {
+    let (user_id, _) = mfa_service::complete_challenge(
+        &state.db,
+        &payload.mfa_token,
+        ChallengePurpose::Verify,
+        &payload.code,
+    )
+    .await
+    .map_err(|err| match err {
+        // Wrong code at login is an authentication failure, not a bad request
+        MfaError::InvalidCode | MfaError::NotEnabled => StatusCode::UNAUTHORIZED,
+        other => mfa_error_status(other),
+    })?;
+
+    let user = load_active_user(&state, user_id).await?;
+    Ok(Json(issue_tokens(&state, &user, &client).await?))
+}
+
+/// POST /auth/mfa/enroll - Get a TOTP secret when MFA is mandatory but not set up yet
+pub async fn begin_enrollment(
+    State(state): State<SharedState>,
+    Json(payload): Json<MfaEnrollRequest>,
+) -> Result<Json<TotpSetupResponse>, StatusCode> {
+    let setup = mfa_service::begin_enrollment(&state.db, &payload.mfa_token, &state.config.mfa_issuer)
+        .await
+        .map_err(mfa_error_status)?;
+
+    Ok(Json(setup.into()))
+}
+
+/// POST /auth/mfa/enroll/confirm - Confirm the new secret and finish logging in
+pub async fn confirm_enrollment(
+    State(state): State<SharedState>,
+    client: ClientInfo,
+    Json(payload): Json<MfaVerifyRequest>,
+) -> Result<Json<MfaEnrollResponse>, StatusCode> {
+    let (user_id, recovery_codes) = mfa_service::complete_challenge(
+        &state.db,
+        &payload.mfa_token,
+        ChallengePurpose::Enroll,
+        &payload.code,
+    )
+    .await
+    .map_err(mfa_error_status)?;
+
+    let user = load_active_user(&state, user_id).await?;
+    let tokens = issue_tokens(&state, &user, &client).await?;
+
+    Ok(Json(MfaEnrollResponse {
+        token: tokens.token,
+        refresh_token: tokens.refresh_token,
+        recovery_codes: recovery_codes.unwrap_or_default(),
+    }))
+}
+
+// ---------------------------------------------------------------------------
+// Managing your own second factor (logged in)
+// ---------------------------------------------------------------------------
+
+/// GET /auth/mfa - Two-factor status for the current user
+pub async fn get_status(
+    State(state): State<SharedState>,
+    principal: Principal,
+) -> Result<Json<MfaStatusResponse>, StatusCode> {
+    let enabled = mfa_service::is_enabled(&state.db, principal.id)
+        .await
+        .map_err(mfa_error_status)?;
+    let recovery_codes_remaining = mfa_service::remaining_recovery_codes(&state.db, principal.id)
+        .await
+        .map_err(mfa_error_status)?;
+    let required = settings_service::is_mfa_required_for(&state.db, &principal.role)
+        .await
+        .map_err(settings_error_status)?;
+
+    Ok(Json(MfaStatusResponse {
+        enabled,
+        required,
+        recovery_codes_remaining,
+    }))
+}
+
+/// POST /auth/mfa/totp/setup - Start TOTP setup
+pub async fn setup_totp(
+    State(state): State<SharedState>,
+    principal: Principal,
+) -> Result<Json<TotpSetupResponse>, StatusCode> {
+    let setup = mfa_service::begin_setup(&state.db, principal.id, &principal.email, &state.config.mfa_issuer)
+        .await
+        .map_err(mfa_error_status)?;
+
+    Ok(Json(setup.into()))
+}
+
+/// POST /auth/mfa/totp/confirm - Confirm TOTP setup with a code from the app
+pub async fn confirm_totp(
+    State(state): State<SharedState>,
+    principal: Principal,
+    Json(payload): Json<MfaCodeRequest>,
+) -> Result<Json<RecoveryCodesResponse>, StatusCode> {
+    let recovery_codes = mfa_service::confirm_setup(&state.db, principal.id, &payload.code)
+        .await
+        .map_err(mfa_error_status)?;
+
+    Ok(Json(RecoveryCodesResponse { recovery_codes }))
+}
+
+/// POST /auth/mfa/totp/disable - Turn TOTP off (not allowed where it is mandatory)
+pub async fn disable_totp(
+    State(state): State<SharedState>,
+    principal: Principal,
+    Json(payload): Json<MfaCodeRequest>,
+) -> Result<StatusCode, StatusCode> {
+    let required = settings_service::is_mfa_required_for(&state.db, &principal.role)
+        .await
+        .map_err(settings_error_status)?;
+    if required {
+        return Err(StatusCode::FORBIDDEN);
+    }
+
+    mfa_service::disable(&state.db, principal.id, &payload.code)
+        .await
+        .map_err(mfa_error_status)?;
+
+    Ok(StatusCode::NO_CONTENT)
+}
+
+/// POST /auth/mfa/recovery-codes - Replace all recovery codes
+pub async fn regenerate_recovery_codes(
+    State(state): State<SharedState>,
+    principal: Principal,
+    Json(payload): Json<MfaCodeRequest>,
+) -> Result<Json<RecoveryCodesResponse>, StatusCode> {
+    let recovery_codes = mfa_service::regenerate_recovery_codes(&state.db, principal.id, &payload.code)
+        .await
+        .map_err(mfa_error_status)?;
+
+    Ok(Json(RecoveryCodesResponse { recovery_codes }))
+}
+
+// ---------------------------------------------------------------------------
+// Admin policy
+// ---------------------------------------------------------------------------
+
+/// GET /admin/settings/mfa - Roles that must use two-factor authentication
+pub async fn get_mfa_settings(
+    State(state): State<SharedState>,
+) -> Result<Json<MfaSettings>, StatusCode> {
+    let required_roles = settings_service::mfa_required_roles(&state.db)
+        .await
+        .map_err(settings_error_status)?;
+
+    Ok(Json(MfaSettings { required_roles }))
+}
+
+/// PUT /admin/settings/mfa - Change which roles must use two-factor authentication
+pub async fn update_mfa_settings(
+    State(state): State<SharedState>,
+    principal: Principal,
+    Json(payload): Json<MfaSettings>,
+) -> Result<Json<MfaSettings>, StatusCode> {
+    let required_roles =
+        settings_service::set_mfa_required_roles(&state.db, &payload.required_roles, principal.id)
+            .await
+            .map_err(settings_error_status)?;
+
+    tracing::info!("🔐 MFA now required for roles {:?} (set by {})", required_roles, principal.id);
+
+    Ok(Json(MfaSettings { required_roles }))
+}
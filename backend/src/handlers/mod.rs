@@ -12,3 +12,19 @@ pub mod ws_handler;
 pub mod user_handler;
 pub mod protected;
 pub mod agent_ticket_handler;
+pub mod invitation_handler;
+pub mod mfa_handler;
+pub mod lockout_handler;
+pub mod api_key_handler;
+pub mod role_handler;
+pub mod oidc_handler;
+
+pub mod signing_key_handler;
+pub mod session_handler;
+pub mod search_handler;
+pub mod sla_handler;
+pub mod business_calendar_handler;
+pub mod routing_handler;
+pub mod team_handler;
+pub mod custom_field_handler;
+pub mod ticket_link_handler;
\ No newline at end of file
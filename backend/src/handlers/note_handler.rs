@@ -2,9 +2,9 @@ use axum::{extract::{State, Path}, Json};
 use axum::http::StatusCode;
 
 use crate::{
-    middleware::auth::AuthUser,
+    middleware::auth::Principal,
     dto::note_dto::{CreateNoteRequest, CreateCommentRequest},
-    models::{note::{Note, NoteWithAuthor}, comment::Comment},
+    models::{note::{Note, NoteWithAuthor}, comment::Comment, permission::Permission},
     state::SharedState,
 };
 use sqlx::query_as_unchecked;
@@ -12,9 +12,11 @@ use sqlx::query_as_unchecked;
 /// POST /notes - Add a new note and return it with author_email
 pub async fn add_note(
     State(state): State<SharedState>,
-    AuthUser(user): AuthUser,
+    principal: Principal,
     Json(payload): Json<CreateNoteRequest>,
 ) -> Result<Json<NoteWithAuthor>, StatusCode> {
+    principal.require(Permission::NotesWrite)?;
+
     let note = query_as_unchecked!(
         NoteWithAuthor,
         r#"
@@ -24,7 +26,7 @@ pub async fn add_note(
                   (SELECT email FROM users WHERE id = $2) AS author_email
         "#,
         payload.ticket_id,
-        user.id,
+        principal.id,
         payload.content
     )
     .fetch_one(&state.db)
@@ -40,7 +42,10 @@ pub async fn add_note(
 /// GET /notes - Fetch all notes with author_email
 pub async fn get_notes(
     State(state): State<SharedState>,
+    principal: Principal,
 ) -> Result<Json<Vec<NoteWithAuthor>>, StatusCode> {
+    principal.require(Permission::NotesRead)?;
+
     let notes = query_as_unchecked!(
         NoteWithAuthor,
         r#"
@@ -69,9 +74,11 @@ pub async fn get_notes(
 /// POST /comments - Add a comment to a note
 pub async fn add_comment(
     State(state): State<SharedState>,
-    AuthUser(user): AuthUser,
+    principal: Principal,
     Json(payload): Json<CreateCommentRequest>,
 ) -> Result<Json<Comment>, StatusCode> {
+    principal.require(Permission::NotesWrite)?;
+
     let comment = query_as_unchecked!(
         Comment,
         r#"
@@ -80,7 +87,7 @@ pub async fn add_comment(
         RETURNING *
         "#,
         payload.note_id,
-        user.id,
+        principal.id,
         payload.content
     )
     .fetch_one(&state.db)
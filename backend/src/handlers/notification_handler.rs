@@ -4,15 +4,13 @@ use axum::{
     response::IntoResponse,
     Json,
 };
-use axum_extra::extract::TypedHeader;
-use headers::{Authorization, authorization::Bearer};
 use uuid::Uuid;
 
 use crate::{
     dto::notification_dto::CreateNotificationRequest,
+    middleware::auth::Principal,
     models::notification::Notification,
     state::SharedState,
-    utils::jwt::decode_token,
 };
 use crate::services::notification_services::notify_user;
 
@@ -39,14 +37,9 @@ pub async fn create_notification(
 /// Get all notifications for the authenticated user
 pub async fn get_notifications(
     State(state): State<SharedState>,
-    TypedHeader(Authorization(bearer)): TypedHeader<Authorization<Bearer>>,
+    principal: Principal,
 ) -> Result<Json<Vec<Notification>>, StatusCode> {
-    let token = bearer.token();
-
-    let claims = decode_token(token, &state.config.jwt_secret)
-        .map_err(|_| StatusCode::UNAUTHORIZED)?;
-
-    let user_id = claims.sub;
+    let user_id = principal.id;
 
     let notifications = sqlx::query_as_unchecked!(
         Notification,
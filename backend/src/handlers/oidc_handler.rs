@@ -0,0 +1,151 @@
+use axum::{
+    extract::{Path, Query, State},
+    http::StatusCode,
+    response::Redirect,
+    Json,
+};
+use sqlx::query_as_unchecked;
+
+use crate::{
+    config::OidcProviderConfig,
+    dto::{
+        auth_dto::LoginResponse,
+        oidc_dto::{OidcCallbackQuery, OidcExchangeRequest, OidcProviderResponse},
+    },
+    handlers::auth_handler::complete_login,
+    models::{session::ClientInfo, user::User},
+    services::oidc_service::{self, OidcLoginError},
+    state::SharedState,
+};
+
+fn provider<'a>(state: &'a SharedState, name: &str) -> Result<&'a OidcProviderConfig, StatusCode> {
+    state.config.oidc_provider(name).ok_or(StatusCode::NOT_FOUND)
+}
+
+fn redirect_uri(state: &SharedState, provider: &OidcProviderConfig) -> String {
+    format!("{}/auth/oidc/{}/callback", state.config.public_url, provider.name)
+}
+
+/// Error code handed to the frontend when the callback fails
+fn callback_error_code(err: &OidcLoginError) -> &'static str {
+    match err {
+        OidcLoginError::InvalidState | OidcLoginError::InvalidHandoff => "invalid_state",
+        OidcLoginError::MissingEmail => "missing_email",
+        OidcLoginError::NoRole | OidcLoginError::UnknownRole(_) => "no_role",
+        OidcLoginError::EmailNotVerified => "email_not_verified",
+        OidcLoginError::AccountDisabled => "account_disabled",
+        OidcLoginError::Provider(_) => "provider_error",
+        OidcLoginError::Database(_) | OidcLoginError::Hash(_) => "server_error",
+    }
+}
+
+/// GET /auth/oidc/providers - Identity providers staff can sign in with
+pub async fn list_providers(State(state): State<SharedState>) -> Json<Vec<OidcProviderResponse>> {
+    let providers = state
+        .config
+        .oidc_providers
+        .iter()
+        .map(|p| OidcProviderResponse {
+            name: p.name.clone(),
+            login_url: format!("/auth/oidc/{}/login", p.name),
+        })
+        .collect();
+
+    Json(providers)
+}
+
+/// GET /auth/oidc/{provider}/login - Redirect the browser to the provider
+pub async fn login(
+    State(state): State<SharedState>,
+    Path(name): Path<String>,
+) -> Result<Redirect, StatusCode> {
+    let provider = provider(&state, &name)?;
+
+    let url = oidc_service::begin_login(&state.db, &state.oidc, provider, &redirect_uri(&state, provider))
+        .await
+        .map_err(|err| match err {
+            OidcLoginError::Provider(err) => {
+                tracing::error!("OIDC discovery for {} failed: {}", provider.name, err);
+                StatusCode::BAD_GATEWAY
+            }
+            err => {
+                tracing::error!("DB error starting OIDC login: {:?}", err);
+                StatusCode::INTERNAL_SERVER_ERROR
+            }
+        })?;
+
+    Ok(Redirect::to(&url))
+}
+
+/// GET /auth/oidc/{provider}/callback - The provider sends the browser back here.
+/// Always ends at the frontend, with `?code=` to redeem or `?error=`.
+pub async fn callback(
+    State(state): State<SharedState>,
+    Path(name): Path<String>,
+    Query(query): Query<OidcCallbackQuery>,
+) -> Result<Redirect, StatusCode> {
+    let provider = provider(&state, &name)?;
+    let frontend = format!("{}/auth/sso/callback", state.config.frontend_url);
+
+    let (Some(code), Some(login_state)) = (query.code, query.state) else {
+        let error = query.error.unwrap_or_else(|| "invalid_request".into());
+        tracing::warn!("OIDC login at {} came back with {}", provider.name, error);
+        return Ok(Redirect::to(&format!("{frontend}?error={}", urlencoding::encode(&error))));
+    };
+
+    let result = oidc_service::complete_login(
+        &state.db,
+        &state.oidc,
+        provider,
+        &redirect_uri(&state, provider),
+        &code,
+        &login_state,
+    )
+    .await;
+
+    match result {
+        Ok(handoff) => Ok(Redirect::to(&format!("{frontend}?code={handoff}"))),
+        Err(err) => {
+            match &err {
+                OidcLoginError::Database(_) | OidcLoginError::Hash(_) => {
+                    tracing::error!("OIDC login at {} failed: {:?}", provider.name, err)
+                }
+                _ => tracing::warn!("OIDC login at {} refused: {}", provider.name, err),
+            }
+            Ok(Redirect::to(&format!("{frontend}?error={}", callback_error_code(&err))))
+        }
+    }
+}
+
+/// POST /auth/oidc/exchange - Trade the handoff code for a session
+/// (or an MFA challenge, if the user's role requires one)
+pub async fn exchange(
+    State(state): State<SharedState>,
+    client: ClientInfo,
+    Json(payload): Json<OidcExchangeRequest>,
+) -> Result<Json<LoginResponse>, StatusCode> {
+    let user_id = oidc_service::redeem_handoff(&state.db, &payload.code)
+        .await
+        .map_err(|err| match err {
+            OidcLoginError::InvalidHandoff => StatusCode::BAD_REQUEST,
+            err => {
+                tracing::error!("DB error redeeming OIDC handoff: {:?}", err);
+                StatusCode::INTERNAL_SERVER_ERROR
+            }
+        })?;
+
+    let user = query_as_unchecked!(User, "SELECT * FROM users WHERE id = $1", user_id)
+        .fetch_optional(&state.db)
+        .await
+        .map_err(|err| {
+            tracing::error!("DB error loading SSO user: {:?}", err);
+            StatusCode::INTERNAL_SERVER_ERROR
+        })?
+        .ok_or(StatusCode::BAD_REQUEST)?;
+
+    if !user.is_active {
+        return Err(StatusCode::FORBIDDEN);
+    }
+
+    Ok(Json(complete_login(&state, &user, &client).await?))
+}
@@ -1,9 +1,9 @@
 use axum::{extract::State, Json};
-use crate::{state::SharedState, middleware::auth::AuthUser};
+use crate::{state::SharedState, middleware::auth::Principal};
 
 pub async fn protected_handler(
     State(_state): State<SharedState>,
-    AuthUser(user): AuthUser,
+    principal: Principal,
 ) -> Json<String> {
-    Json(format!("Hello, {}!", user.email))
+    Json(format!("Hello, {}!", principal.email))
 }
@@ -0,0 +1,155 @@
+use axum::{
+    extract::{Path, State},
+    http::StatusCode,
+    Json,
+};
+use uuid::Uuid;
+
+use crate::{
+    dto::role_dto::{AssignRoleRequest, CreateRoleRequest, UpdateRoleRequest},
+    middleware::auth::Principal,
+    models::role::{PermissionInfo, Role},
+    services::role_service::{self, RoleError},
+    state::SharedState,
+};
+
+fn role_error_status(err: RoleError) -> StatusCode {
+    match err {
+        RoleError::NotFound | RoleError::UserNotFound => StatusCode::NOT_FOUND,
+        RoleError::InvalidName | RoleError::UnknownPermission(_) => StatusCode::BAD_REQUEST,
+        RoleError::Protected => StatusCode::FORBIDDEN,
+        RoleError::AlreadyExists | RoleError::InUse | RoleError::LastAdmin => StatusCode::CONFLICT,
+        RoleError::Database(err) => {
+            tracing::error!("DB error handling roles: {:?}", err);
+            StatusCode::INTERNAL_SERVER_ERROR
+        }
+    }
+}
+
+/// Nobody can give out permissions they don't hold themselves.
+/// An unknown role is `400` so callers can tell it from a missing user.
+pub async fn ensure_can_grant_role(
+    state: &SharedState,
+    principal: &Principal,
+    role: &str,
+) -> Result<(), StatusCode> {
+    let role = role_service::get_role(&state.db, role)
+        .await
+        .map_err(|err| match err {
+            RoleError::NotFound => StatusCode::BAD_REQUEST,
+            err => role_error_status(err),
+        })?;
+
+    if principal.can_grant(&role.permissions) {
+        Ok(())
+    } else {
+        Err(StatusCode::FORBIDDEN)
+    }
+}
+
+/// GET /admin/roles - Every role with its permissions
+pub async fn list_roles(
+    State(state): State<SharedState>,
+) -> Result<Json<Vec<Role>>, StatusCode> {
+    let roles = role_service::list_roles(&state.db)
+        .await
+        .map_err(role_error_status)?;
+
+    Ok(Json(roles))
+}
+
+/// GET /admin/permissions - Permissions that can be put in a role
+pub async fn list_permissions(
+    State(state): State<SharedState>,
+) -> Result<Json<Vec<PermissionInfo>>, StatusCode> {
+    let permissions = role_service::list_permissions(&state.db)
+        .await
+        .map_err(role_error_status)?;
+
+    Ok(Json(permissions))
+}
+
+/// POST /admin/roles - Define a custom role
+pub async fn create_role(
+    State(state): State<SharedState>,
+    principal: Principal,
+    Json(payload): Json<CreateRoleRequest>,
+) -> Result<Json<Role>, StatusCode> {
+    if !principal.can_grant(&payload.permissions) {
+        return Err(StatusCode::FORBIDDEN);
+    }
+
+    let role = role_service::create_role(
+        &state.db,
+        payload.name.trim(),
+        payload.description.as_deref().unwrap_or_default().trim(),
+        &payload.permissions,
+    )
+    .await
+    .map_err(role_error_status)?;
+
+    Ok(Json(role))
+}
+
+/// PUT /admin/roles/{name} - Change a role's description or permissions
+pub async fn update_role(
+    State(state): State<SharedState>,
+    principal: Principal,
+    Path(name): Path<String>,
+    Json(payload): Json<UpdateRoleRequest>,
+) -> Result<Json<Role>, StatusCode> {
+    if let Some(permissions) = &payload.permissions {
+        if !principal.can_grant(permissions) {
+            return Err(StatusCode::FORBIDDEN);
+        }
+    }
+
+    let role = role_service::update_role(
+        &state.db,
+        &name,
+        payload.description.as_deref().map(str::trim),
+        payload.permissions.as_deref(),
+    )
+    .await
+    .map_err(role_error_status)?;
+
+    Ok(Json(role))
+}
+
+/// DELETE /admin/roles/{name} - Delete a custom role nobody has
+pub async fn delete_role(
+    State(state): State<SharedState>,
+    Path(name): Path<String>,
+) -> Result<StatusCode, StatusCode> {
+    role_service::delete_role(&state.db, &name)
+        .await
+        .map_err(role_error_status)?;
+
+    Ok(StatusCode::NO_CONTENT)
+}
+
+/// PUT /admin/users/{id}/role - Move a user to another role
+pub async fn assign_role(
+    State(state): State<SharedState>,
+    principal: Principal,
+    Path(user_id): Path<Uuid>,
+    Json(payload): Json<AssignRoleRequest>,
+) -> Result<StatusCode, StatusCode> {
+    ensure_can_grant_role(&state, &principal, &payload.role).await?;
+
+    // Nor take a role away from someone who can do more than they can
+    let current = role_service::user_access(&state.db, user_id)
+        .await
+        .map_err(role_error_status)?;
+    if let Some(current) = current {
+        if !principal.can_grant(&current.permissions) {
+            return Err(StatusCode::FORBIDDEN);
+        }
+    }
+
+    role_service::assign_role(&state.db, user_id, &payload.role)
+        .await
+        .map_err(role_error_status)?;
+
+    Ok(StatusCode::NO_CONTENT)
+}
@@ -0,0 +1,193 @@
+use axum::{
+    extract::{Path, Query, State},
+    http::StatusCode,
+    Json,
+};
+use uuid::Uuid;
+use validator::Validate;
+
+use crate::{
+    dto::routing_dto::{
+        AgentRoutingProfileRequest, AvailabilityRequest, RouteTicketRequest, RouteTicketResponse,
+        RoutingPreviewQuery, RoutingSettingsRequest,
+    },
+    middleware::auth::Principal,
+    models::routing::{AgentRoutingProfile, RoutingDecision, RoutingSettings, RoutingStrategy},
+    services::{
+        notification_services::notify_user,
+        routing_service::{self, ProfileInput, RoutingError},
+        ticket_service::{self, TicketError},
+    },
+    state::SharedState,
+};
+
+fn routing_error_status(err: RoutingError) -> StatusCode {
+    match err {
+        RoutingError::NotFound | RoutingError::Ticket(TicketError::NotFound) => StatusCode::NOT_FOUND,
+        RoutingError::NotAnAgent | RoutingError::InvalidCapacity => StatusCode::BAD_REQUEST,
+        RoutingError::Ticket(TicketError::InvalidTransition { .. }) => StatusCode::CONFLICT,
+        err => {
+            tracing::error!("Error routing tickets: {:?}", err);
+            StatusCode::INTERNAL_SERVER_ERROR
+        }
+    }
+}
+
+async fn strategy_or_default(state: &SharedState, strategy: Option<RoutingStrategy>) -> Result<RoutingStrategy, StatusCode> {
+    match strategy {
+        Some(strategy) => Ok(strategy),
+        None => Ok(routing_service::settings(&state.db)
+            .await
+            .map_err(routing_error_status)?
+            .strategy),
+    }
+}
+
+/// GET /admin/routing
+pub async fn get_settings(
+    State(state): State<SharedState>,
+) -> Result<Json<RoutingSettings>, StatusCode> {
+    let settings = routing_service::settings(&state.db)
+        .await
+        .map_err(routing_error_status)?;
+
+    Ok(Json(settings))
+}
+
+/// PUT /admin/routing - Applies to tickets created from now on
+pub async fn update_settings(
+    State(state): State<SharedState>,
+    principal: Principal,
+    Json(payload): Json<RoutingSettingsRequest>,
+) -> Result<Json<RoutingSettings>, StatusCode> {
+    let settings = RoutingSettings {
+        auto_assign: payload.auto_assign,
+        strategy: payload.strategy,
+    };
+    let settings = routing_service::set_settings(&state.db, settings, principal.id)
+        .await
+        .map_err(routing_error_status)?;
+
+    tracing::info!(
+        "🧭 Routing set to {} (auto-assign: {}) by {}",
+        settings.strategy.as_str(),
+        settings.auto_assign,
+        principal.id
+    );
+    Ok(Json(settings))
+}
+
+/// GET /admin/routing/agents
+pub async fn list_profiles(
+    State(state): State<SharedState>,
+) -> Result<Json<Vec<AgentRoutingProfile>>, StatusCode> {
+    let profiles = routing_service::list_profiles(&state.db)
+        .await
+        .map_err(routing_error_status)?;
+
+    Ok(Json(profiles))
+}
+
+/// PUT /admin/routing/agents/{user_id} - Skills, capacity and availability
+pub async fn save_profile(
+    State(state): State<SharedState>,
+    principal: Principal,
+    Path(user_id): Path<Uuid>,
+    Json(payload): Json<AgentRoutingProfileRequest>,
+) -> Result<Json<AgentRoutingProfile>, StatusCode> {
+    if let Err(errors) = payload.validate() {
+        tracing::warn!("Validation failed for routing profile: {:?}", errors);
+        return Err(StatusCode::BAD_REQUEST);
+    }
+
+    let input = ProfileInput {
+        skills: payload.skills,
+        max_open_tickets: payload.max_open_tickets,
+        available: payload.available,
+    };
+    let profile = routing_service::save_profile(&state.db, user_id, &input)
+        .await
+        .map_err(routing_error_status)?;
+
+    tracing::info!("🧭 Routing profile of {} saved by {}", user_id, principal.id);
+    Ok(Json(profile))
+}
+
+/// DELETE /admin/routing/agents/{user_id}
+pub async fn delete_profile(
+    State(state): State<SharedState>,
+    principal: Principal,
+    Path(user_id): Path<Uuid>,
+) -> Result<StatusCode, StatusCode> {
+    routing_service::delete_profile(&state.db, user_id)
+        .await
+        .map_err(routing_error_status)?;
+
+    tracing::info!("🧭 Routing profile of {} deleted by {}", user_id, principal.id);
+    Ok(StatusCode::NO_CONTENT)
+}
+
+/// PUT /agent/availability - An agent going on or off shift
+pub async fn set_my_availability(
+    State(state): State<SharedState>,
+    principal: Principal,
+    Json(payload): Json<AvailabilityRequest>,
+) -> Result<Json<AgentRoutingProfile>, StatusCode> {
+    let profile = routing_service::set_availability(&state.db, principal.id, payload.available)
+        .await
+        .map_err(routing_error_status)?;
+
+    Ok(Json(profile))
+}
+
+/// GET /tickets/{ticket_id}/routing - Dry run: who would get the ticket and why
+pub async fn preview_routing(
+    State(state): State<SharedState>,
+    Path(ticket_id): Path<Uuid>,
+    Query(query): Query<RoutingPreviewQuery>,
+) -> Result<Json<RoutingDecision>, StatusCode> {
+    let strategy = strategy_or_default(&state, query.strategy).await?;
+    let decision = routing_service::preview(&state.db, ticket_id, strategy)
+        .await
+        .map_err(routing_error_status)?;
+
+    Ok(Json(decision))
+}
+
+/// POST /tickets/{ticket_id}/routing - Hand the ticket to someone other than its current assignee
+pub async fn route_ticket(
+    State(state): State<SharedState>,
+    principal: Principal,
+    Path(ticket_id): Path<Uuid>,
+    payload: Option<Json<RouteTicketRequest>>,
+) -> Result<Json<RouteTicketResponse>, StatusCode> {
+    let Json(payload) = payload.unwrap_or_default();
+    let strategy = strategy_or_default(&state, payload.strategy).await?;
+
+    let (decision, routed) = routing_service::route_ticket(&state.db, ticket_id, strategy, Some(principal.id))
+        .await
+        .map_err(routing_error_status)?;
+
+    let ticket = match routed {
+        Some(ticket) => {
+            if let Some(agent_id) = ticket.assigned_to {
+                let _ = notify_user(
+                    &state.db,
+                    agent_id,
+                    "You have been assigned a new ticket.",
+                    Some(format!("/dashboard/ticket/{}", ticket.id)),
+                )
+                .await;
+            }
+            ticket
+        }
+        None => ticket_service::get_ticket_by_id(&state.db, ticket_id)
+            .await
+            .map_err(|err| {
+                tracing::error!("DB error fetching ticket: {:?}", err);
+                StatusCode::INTERNAL_SERVER_ERROR
+            })?,
+    };
+
+    Ok(Json(RouteTicketResponse { ticket, decision }))
+}
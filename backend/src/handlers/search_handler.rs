@@ -0,0 +1,74 @@
+use axum::{
+    extract::{Query, State},
+    http::StatusCode,
+    Json,
+};
+
+use crate::{
+    dto::search_dto::{SearchQuery, SearchResponse},
+    middleware::auth::Principal,
+    models::permission::Permission,
+    services::search_service::{self, SearchScope},
+    state::SharedState,
+};
+
+/// Longest query we pass on to Postgres
+const MAX_QUERY_LEN: usize = 256;
+
+/// The tickets (and notes) the caller could open anyway
+fn search_scope(principal: &Principal) -> SearchScope {
+    // A key only reads internal notes with its own notes scope
+    let key_reads_notes = principal
+        .api_key_scopes()
+        .is_none_or(|scopes| scopes.iter().any(|s| s == "notes:read"));
+
+    SearchScope {
+        all_tickets: principal.has(Permission::TicketsReadAll),
+        assigned_to: principal
+            .has(Permission::TicketsReadAssigned)
+            .then_some(principal.id),
+        teams: if principal.has(Permission::TicketsReadAssigned) {
+            principal.teams.iter().copied().collect()
+        } else {
+            Vec::new()
+        },
+        customer_email: (principal.has(Permission::TicketsReadOwn) && principal.email_verified)
+            .then(|| principal.email.clone()),
+        include_notes: principal.has(Permission::NotesRead) && key_reads_notes,
+    }
+}
+
+/// GET /search?q=&page=&per_page= - Tickets whose subject, description,
+/// messages or notes match, best first, with highlighted snippets
+pub async fn search(
+    State(state): State<SharedState>,
+    principal: Principal,
+    Query(query): Query<SearchQuery>,
+) -> Result<Json<SearchResponse>, StatusCode> {
+    let q = query.q.as_deref().map(str::trim).unwrap_or_default();
+    if q.is_empty() || q.chars().count() > MAX_QUERY_LEN {
+        return Err(StatusCode::BAD_REQUEST);
+    }
+    let page = query.page.unwrap_or(1).max(1);
+    let per_page = query.per_page.unwrap_or(20).clamp(1, 100);
+
+    let (results, total) = search_service::search(
+        &state.db,
+        q,
+        &search_scope(&principal),
+        per_page,
+        (page - 1).saturating_mul(per_page),
+    )
+    .await
+    .map_err(|err| {
+        tracing::error!("Search failed: {:?}", err);
+        StatusCode::INTERNAL_SERVER_ERROR
+    })?;
+
+    Ok(Json(SearchResponse {
+        results,
+        total,
+        page,
+        per_page,
+    }))
+}
@@ -0,0 +1,116 @@
+use axum::{
+    extract::{Path, State},
+    http::StatusCode,
+    Json,
+};
+use uuid::Uuid;
+
+use crate::{
+    dto::session_dto::SessionResponse,
+    handlers::user_handler::ensure_can_manage,
+    middleware::auth::{AuthMethod, Principal},
+    services::session_service::{self, SessionError},
+    state::SharedState,
+};
+
+fn session_error_status(err: SessionError) -> StatusCode {
+    match err {
+        SessionError::NotFound => StatusCode::NOT_FOUND,
+        err => {
+            tracing::error!("Error handling sessions: {:?}", err);
+            StatusCode::INTERNAL_SERVER_ERROR
+        }
+    }
+}
+
+fn current_session(principal: &Principal) -> Option<Uuid> {
+    match principal.auth_method {
+        AuthMethod::Session { session_id } => Some(session_id),
+        AuthMethod::ApiKey { .. } => None,
+    }
+}
+
+async fn active_sessions(
+    state: &SharedState,
+    user_id: Uuid,
+    current: Option<Uuid>,
+) -> Result<Vec<SessionResponse>, StatusCode> {
+    let sessions = session_service::list_active(&state.db, user_id)
+        .await
+        .map_err(session_error_status)?;
+
+    Ok(sessions
+        .into_iter()
+        .map(|session| SessionResponse {
+            current: Some(session.id) == current,
+            session,
+        })
+        .collect())
+}
+
+/// GET /me/sessions - Devices you are logged in on, most recently used first
+pub async fn list_my_sessions(
+    State(state): State<SharedState>,
+    principal: Principal,
+) -> Result<Json<Vec<SessionResponse>>, StatusCode> {
+    let sessions = active_sessions(&state, principal.id, current_session(&principal)).await?;
+    Ok(Json(sessions))
+}
+
+/// DELETE /me/sessions/{id} - Log one of your devices out (this one included)
+pub async fn revoke_my_session(
+    State(state): State<SharedState>,
+    principal: Principal,
+    Path(session_id): Path<Uuid>,
+) -> Result<StatusCode, StatusCode> {
+    session_service::revoke_session(&state.db, session_id, Some(principal.id), "user_revoked")
+        .await
+        .map_err(session_error_status)?;
+
+    Ok(StatusCode::NO_CONTENT)
+}
+
+/// DELETE /me/sessions - Log out everywhere except this device
+pub async fn revoke_my_other_sessions(
+    State(state): State<SharedState>,
+    principal: Principal,
+) -> Result<StatusCode, StatusCode> {
+    let revoked = match current_session(&principal) {
+        Some(current) => {
+            session_service::revoke_other_sessions(&state.db, principal.id, current, "user_revoked").await
+        }
+        None => session_service::revoke_all_for_user(&state.db, principal.id, "user_revoked").await,
+    }
+    .map_err(session_error_status)?;
+
+    tracing::info!("🔑 User {} revoked {} other sessions", principal.id, revoked);
+    Ok(StatusCode::NO_CONTENT)
+}
+
+/// GET /admin/users/{id}/sessions - A user's active sessions
+pub async fn list_user_sessions(
+    State(state): State<SharedState>,
+    principal: Principal,
+    Path(user_id): Path<Uuid>,
+) -> Result<Json<Vec<SessionResponse>>, StatusCode> {
+    ensure_can_manage(&state, &principal, user_id).await?;
+
+    let sessions = active_sessions(&state, user_id, current_session(&principal)).await?;
+    Ok(Json(sessions))
+}
+
+/// DELETE /admin/users/{id}/sessions/{session_id} - Log a user out of one device
+pub async fn revoke_user_session(
+    State(state): State<SharedState>,
+    principal: Principal,
+    Path((user_id, session_id)): Path<(Uuid, Uuid)>,
+) -> Result<StatusCode, StatusCode> {
+    ensure_can_manage(&state, &principal, user_id).await?;
+
+    session_service::revoke_session(&state.db, session_id, Some(user_id), "admin_revoked")
+        .await
+        .map_err(session_error_status)?;
+
+    tracing::info!("🔑 Session {} of user {} revoked by {}", session_id, user_id, principal.id);
+    Ok(StatusCode::NO_CONTENT)
+}
@@ -0,0 +1,63 @@
+use axum::{
+    extract::State,
+    http::{header, StatusCode},
+    response::IntoResponse,
+    Json,
+};
+
+use crate::{
+    dto::signing_key_dto::RotateSigningKeyRequest,
+    middleware::auth::Principal,
+    models::signing_key::SigningKeyInfo,
+    services::signing_key_service::SigningKeyError,
+    state::SharedState,
+};
+
+fn signing_key_error_status(err: SigningKeyError) -> StatusCode {
+    tracing::error!("Signing key error: {:?}", err);
+    StatusCode::INTERNAL_SERVER_ERROR
+}
+
+/// GET /.well-known/jwks.json - Public keys our access tokens can be verified with
+pub async fn jwks(State(state): State<SharedState>) -> Result<impl IntoResponse, StatusCode> {
+    let jwks = state
+        .keys
+        .jwks(&state.db)
+        .await
+        .map_err(signing_key_error_status)?;
+
+    // Verifiers refetch on an unknown `kid` anyway, so a short cache is enough
+    Ok(([(header::CACHE_CONTROL, "public, max-age=300")], Json(jwks)))
+}
+
+/// GET /admin/signing-keys - The signing key and retired keys still accepted
+pub async fn list_signing_keys(
+    State(state): State<SharedState>,
+) -> Result<Json<Vec<SigningKeyInfo>>, StatusCode> {
+    let keys = state
+        .keys
+        .list(&state.db)
+        .await
+        .map_err(signing_key_error_status)?;
+
+    Ok(Json(keys))
+}
+
+/// POST /admin/signing-keys/rotate - Start signing with a new key.
+/// Tokens signed with the old one stay valid until they expire.
+pub async fn rotate_signing_key(
+    State(state): State<SharedState>,
+    principal: Principal,
+    payload: Option<Json<RotateSigningKeyRequest>>,
+) -> Result<Json<SigningKeyInfo>, StatusCode> {
+    let Json(payload) = payload.unwrap_or_default();
+
+    let key = state
+        .keys
+        .rotate(&state.db, payload.algorithm)
+        .await
+        .map_err(signing_key_error_status)?;
+
+    tracing::info!("🔑 Signing key rotated to {} ({}) by {}", key.kid, key.algorithm, principal.id);
+    Ok(Json(key))
+}
@@ -0,0 +1,99 @@
+use axum::{
+    extract::{Path, State},
+    http::StatusCode,
+    Json,
+};
+use uuid::Uuid;
+use validator::Validate;
+
+use crate::{
+    dto::sla_dto::SlaPolicyRequest,
+    middleware::auth::Principal,
+    models::sla::SlaPolicy,
+    services::sla_service::{self, PolicyInput, SlaError},
+    state::SharedState,
+};
+
+fn sla_error_status(err: SlaError) -> StatusCode {
+    match err {
+        SlaError::NotFound => StatusCode::NOT_FOUND,
+        SlaError::InvalidDomain | SlaError::InvalidTarget | SlaError::UnknownCalendar => StatusCode::BAD_REQUEST,
+        SlaError::AlreadyExists => StatusCode::CONFLICT,
+        SlaError::Database(err) => {
+            tracing::error!("DB error handling SLA policies: {:?}", err);
+            StatusCode::INTERNAL_SERVER_ERROR
+        }
+    }
+}
+
+fn policy_input(payload: SlaPolicyRequest) -> Result<PolicyInput, StatusCode> {
+    if let Err(errors) = payload.validate() {
+        tracing::warn!("Validation failed for SLA policy: {:?}", errors);
+        return Err(StatusCode::BAD_REQUEST);
+    }
+
+    Ok(PolicyInput {
+        name: payload.name,
+        priority: payload.priority,
+        customer_domain: payload.customer_domain,
+        first_response_minutes: payload.first_response_minutes,
+        resolution_minutes: payload.resolution_minutes,
+        calendar_id: payload.calendar_id,
+    })
+}
+
+/// GET /admin/sla-policies
+pub async fn list_policies(
+    State(state): State<SharedState>,
+) -> Result<Json<Vec<SlaPolicy>>, StatusCode> {
+    let policies = sla_service::list_policies(&state.db)
+        .await
+        .map_err(sla_error_status)?;
+
+    Ok(Json(policies))
+}
+
+/// POST /admin/sla-policies - Targets for a priority, optionally for one customer domain
+pub async fn create_policy(
+    State(state): State<SharedState>,
+    principal: Principal,
+    Json(payload): Json<SlaPolicyRequest>,
+) -> Result<Json<SlaPolicy>, StatusCode> {
+    let input = policy_input(payload)?;
+    let policy = sla_service::create_policy(&state.db, &input)
+        .await
+        .map_err(sla_error_status)?;
+
+    tracing::info!("⏰ SLA policy {} created by {}", policy.id, principal.id);
+    Ok(Json(policy))
+}
+
+/// PUT /admin/sla-policies/{id} - Applies to tickets created or re-prioritized afterwards
+pub async fn update_policy(
+    State(state): State<SharedState>,
+    principal: Principal,
+    Path(policy_id): Path<Uuid>,
+    Json(payload): Json<SlaPolicyRequest>,
+) -> Result<Json<SlaPolicy>, StatusCode> {
+    let input = policy_input(payload)?;
+    let policy = sla_service::update_policy(&state.db, policy_id, &input)
+        .await
+        .map_err(sla_error_status)?;
+
+    tracing::info!("⏰ SLA policy {} updated by {}", policy_id, principal.id);
+    Ok(Json(policy))
+}
+
+/// DELETE /admin/sla-policies/{id}
+pub async fn delete_policy(
+    State(state): State<SharedState>,
+    principal: Principal,
+    Path(policy_id): Path<Uuid>,
+) -> Result<StatusCode, StatusCode> {
+    sla_service::delete_policy(&state.db, policy_id)
+        .await
+        .map_err(sla_error_status)?;
+
+    tracing::info!("⏰ SLA policy {} deleted by {}", policy_id, principal.id);
+    Ok(StatusCode::NO_CONTENT)
+}
@@ -0,0 +1,267 @@
+use axum::{
+    extract::{Path, Query, State},
+    http::StatusCode,
+    Json,
+};
+use uuid::Uuid;
+use validator::Validate;
+
+use crate::{
+    dto::{
+        team_dto::{TeamMemberRequest, TeamRequest},
+        ticket_dto::{ListTicketsQuery, TicketPageResponse},
+    },
+    handlers::ticket_handler::list_ticket_page,
+    middleware::auth::Principal,
+    models::{
+        permission::Permission,
+        team::{Team, TeamDetail},
+        ticket::Ticket,
+    },
+    services::{
+        team_service::{self, TeamError, TeamInput},
+        ticket_service::{self, TicketError, TicketScope},
+    },
+    state::SharedState,
+};
+
+fn team_error_status(err: TeamError) -> StatusCode {
+    match err {
+        TeamError::NotFound => StatusCode::NOT_FOUND,
+        TeamError::UnknownUser | TeamError::NotStaff => StatusCode::BAD_REQUEST,
+        TeamError::AlreadyExists => StatusCode::CONFLICT,
+        TeamError::Database(err) => {
+            tracing::error!("DB error handling teams: {:?}", err);
+            StatusCode::INTERNAL_SERVER_ERROR
+        }
+    }
+}
+
+fn team_input(payload: TeamRequest) -> Result<TeamInput, StatusCode> {
+    if let Err(errors) = payload.validate() {
+        tracing::warn!("Validation failed for team: {:?}", errors);
+        return Err(StatusCode::BAD_REQUEST);
+    }
+
+    Ok(TeamInput {
+        name: payload.name,
+        description: payload.description.filter(|d| !d.trim().is_empty()),
+    })
+}
+
+/// Admins manage every team; leads manage their own team's other members
+/// but can't make or remove leads
+fn may_manage_member(principal: &Principal, caller_leads: bool, member_leads: bool, makes_lead: bool) -> bool {
+    principal.has(Permission::UsersManage) || (caller_leads && !member_leads && !makes_lead)
+}
+
+async fn require_team_manager(
+    state: &SharedState,
+    principal: &Principal,
+    team_id: Uuid,
+    member_id: Uuid,
+    makes_lead: bool,
+) -> Result<(), StatusCode> {
+    if principal.has(Permission::UsersManage) {
+        return Ok(());
+    }
+
+    let caller_leads = team_service::membership(&state.db, team_id, principal.id)
+        .await
+        .map_err(team_error_status)?
+        .unwrap_or(false);
+    let member_leads = team_service::membership(&state.db, team_id, member_id)
+        .await
+        .map_err(team_error_status)?
+        .unwrap_or(false);
+
+    if may_manage_member(principal, caller_leads, member_leads, makes_lead) {
+        Ok(())
+    } else {
+        Err(StatusCode::FORBIDDEN)
+    }
+}
+
+/// GET /admin/teams
+pub async fn list_teams(
+    State(state): State<SharedState>,
+) -> Result<Json<Vec<Team>>, StatusCode> {
+    let teams = team_service::list_teams(&state.db)
+        .await
+        .map_err(team_error_status)?;
+
+    Ok(Json(teams))
+}
+
+/// POST /admin/teams
+pub async fn create_team(
+    State(state): State<SharedState>,
+    principal: Principal,
+    Json(payload): Json<TeamRequest>,
+) -> Result<Json<Team>, StatusCode> {
+    let input = team_input(payload)?;
+    let team = team_service::create_team(&state.db, &input)
+        .await
+        .map_err(team_error_status)?;
+
+    tracing::info!("👥 Team {} created by {}", team.id, principal.id);
+    Ok(Json(team))
+}
+
+/// PUT /admin/teams/{team_id}
+pub async fn update_team(
+    State(state): State<SharedState>,
+    principal: Principal,
+    Path(team_id): Path<Uuid>,
+    Json(payload): Json<TeamRequest>,
+) -> Result<Json<Team>, StatusCode> {
+    let input = team_input(payload)?;
+    let team = team_service::update_team(&state.db, team_id, &input)
+        .await
+        .map_err(team_error_status)?;
+
+    tracing::info!("👥 Team {} updated by {}", team_id, principal.id);
+    Ok(Json(team))
+}
+
+/// DELETE /admin/teams/{team_id} - Its tickets leave the queue
+pub async fn delete_team(
+    State(state): State<SharedState>,
+    principal: Principal,
+    Path(team_id): Path<Uuid>,
+) -> Result<StatusCode, StatusCode> {
+    team_service::delete_team(&state.db, team_id)
+        .await
+        .map_err(team_error_status)?;
+
+    tracing::info!("👥 Team {} deleted by {}", team_id, principal.id);
+    Ok(StatusCode::NO_CONTENT)
+}
+
+/// GET /teams/{team_id} - The team and its members, for members and admins
+pub async fn get_team(
+    State(state): State<SharedState>,
+    principal: Principal,
+    Path(team_id): Path<Uuid>,
+) -> Result<Json<TeamDetail>, StatusCode> {
+    if !(principal.in_team(team_id) || principal.has(Permission::UsersManage)) {
+        return Err(StatusCode::FORBIDDEN);
+    }
+
+    let team = team_service::get_team(&state.db, team_id)
+        .await
+        .map_err(team_error_status)?;
+
+    Ok(Json(team))
+}
+
+/// PUT /teams/{team_id}/members/{user_id} - Add a member or change their lead flag
+pub async fn save_member(
+    State(state): State<SharedState>,
+    principal: Principal,
+    Path((team_id, user_id)): Path<(Uuid, Uuid)>,
+    payload: Option<Json<TeamMemberRequest>>,
+) -> Result<StatusCode, StatusCode> {
+    let Json(payload) = payload.unwrap_or_default();
+    require_team_manager(&state, &principal, team_id, user_id, payload.is_lead).await?;
+
+    team_service::save_member(&state.db, team_id, user_id, payload.is_lead)
+        .await
+        .map_err(team_error_status)?;
+
+    tracing::info!("👥 {} added to team {} by {}", user_id, team_id, principal.id);
+    Ok(StatusCode::NO_CONTENT)
+}
+
+/// DELETE /teams/{team_id}/members/{user_id}
+pub async fn remove_member(
+    State(state): State<SharedState>,
+    principal: Principal,
+    Path((team_id, user_id)): Path<(Uuid, Uuid)>,
+) -> Result<StatusCode, StatusCode> {
+    require_team_manager(&state, &principal, team_id, user_id, false).await?;
+
+    team_service::remove_member(&state.db, team_id, user_id)
+        .await
+        .map_err(team_error_status)?;
+
+    tracing::info!("👥 {} removed from team {} by {}", user_id, team_id, principal.id);
+    Ok(StatusCode::NO_CONTENT)
+}
+
+/// GET /queues/{team_id} - The team's tickets, filtered and sorted like other ticket lists
+pub async fn list_queue(
+    State(state): State<SharedState>,
+    principal: Principal,
+    Path(team_id): Path<Uuid>,
+    Query(query): Query<ListTicketsQuery>,
+) -> Result<Json<TicketPageResponse>, StatusCode> {
+    let allowed = principal.has(Permission::TicketsReadAll)
+        || (principal.has(Permission::TicketsReadAssigned) && principal.in_team(team_id));
+    if !allowed {
+        return Err(StatusCode::FORBIDDEN);
+    }
+
+    list_ticket_page(&state, TicketScope::Team(team_id), &query).await
+}
+
+/// PUT /tickets/{ticket_id}/team/{team_id} - Put the ticket in a team's queue
+/// (`tickets.assign`) and let the team know
+pub async fn move_ticket_to_team(
+    State(state): State<SharedState>,
+    principal: Principal,
+    Path((ticket_id, team_id)): Path<(Uuid, Uuid)>,
+) -> Result<Json<Ticket>, StatusCode> {
+    let ticket = ticket_service::move_to_team(&state.db, ticket_id, team_id, Some(principal.id))
+        .await
+        .map_err(|err| match err {
+            TicketError::NotFound => StatusCode::NOT_FOUND,
+            TicketError::UnknownTeam => StatusCode::BAD_REQUEST,
+            err => {
+                tracing::error!("Error moving ticket to team: {:?}", err);
+                StatusCode::INTERNAL_SERVER_ERROR
+            }
+        })?;
+
+    if let Err(err) = team_service::notify_members(
+        &state.db,
+        team_id,
+        &format!("New ticket in your team's queue: {}", ticket.subject),
+        Some(format!("/dashboard/ticket/{}", ticket.id)),
+    )
+    .await
+    {
+        tracing::error!("Error notifying team {}: {:?}", team_id, err);
+    }
+
+    Ok(Json(ticket))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn leads_manage_their_members_but_not_other_leads() {
+        let lead = Principal::for_test(&[Permission::TicketsReadAssigned]);
+
+        assert!(may_manage_member(&lead, true, false, false));
+        assert!(!may_manage_member(&lead, true, true, false));
+        assert!(!may_manage_member(&lead, true, false, true));
+    }
+
+    #[test]
+    fn plain_members_manage_nobody() {
+        let member = Principal::for_test(&[Permission::TicketsReadAssigned]);
+
+        assert!(!may_manage_member(&member, false, false, false));
+    }
+
+    #[test]
+    fn admins_manage_any_team() {
+        let admin = Principal::for_test(&[Permission::UsersManage]);
+
+        assert!(may_manage_member(&admin, false, false, false));
+        assert!(may_manage_member(&admin, false, true, true));
+    }
+}
@@ -1,53 +1,262 @@
+use std::collections::HashSet;
+use std::str::FromStr;
+
 use axum::{
-    extract::{Path, State, Request},
-    http::StatusCode,
+    body::Body,
+    extract::{Path, Query, State},
+    http::{header, StatusCode},
+    response::IntoResponse,
     Json,
 };
+use futures_util::{stream, StreamExt, TryStreamExt};
+use serde_json::{Map, Value};
 use crate::{
-    dto::ticket_dto::{CreateTicketRequest, UpdateTicketRequest},
-    models::ticket::{Ticket, TicketPriority},
+    dto::ticket_dto::{
+        CreateTicketRequest, ListTicketsQuery, SetTicketTagsRequest, SplitTicketRequest,
+        TicketDetailResponse, TicketPageResponse, UpdateTicketRequest,
+    },
+    handlers::custom_field_handler::custom_field_error_status,
+    handlers::ticket_link_handler::visible_links,
+    middleware::auth::Principal,
+    models::custom_field::CustomField,
+    models::permission::Permission,
+    models::ticket::{CreateTicketInput, Ticket, TicketPriority, TicketStatus},
+    models::ticket_event::TicketEvent,
     state::SharedState,
-    utils::jwt::Claims,
+    services::collaboration_service,
+    services::custom_field_service,
     services::notification_services::notify_user,
-    models::user::User,
+    services::routing_service,
+    services::team_service,
+    services::ticket_history_service,
+    services::ticket_link_service,
+    services::ticket_service::{
+        self, MovedMessages, ReplySide, TicketChanges, TicketCursor, TicketError, TicketFilter, TicketScope,
+        TicketSort, TICKET_COLUMNS,
+    },
+    utils::csv,
 };
 use uuid::Uuid;
-use chrono::Utc;
 use validator::Validate;
-use sqlx::{query, query_as};
+
+/// Whether the ticket's customer is the caller. Matching is by email, so
+/// it only counts once the caller has verified that address.
+fn is_own(principal: &Principal, ticket: &Ticket) -> bool {
+    principal.email_verified && ticket.customer_email.as_deref() == Some(principal.email.as_str())
+}
+
+/// Whether the caller may see this ticket at all. Agents see what's
+/// assigned to them and what's in their teams' queues.
+pub(crate) fn can_view(principal: &Principal, ticket: &Ticket) -> bool {
+    principal.has(Permission::TicketsReadAll)
+        || (principal.has(Permission::TicketsReadAssigned)
+            && (ticket.assigned_to == Some(principal.id)
+                || ticket.team_id.is_some_and(|team_id| principal.in_team(team_id))))
+        || (principal.has(Permission::TicketsReadOwn) && is_own(principal, ticket))
+}
+
+/// Whose side a message from the caller is on. Only meaningful for callers
+/// who can see the ticket: its customer, or staff working on it.
+pub(crate) fn reply_side(principal: &Principal, ticket: &Ticket) -> ReplySide {
+    if is_own(principal, ticket) {
+        ReplySide::Customer
+    } else {
+        ReplySide::Staff
+    }
+}
+
+fn ticket_error_status(err: TicketError) -> StatusCode {
+    match err {
+        TicketError::NotFound => StatusCode::NOT_FOUND,
+        TicketError::UnknownTeam => StatusCode::BAD_REQUEST,
+        TicketError::InvalidTransition { from, to } => {
+            tracing::warn!("Rejected ticket status change {:?} -> {:?}", from, to);
+            StatusCode::CONFLICT
+        }
+        TicketError::MissingRequiredFields(keys) => {
+            tracing::warn!("Rejected closing a ticket without {}", keys.join(", "));
+            StatusCode::CONFLICT
+        }
+        TicketError::Field(err) => custom_field_error_status(err),
+        TicketError::SameTicket | TicketError::UnknownMessages => StatusCode::BAD_REQUEST,
+        TicketError::UnknownAssignee | TicketError::NotAssignable => StatusCode::BAD_REQUEST,
+        TicketError::AlreadyMerged | TicketError::MergeIntoClosed => StatusCode::CONFLICT,
+        TicketError::ChildNotAllowed(child_id) => {
+            tracing::warn!("Rejected closing child ticket {} the caller can't work on", child_id);
+            StatusCode::FORBIDDEN
+        }
+        err => {
+            tracing::error!("Error updating ticket: {:?}", err);
+            StatusCode::INTERNAL_SERVER_ERROR
+        }
+    }
+}
+
+async fn fetch_ticket(state: &SharedState, ticket_id: Uuid) -> Result<Ticket, StatusCode> {
+    sqlx::query_as::<_, Ticket>(&format!("SELECT {TICKET_COLUMNS} FROM tickets WHERE id = $1"))
+        .bind(ticket_id)
+        .fetch_optional(&state.db)
+        .await
+        .map_err(|err| {
+            tracing::error!("DB error fetching ticket: {:?}", err);
+            StatusCode::INTERNAL_SERVER_ERROR
+        })?
+        .ok_or(StatusCode::NOT_FOUND)
+}
+
+/// Filters from the list query string; unknown statuses or priorities are a 400
+fn ticket_filter(query: &ListTicketsQuery) -> Result<TicketFilter, StatusCode> {
+    fn parse_list<T: FromStr>(raw: Option<&str>) -> Result<Vec<T>, StatusCode> {
+        raw.unwrap_or_default()
+            .split(',')
+            .map(str::trim)
+            .filter(|value| !value.is_empty())
+            .map(|value| value.parse().map_err(|_| StatusCode::BAD_REQUEST))
+            .collect()
+    }
+
+    let unassigned = query.unassigned.unwrap_or(false);
+    if unassigned && query.assigned_to.is_some() {
+        return Err(StatusCode::BAD_REQUEST);
+    }
+
+    Ok(TicketFilter {
+        statuses: parse_list(query.status.as_deref())?,
+        priorities: parse_list(query.priority.as_deref())?,
+        assigned_to: query.assigned_to,
+        unassigned,
+        customer_email: query
+            .customer_email
+            .as_deref()
+            .map(str::trim)
+            .filter(|email| !email.is_empty())
+            .map(str::to_string),
+        tags: parse_list::<String>(query.tag.as_deref())?,
+        fields: query
+            .fields
+            .as_deref()
+            .unwrap_or_default()
+            .split(',')
+            .map(str::trim)
+            .filter(|pair| !pair.is_empty())
+            .map(|pair| {
+                pair.split_once(':')
+                    .map(|(key, value)| (key.trim().to_string(), value.trim().to_string()))
+                    .ok_or(StatusCode::BAD_REQUEST)
+            })
+            .collect::<Result<_, _>>()?,
+        created_after: query.created_after,
+        created_before: query.created_before,
+        updated_after: query.updated_after,
+        updated_before: query.updated_before,
+    })
+}
+
+/// One page of the tickets in `scope`, filtered and sorted as the query string asks.
+/// Shared by every ticket list endpoint.
+pub(crate) async fn list_ticket_page(
+    state: &SharedState,
+    scope: TicketScope,
+    query: &ListTicketsQuery,
+) -> Result<Json<TicketPageResponse>, StatusCode> {
+    let mut filter = ticket_filter(query)?;
+    filter.fields = custom_field_service::normalize_filters(&state.db, &filter.fields)
+        .await
+        .map_err(custom_field_error_status)?;
+    let sort = match query.sort.as_deref() {
+        Some(raw) => raw.parse().map_err(|_| StatusCode::BAD_REQUEST)?,
+        None => TicketSort::default(),
+    };
+    let cursor = query
+        .cursor
+        .as_deref()
+        .map(|raw| TicketCursor::decode(raw, sort).ok_or(StatusCode::BAD_REQUEST))
+        .transpose()?;
+    let limit = query.limit.unwrap_or(25).clamp(1, 100);
+
+    let page = ticket_service::list_tickets(&state.db, &scope, &filter, sort, cursor.as_ref(), limit)
+        .await
+        .map_err(|err| {
+            tracing::error!("DB error listing tickets: {:?}", err);
+            StatusCode::INTERNAL_SERVER_ERROR
+        })?;
+
+    Ok(Json(TicketPageResponse {
+        tickets: page.tickets,
+        total: page.total,
+        next_cursor: page.next_cursor.map(|cursor| cursor.encode()),
+    }))
+}
 
 /// Create a new ticket
 pub async fn create_ticket(
     State(state): State<SharedState>,
+    principal: Principal,
     Json(payload): Json<CreateTicketRequest>,
 ) -> Result<Json<Ticket>, StatusCode> {
+    principal.require(Permission::TicketsCreate)?;
+
     if let Err(errors) = payload.validate() {
         tracing::warn!("Validation failed for create_ticket: {:?}", errors);
         return Err(StatusCode::BAD_REQUEST);
     }
 
-    let ticket = sqlx::query_as::<_, Ticket>(
+    let input = CreateTicketInput {
+        subject: payload.subject,
+        description: payload.description,
+        priority: payload.priority.unwrap_or(TicketPriority::Medium),
+        customer_email: payload.customer_email,
+        team_id: payload.team_id,
+        tags: payload.tags,
+        custom_fields: payload.custom_fields,
+    };
+    let ticket = ticket_service::create_ticket(&state.db, input)
+        .await
+        .map_err(ticket_error_status)?;
+
+    // 🧭 Hand it to an agent; a routing failure leaves the ticket unassigned
+    let routed = routing_service::route_new_ticket(&state.db, ticket.id)
+        .await
+        .unwrap_or_else(|err| {
+            tracing::error!("Error routing new ticket {}: {:?}", ticket.id, err);
+            None
+        });
+    if let Some(ticket) = routed {
+        if let Some(agent_id) = ticket.assigned_to {
+            let _ = notify_user(
+                &state.db,
+                agent_id,
+                "You have been assigned a new ticket.",
+                Some(format!("/dashboard/ticket/{}", ticket.id)),
+            )
+            .await;
+        }
+        return Ok(Json(ticket));
+    }
+
+    // 🔔 Nobody took it: tell its team, or without one everyone whose role can see all tickets
+    if let Some(team_id) = ticket.team_id {
+        if let Err(err) = team_service::notify_members(
+            &state.db,
+            team_id,
+            &format!("New ticket in your team's queue: {}", ticket.subject),
+            Some(format!("/dashboard/ticket/{}", ticket.id)),
+        )
+        .await
+        {
+            tracing::error!("Error notifying team {}: {:?}", team_id, err);
+        }
+        return Ok(Json(ticket));
+    }
+
+    let staff = sqlx::query_scalar::<_, Uuid>(
         r#"
-        INSERT INTO tickets (subject, description, priority, customer_email)
-        VALUES ($1, $2, $3::ticket_priority, $4)
-        RETURNING *
+        SELECT u.id FROM users u
+        JOIN role_permissions rp ON rp.role = u.role
+        WHERE rp.permission = $1 AND COALESCE(u.is_active, true)
         "#,
     )
-    .bind(&payload.subject)
-    .bind(&payload.description)
-    .bind(payload.priority.unwrap_or(TicketPriority::Medium))
-    .bind(&payload.customer_email)
-    .fetch_one(&state.db)
-    .await
-    .map_err(|err| {
-        tracing::error!("DB error creating ticket: {:?}", err);
-        StatusCode::INTERNAL_SERVER_ERROR
-    })?;
-
-    // 🔔 Notify all agents and admins
-    let users = sqlx::query_as::<_, User>(
-        "SELECT id, role FROM users WHERE role IN ('agent', 'admin')"
-    )
+    .bind(Permission::TicketsReadAll.as_str())
     .fetch_all(&state.db)
     .await
     .map_err(|err| {
@@ -55,10 +264,10 @@ pub async fn create_ticket(
         StatusCode::INTERNAL_SERVER_ERROR
     })?;
 
-    for user in users {
+    for user_id in staff {
         let _ = notify_user(
             &state.db,
-            user.id,
+            user_id,
             &format!("New ticket created: {}", ticket.subject),
             Some(format!("/dashboard/ticket/{}", ticket.id)),
         )
@@ -72,72 +281,248 @@ pub async fn create_ticket(
 pub async fn get_ticket_by_id(
     State(state): State<SharedState>,
     Path(ticket_id): Path<Uuid>,
-    req: Request,
-) -> Result<Json<Ticket>, StatusCode> {
-    let Some(claims) = req.extensions().get::<Claims>() else {
-        tracing::warn!("Missing JWT claims in request");
-        return Err(StatusCode::UNAUTHORIZED);
-    };
+    principal: Principal,
+) -> Result<Json<TicketDetailResponse>, StatusCode> {
+    let ticket = fetch_ticket(&state, ticket_id).await?;
 
-    let ticket = sqlx::query_as::<_, Ticket>("SELECT * FROM tickets WHERE id = $1")
-        .bind(ticket_id)
-        .fetch_optional(&state.db)
+    if !can_view(&principal, &ticket) {
+        return Err(StatusCode::FORBIDDEN);
+    }
+
+    let tags = ticket_service::tags_for(&state.db, &[ticket_id])
         .await
         .map_err(|err| {
-            tracing::error!("DB error fetching ticket: {:?}", err);
+            tracing::error!("DB error fetching ticket tags: {:?}", err);
+            StatusCode::INTERNAL_SERVER_ERROR
+        })?
+        .remove(&ticket_id)
+        .unwrap_or_default();
+    let custom_fields = custom_field_service::values_for(&state.db, &[ticket_id])
+        .await
+        .map_err(custom_field_error_status)?
+        .remove(&ticket_id)
+        .unwrap_or_default();
+    let links = visible_links(&state, &principal, ticket_id).await?;
+
+    Ok(Json(TicketDetailResponse { ticket, tags, custom_fields, links }))
+}
+
+/// A ticket the caller may see
+pub(crate) async fn fetch_visible(state: &SharedState, principal: &Principal, ticket_id: Uuid) -> Result<Ticket, StatusCode> {
+    let ticket = fetch_ticket(state, ticket_id).await?;
+    if !can_view(principal, &ticket) {
+        return Err(StatusCode::FORBIDDEN);
+    }
+
+    Ok(ticket)
+}
+
+/// Staff who can work on the ticket (`tickets.update`)
+pub(crate) async fn fetch_for_update(state: &SharedState, principal: &Principal, ticket_id: Uuid) -> Result<Ticket, StatusCode> {
+    principal.require(Permission::TicketsUpdate)?;
+    fetch_visible(state, principal, ticket_id).await
+}
+
+/// PUT /tickets/{id}/tags - Replace the ticket's tags
+pub async fn set_ticket_tags(
+    State(state): State<SharedState>,
+    Path(ticket_id): Path<Uuid>,
+    principal: Principal,
+    Json(payload): Json<SetTicketTagsRequest>,
+) -> Result<Json<Vec<String>>, StatusCode> {
+    if let Err(errors) = payload.validate() {
+        tracing::warn!("Validation failed for set_ticket_tags: {:?}", errors);
+        return Err(StatusCode::BAD_REQUEST);
+    }
+    fetch_for_update(&state, &principal, ticket_id).await?;
+
+    let tags = ticket_service::set_tags(&state.db, ticket_id, &payload.tags, Some(principal.id))
+        .await
+        .map_err(ticket_error_status)?;
+
+    Ok(Json(tags))
+}
+
+/// PUT /tickets/{id}/fields - Set custom field values by key; `null` clears one
+pub async fn set_ticket_fields(
+    State(state): State<SharedState>,
+    Path(ticket_id): Path<Uuid>,
+    principal: Principal,
+    Json(payload): Json<Map<String, Value>>,
+) -> Result<Json<TicketDetailResponse>, StatusCode> {
+    fetch_for_update(&state, &principal, ticket_id).await?;
+
+    let mut conn = state.db.acquire().await.map_err(|err| {
+        tracing::error!("DB error acquiring connection: {:?}", err);
+        StatusCode::INTERNAL_SERVER_ERROR
+    })?;
+    custom_field_service::set_values(&mut conn, ticket_id, &payload)
+        .await
+        .map_err(custom_field_error_status)?;
+    drop(conn);
+
+    get_ticket_by_id(State(state), Path(ticket_id), principal).await
+}
+
+/// POST /tickets/{id}/merge/{target_id} - Merge a duplicate into `target_id`,
+/// closing it. The caller must be able to work on both tickets.
+pub async fn merge_ticket(
+    State(state): State<SharedState>,
+    Path((ticket_id, target_id)): Path<(Uuid, Uuid)>,
+    principal: Principal,
+) -> Result<Json<MovedMessages>, StatusCode> {
+    fetch_for_update(&state, &principal, ticket_id).await?;
+    fetch_for_update(&state, &principal, target_id).await?;
+
+    let merged = ticket_service::merge_tickets(&state.db, ticket_id, target_id, Some(principal.id))
+        .await
+        .map_err(ticket_error_status)?;
+
+    collaboration_service::broadcast_messages_moved(&state, "ticket_merged", ticket_id, target_id, &merged.message_ids)
+        .await;
+
+    tracing::info!("🔀 Ticket {} merged into {} by {}", ticket_id, target_id, principal.id);
+    Ok(Json(merged))
+}
+
+/// POST /tickets/{id}/split - Move the chosen messages into a new ticket
+pub async fn split_ticket(
+    State(state): State<SharedState>,
+    Path(ticket_id): Path<Uuid>,
+    principal: Principal,
+    Json(payload): Json<SplitTicketRequest>,
+) -> Result<Json<MovedMessages>, StatusCode> {
+    if let Err(errors) = payload.validate() {
+        tracing::warn!("Validation failed for split_ticket: {:?}", errors);
+        return Err(StatusCode::BAD_REQUEST);
+    }
+    fetch_for_update(&state, &principal, ticket_id).await?;
+
+    let split = ticket_service::split_ticket(
+        &state.db,
+        ticket_id,
+        &payload.message_ids,
+        payload.subject.as_deref(),
+        Some(principal.id),
+    )
+    .await
+    .map_err(ticket_error_status)?;
+
+    collaboration_service::broadcast_messages_moved(&state, "ticket_split", ticket_id, split.target.id, &split.message_ids)
+        .await;
+
+    tracing::info!("✂️ Ticket {} split into {} by {}", ticket_id, split.target.id, principal.id);
+    Ok(Json(split))
+}
+
+/// PUT /tickets/{id}/watch - Follow a ticket the caller can see
+pub async fn watch_ticket(
+    State(state): State<SharedState>,
+    Path(ticket_id): Path<Uuid>,
+    principal: Principal,
+) -> Result<StatusCode, StatusCode> {
+    let ticket = fetch_ticket(&state, ticket_id).await?;
+    if !can_view(&principal, &ticket) {
+        return Err(StatusCode::FORBIDDEN);
+    }
+
+    ticket_service::watch(&state.db, ticket_id, principal.id)
+        .await
+        .map_err(ticket_error_status)?;
+
+    Ok(StatusCode::NO_CONTENT)
+}
+
+/// DELETE /tickets/{id}/watch
+pub async fn unwatch_ticket(
+    State(state): State<SharedState>,
+    Path(ticket_id): Path<Uuid>,
+    principal: Principal,
+) -> Result<StatusCode, StatusCode> {
+    ticket_service::unwatch(&state.db, ticket_id, principal.id)
+        .await
+        .map_err(|err| {
+            tracing::error!("DB error unwatching ticket: {:?}", err);
             StatusCode::INTERNAL_SERVER_ERROR
         })?;
 
-    let Some(ticket) = ticket else {
-        return Err(StatusCode::NOT_FOUND);
-    };
+    Ok(StatusCode::NO_CONTENT)
+}
 
-    match claims.role.as_str() {
-        "user" => {
-            if Some(claims.email.clone()) != ticket.customer_email {
-                return Err(StatusCode::FORBIDDEN);
-            }
-        }
-        "agent" => {
-            if ticket.assigned_to != Some(claims.sub) {
-                return Err(StatusCode::FORBIDDEN);
-            }
-        }
-        "admin" => {}
-        _ => return Err(StatusCode::FORBIDDEN),
+/// GET /tickets/{id}/history - Who changed what on the ticket, oldest first.
+/// Staff only: callers who can work on the ticket (`tickets.update`).
+pub async fn get_ticket_history(
+    State(state): State<SharedState>,
+    Path(ticket_id): Path<Uuid>,
+    principal: Principal,
+) -> Result<Json<Vec<TicketEvent>>, StatusCode> {
+    principal.require(Permission::TicketsUpdate)?;
+
+    let ticket = fetch_ticket(&state, ticket_id).await?;
+    if !can_view(&principal, &ticket) {
+        return Err(StatusCode::FORBIDDEN);
     }
 
-    Ok(Json(ticket))
+    let events = ticket_history_service::list_for_ticket(&state.db, ticket_id)
+        .await
+        .map_err(|err| {
+            tracing::error!("DB error fetching ticket history: {:?}", err);
+            StatusCode::INTERNAL_SERVER_ERROR
+        })?;
+
+    Ok(Json(events))
 }
 
 /// List tickets for the authenticated user
 pub async fn list_tickets(
     State(state): State<SharedState>,
-    req: Request,
-) -> Result<Json<Vec<Ticket>>, StatusCode> {
-    let Some(claims) = req.extensions().get::<Claims>() else {
-        tracing::warn!("Missing JWT claims in request");
-        return Err(StatusCode::UNAUTHORIZED);
-    };
+    principal: Principal,
+    Query(query): Query<ListTicketsQuery>,
+) -> Result<Json<TicketPageResponse>, StatusCode> {
+    principal.require(Permission::TicketsReadOwn)?;
 
-    let tickets = query_as::<_, Ticket>(
-        "SELECT * FROM tickets WHERE customer_email = $1"
-    )
-    .bind(&claims.email)
-    .fetch_all(&state.db)
-    .await
-    .map_err(|err| {
-        tracing::error!("DB error listing user tickets: {:?}", err);
+    // Anyone can register with any address; don't hand out its tickets until it's verified
+    if !principal.email_verified {
+        return Ok(Json(TicketPageResponse {
+            tickets: Vec::new(),
+            total: 0,
+            next_cursor: None,
+        }));
+    }
+
+    list_ticket_page(&state, TicketScope::Customer(principal.email), &query).await
+}
+
+/// Whether the caller may close every one of `children` in a cascade:
+/// staff who can work on each of them
+fn may_close_all(principal: &Principal, children: &[Ticket]) -> bool {
+    principal.has(Permission::TicketsUpdate) && children.iter().all(|child| can_view(principal, child))
+}
+
+/// The ticket's descendants, if the caller may close them all along with it
+async fn closable_children(state: &SharedState, principal: &Principal, ticket_id: Uuid) -> Result<HashSet<Uuid>, StatusCode> {
+    let db_error = |err: sqlx::Error| {
+        tracing::error!("DB error fetching child tickets: {:?}", err);
         StatusCode::INTERNAL_SERVER_ERROR
-    })?;
+    };
+
+    let mut conn = state.db.acquire().await.map_err(db_error)?;
+    let child_ids = ticket_link_service::descendants(&mut conn, ticket_id).await.map_err(db_error)?;
+    drop(conn);
+    let children = ticket_service::get_tickets_by_ids(&state.db, &child_ids).await.map_err(db_error)?;
 
-    Ok(Json(tickets))
+    if !may_close_all(principal, &children) {
+        return Err(StatusCode::FORBIDDEN);
+    }
+
+    Ok(children.into_iter().map(|child| child.id).collect())
 }
 
 /// Update ticket by ID
 pub async fn update_ticket(
     State(state): State<SharedState>,
     Path(ticket_id): Path<Uuid>,
+    principal: Principal,
     Json(payload): Json<UpdateTicketRequest>,
 ) -> Result<Json<Ticket>, StatusCode> {
     if let Err(errors) = payload.validate() {
@@ -145,75 +530,71 @@ pub async fn update_ticket(
         return Err(StatusCode::BAD_REQUEST);
     }
 
-    let ticket = sqlx::query_as::<_, Ticket>(
-        r#"
-        UPDATE tickets
-        SET
-            subject = COALESCE($1, subject),
-            description = COALESCE($2, description),
-            status = COALESCE($3, status),
-            priority = COALESCE($4, priority),
-            assigned_to = COALESCE($5, assigned_to),
-            updated_at = $6
-        WHERE id = $7
-        RETURNING *
-        "#,
-    )
-    .bind(&payload.subject)
-    .bind(&payload.description)
-    .bind(&payload.status)
-    .bind(&payload.priority)
-    .bind(&payload.assigned_to)
-    .bind(Utc::now())
-    .bind(ticket_id)
-    .fetch_optional(&state.db)
-    .await
-    .map_err(|err| {
-        tracing::error!("DB error updating ticket: {:?}", err);
-        StatusCode::INTERNAL_SERVER_ERROR
-    })?;
+    let current = fetch_ticket(&state, ticket_id).await?;
+    let allowed = (principal.has(Permission::TicketsUpdate) && can_view(&principal, &current))
+        || (principal.has(Permission::TicketsUpdateOwn) && is_own(&principal, &current));
+    if !allowed {
+        return Err(StatusCode::FORBIDDEN);
+    }
+    if payload.assigned_to.is_some() {
+        principal.require(Permission::TicketsAssign)?;
+    }
+    let close_children = match payload.status {
+        Some(TicketStatus::Closed) if payload.close_children => Some(closable_children(&state, &principal, ticket_id).await?),
+        _ => None,
+    };
+
+    let changes = TicketChanges {
+        subject: payload.subject,
+        description: payload.description,
+        status: payload.status,
+        priority: payload.priority,
+        assigned_to: payload.assigned_to,
+        close_children,
+    };
+    let ticket = ticket_service::update_ticket(&state.db, ticket_id, &changes, Some(principal.id))
+        .await
+        .map_err(ticket_error_status)?;
+
+    // Notify agent if assigned
+    if let Some(agent_id) = ticket.assigned_to {
+        let _ = notify_user(
+            &state.db,
+            agent_id,
+            &format!("Ticket updated: {}", ticket.subject),
+            Some(format!("/dashboard/ticket/{}", ticket.id)),
+        ).await;
+    }
 
-    if let Some(updated_ticket) = &ticket {
-        // Notify agent if assigned
-        if let Some(agent_id) = updated_ticket.assigned_to {
+    // Notify the customer if the email belongs to a verified account
+    if let Some(email) = &ticket.customer_email {
+        if let Ok(user_id) = sqlx::query_scalar!(
+            "SELECT id FROM users WHERE email = $1 AND email_verified_at IS NOT NULL",
+            email
+        )
+        .fetch_one(&state.db)
+        .await
+        {
             let _ = notify_user(
                 &state.db,
-                agent_id,
-                &format!("Ticket updated: {}", updated_ticket.subject),
-                Some(format!("/dashboard/ticket/{}", updated_ticket.id)),
+                user_id,
+                &format!("Your ticket was updated: {}", ticket.subject),
+                Some(format!("/dashboard/ticket/{}", ticket.id)),
             ).await;
         }
-
-        // Notify customer if email found in users
-        if let Some(email) = &updated_ticket.customer_email {
-            if let Ok(user) = sqlx::query_as::<_, User>(
-                "SELECT * FROM users WHERE email = $1"
-            )
-            .bind(email)
-            .fetch_one(&state.db)
-            .await
-            {
-                let _ = notify_user(
-                    &state.db,
-                    user.id,
-                    &format!("Your ticket was updated: {}", updated_ticket.subject),
-                    Some(format!("/dashboard/ticket/{}", updated_ticket.id)),
-                ).await;
-            }
-        }
     }
 
-    match ticket {
-        Some(t) => Ok(Json(t)),
-        None => Err(StatusCode::NOT_FOUND),
-    }
+    Ok(Json(ticket))
 }
 
 /// Delete a ticket
 pub async fn delete_ticket(
     State(state): State<SharedState>,
     Path(ticket_id): Path<Uuid>,
+    principal: Principal,
 ) -> Result<StatusCode, StatusCode> {
+    principal.require(Permission::TicketsDelete)?;
+
     let result = sqlx::query!(
         "DELETE FROM tickets WHERE id = $1",
         ticket_id
@@ -232,72 +613,215 @@ pub async fn delete_ticket(
     }
 }
 
-/// Admin-only: List all tickets
+/// List all tickets (`tickets.read_all`)
 pub async fn admin_list_tickets(
     State(state): State<SharedState>,
-    req: Request,
-) -> Result<Json<Vec<Ticket>>, StatusCode> {
-    let Some(claims) = req.extensions().get::<Claims>() else {
-        return Err(StatusCode::UNAUTHORIZED);
-    };
+    principal: Principal,
+    Query(query): Query<ListTicketsQuery>,
+) -> Result<Json<TicketPageResponse>, StatusCode> {
+    principal.require(Permission::TicketsReadAll)?;
 
-    if claims.role != "admin" {
-        return Err(StatusCode::FORBIDDEN);
+    list_ticket_page(&state, TicketScope::All, &query).await
+}
+
+/// Tickets loaded per query while exporting
+const EXPORT_BATCH: i64 = 500;
+
+fn export_db_error(err: sqlx::Error) -> StatusCode {
+    tracing::error!("DB error exporting tickets: {:?}", err);
+    StatusCode::INTERNAL_SERVER_ERROR
+}
+
+/// CSV records for one batch of exported tickets
+async fn export_rows(state: &SharedState, fields: &[CustomField], tickets: &[Ticket]) -> Result<String, StatusCode> {
+    let ids: Vec<Uuid> = tickets.iter().map(|ticket| ticket.id).collect();
+    let mut tags = ticket_service::tags_for(&state.db, &ids).await.map_err(export_db_error)?;
+    let mut values = custom_field_service::values_for(&state.db, &ids)
+        .await
+        .map_err(custom_field_error_status)?;
+
+    let mut rows = String::new();
+    for ticket in tickets {
+        let optional = |value: Option<String>| value.unwrap_or_default();
+        let ticket_values = values.remove(&ticket.id).unwrap_or_default();
+        let custom = fields.iter().map(|field| match ticket_values.get(&field.key) {
+            Some(Value::String(text)) => text.clone(),
+            Some(value) => value.to_string(),
+            None => String::new(),
+        });
+
+        rows.push_str(&csv::record(
+            [
+                ticket.id.to_string(),
+                ticket.subject.clone(),
+                ticket.status.as_str().to_string(),
+                ticket.priority.as_str().to_string(),
+                optional(ticket.assigned_to.map(|id| id.to_string())),
+                optional(ticket.team_id.map(|id| id.to_string())),
+                optional(ticket.customer_email.clone()),
+                optional(ticket.created_at.map(|at| at.to_rfc3339())),
+                optional(ticket.updated_at.map(|at| at.to_rfc3339())),
+                tags.remove(&ticket.id).unwrap_or_default().join(";"),
+            ]
+            .into_iter()
+            .chain(custom),
+        ));
     }
 
-    let tickets = sqlx::query_as::<_, Ticket>(
-        "SELECT * FROM tickets"
-    )
-    .fetch_all(&state.db)
-    .await
-    .map_err(|err| {
-        tracing::error!("DB error listing tickets (admin): {:?}", err);
-        StatusCode::INTERNAL_SERVER_ERROR
-    })?;
+    Ok(rows)
+}
+
+/// GET /admin/tickets/export - Every ticket matching the list filters as
+/// CSV, with tags and a column per custom field. The first batch is loaded
+/// up front so errors still get a status; the rest follow the keyset
+/// cursor while the body streams.
+pub async fn export_tickets(
+    State(state): State<SharedState>,
+    principal: Principal,
+    Query(query): Query<ListTicketsQuery>,
+) -> Result<impl IntoResponse, StatusCode> {
+    principal.require(Permission::TicketsReadAll)?;
+
+    let mut filter = ticket_filter(&query)?;
+    filter.fields = custom_field_service::normalize_filters(&state.db, &filter.fields)
+        .await
+        .map_err(custom_field_error_status)?;
+    let sort = match query.sort.as_deref() {
+        Some(raw) => raw.parse().map_err(|_| StatusCode::BAD_REQUEST)?,
+        None => TicketSort::default(),
+    };
 
-    Ok(Json(tickets))
+    let fields = custom_field_service::list_fields(&state.db)
+        .await
+        .map_err(custom_field_error_status)?;
+    let first = ticket_service::list_tickets(&state.db, &TicketScope::All, &filter, sort, None, EXPORT_BATCH)
+        .await
+        .map_err(export_db_error)?;
+
+    let mut head = csv::record(
+        [
+            "id", "subject", "status", "priority", "assigned_to", "team_id", "customer_email",
+            "created_at", "updated_at", "tags",
+        ]
+        .into_iter()
+        .map(str::to_string)
+        .chain(fields.iter().map(|field| field.key.clone())),
+    );
+    head.push_str(&export_rows(&state, &fields, &first.tickets).await?);
+
+    let rest = stream::try_unfold(first.next_cursor, move |cursor| {
+        let (state, filter, fields) = (state.clone(), filter.clone(), fields.clone());
+        async move {
+            let Some(cursor) = cursor else {
+                return Ok(None);
+            };
+            let page = ticket_service::list_tickets(&state.db, &TicketScope::All, &filter, sort, Some(&cursor), EXPORT_BATCH)
+                .await
+                .map_err(export_db_error)?;
+            let rows = export_rows(&state, &fields, &page.tickets).await?;
+            Ok::<_, StatusCode>(Some((rows, page.next_cursor)))
+        }
+    })
+    .map_err(|status| std::io::Error::other(format!("ticket export failed: {status}")));
+
+    Ok((
+        [
+            (header::CONTENT_TYPE, "text/csv; charset=utf-8"),
+            (header::CONTENT_DISPOSITION, "attachment; filename=\"tickets.csv\""),
+        ],
+        Body::from_stream(stream::once(async { Ok::<_, std::io::Error>(head) }).chain(rest)),
+    ))
 }
 
-/// Assign a ticket to an agent (admin/agent only)
+/// Assign a ticket to an agent (`tickets.assign`); this (re)opens it
 pub async fn assign_ticket(
     Path((ticket_id, agent_id)): Path<(Uuid, Uuid)>,
     State(state): State<SharedState>,
-    req: Request,
+    principal: Principal,
 ) -> Result<StatusCode, StatusCode> {
-    let Some(claims) = req.extensions().get::<Claims>() else {
-        return Err(StatusCode::UNAUTHORIZED);
-    };
+    principal.require(Permission::TicketsAssign)?;
+    fetch_visible(&state, &principal, ticket_id).await?;
 
-    if claims.role != "admin" && claims.role != "agent" {
-        return Err(StatusCode::FORBIDDEN);
-    }
+    ticket_service::assign_ticket(&state.db, ticket_id, agent_id, Some(principal.id))
+        .await
+        .map_err(ticket_error_status)?;
 
-    let result = sqlx::query(
-        r#"
-        UPDATE tickets
-        SET assigned_to = $1, status = 'InProgress'
-        WHERE id = $2
-        "#,
-    )
-    .bind(agent_id)
-    .bind(ticket_id)
-    .execute(&state.db)
-    .await;
+    let _ = notify_user(
+        &state.db,
+        agent_id,
+        "You have been assigned a new ticket.",
+        Some(format!("/dashboard/ticket/{}", ticket_id)),
+    ).await;
 
-    match result {
-        Ok(query_result) if query_result.rows_affected() == 1 => {
-            let _ = notify_user(
-                &state.db,
-                agent_id,
-                "You have been assigned a new ticket.",
-                Some(format!("/dashboard/ticket/{}", ticket_id)),
-            ).await;
-            Ok(StatusCode::OK)
-        }
-        Ok(_) => Err(StatusCode::NOT_FOUND),
-        Err(e) => {
-            tracing::error!("Error assigning ticket: {:?}", e);
-            Err(StatusCode::INTERNAL_SERVER_ERROR)
+    Ok(StatusCode::OK)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn agent(teams: &[Uuid]) -> Principal {
+        Principal {
+            teams: teams.iter().copied().collect(),
+            ..Principal::for_test(&[Permission::TicketsReadAssigned, Permission::TicketsUpdate])
         }
     }
+
+    fn ticket(team_id: Option<Uuid>) -> Ticket {
+        Ticket { team_id, customer_email: Some("customer@example.com".into()), ..Ticket::for_test() }
+    }
+
+    #[test]
+    fn agents_see_their_teams_queues_only() {
+        let (billing, hardware) = (Uuid::new_v4(), Uuid::new_v4());
+        let agent = agent(&[billing]);
+
+        assert!(can_view(&agent, &ticket(Some(billing))));
+        assert!(!can_view(&agent, &ticket(Some(hardware))));
+        assert!(!can_view(&agent, &ticket(None)));
+    }
+
+    #[test]
+    fn agents_see_what_is_assigned_to_them_in_any_queue() {
+        let agent = agent(&[]);
+        let mut assigned = ticket(Some(Uuid::new_v4()));
+        assigned.assigned_to = Some(agent.id);
+
+        assert!(can_view(&agent, &assigned));
+    }
+
+    #[test]
+    fn cascades_need_every_child_to_be_visible() {
+        let (billing, hardware) = (Uuid::new_v4(), Uuid::new_v4());
+        let agent = agent(&[billing]);
+        let own_child = ticket(Some(billing));
+        let other_teams_child = ticket(Some(hardware));
+
+        assert!(may_close_all(&agent, &[]));
+        assert!(may_close_all(&agent, std::slice::from_ref(&own_child)));
+        assert!(!may_close_all(&agent, &[own_child, other_teams_child]));
+    }
+
+    #[test]
+    fn customers_cannot_cascade() {
+        let mut customer = agent(&[]);
+        customer.permissions = HashSet::from([Permission::TicketsReadOwn, Permission::TicketsUpdateOwn]);
+        let child = ticket(None);
+
+        assert!(!may_close_all(&customer, &[child]));
+    }
+
+    #[test]
+    fn replies_are_the_customers_only_from_the_verified_customer() {
+        let ticket = ticket(None);
+        let mut customer = agent(&[]);
+        customer.email = "customer@example.com".into();
+        assert_eq!(reply_side(&customer, &ticket), ReplySide::Customer);
+
+        customer.email_verified = false;
+        assert_eq!(reply_side(&customer, &ticket), ReplySide::Staff);
+        assert!(!can_view(&customer, &ticket));
+
+        assert_eq!(reply_side(&agent(&[]), &ticket), ReplySide::Staff);
+    }
 }
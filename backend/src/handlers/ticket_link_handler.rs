@@ -0,0 +1,156 @@
+use std::collections::HashMap;
+
+use axum::{
+    extract::{Path, State},
+    http::StatusCode,
+    Json,
+};
+use uuid::Uuid;
+
+use crate::{
+    dto::ticket_dto::CreateTicketLinkRequest,
+    handlers::ticket_handler::{can_view, fetch_for_update},
+    middleware::auth::Principal,
+    models::{
+        ticket::Ticket,
+        ticket_link::{TicketLink, TicketLinkRow},
+    },
+    services::{
+        ticket_link_service::{self, LinkError},
+        ticket_service,
+    },
+    state::SharedState,
+};
+
+fn link_error_status(err: LinkError) -> StatusCode {
+    match err {
+        LinkError::NotFound => StatusCode::NOT_FOUND,
+        LinkError::SelfLink => StatusCode::BAD_REQUEST,
+        LinkError::AlreadyLinked | LinkError::AlreadyHasParent | LinkError::Cycle => StatusCode::CONFLICT,
+        LinkError::Database(err) => {
+            tracing::error!("DB error handling ticket links: {:?}", err);
+            StatusCode::INTERNAL_SERVER_ERROR
+        }
+    }
+}
+
+fn to_link(row: &TicketLinkRow, ticket_id: Uuid, other: &Ticket) -> Option<TicketLink> {
+    let (kind, _) = row.seen_from(ticket_id)?;
+
+    Some(TicketLink {
+        id: row.id,
+        kind,
+        ticket_id: other.id,
+        subject: other.subject.clone(),
+        status: other.status,
+        created_by: row.created_by,
+        created_at: row.created_at,
+    })
+}
+
+/// The ticket's links, leaving out tickets the caller can't see
+pub(crate) async fn visible_links(
+    state: &SharedState,
+    principal: &Principal,
+    ticket_id: Uuid,
+) -> Result<Vec<TicketLink>, StatusCode> {
+    let rows = ticket_link_service::links_for(&state.db, ticket_id)
+        .await
+        .map_err(link_error_status)?;
+    if rows.is_empty() {
+        return Ok(Vec::new());
+    }
+
+    let other_ids: Vec<Uuid> = rows
+        .iter()
+        .filter_map(|row| row.seen_from(ticket_id).map(|(_, other_id)| other_id))
+        .collect();
+    let others: HashMap<Uuid, Ticket> = ticket_service::get_tickets_by_ids(&state.db, &other_ids)
+        .await
+        .map_err(|err| {
+            tracing::error!("DB error fetching linked tickets: {:?}", err);
+            StatusCode::INTERNAL_SERVER_ERROR
+        })?
+        .into_iter()
+        .filter(|other| can_view(principal, other))
+        .map(|other| (other.id, other))
+        .collect();
+
+    Ok(rows
+        .iter()
+        .filter_map(|row| {
+            let (_, other_id) = row.seen_from(ticket_id)?;
+            to_link(row, ticket_id, others.get(&other_id)?)
+        })
+        .collect())
+}
+
+/// GET /tickets/{ticket_id}/links
+pub async fn list_links(
+    State(state): State<SharedState>,
+    principal: Principal,
+    Path(ticket_id): Path<Uuid>,
+) -> Result<Json<Vec<TicketLink>>, StatusCode> {
+    let ticket = ticket_service::get_ticket_by_id(&state.db, ticket_id)
+        .await
+        .map_err(|err| match err {
+            sqlx::Error::RowNotFound => StatusCode::NOT_FOUND,
+            err => {
+                tracing::error!("DB error fetching ticket: {:?}", err);
+                StatusCode::INTERNAL_SERVER_ERROR
+            }
+        })?;
+    if !can_view(&principal, &ticket) {
+        return Err(StatusCode::FORBIDDEN);
+    }
+
+    let links = visible_links(&state, &principal, ticket_id).await?;
+    Ok(Json(links))
+}
+
+/// POST /tickets/{ticket_id}/links - The caller must be able to work on both tickets
+pub async fn create_link(
+    State(state): State<SharedState>,
+    principal: Principal,
+    Path(ticket_id): Path<Uuid>,
+    Json(payload): Json<CreateTicketLinkRequest>,
+) -> Result<Json<TicketLink>, StatusCode> {
+    fetch_for_update(&state, &principal, ticket_id).await?;
+    let other = fetch_for_update(&state, &principal, payload.ticket_id).await?;
+
+    let row = ticket_link_service::create_link(
+        &state.db,
+        ticket_id,
+        payload.kind,
+        payload.ticket_id,
+        Some(principal.id),
+    )
+    .await
+    .map_err(link_error_status)?;
+
+    tracing::info!(
+        "🔗 Ticket {} linked to {} ({}) by {}",
+        ticket_id,
+        payload.ticket_id,
+        payload.kind.as_str(),
+        principal.id
+    );
+    let link = to_link(&row, ticket_id, &other).ok_or(StatusCode::INTERNAL_SERVER_ERROR)?;
+    Ok(Json(link))
+}
+
+/// DELETE /tickets/{ticket_id}/links/{link_id}
+pub async fn delete_link(
+    State(state): State<SharedState>,
+    principal: Principal,
+    Path((ticket_id, link_id)): Path<(Uuid, Uuid)>,
+) -> Result<StatusCode, StatusCode> {
+    fetch_for_update(&state, &principal, ticket_id).await?;
+
+    ticket_link_service::delete_link(&state.db, ticket_id, link_id)
+        .await
+        .map_err(link_error_status)?;
+
+    tracing::info!("🔗 Link {} removed from ticket {} by {}", link_id, ticket_id, principal.id);
+    Ok(StatusCode::NO_CONTENT)
+}
@@ -1,11 +1,41 @@
-use axum::{extract::State, Json};
+use axum::{
+    extract::{Path, Query, State},
+    Json,
+};
 use axum::http::StatusCode;
+use chrono::Utc;
 use sqlx::query;
+use uuid::Uuid;
+use validator::Validate;
 use crate::{
-    models::user::PublicUser,
+    dto::user_dto::{
+        AdminUpdateUserRequest, ChangePasswordRequest, ListUsersQuery, ProfileResponse,
+        SetUserStatusRequest, UpdateProfileRequest, UserListResponse,
+    },
+    error::ApiError,
+    middleware::auth::{AuthMethod, Principal},
+    models::user::{PublicUser, User, UserSummary},
+    services::{
+        login_throttle_service,
+        role_service,
+        user_service::{self, UserError, UserFilter},
+    },
     state::SharedState,
+    utils::hash::{hash_password, verify_password},
 };
 
+fn user_error_status(err: UserError) -> StatusCode {
+    match err {
+        UserError::NotFound => StatusCode::NOT_FOUND,
+        UserError::InvalidTimezone(_) | UserError::InvalidAvatarUrl => StatusCode::BAD_REQUEST,
+        UserError::EmailTaken | UserError::LastAdmin | UserError::HasActivity => StatusCode::CONFLICT,
+        err => {
+            tracing::error!("Error handling users: {:?}", err);
+            StatusCode::INTERNAL_SERVER_ERROR
+        }
+    }
+}
+
 pub async fn get_agents(
     State(state): State<SharedState>,
 ) -> Result<Json<Vec<PublicUser>>, StatusCode> {
@@ -37,3 +67,270 @@ pub async fn get_agents(
 
     Ok(Json(agents))
 }
+
+fn profile(user: User, principal: &Principal) -> ProfileResponse {
+    let mut permissions: Vec<String> = principal
+        .permissions
+        .iter()
+        .map(|p| p.as_str().to_string())
+        .collect();
+    permissions.sort();
+
+    ProfileResponse {
+        id: user.id,
+        name: user.name,
+        email: user.email,
+        email_verified: user.email_verified_at.is_some(),
+        role: user.role,
+        permissions,
+        timezone: user.timezone,
+        avatar_url: user.avatar_url,
+        created_at: user.created_at,
+    }
+}
+
+/// GET /me - The caller's profile and permissions
+pub async fn get_me(
+    State(state): State<SharedState>,
+    principal: Principal,
+) -> Result<Json<ProfileResponse>, StatusCode> {
+    let user = user_service::get_user(&state.db, principal.id)
+        .await
+        .map_err(user_error_status)?;
+
+    Ok(Json(profile(user, &principal)))
+}
+
+/// PUT /me - Change your name, time zone or avatar
+pub async fn update_me(
+    State(state): State<SharedState>,
+    principal: Principal,
+    Json(payload): Json<UpdateProfileRequest>,
+) -> Result<Json<ProfileResponse>, StatusCode> {
+    if let Err(errors) = payload.validate() {
+        tracing::warn!("Validation failed for update_me: {:?}", errors);
+        return Err(StatusCode::BAD_REQUEST);
+    }
+
+    let user = user_service::update_profile(
+        &state.db,
+        principal.id,
+        payload.name.as_deref().map(str::trim),
+        payload.timezone.as_deref().map(str::trim),
+        payload.avatar_url.as_deref(),
+    )
+    .await
+    .map_err(user_error_status)?;
+
+    Ok(Json(profile(user, &principal)))
+}
+
+/// POST /me/password - Change your password, proving you know the current one.
+/// All your other sessions are logged out.
+pub async fn change_password(
+    State(state): State<SharedState>,
+    principal: Principal,
+    Json(payload): Json<ChangePasswordRequest>,
+) -> Result<StatusCode, ApiError> {
+    // Re-authentication means a password, not whatever token is at hand
+    let AuthMethod::Session { session_id } = principal.auth_method else {
+        return Err(StatusCode::FORBIDDEN.into());
+    };
+    state.password_policy.check(&payload.new_password)?;
+
+    let user = user_service::get_user(&state.db, principal.id)
+        .await
+        .map_err(user_error_status)?;
+
+    // Same lockout as the login form, so a stolen session can't guess it either
+    if user.locked_until.is_some_and(|until| until > Utc::now()) {
+        return Err(StatusCode::FORBIDDEN.into());
+    }
+    let valid = verify_password(&payload.current_password, &user.password_hash)
+        .map_err(|_| StatusCode::INTERNAL_SERVER_ERROR)?;
+    if !valid {
+        login_throttle_service::register_failure(&state.db, user.id)
+            .await
+            .map_err(|err| {
+                tracing::error!("Login throttle DB error: {:?}", err);
+                StatusCode::INTERNAL_SERVER_ERROR
+            })?;
+        return Err(StatusCode::FORBIDDEN.into());
+    }
+
+    let password_hash = hash_password(&payload.new_password)
+        .map_err(|_| StatusCode::INTERNAL_SERVER_ERROR)?;
+
+    user_service::change_password(&state.db, user.id, &password_hash, Some(session_id))
+        .await
+        .map_err(user_error_status)?;
+
+    Ok(StatusCode::NO_CONTENT)
+}
+
+/// Admins can only manage users whose permissions they hold themselves
+pub(crate) async fn ensure_can_manage(
+    state: &SharedState,
+    principal: &Principal,
+    user_id: Uuid,
+) -> Result<(), StatusCode> {
+    let user = user_service::get_user_summary(&state.db, user_id)
+        .await
+        .map_err(user_error_status)?;
+
+    let role = role_service::get_role(&state.db, &user.role)
+        .await
+        .map_err(|err| {
+            tracing::error!("Error loading role {}: {:?}", user.role, err);
+            StatusCode::INTERNAL_SERVER_ERROR
+        })?;
+
+    if principal.can_grant(&role.permissions) {
+        Ok(())
+    } else {
+        Err(StatusCode::FORBIDDEN)
+    }
+}
+
+/// GET /admin/users?search=&role=&is_active=&page=&per_page= - Users, oldest first
+pub async fn list_users(
+    State(state): State<SharedState>,
+    Query(query): Query<ListUsersQuery>,
+) -> Result<Json<UserListResponse>, StatusCode> {
+    let page = query.page.unwrap_or(1).max(1);
+    let per_page = query.per_page.unwrap_or(25).clamp(1, 100);
+
+    let filter = UserFilter {
+        search: query.search.as_deref().map(str::trim).filter(|s| !s.is_empty()),
+        role: query.role.as_deref(),
+        is_active: query.is_active,
+    };
+    let (users, total) = user_service::list_users(&state.db, &filter, per_page, (page - 1).saturating_mul(per_page))
+        .await
+        .map_err(user_error_status)?;
+
+    Ok(Json(UserListResponse {
+        users,
+        total,
+        page,
+        per_page,
+    }))
+}
+
+/// GET /admin/users/{id}
+pub async fn get_user(
+    State(state): State<SharedState>,
+    Path(user_id): Path<Uuid>,
+) -> Result<Json<UserSummary>, StatusCode> {
+    let user = user_service::get_user_summary(&state.db, user_id)
+        .await
+        .map_err(user_error_status)?;
+
+    Ok(Json(user))
+}
+
+/// PUT /admin/users/{id} - Change a user's name, email or time zone
+pub async fn update_user(
+    State(state): State<SharedState>,
+    principal: Principal,
+    Path(user_id): Path<Uuid>,
+    Json(payload): Json<AdminUpdateUserRequest>,
+) -> Result<Json<UserSummary>, StatusCode> {
+    if let Err(errors) = payload.validate() {
+        tracing::warn!("Validation failed for update_user: {:?}", errors);
+        return Err(StatusCode::BAD_REQUEST);
+    }
+    ensure_can_manage(&state, &principal, user_id).await?;
+
+    let user = user_service::update_user(
+        &state.db,
+        user_id,
+        payload.name.as_deref().map(str::trim),
+        payload.email.as_deref().map(str::trim),
+        payload.timezone.as_deref().map(str::trim),
+    )
+    .await
+    .map_err(user_error_status)?;
+
+    Ok(Json(user))
+}
+
+/// PUT /admin/users/{id}/status - Enable or disable an account.
+/// Disabling logs the user out everywhere at once.
+pub async fn set_user_status(
+    State(state): State<SharedState>,
+    principal: Principal,
+    Path(user_id): Path<Uuid>,
+    Json(payload): Json<SetUserStatusRequest>,
+) -> Result<Json<UserSummary>, StatusCode> {
+    if user_id == principal.id {
+        return Err(StatusCode::BAD_REQUEST);
+    }
+    ensure_can_manage(&state, &principal, user_id).await?;
+
+    let user = user_service::set_active(&state.db, user_id, payload.is_active)
+        .await
+        .map_err(user_error_status)?;
+
+    tracing::info!(
+        "👤 User {} {} by {}",
+        user_id,
+        if payload.is_active { "enabled" } else { "disabled" },
+        principal.id
+    );
+    Ok(Json(user))
+}
+
+/// POST /admin/users/{id}/logout - Revoke all of a user's sessions
+pub async fn force_logout(
+    State(state): State<SharedState>,
+    principal: Principal,
+    Path(user_id): Path<Uuid>,
+) -> Result<StatusCode, StatusCode> {
+    ensure_can_manage(&state, &principal, user_id).await?;
+
+    let revoked = user_service::force_logout(&state.db, user_id)
+        .await
+        .map_err(user_error_status)?;
+
+    tracing::info!("👤 {} sessions of user {} revoked by {}", revoked, user_id, principal.id);
+    Ok(StatusCode::NO_CONTENT)
+}
+
+/// DELETE /admin/users/{id} - Delete a user with no ticket history
+pub async fn delete_user(
+    State(state): State<SharedState>,
+    principal: Principal,
+    Path(user_id): Path<Uuid>,
+) -> Result<StatusCode, StatusCode> {
+    if user_id == principal.id {
+        return Err(StatusCode::BAD_REQUEST);
+    }
+    ensure_can_manage(&state, &principal, user_id).await?;
+
+    user_service::delete_user(&state.db, user_id)
+        .await
+        .map_err(user_error_status)?;
+
+    tracing::info!("👤 User {} deleted by {}", user_id, principal.id);
+    Ok(StatusCode::NO_CONTENT)
+}
+
+/// POST /admin/users/{id}/anonymize - Scrub a user's personal data, keeping their history
+pub async fn anonymize_user(
+    State(state): State<SharedState>,
+    principal: Principal,
+    Path(user_id): Path<Uuid>,
+) -> Result<StatusCode, StatusCode> {
+    if user_id == principal.id {
+        return Err(StatusCode::BAD_REQUEST);
+    }
+    ensure_can_manage(&state, &principal, user_id).await?;
+
+    user_service::anonymize_user(&state.db, user_id)
+        .await
+        .map_err(user_error_status)?;
+
+    tracing::info!("👤 User {} anonymized by {}", user_id, principal.id);
+    Ok(StatusCode::NO_CONTENT)
+}
@@ -15,10 +15,11 @@ use uuid::Uuid;
 use std::collections::HashMap; // ✅ Added HashMap here
 
 use crate::{
+    handlers::ticket_handler::{fetch_visible, reply_side},
+    middleware::auth::{authenticate_token, Principal},
     models::message::CreateMessageInput,
-    services::collaboration_service::add_message_to_ticket,
+    services::{collaboration_service::add_message_to_ticket, ticket_service::ReplySide},
     state::SharedState,
-    utils::jwt::decode_token,
 };
 
 // ✅ Custom SecWebSocketProtocol header definition
@@ -68,7 +69,6 @@ impl std::fmt::Display for SecWebSocketProtocol {
 #[derive(Debug, Deserialize)]
 struct IncomingWsMessage {
     content: String,
-    is_from_customer: bool,
 }
 
 #[derive(Debug, Serialize)]
@@ -81,6 +81,29 @@ struct OutgoingWsMessage {
     created_at: String,
 }
 
+/// Resolve the token a WebSocket client sent to the caller.
+/// Browsers can't set headers on upgrades, so the token comes in the
+/// subprotocol or the query string instead of `Authorization`.
+async fn ws_principal(state: &SharedState, token: &str) -> Result<Principal, StatusCode> {
+    let principal = authenticate_token(state, token).await?;
+
+    // Chatting on a ticket is posting messages
+    if let Some(scopes) = principal.api_key_scopes() {
+        if !scopes.iter().any(|s| s == "messages:write") {
+            return Err(StatusCode::FORBIDDEN);
+        }
+    }
+
+    Ok(principal)
+}
+
+/// The caller must be able to see the ticket. Their side on it decides
+/// whether what they post counts as the customer's.
+async fn ws_side(state: &SharedState, principal: &Principal, ticket_id: Uuid) -> Result<ReplySide, StatusCode> {
+    let ticket = fetch_visible(state, principal, ticket_id).await?;
+    Ok(reply_side(principal, &ticket))
+}
+
 // ✅ Original handler for subprotocol authentication
 pub async fn handle_ws_upgrade(
     ws: WebSocketUpgrade,
@@ -88,79 +111,51 @@ pub async fn handle_ws_upgrade(
     State(state): State<SharedState>,
     TypedHeader(protocol): TypedHeader<SecWebSocketProtocol>,
 ) -> impl IntoResponse {
-    println!("🔐 Received Sec-WebSocket-Protocol: {:?}", protocol.0);
-
-    let token_opt = protocol.0.first().cloned();
+    let Some(token) = protocol.0.first().cloned() else {
+        tracing::warn!("WebSocket upgrade without Sec-WebSocket-Protocol token");
+        return StatusCode::BAD_REQUEST.into_response();
+    };
 
-    match token_opt {
-        Some(token) => match decode_token(&token, &state.config.jwt_secret) {
-            Ok(token_data) => {
-                let user_id = token_data.sub;
-                println!("✅ JWT decoded for user: {:?}", user_id);
+    let principal = match ws_principal(&state, &token).await {
+        Ok(principal) => principal,
+        Err(status) => return status.into_response(),
+    };
 
-                ws.protocols([token.clone()])
-                    .on_upgrade(move |socket| handle_socket(socket, ticket_id, user_id, state))
-            }
-            Err(err) => {
-                eprintln!("❌ Invalid JWT: {:?}", err);
-                StatusCode::UNAUTHORIZED.into_response()
-            }
-        },
-        None => {
-            eprintln!("❌ Missing Sec-WebSocket-Protocol header");
-            StatusCode::BAD_REQUEST.into_response()
-        }
+    match ws_side(&state, &principal, ticket_id).await {
+        Ok(side) => ws
+            .protocols([token])
+            .on_upgrade(move |socket| handle_socket(socket, ticket_id, principal.id, side, state)),
+        Err(status) => status.into_response(),
     }
 }
 
-// ✅ New handler for query parameter authentication
-// Enhanced version of your handle_ws_upgrade_query function with detailed logging
-
+// ✅ Handler for query parameter authentication
 pub async fn handle_ws_upgrade_query(
     ws: WebSocketUpgrade,
     Path(ticket_id): Path<Uuid>,
     Query(params): Query<HashMap<String, String>>,
     State(state): State<SharedState>,
 ) -> impl IntoResponse {
-    println!("🔐 WebSocket connection attempt for ticket: {}", ticket_id);
-    println!("🗝️ Using JWT secret: '{}'", state.config.jwt_secret);
-    println!("🎯 All query parameters: {:?}", params);
-
-    // Try to get token from query parameter
-    let token = match params.get("token") {
-        Some(token) => {
-            println!("✅ Token found in query params");
-            println!("🔑 Token length: {}", token.len());
-            println!("🔑 Token first 30 chars: {}", &token[..std::cmp::min(30, token.len())]);
-            token.clone()
-        }
-        None => {
-            eprintln!("❌ Missing token query parameter");
-            eprintln!("📋 Available params: {:?}", params.keys().collect::<Vec<_>>());
-            return StatusCode::BAD_REQUEST.into_response();
-        }
+    let Some(token) = params.get("token") else {
+        tracing::warn!("WebSocket upgrade for ticket {} without token", ticket_id);
+        return StatusCode::BAD_REQUEST.into_response();
     };
 
-    println!("🔓 Attempting to decode JWT token...");
-    match decode_token(&token, &state.config.jwt_secret) {
-        Ok(token_data) => {
-            let user_id = token_data.sub;
-            println!("✅ JWT decoded successfully for user: {:?}", user_id);
-            println!("📧 User email: {:?}", token_data.email);
-            println!("👤 User role: {:?}", token_data.role);
+    let principal = match ws_principal(&state, token).await {
+        Ok(principal) => principal,
+        Err(status) => return status.into_response(),
+    };
 
-            ws.on_upgrade(move |socket| handle_socket(socket, ticket_id, user_id, state))
-        }
-        Err(err) => {
-            eprintln!("❌ JWT decoding failed: {:?}", err);
-            eprintln!("🔑 Token that failed (first 50 chars): {}", &token[..std::cmp::min(50, token.len())]);
-            eprintln!("🗝️ JWT secret being used: '{}'", state.config.jwt_secret);
-            StatusCode::UNAUTHORIZED.into_response()
+    match ws_side(&state, &principal, ticket_id).await {
+        Ok(side) => {
+            tracing::info!("🔗 WebSocket upgrade for ticket {} by {}", ticket_id, principal.email);
+            ws.on_upgrade(move |socket| handle_socket(socket, ticket_id, principal.id, side, state))
         }
+        Err(status) => status.into_response(),
     }
 }
 // ✅ Shared socket handling logic
-async fn handle_socket(socket: WebSocket, ticket_id: Uuid, sender_id: Uuid, state: SharedState) {
+async fn handle_socket(socket: WebSocket, ticket_id: Uuid, sender_id: Uuid, side: ReplySide, state: SharedState) {
     println!("🔗 WebSocket established: ticket = {ticket_id}, user = {sender_id}");
 
     let mut rx = {
@@ -196,7 +191,6 @@ async fn handle_socket(socket: WebSocket, ticket_id: Uuid, sender_id: Uuid, stat
             if let Ok(incoming) = serde_json::from_str::<IncomingWsMessage>(&text) {
                 let create_msg = CreateMessageInput {
                     content: incoming.content,
-                    is_from_customer: incoming.is_from_customer,
                     channel: Some("web".to_string()),
                     in_reply_to: None,
                     subject: None,
@@ -210,6 +204,7 @@ async fn handle_socket(socket: WebSocket, ticket_id: Uuid, sender_id: Uuid, stat
                     &db,
                     ticket_id,
                     sender_id,
+                    side,
                     create_msg,
                     Some(state_clone.clone()),
                 )
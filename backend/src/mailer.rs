@@ -0,0 +1,181 @@
+use std::{
+    path::PathBuf,
+    sync::{Arc, Mutex},
+};
+
+use anyhow::{anyhow, Context, Result};
+use async_trait::async_trait;
+use chrono::Utc;
+use lettre::{
+    message::Mailbox, transport::smtp::authentication::Credentials, AsyncSmtpTransport,
+    AsyncTransport, Message, Tokio1Executor,
+};
+use uuid::Uuid;
+
+use crate::config::AppConfig;
+
+/// A plain-text email
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Email {
+    pub to: String,
+    pub subject: String,
+    pub body: String,
+}
+
+/// Outgoing mail transport. Pick one with `MAIL_TRANSPORT` (smtp | file | memory).
+#[async_trait]
+pub trait Mailer: Send + Sync {
+    async fn send(&self, email: Email) -> Result<()>;
+}
+
+pub type SharedMailer = Arc<dyn Mailer>;
+
+/// Build the mailer selected in config
+pub fn from_config(config: &AppConfig) -> Result<SharedMailer> {
+    match config.mail_transport.as_str() {
+        "smtp" => Ok(Arc::new(SmtpMailer::new(config)?)),
+        "file" => Ok(Arc::new(FileMailer::new(&config.mail_outbox_dir, &config.mail_from))),
+        "memory" => Ok(Arc::new(InMemoryMailer::default())),
+        other => Err(anyhow!("Unknown MAIL_TRANSPORT '{other}' (expected smtp, file or memory)")),
+    }
+}
+
+/// Sends mail through an SMTP relay (STARTTLS)
+pub struct SmtpMailer {
+    transport: AsyncSmtpTransport<Tokio1Executor>,
+    from: Mailbox,
+}
+
+impl SmtpMailer {
+    pub fn new(config: &AppConfig) -> Result<Self> {
+        let host = config
+            .smtp_host
+            .as_deref()
+            .context("SMTP_HOST is required when MAIL_TRANSPORT=smtp")?;
+
+        let mut builder = AsyncSmtpTransport::<Tokio1Executor>::starttls_relay(host)?
+            .port(config.smtp_port);
+
+        if let (Some(user), Some(pass)) = (&config.smtp_username, &config.smtp_password) {
+            builder = builder.credentials(Credentials::new(user.clone(), pass.clone()));
+        }
+
+        Ok(Self {
+            transport: builder.build(),
+            from: config.mail_from.parse().context("MAIL_FROM is not a valid address")?,
+        })
+    }
+}
+
+#[async_trait]
+impl Mailer for SmtpMailer {
+    async fn send(&self, email: Email) -> Result<()> {
+        let message = Message::builder()
+            .from(self.from.clone())
+            .to(email.to.parse()?)
+            .subject(email.subject)
+            .body(email.body)?;
+
+        self.transport.send(message).await?;
+        Ok(())
+    }
+}
+
+/// Writes each email as an `.eml` file into a directory. Handy for local
+/// development: nothing leaves the machine and links can be copied from disk.
+pub struct FileMailer {
+    dir: PathBuf,
+    from: String,
+}
+
+impl FileMailer {
+    pub fn new(dir: impl Into<PathBuf>, from: &str) -> Self {
+        Self {
+            dir: dir.into(),
+            from: from.to_string(),
+        }
+    }
+}
+
+#[async_trait]
+impl Mailer for FileMailer {
+    async fn send(&self, email: Email) -> Result<()> {
+        tokio::fs::create_dir_all(&self.dir).await?;
+
+        let now = Utc::now();
+        let path = self
+            .dir
+            .join(format!("{}-{}.eml", now.format("%Y%m%dT%H%M%S"), Uuid::new_v4()));
+        let contents = format!(
+            "From: {}\r\nTo: {}\r\nDate: {}\r\nSubject: {}\r\n\r\n{}\r\n",
+            self.from,
+            email.to,
+            now.to_rfc2822(),
+            email.subject,
+            email.body
+        );
+
+        tokio::fs::write(&path, contents).await?;
+        tracing::info!("📨 Email to {} written to {}", email.to, path.display());
+        Ok(())
+    }
+}
+
+/// Keeps sent mail in memory (tests and throwaway environments)
+#[derive(Default)]
+pub struct InMemoryMailer {
+    sent: Mutex<Vec<Email>>,
+}
+
+impl InMemoryMailer {
+    /// Everything sent so far, oldest first
+    #[cfg(test)]
+    pub fn sent(&self) -> Vec<Email> {
+        self.sent.lock().unwrap().clone()
+    }
+}
+
+#[async_trait]
+impl Mailer for InMemoryMailer {
+    async fn send(&self, email: Email) -> Result<()> {
+        self.sent.lock().unwrap().push(email);
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn email() -> Email {
+        Email {
+            to: "someone@example.com".into(),
+            subject: "Hello".into(),
+            body: "Line one\nLine two".into(),
+        }
+    }
+
+    #[tokio::test]
+    async fn in_memory_mailer_records_messages() {
+        let mailer = InMemoryMailer::default();
+        mailer.send(email()).await.unwrap();
+
+        assert_eq!(mailer.sent(), vec![email()]);
+    }
+
+    #[tokio::test]
+    async fn file_mailer_writes_eml_file() {
+        let dir = std::env::temp_dir().join(format!("mailer-test-{}", Uuid::new_v4()));
+        let mailer = FileMailer::new(&dir, "support@example.com");
+        mailer.send(email()).await.unwrap();
+
+        let mut entries = std::fs::read_dir(&dir).unwrap();
+        let path = entries.next().unwrap().unwrap().path();
+        let contents = std::fs::read_to_string(&path).unwrap();
+        std::fs::remove_dir_all(&dir).unwrap();
+
+        assert!(contents.contains("To: someone@example.com"));
+        assert!(contents.contains("Subject: Hello"));
+        assert!(contents.ends_with("Line one\nLine two\r\n"));
+    }
+}
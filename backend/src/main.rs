@@ -1,11 +1,13 @@
 mod app;
 mod config;
 mod db;
+mod mailer;
 mod state;
 mod routes;
 mod handlers;
 mod models;
 mod dto;
+mod error;
 mod utils;
 mod services;
 pub mod middleware;
@@ -1,53 +1,185 @@
+use std::collections::HashSet;
+
 use axum::{
     extract::FromRequestParts,
-    http::{request::Parts, StatusCode},
+    http::{request::Parts, HeaderMap, StatusCode},
 };
+use uuid::Uuid;
 
 use crate::{
-    models::user::PublicUser,
-    state::SharedState,
-    utils::jwt::{decode_token, Claims},
+    models::{api_key::API_KEY_PREFIX, permission::Permission},
+    services::{
+        api_key_service, role_service, session_service::touch_session,
+        signing_key_service::SigningKeyError,
+    },
 };
 use crate::state::AppState;
 
+/// How the caller proved who they are
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum AuthMethod {
+    /// Access token from a login; `session_id` is its `sid` claim
+    Session { session_id: Uuid },
+    /// `sk_` key acting as its owner, limited to `scopes`
+    ApiKey { key_id: Uuid, scopes: Vec<String> },
+}
 
-#[derive(Debug)]
-pub struct AuthUser(pub PublicUser);
+/// The authenticated caller. Resolved once per request by `require_auth`
+/// (or by the extractor itself on routes outside it) and read from the
+/// database, so role changes and disabled accounts take effect immediately.
+#[derive(Debug, Clone)]
+pub struct Principal {
+    pub id: Uuid,
+    pub email: String,
+    pub name: String,
+    pub role: String,
+    /// Whether `email` is proven to be theirs; tickets are matched by it
+    pub email_verified: bool,
+    pub permissions: HashSet<Permission>,
+    /// Teams the caller is a member of
+    pub teams: HashSet<Uuid>,
+    pub auth_method: AuthMethod,
+}
 
-impl<S> FromRequestParts<S> for AuthUser
-where
-    S: Send + Sync + std::ops::Deref<Target = AppState>,  
-{
-    type Rejection = StatusCode;
+impl Principal {
+    pub fn has(&self, permission: Permission) -> bool {
+        self.permissions.contains(&permission)
+    }
+
+    pub fn in_team(&self, team_id: Uuid) -> bool {
+        self.teams.contains(&team_id)
+    }
+
+    /// `Err(FORBIDDEN)` unless the caller has `permission`
+    pub fn require(&self, permission: Permission) -> Result<(), StatusCode> {
+        if self.has(permission) {
+            Ok(())
+        } else {
+            Err(StatusCode::FORBIDDEN)
+        }
+    }
+
+    /// Whether the caller holds every one of `permissions` and so may hand
+    /// them out, through a role definition, a role assignment or an invitation.
+    /// Unknown names are skipped here; `role_service` rejects them.
+    pub fn can_grant(&self, permissions: &[String]) -> bool {
+        permissions
+            .iter()
+            .filter_map(|p| p.parse().ok())
+            .all(|p| self.has(p))
+    }
+
+    /// Scopes of the API key in use, `None` for a normal login
+    pub fn api_key_scopes(&self) -> Option<&[String]> {
+        match &self.auth_method {
+            AuthMethod::ApiKey { scopes, .. } => Some(scopes),
+            AuthMethod::Session { .. } => None,
+        }
+    }
 
-    async fn from_request_parts(
-        parts: &mut Parts,    
-        state: &S,
-    ) -> Result<Self, Self::Rejection> {
-        let auth_header = parts 
-            .headers
-            .get(axum::http::header::AUTHORIZATION)
-            .ok_or(StatusCode::UNAUTHORIZED)?
-            .to_str()
-            .map_err(|_| StatusCode::UNAUTHORIZED)?;
-
-        let token = auth_header
-            .strip_prefix("Bearer ")
+    /// A verified agent logged in with `permissions` and no teams, for tests
+    #[cfg(test)]
+    pub fn for_test(permissions: &[Permission]) -> Self {
+        Principal {
+            id: Uuid::new_v4(),
+            email: "agent@example.com".into(),
+            name: "Agent".into(),
+            role: "agent".into(),
+            email_verified: true,
+            permissions: permissions.iter().copied().collect(),
+            teams: HashSet::new(),
+            auth_method: AuthMethod::Session { session_id: Uuid::new_v4() },
+        }
+    }
+}
+
+/// Token from an `Authorization: Bearer ...` header
+pub fn bearer_token(headers: &HeaderMap) -> Option<&str> {
+    headers
+        .get(axum::http::header::AUTHORIZATION)
+        .and_then(|h| h.to_str().ok())
+        .and_then(|h| h.strip_prefix("Bearer "))
+}
+
+/// Resolve the `Authorization: Bearer ...` header to a caller
+pub async fn authenticate(state: &AppState, headers: &HeaderMap) -> Result<Principal, StatusCode> {
+    let token = bearer_token(headers).ok_or(StatusCode::UNAUTHORIZED)?;
+    authenticate_token(state, token).await
+}
+
+/// Resolve a session JWT or `sk_` API key to a caller
+pub async fn authenticate_token(state: &AppState, token: &str) -> Result<Principal, StatusCode> {
+    let (user_id, auth_method) = if token.starts_with(API_KEY_PREFIX) {
+        let owner = api_key_service::authenticate(&state.db, token)
+            .await
+            .map_err(|err| {
+                tracing::error!("API key lookup DB error: {:?}", err);
+                StatusCode::INTERNAL_SERVER_ERROR
+            })?
             .ok_or(StatusCode::UNAUTHORIZED)?;
 
-        let claims: Claims = decode_token(token, &state.config.jwt_secret)
-            .map_err(|_| StatusCode::UNAUTHORIZED)?;
+        (
+            owner.user_id,
+            AuthMethod::ApiKey {
+                key_id: owner.key_id,
+                scopes: owner.scopes,
+            },
+        )
+    } else {
+        let claims = state.keys.verify(&state.db, token).await.map_err(|err| match err {
+            SigningKeyError::Database(_) | SigningKeyError::CorruptKey(_) | SigningKeyError::Generation(_) => {
+                tracing::error!("Signing key error verifying token: {:?}", err);
+                StatusCode::INTERNAL_SERVER_ERROR
+            }
+            SigningKeyError::Token(_) | SigningKeyError::UnknownKey => StatusCode::UNAUTHORIZED,
+        })?;
+
+        // Reject tokens whose session was logged out or revoked
+        let active = touch_session(&state.db, claims.sid)
+            .await
+            .map_err(|_| StatusCode::INTERNAL_SERVER_ERROR)?;
+        if !active {
+            return Err(StatusCode::UNAUTHORIZED);
+        }
+
+        (claims.sub, AuthMethod::Session { session_id: claims.sid })
+    };
+
+    let access = role_service::user_access(&state.db, user_id)
+        .await
+        .map_err(|err| {
+            tracing::error!("DB error loading permissions: {:?}", err);
+            StatusCode::INTERNAL_SERVER_ERROR
+        })?
+        .ok_or(StatusCode::UNAUTHORIZED)?;
+
+    Ok(Principal {
+        id: access.user_id,
+        email: access.email,
+        name: access.name,
+        role: access.role,
+        email_verified: access.email_verified,
+        // Rows the code doesn't know about grant nothing
+        permissions: access.permissions.iter().filter_map(|p| p.parse().ok()).collect(),
+        teams: access.team_ids.into_iter().collect(),
+        auth_method,
+    })
+}
+
+impl<S> FromRequestParts<S> for Principal
+where
+    S: Send + Sync + std::ops::Deref<Target = AppState>,
+{
+    type Rejection = StatusCode;
 
-        let user = PublicUser {
-            id: claims.sub,
-            name: "".to_string(),
-            email: claims.email,
-            role: claims.role,
-            is_active: Some(true),
-            created_at: None,
-            updated_at: None,
-        };
+    async fn from_request_parts(parts: &mut Parts, state: &S) -> Result<Self, Self::Rejection> {
+        // `require_auth` already did the work on protected routes
+        if let Some(principal) = parts.extensions.get::<Principal>() {
+            return Ok(principal.clone());
+        }
 
-        Ok(AuthUser(user))
+        let principal = authenticate(state, &parts.headers).await?;
+        parts.extensions.insert(principal.clone());
+        Ok(principal)
     }
-}
\ No newline at end of file
+}
@@ -1,13 +1,13 @@
 use axum::{
     extract::State,
-    http::{Request, StatusCode},
+    http::{Method, Request, StatusCode},
     middleware::Next,
     body::Body,
     response::Response,
 };
 use crate::{
+    middleware::auth::authenticate,
     state::SharedState,
-    utils::jwt::decode_token,
 };
 
 pub async fn require_auth(
@@ -15,20 +15,87 @@ pub async fn require_auth(
     mut req: Request<Body>,
     next: Next,
 ) -> Result<Response, StatusCode> {
-    // Extract Bearer token
-    let auth_header = req.headers()
-        .get("Authorization")
-        .and_then(|h| h.to_str().ok())
-        .and_then(|h| h.strip_prefix("Bearer "))
-        .ok_or(StatusCode::UNAUTHORIZED)?;
+    // Session JWT or API key from the Bearer header
+    let principal = authenticate(&state, req.headers()).await?;
 
-    // Decode token
-    let claims = decode_token(auth_header, &state.config.jwt_secret)
-        .map_err(|_| StatusCode::UNAUTHORIZED)?;
+    // API keys only reach the endpoints their scopes cover
+    if let Some(scopes) = principal.api_key_scopes() {
+        let allowed = required_scope(req.method(), req.uri().path())
+            .is_some_and(|needed| scopes.iter().any(|s| s == needed));
+        if !allowed {
+            return Err(StatusCode::FORBIDDEN);
+        }
+    }
 
-    // Add email to request extensions
-    req.extensions_mut().insert(claims.email);
+    // Handlers and route layers pick the caller up from here
+    req.extensions_mut().insert(principal);
 
     // Call next middleware or handler
     Ok(next.run(req).await)
 }
+
+/// Scope an API key needs to call `method path`.
+/// `None` means the endpoint is not available to API keys at all
+/// (account security, key management and admin settings need a real login).
+fn required_scope(method: &Method, path: &str) -> Option<&'static str> {
+    let read = method == Method::GET || method == Method::HEAD;
+    let pick = |read_scope, write_scope| Some(if read { read_scope } else { write_scope });
+    let first = path.trim_start_matches('/').split('/').next().unwrap_or_default();
+
+    match first {
+        // Agent replies are notes on the ticket
+        "agent" if path.ends_with("/reply") => pick("notes:read", "notes:write"),
+        "tickets" | "agent" => pick("tickets:read", "tickets:write"),
+        "admin" if path == "/admin/tickets" => pick("tickets:read", "tickets:write"),
+        "notes" | "comments" => pick("notes:read", "notes:write"),
+        "messages" => pick("messages:read", "messages:write"),
+        "attachments" if !read => Some("attachments:write"),
+        "kb" => pick("kb:read", "kb:write"),
+        "analytics" if read => Some("reports:read"),
+        "notifications" => pick("notifications:read", "notifications:write"),
+        // Notes only show up in results with `notes:read` as well
+        "search" if read => Some("tickets:read"),
+        "agents" if read => Some("users:read"),
+        // Only the profile itself; the session list is account security
+        "me" if read && path == "/me" => Some("users:read"),
+        _ => None,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn read_and_write_scopes_follow_method() {
+        assert_eq!(required_scope(&Method::GET, "/tickets"), Some("tickets:read"));
+        assert_eq!(required_scope(&Method::POST, "/tickets"), Some("tickets:write"));
+        assert_eq!(required_scope(&Method::DELETE, "/tickets/123"), Some("tickets:write"));
+        assert_eq!(required_scope(&Method::PUT, "/kb/articles/1"), Some("kb:write"));
+        assert_eq!(required_scope(&Method::GET, "/search"), Some("tickets:read"));
+    }
+
+    #[test]
+    fn agent_reply_needs_notes_scope() {
+        assert_eq!(required_scope(&Method::GET, "/agent/tickets/1"), Some("tickets:read"));
+        assert_eq!(required_scope(&Method::POST, "/agent/tickets/1/reply"), Some("notes:write"));
+    }
+
+    #[test]
+    fn security_and_admin_endpoints_are_off_limits() {
+        assert_eq!(required_scope(&Method::GET, "/api-keys"), None);
+        assert_eq!(required_scope(&Method::POST, "/auth/mfa/totp/setup"), None);
+        assert_eq!(required_scope(&Method::GET, "/admin/lockouts"), None);
+        assert_eq!(required_scope(&Method::GET, "/admin/tickets"), Some("tickets:read"));
+        assert_eq!(required_scope(&Method::GET, "/admin/users"), None);
+    }
+
+    #[test]
+    fn profile_is_read_only_for_keys() {
+        assert_eq!(required_scope(&Method::GET, "/me"), Some("users:read"));
+        assert_eq!(required_scope(&Method::PUT, "/me"), None);
+        assert_eq!(required_scope(&Method::POST, "/me/password"), None);
+        assert_eq!(required_scope(&Method::GET, "/me/sessions"), None);
+        assert_eq!(required_scope(&Method::DELETE, "/me/sessions"), None);
+    }
+}
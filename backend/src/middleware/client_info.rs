@@ -0,0 +1,33 @@
+use std::{convert::Infallible, net::SocketAddr};
+
+use axum::{
+    extract::{ConnectInfo, FromRequestParts},
+    http::{header::USER_AGENT, request::Parts, HeaderMap},
+};
+
+use crate::{middleware::rate_limit::get_real_ip, models::session::ClientInfo};
+
+/// Client details from the headers and the peer address, trusting the same
+/// proxy headers as the rate limiter
+pub fn client_info(headers: &HeaderMap, peer: Option<SocketAddr>) -> ClientInfo {
+    ClientInfo::new(
+        headers.get(USER_AGENT).and_then(|ua| ua.to_str().ok()),
+        peer.map(|addr| get_real_ip(headers, addr.ip()).to_string()),
+    )
+}
+
+impl<S> FromRequestParts<S> for ClientInfo
+where
+    S: Send + Sync,
+{
+    type Rejection = Infallible;
+
+    async fn from_request_parts(parts: &mut Parts, _state: &S) -> Result<Self, Self::Rejection> {
+        let peer = parts
+            .extensions
+            .get::<ConnectInfo<SocketAddr>>()
+            .map(|ConnectInfo(addr)| *addr);
+
+        Ok(client_info(&parts.headers, peer))
+    }
+}
@@ -1,5 +1,6 @@
-pub mod role_guard;
+pub mod permission;
 pub mod auth;
 pub mod auth_extension;
 pub mod rate_limit;
-pub mod csrf;
\ No newline at end of file
+pub mod csrf;
+pub mod client_info;
\ No newline at end of file
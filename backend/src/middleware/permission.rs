@@ -0,0 +1,28 @@
+use axum::{
+    body::Body,
+    extract::{FromRequestParts, State},
+    http::{Request, StatusCode},
+    middleware::Next,
+    response::Response,
+};
+
+use crate::{
+    middleware::auth::Principal,
+    models::permission::Permission,
+    state::SharedState,
+};
+
+/// Route layer that only lets callers with `permission` through
+pub async fn require_permission(
+    State(state): State<SharedState>,
+    req: Request<Body>,
+    next: Next,
+    permission: Permission,
+) -> Result<Response, StatusCode> {
+    let (mut parts, body) = req.into_parts();
+    Principal::from_request_parts(&mut parts, &state)
+        .await?
+        .require(permission)?;
+
+    Ok(next.run(Request::from_parts(parts, body)).await)
+}
@@ -1,7 +1,7 @@
 use axum::{
     body::Body,
     extract::ConnectInfo,
-    http::{Request, StatusCode},
+    http::{HeaderMap, Request, StatusCode},
     middleware::Next,
     response::Response,
 };
@@ -29,7 +29,7 @@ pub async fn rate_limit_middleware(
     next: Next,
 ) -> Result<Response, StatusCode> {
     // Extract IP address
-    let ip = get_real_ip(&request, addr.ip());
+    let ip = get_real_ip(request.headers(), addr.ip());
     
     // Create or get rate limiter for this IP
     let quota = Quota::per_second(NonZeroU32::new(1).unwrap())
@@ -51,9 +51,8 @@ pub async fn rate_limit_middleware(
 }
 
 /// Extract real IP from headers (for reverse proxy support)
-fn get_real_ip(request: &Request<Body>, fallback_ip: IpAddr) -> IpAddr {
+pub fn get_real_ip(headers: &HeaderMap, fallback_ip: IpAddr) -> IpAddr {
     // Check common proxy headers in order of preference
-    let headers = request.headers();
     
     // X-Forwarded-For (most common)
     if let Some(xff) = headers.get("x-forwarded-for") {
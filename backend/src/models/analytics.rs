@@ -3,8 +3,10 @@ use serde::Serialize;
 #[derive(Debug, Serialize)]
 pub struct TicketStats {
     pub total: i64,
+    pub new: i64,
     pub open: i64,
-    pub in_progress: i64,
+    pub pending: i64,
+    pub on_hold: i64,
     pub resolved: i64,
     pub closed: i64,
 }
@@ -0,0 +1,39 @@
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use sqlx::FromRow;
+use uuid::Uuid;
+
+/// Every scope an API key can be granted. `:read` covers GET requests,
+/// `:write` everything else on the same resource.
+pub const API_KEY_SCOPES: &[&str] = &[
+    "tickets:read",
+    "tickets:write",
+    "notes:read",
+    "notes:write",
+    "messages:read",
+    "messages:write",
+    "attachments:write",
+    "kb:read",
+    "kb:write",
+    "reports:read",
+    "notifications:read",
+    "notifications:write",
+    "users:read",
+];
+
+/// Prefix that tells API keys apart from JWTs in the `Authorization` header
+pub const API_KEY_PREFIX: &str = "sk_";
+
+/// API key metadata (the key itself is never read back, only its hash is stored)
+#[derive(Debug, Clone, Serialize, Deserialize, FromRow)]
+pub struct ApiKey {
+    pub id: Uuid,
+    pub user_id: Uuid,
+    pub name: String,
+    pub prefix: String, // first characters of the key, to recognise it in lists
+    pub scopes: Vec<String>,
+    pub created_at: DateTime<Utc>,
+    pub expires_at: Option<DateTime<Utc>>,
+    pub last_used_at: Option<DateTime<Utc>>,
+    pub revoked_at: Option<DateTime<Utc>>,
+}
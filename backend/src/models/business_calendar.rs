@@ -0,0 +1,28 @@
+use chrono::{DateTime, NaiveDate, Utc};
+use serde::{Deserialize, Serialize};
+use sqlx::FromRow;
+use uuid::Uuid;
+
+use crate::utils::business_time::Shift;
+
+/// When the support desk is open: weekly hours in a time zone, minus holidays
+#[derive(Debug, Clone, Serialize, Deserialize, FromRow)]
+pub struct BusinessCalendar {
+    pub id: Uuid,
+    pub name: String,
+    /// IANA name, e.g. `Europe/Berlin`
+    pub timezone: String,
+    #[sqlx(skip)]
+    pub hours: Vec<Shift>,
+    #[sqlx(skip)]
+    pub holidays: Vec<Holiday>,
+    pub created_at: DateTime<Utc>,
+    pub updated_at: DateTime<Utc>,
+}
+
+/// A date the desk stays closed; shifts opening on it don't happen
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize, FromRow)]
+pub struct Holiday {
+    pub date: NaiveDate,
+    pub name: String,
+}
@@ -0,0 +1,70 @@
+use std::str::FromStr;
+
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use sqlx::FromRow;
+use uuid::Uuid;
+
+/// What kind of value a custom field holds
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum CustomFieldType {
+    Text,
+    Number,
+    /// One of the field's `options`
+    Dropdown,
+    /// A calendar date, `YYYY-MM-DD`
+    Date,
+    Checkbox,
+}
+
+impl CustomFieldType {
+    pub fn as_str(self) -> &'static str {
+        match self {
+            CustomFieldType::Text => "text",
+            CustomFieldType::Number => "number",
+            CustomFieldType::Dropdown => "dropdown",
+            CustomFieldType::Date => "date",
+            CustomFieldType::Checkbox => "checkbox",
+        }
+    }
+}
+
+impl FromStr for CustomFieldType {
+    type Err = ();
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "text" => Ok(CustomFieldType::Text),
+            "number" => Ok(CustomFieldType::Number),
+            "dropdown" => Ok(CustomFieldType::Dropdown),
+            "date" => Ok(CustomFieldType::Date),
+            "checkbox" => Ok(CustomFieldType::Checkbox),
+            _ => Err(()),
+        }
+    }
+}
+
+/// An admin-defined ticket field, e.g. order number or plan tier
+#[derive(Debug, Clone, Serialize, Deserialize, FromRow)]
+pub struct CustomField {
+    pub id: Uuid,
+    /// Name used in the API, list filters and exports, e.g. `order_number`
+    pub key: String,
+    pub label: String,
+    pub field_type: String, // see `CustomFieldType`
+    /// Choices for dropdowns
+    pub options: Vec<String>,
+    /// Tickets can't be closed while this field is empty
+    pub required_on_close: bool,
+    pub position: i32,
+    pub created_at: DateTime<Utc>,
+    pub updated_at: DateTime<Utc>,
+}
+
+impl CustomField {
+    /// Saved types are checked by the table; fall back to text rather than fail a read
+    pub fn kind(&self) -> CustomFieldType {
+        self.field_type.parse().unwrap_or(CustomFieldType::Text)
+    }
+}
@@ -0,0 +1,59 @@
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use sqlx::FromRow;
+use uuid::Uuid;
+
+/// One row of the login audit log
+#[derive(Debug, Clone, Serialize, Deserialize, FromRow)]
+pub struct LoginAttempt {
+    pub id: Uuid,
+    pub email: String,
+    pub user_id: Option<Uuid>,
+    pub ip: String,
+    pub outcome: String, // see `LoginOutcome`
+    pub cleared: bool,
+    pub created_at: DateTime<Utc>,
+}
+
+/// How a login attempt ended. The API answers all failures the same way;
+/// only the audit log tells them apart.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum LoginOutcome {
+    Success,
+    BadCredentials,
+    UnknownUser,
+    Disabled,
+    AccountLocked,
+    IpThrottled,
+}
+
+impl LoginOutcome {
+    pub fn as_str(self) -> &'static str {
+        match self {
+            LoginOutcome::Success => "success",
+            LoginOutcome::BadCredentials => "bad_credentials",
+            LoginOutcome::UnknownUser => "unknown_user",
+            LoginOutcome::Disabled => "disabled",
+            LoginOutcome::AccountLocked => "account_locked",
+            LoginOutcome::IpThrottled => "ip_throttled",
+        }
+    }
+}
+
+/// An account that is currently locked out
+#[derive(Debug, Clone, Serialize)]
+pub struct LockedAccount {
+    pub user_id: Uuid,
+    pub email: String,
+    pub failed_login_count: i32,
+    pub last_failed_login_at: Option<DateTime<Utc>>,
+    pub locked_until: DateTime<Utc>,
+}
+
+/// An IP address that is currently throttled
+#[derive(Debug, Clone, Serialize)]
+pub struct ThrottledIp {
+    pub ip: String,
+    pub recent_failures: i64,
+    pub blocked_until: DateTime<Utc>,
+}
@@ -41,7 +41,6 @@ pub struct MessageWithSender {
 #[derive(Debug, Serialize, Deserialize)]
 pub struct CreateMessageInput {
     pub content: String,
-    pub is_from_customer: bool,
     pub channel: Option<String>,
     pub in_reply_to: Option<Uuid>,
     pub subject: Option<String>,
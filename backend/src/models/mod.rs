@@ -7,3 +7,18 @@ pub mod attachment;
 pub mod knowledge_base;
 pub mod notification;
 pub mod analytics;
+pub mod invitation;
+pub mod login_attempt;
+pub mod api_key;
+pub mod permission;
+pub mod role;
+pub mod signing_key;
+pub mod session;
+pub mod search;
+pub mod ticket_event;
+pub mod sla;
+pub mod business_calendar;
+pub mod routing;
+pub mod team;
+pub mod custom_field;
+pub mod ticket_link;
\ No newline at end of file
@@ -0,0 +1,87 @@
+use std::str::FromStr;
+
+/// Everything a role can be allowed to do. The string forms are the rows of
+/// the `permissions` table; keep both in sync.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Permission {
+    TicketsCreate,
+    TicketsReadOwn,
+    TicketsReadAssigned,
+    TicketsReadAll,
+    TicketsUpdateOwn,
+    TicketsUpdate,
+    TicketsDelete,
+    TicketsAssign,
+    NotesRead,
+    NotesWrite,
+    KbManage,
+    ReportsView,
+    UsersManage,
+    RolesManage,
+    SettingsManage,
+}
+
+impl Permission {
+    pub const ALL: &'static [Permission] = &[
+        Permission::TicketsCreate,
+        Permission::TicketsReadOwn,
+        Permission::TicketsReadAssigned,
+        Permission::TicketsReadAll,
+        Permission::TicketsUpdateOwn,
+        Permission::TicketsUpdate,
+        Permission::TicketsDelete,
+        Permission::TicketsAssign,
+        Permission::NotesRead,
+        Permission::NotesWrite,
+        Permission::KbManage,
+        Permission::ReportsView,
+        Permission::UsersManage,
+        Permission::RolesManage,
+        Permission::SettingsManage,
+    ];
+
+    pub fn as_str(self) -> &'static str {
+        match self {
+            Permission::TicketsCreate => "tickets.create",
+            Permission::TicketsReadOwn => "tickets.read_own",
+            Permission::TicketsReadAssigned => "tickets.read_assigned",
+            Permission::TicketsReadAll => "tickets.read_all",
+            Permission::TicketsUpdateOwn => "tickets.update_own",
+            Permission::TicketsUpdate => "tickets.update",
+            Permission::TicketsDelete => "tickets.delete",
+            Permission::TicketsAssign => "tickets.assign",
+            Permission::NotesRead => "notes.read",
+            Permission::NotesWrite => "notes.write",
+            Permission::KbManage => "kb.manage",
+            Permission::ReportsView => "reports.view",
+            Permission::UsersManage => "users.manage",
+            Permission::RolesManage => "roles.manage",
+            Permission::SettingsManage => "settings.manage",
+        }
+    }
+}
+
+impl FromStr for Permission {
+    type Err = ();
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        Permission::ALL
+            .iter()
+            .copied()
+            .find(|p| p.as_str() == s)
+            .ok_or(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn string_forms_round_trip() {
+        for permission in Permission::ALL {
+            assert_eq!(permission.as_str().parse::<Permission>(), Ok(*permission));
+        }
+        assert!("tickets.nuke".parse::<Permission>().is_err());
+    }
+}
@@ -0,0 +1,23 @@
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use sqlx::FromRow;
+
+/// Role every self-registered account gets
+pub const DEFAULT_ROLE: &str = "user";
+/// Built-in role that always holds every permission
+pub const ADMIN_ROLE: &str = "admin";
+
+#[derive(Debug, Clone, Serialize, Deserialize, FromRow)]
+pub struct Role {
+    pub name: String,
+    pub description: String,
+    pub is_system: bool,
+    pub permissions: Vec<String>,
+    pub created_at: DateTime<Utc>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, FromRow)]
+pub struct PermissionInfo {
+    pub name: String,
+    pub description: String,
+}
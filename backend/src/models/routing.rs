@@ -0,0 +1,106 @@
+use std::str::FromStr;
+
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use sqlx::FromRow;
+use uuid::Uuid;
+
+/// How the assignment engine picks an agent for a ticket
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum RoutingStrategy {
+    /// Whoever was handed a ticket longest ago
+    #[default]
+    RoundRobin,
+    /// Whoever has the fewest open tickets
+    LeastOpen,
+    /// Agents with a skill matching one of the ticket's tags; most matches first
+    Skills,
+}
+
+impl RoutingStrategy {
+    pub fn as_str(self) -> &'static str {
+        match self {
+            RoutingStrategy::RoundRobin => "round_robin",
+            RoutingStrategy::LeastOpen => "least_open",
+            RoutingStrategy::Skills => "skills",
+        }
+    }
+}
+
+impl FromStr for RoutingStrategy {
+    type Err = ();
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "round_robin" => Ok(RoutingStrategy::RoundRobin),
+            "least_open" => Ok(RoutingStrategy::LeastOpen),
+            "skills" => Ok(RoutingStrategy::Skills),
+            _ => Err(()),
+        }
+    }
+}
+
+/// Whether new tickets are routed automatically, and how
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub struct RoutingSettings {
+    pub auto_assign: bool,
+    pub strategy: RoutingStrategy,
+}
+
+impl Default for RoutingSettings {
+    fn default() -> Self {
+        Self { auto_assign: true, strategy: RoutingStrategy::default() }
+    }
+}
+
+/// An agent the engine may hand tickets to
+#[derive(Debug, Clone, Serialize, Deserialize, FromRow)]
+pub struct AgentRoutingProfile {
+    pub user_id: Uuid,
+    /// Lower-case tag names this agent handles
+    pub skills: Vec<String>,
+    /// `None` means no limit
+    pub max_open_tickets: Option<i32>,
+    pub available: bool,
+    pub last_assigned_at: Option<DateTime<Utc>>,
+    pub updated_at: DateTime<Utc>,
+}
+
+/// An agent with a profile, as the engine sees them when routing
+#[derive(Debug, Clone, FromRow)]
+pub struct RoutingCandidate {
+    pub user_id: Uuid,
+    pub name: String,
+    pub skills: Vec<String>,
+    pub max_open_tickets: Option<i32>,
+    pub available: bool,
+    pub last_assigned_at: Option<DateTime<Utc>>,
+    /// Assigned tickets that aren't Resolved or Closed
+    pub open_tickets: i64,
+    /// Teams the agent is a member of
+    pub team_ids: Vec<Uuid>,
+}
+
+/// Why one agent was or wasn't considered
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct CandidateReport {
+    pub agent_id: Uuid,
+    pub name: String,
+    pub eligible: bool,
+    pub open_tickets: i64,
+    pub matched_skills: Vec<String>,
+    pub reason: String,
+}
+
+/// Which agent a strategy picks for a ticket and why
+#[derive(Debug, Clone, Serialize)]
+pub struct RoutingDecision {
+    pub ticket_id: Uuid,
+    pub strategy: RoutingStrategy,
+    /// `None` when nobody is eligible
+    pub agent_id: Option<Uuid>,
+    pub reason: String,
+    /// Every agent with a routing profile, the chosen one first
+    pub candidates: Vec<CandidateReport>,
+}
@@ -0,0 +1,35 @@
+use chrono::{DateTime, Utc};
+use serde::Serialize;
+use uuid::Uuid;
+
+use crate::models::ticket::Ticket;
+
+/// Where a search match was found
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum SearchHitKind {
+    Ticket,
+    Message,
+    Note,
+}
+
+/// One matching ticket, message or note with the matched words highlighted
+#[derive(Debug, Clone, Serialize)]
+pub struct SearchHit {
+    pub kind: SearchHitKind,
+    /// Id of the ticket, message or note
+    pub id: Uuid,
+    /// HTML-escaped excerpt with matches wrapped in `<mark>`
+    pub snippet: String,
+    pub rank: f32,
+    pub created_at: Option<DateTime<Utc>>,
+}
+
+/// A ticket and its best matches; results are ordered by `rank`, the
+/// ticket's best hit
+#[derive(Debug, Clone, Serialize)]
+pub struct SearchResult {
+    pub ticket: Ticket,
+    pub rank: f32,
+    pub hits: Vec<SearchHit>,
+}
@@ -0,0 +1,57 @@
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use sqlx::FromRow;
+use uuid::Uuid;
+
+/// Longest user agent stored with a session; anything after is cut off
+pub const MAX_USER_AGENT_LEN: usize = 512;
+
+/// A login on one device, as shown in the session list
+#[derive(Debug, Clone, Serialize, Deserialize, FromRow)]
+pub struct Session {
+    pub id: Uuid,
+    pub user_id: Uuid,
+    pub user_agent: Option<String>,
+    pub ip_address: Option<String>,
+    pub created_at: DateTime<Utc>,
+    pub last_seen_at: DateTime<Utc>,
+    pub expires_at: DateTime<Utc>,
+    pub revoked_at: Option<DateTime<Utc>>,
+}
+
+/// The device a request came from, recorded when a session starts
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct ClientInfo {
+    pub user_agent: Option<String>,
+    pub ip_address: Option<String>,
+}
+
+impl ClientInfo {
+    pub fn new(user_agent: Option<&str>, ip_address: Option<String>) -> Self {
+        let user_agent = user_agent
+            .map(str::trim)
+            .filter(|ua| !ua.is_empty())
+            .map(|ua| ua.chars().take(MAX_USER_AGENT_LEN).collect());
+
+        Self { user_agent, ip_address }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn user_agent_is_trimmed_and_capped() {
+        let long = "x".repeat(MAX_USER_AGENT_LEN + 10);
+        assert_eq!(
+            ClientInfo::new(Some(&long), None).user_agent.map(|ua| ua.len()),
+            Some(MAX_USER_AGENT_LEN)
+        );
+        assert_eq!(ClientInfo::new(Some("  "), None).user_agent, None);
+        assert_eq!(
+            ClientInfo::new(Some(" curl/8.0 "), Some("10.0.0.1".into())),
+            ClientInfo { user_agent: Some("curl/8.0".into()), ip_address: Some("10.0.0.1".into()) }
+        );
+    }
+}
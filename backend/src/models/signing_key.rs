@@ -0,0 +1,53 @@
+use std::str::FromStr;
+
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use sqlx::FromRow;
+
+/// Algorithms access tokens can be signed with. The string forms are the
+/// JOSE `alg` names, as stored in `signing_keys.algorithm`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum KeyAlgorithm {
+    #[serde(rename = "RS256")]
+    Rs256,
+    #[serde(rename = "EdDSA")]
+    EdDsa,
+}
+
+impl KeyAlgorithm {
+    pub fn as_str(self) -> &'static str {
+        match self {
+            KeyAlgorithm::Rs256 => "RS256",
+            KeyAlgorithm::EdDsa => "EdDSA",
+        }
+    }
+
+    pub fn jwt_algorithm(self) -> jsonwebtoken::Algorithm {
+        match self {
+            KeyAlgorithm::Rs256 => jsonwebtoken::Algorithm::RS256,
+            KeyAlgorithm::EdDsa => jsonwebtoken::Algorithm::EdDSA,
+        }
+    }
+}
+
+impl FromStr for KeyAlgorithm {
+    type Err = ();
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "RS256" => Ok(KeyAlgorithm::Rs256),
+            "EdDSA" => Ok(KeyAlgorithm::EdDsa),
+            _ => Err(()),
+        }
+    }
+}
+
+/// Signing key metadata for admins (the private key is never read back out)
+#[derive(Debug, Clone, Serialize, FromRow)]
+pub struct SigningKeyInfo {
+    pub kid: String,
+    pub algorithm: String,
+    pub created_at: DateTime<Utc>,
+    /// `None` for the key currently signing tokens
+    pub retired_at: Option<DateTime<Utc>>,
+}
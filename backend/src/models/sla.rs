@@ -0,0 +1,43 @@
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use sqlx::FromRow;
+use uuid::Uuid;
+
+use crate::models::ticket::TicketPriority;
+
+/// Response and resolution targets for tickets of one priority, optionally
+/// only those from one customer organization (email domain)
+#[derive(Debug, Clone, Serialize, Deserialize, FromRow)]
+pub struct SlaPolicy {
+    pub id: Uuid,
+    pub name: String,
+    pub priority: TicketPriority,
+    /// e.g. `example.com`; `None` applies to every other customer
+    pub customer_domain: Option<String>,
+    pub first_response_minutes: i32,
+    pub resolution_minutes: i32,
+    /// Count targets in this business calendar's hours; `None` counts around the clock
+    pub calendar_id: Option<Uuid>,
+    pub created_at: DateTime<Utc>,
+    pub updated_at: DateTime<Utc>,
+}
+
+/// Where a ticket stands against its SLA. All `None` when no policy applied.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize, FromRow)]
+pub struct TicketSla {
+    #[sqlx(rename = "sla_policy_id")]
+    pub policy_id: Option<Uuid>,
+    pub first_response_due_at: Option<DateTime<Utc>>,
+    pub first_responded_at: Option<DateTime<Utc>>,
+    pub first_response_breached_at: Option<DateTime<Utc>>,
+    pub resolution_due_at: Option<DateTime<Utc>>,
+    /// When the ticket was last resolved or closed; cleared if it reopens
+    pub resolved_at: Option<DateTime<Utc>>,
+    pub resolution_breached_at: Option<DateTime<Utc>>,
+    /// Set while the clock is stopped (the ticket is Pending, Resolved or Closed)
+    #[sqlx(rename = "sla_paused_at")]
+    pub paused_at: Option<DateTime<Utc>>,
+    /// Time the clock has spent stopped so far, not counting `paused_at`
+    #[sqlx(rename = "sla_paused_seconds")]
+    pub paused_seconds: i64,
+}
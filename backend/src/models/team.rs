@@ -0,0 +1,32 @@
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use sqlx::FromRow;
+use uuid::Uuid;
+
+/// A group of staff with a shared ticket queue, e.g. Billing or Tier 2
+#[derive(Debug, Clone, Serialize, Deserialize, FromRow)]
+pub struct Team {
+    pub id: Uuid,
+    pub name: String,
+    pub description: Option<String>,
+    pub created_at: DateTime<Utc>,
+    pub updated_at: DateTime<Utc>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, FromRow)]
+pub struct TeamMember {
+    pub user_id: Uuid,
+    pub name: String,
+    pub email: String,
+    /// Leads can add and remove the team's other members
+    pub is_lead: bool,
+    pub added_at: DateTime<Utc>,
+}
+
+/// A team with everyone in it, leads first
+#[derive(Debug, Clone, Serialize)]
+pub struct TeamDetail {
+    #[serde(flatten)]
+    pub team: Team,
+    pub members: Vec<TeamMember>,
+}
@@ -1,19 +1,79 @@
+use std::str::FromStr;
+
 use chrono::{DateTime, Utc};
 use serde::{Deserialize, Serialize};
 use sqlx::{FromRow, Type};
 use uuid::Uuid;
 
+use crate::models::sla::TicketSla;
+
 /// Matches PostgreSQL enum `ticket_status`
-#[derive(Debug, Clone, Serialize, Deserialize, Type, PartialEq, Eq)]
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, Type, PartialEq, Eq)]
 #[sqlx(type_name = "ticket_status")]
 #[serde(rename_all = "PascalCase")]
 pub enum TicketStatus {
+    /// Nobody has picked it up yet
+    New,
     Open,
-    InProgress,
+    /// Waiting on the customer; their reply reopens it
+    Pending,
+    /// Waiting on something other than the customer
+    OnHold,
+    /// Fixed as far as we can tell; the customer can still reopen it by replying
+    Resolved,
+    /// Done for good
     Closed,
 }
 
-/// Matches PostgreSQL enum `ticket_priority`
+impl TicketStatus {
+    pub fn as_str(self) -> &'static str {
+        match self {
+            TicketStatus::New => "New",
+            TicketStatus::Open => "Open",
+            TicketStatus::Pending => "Pending",
+            TicketStatus::OnHold => "OnHold",
+            TicketStatus::Resolved => "Resolved",
+            TicketStatus::Closed => "Closed",
+        }
+    }
+
+    /// Whether a ticket in this status may be moved to `next`.
+    /// Staying put is always allowed; nothing goes back to `New`,
+    /// `Resolved` can only be reopened or closed and `Closed` is final.
+    pub fn can_transition_to(self, next: TicketStatus) -> bool {
+        use TicketStatus::*;
+
+        match (self, next) {
+            (from, to) if from == to => true,
+            (_, New) | (Closed, _) => false,
+            (Resolved, to) => matches!(to, Open | Closed),
+            _ => true,
+        }
+    }
+
+    /// Statuses a customer reply moves back to `Open`
+    pub fn reopens_on_customer_reply(self) -> bool {
+        matches!(self, TicketStatus::Pending | TicketStatus::Resolved)
+    }
+}
+
+impl FromStr for TicketStatus {
+    type Err = ();
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "New" => Ok(TicketStatus::New),
+            "Open" => Ok(TicketStatus::Open),
+            "Pending" => Ok(TicketStatus::Pending),
+            "OnHold" => Ok(TicketStatus::OnHold),
+            "Resolved" => Ok(TicketStatus::Resolved),
+            "Closed" => Ok(TicketStatus::Closed),
+            _ => Err(()),
+        }
+    }
+}
+
+/// Matches PostgreSQL enum `ticket_priority`. Sorts Low < Medium < High.
 #[derive(Debug, Clone, Serialize, Deserialize, Type, PartialEq, Eq)]
 #[sqlx(type_name = "ticket_priority")]
 #[serde(rename_all = "PascalCase")]
@@ -23,6 +83,29 @@ pub enum TicketPriority {
     High,
 }
 
+impl TicketPriority {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            TicketPriority::Low => "Low",
+            TicketPriority::Medium => "Medium",
+            TicketPriority::High => "High",
+        }
+    }
+}
+
+impl FromStr for TicketPriority {
+    type Err = ();
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "Low" => Ok(TicketPriority::Low),
+            "Medium" => Ok(TicketPriority::Medium),
+            "High" => Ok(TicketPriority::High),
+            _ => Err(()),
+        }
+    }
+}
+
 /// Ticket table mapping
 #[derive(Debug, Clone, Serialize, Deserialize, FromRow)]
 pub struct Ticket {
@@ -36,15 +119,27 @@ pub struct Ticket {
     pub updated_at: Option<DateTime<Utc>>,
     pub customer_email: Option<String>,
     pub user_id: Option<Uuid>, // User who created the ticket
+    /// Team queue the ticket is in
+    pub team_id: Option<Uuid>,
+    /// Set once the ticket was merged into another as a duplicate
+    pub merged_into_id: Option<Uuid>,
+    #[sqlx(flatten)]
+    pub sla: TicketSla,
 }
 
 /// Create ticket DTO
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct CreateTicketInput {
     pub subject: String,
-    pub description: String,
+    pub description: Option<String>,
     pub priority: TicketPriority,
     pub customer_email: String,
+    /// Team queue to file it in
+    pub team_id: Option<Uuid>,
+    /// Tag names; unknown ones are created
+    pub tags: Vec<String>,
+    /// Custom field values by key
+    pub custom_fields: serde_json::Map<String, serde_json::Value>,
 }
 
 /// Update ticket status DTO
@@ -52,3 +147,57 @@ pub struct CreateTicketInput {
 pub struct UpdateTicketInput {
     pub status: TicketStatus,
 }
+
+#[cfg(test)]
+impl Ticket {
+    /// An open, unassigned ticket with no customer, team or SLA, for tests
+    /// to adjust with struct update syntax
+    pub fn for_test() -> Self {
+        Ticket {
+            id: Uuid::new_v4(),
+            subject: "Printer on fire".into(),
+            description: String::new(),
+            status: TicketStatus::Open,
+            priority: TicketPriority::Medium,
+            assigned_to: None,
+            created_at: Some(Utc::now()),
+            updated_at: Some(Utc::now()),
+            customer_email: None,
+            user_id: None,
+            team_id: None,
+            merged_into_id: None,
+            sla: Default::default(),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn lifecycle_transitions() {
+        use TicketStatus::*;
+
+        assert!(New.can_transition_to(Open));
+        assert!(Open.can_transition_to(Pending));
+        assert!(Pending.can_transition_to(OnHold));
+        assert!(OnHold.can_transition_to(Resolved));
+        assert!(Resolved.can_transition_to(Open));
+        assert!(Resolved.can_transition_to(Closed));
+        assert!(Closed.can_transition_to(Closed));
+
+        assert!(!Open.can_transition_to(New));
+        assert!(!Resolved.can_transition_to(Pending));
+        assert!(!Closed.can_transition_to(Open));
+    }
+
+    #[test]
+    fn only_waiting_or_resolved_tickets_reopen_on_reply() {
+        use TicketStatus::*;
+
+        assert!(Pending.reopens_on_customer_reply());
+        assert!(Resolved.reopens_on_customer_reply());
+        assert!(![New, Open, OnHold, Closed].iter().any(|s| s.reopens_on_customer_reply()));
+    }
+}
@@ -0,0 +1,55 @@
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use sqlx::FromRow;
+use uuid::Uuid;
+
+/// One changed field in a ticket's history
+#[derive(Debug, Clone, Serialize, Deserialize, FromRow)]
+pub struct TicketEvent {
+    pub id: Uuid,
+    pub ticket_id: Uuid,
+    pub actor_id: Option<Uuid>,
+    pub actor_name: Option<String>,
+    pub field: String, // see `TicketField`
+    pub old_value: Option<String>,
+    pub new_value: Option<String>,
+    pub created_at: DateTime<Utc>,
+}
+
+/// Ticket fields whose changes are recorded
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum TicketField {
+    Status,
+    Priority,
+    Assignee,
+    Team,
+    Tags,
+    Subject,
+    Description,
+    /// On a merged duplicate: the ticket it went into
+    MergedInto,
+    /// On a merge target: the duplicate merged into it
+    MergedFrom,
+    /// On a split ticket: the ticket it was split off
+    SplitFrom,
+    /// On the original: the ticket messages were split into
+    SplitInto,
+}
+
+impl TicketField {
+    pub fn as_str(self) -> &'static str {
+        match self {
+            TicketField::Status => "status",
+            TicketField::Priority => "priority",
+            TicketField::Assignee => "assignee",
+            TicketField::Team => "team",
+            TicketField::Tags => "tags",
+            TicketField::Subject => "subject",
+            TicketField::Description => "description",
+            TicketField::MergedInto => "merged_into",
+            TicketField::MergedFrom => "merged_from",
+            TicketField::SplitFrom => "split_from",
+            TicketField::SplitInto => "split_into",
+        }
+    }
+}
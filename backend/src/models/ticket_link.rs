@@ -0,0 +1,112 @@
+use std::str::FromStr;
+
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use sqlx::FromRow;
+use uuid::Uuid;
+
+use crate::models::ticket::TicketStatus;
+
+/// How another ticket relates to the one being looked at
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum LinkKind {
+    Parent,
+    Child,
+    Related,
+    /// The ticket is a duplicate of the other one
+    DuplicateOf,
+    DuplicatedBy,
+    /// The ticket can't move until the other one is done
+    BlockedBy,
+    Blocks,
+}
+
+impl LinkKind {
+    pub fn as_str(self) -> &'static str {
+        match self {
+            LinkKind::Parent => "parent",
+            LinkKind::Child => "child",
+            LinkKind::Related => "related",
+            LinkKind::DuplicateOf => "duplicate_of",
+            LinkKind::DuplicatedBy => "duplicated_by",
+            LinkKind::BlockedBy => "blocked_by",
+            LinkKind::Blocks => "blocks",
+        }
+    }
+
+    /// The same link seen from the other ticket
+    pub fn inverse(self) -> LinkKind {
+        match self {
+            LinkKind::Parent => LinkKind::Child,
+            LinkKind::Child => LinkKind::Parent,
+            LinkKind::Related => LinkKind::Related,
+            LinkKind::DuplicateOf => LinkKind::DuplicatedBy,
+            LinkKind::DuplicatedBy => LinkKind::DuplicateOf,
+            LinkKind::BlockedBy => LinkKind::Blocks,
+            LinkKind::Blocks => LinkKind::BlockedBy,
+        }
+    }
+
+    /// Whether links of this kind are stored from this side (see `ticket_links`)
+    pub fn is_stored(self) -> bool {
+        matches!(
+            self,
+            LinkKind::Parent | LinkKind::Related | LinkKind::DuplicateOf | LinkKind::BlockedBy
+        )
+    }
+}
+
+impl FromStr for LinkKind {
+    type Err = ();
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "parent" => Ok(LinkKind::Parent),
+            "child" => Ok(LinkKind::Child),
+            "related" => Ok(LinkKind::Related),
+            "duplicate_of" => Ok(LinkKind::DuplicateOf),
+            "duplicated_by" => Ok(LinkKind::DuplicatedBy),
+            "blocked_by" => Ok(LinkKind::BlockedBy),
+            "blocks" => Ok(LinkKind::Blocks),
+            _ => Err(()),
+        }
+    }
+}
+
+/// A `ticket_links` row: `ticket_id` has `kind` `linked_ticket_id`
+#[derive(Debug, Clone, FromRow)]
+pub struct TicketLinkRow {
+    pub id: Uuid,
+    pub ticket_id: Uuid,
+    pub linked_ticket_id: Uuid,
+    pub kind: String, // a stored `LinkKind`
+    pub created_by: Option<Uuid>,
+    pub created_at: DateTime<Utc>,
+}
+
+impl TicketLinkRow {
+    /// The link as seen from `ticket_id`, and the ticket at its other end
+    pub fn seen_from(&self, ticket_id: Uuid) -> Option<(LinkKind, Uuid)> {
+        let kind = self.kind.parse::<LinkKind>().ok()?;
+        if self.ticket_id == ticket_id {
+            Some((kind, self.linked_ticket_id))
+        } else if self.linked_ticket_id == ticket_id {
+            Some((kind.inverse(), self.ticket_id))
+        } else {
+            None
+        }
+    }
+}
+
+/// A link as listed on a ticket, with enough of the other ticket to show it
+#[derive(Debug, Clone, Serialize)]
+pub struct TicketLink {
+    pub id: Uuid,
+    pub kind: LinkKind,
+    pub ticket_id: Uuid,
+    pub subject: String,
+    pub status: TicketStatus,
+    pub created_by: Option<Uuid>,
+    pub created_at: DateTime<Utc>,
+}
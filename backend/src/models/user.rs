@@ -13,6 +13,30 @@ pub struct User {
     pub is_active: bool,                 // BOOLEAN NOT NULL DEFAULT TRUE
     pub created_at: Option<DateTime<Utc>>, // ✅ Made optional to allow NULLs
     pub updated_at: Option<DateTime<Utc>>, // ✅ Made optional to allow NULLs
+    pub failed_login_count: i32,         // consecutive failed logins, reset on success
+    pub last_failed_login_at: Option<DateTime<Utc>>,
+    pub locked_until: Option<DateTime<Utc>>, // login refused until then
+    pub email_verified_at: Option<DateTime<Utc>>, // NULL until a verification link is used
+    pub timezone: String,                // IANA name, e.g. "Europe/Berlin"
+    pub avatar_url: Option<String>,
+    pub anonymized_at: Option<DateTime<Utc>>, // personal data scrubbed by an admin
+}
+
+/// A user as admins see them in `/admin/users` (no secrets or lockout counters)
+#[derive(Debug, Clone, Serialize, FromRow)]
+pub struct UserSummary {
+    pub id: Uuid,
+    pub name: String,
+    pub email: String,
+    pub role: String,
+    pub is_active: bool,
+    pub email_verified_at: Option<DateTime<Utc>>,
+    pub timezone: String,
+    pub avatar_url: Option<String>,
+    pub locked_until: Option<DateTime<Utc>>,
+    pub anonymized_at: Option<DateTime<Utc>>,
+    pub created_at: Option<DateTime<Utc>>,
+    pub updated_at: Option<DateTime<Utc>>,
 }
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct PublicUser {
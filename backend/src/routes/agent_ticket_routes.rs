@@ -1,6 +1,7 @@
 use axum::http::StatusCode;
 use axum::{
-    extract::{Path, State},
+    extract::{Path, Query, State},
+    middleware,
     routing::{get, post},
     Json, Router,
 };
@@ -10,8 +11,11 @@ use std::sync::Arc;
 use uuid::Uuid;
 
 use crate::{
-    middleware::auth::AuthUser,
-    models::{note::Note, ticket::Ticket},
+    dto::ticket_dto::{ListTicketsQuery, TicketPageResponse},
+    handlers::ticket_handler::list_ticket_page,
+    middleware::{auth::Principal, permission::require_permission},
+    services::ticket_service::{TicketScope, TICKET_COLUMNS},
+    models::{note::Note, permission::Permission, ticket::Ticket},
     state::{AppState, SharedState},
 };
 
@@ -21,70 +25,49 @@ pub fn routes(state: Arc<AppState>) -> Router {
         .route("/agent/tickets", get(list_tickets_for_agent))
         .route("/agent/tickets/{id}", get(get_ticket_by_id_for_agent)) // ✅ Fixed
         .route("/agent/tickets/{id}/reply", post(reply_to_ticket)) // ✅ Fixed
+        .route_layer(middleware::from_fn_with_state(
+            state.clone(),
+            |state, req, next| require_permission(state, req, next, Permission::TicketsReadAssigned),
+        ))
         .with_state(state)
 }
 
 // === Handler: GET /agent/tickets ===
 async fn list_tickets_for_agent(
     State(state): State<SharedState>,
-    user: AuthUser,
-) -> Result<Json<Vec<Ticket>>, StatusCode> {
-    let agent_id = user.0.id;
+    principal: Principal,
+    Query(query): Query<ListTicketsQuery>,
+) -> Result<Json<TicketPageResponse>, StatusCode> {
+    list_ticket_page(&state, TicketScope::Assignee(principal.id), &query).await
+}
 
-    let tickets = query_as::<_, Ticket>(
-        r#"
-    SELECT id, subject, description,
-           customer_email, assigned_to,
-           priority, status,
-           created_at, updated_at,
-           user_id
-    FROM tickets
-    WHERE assigned_to = $1
-    "#,
-    )
-    .bind(agent_id)
-    .fetch_all(&state.db)
+/// One of the agent's own tickets or one in their teams' queues
+async fn fetch_agent_ticket(state: &SharedState, principal: &Principal, ticket_id: Uuid) -> Result<Ticket, StatusCode> {
+    let teams: Vec<Uuid> = principal.teams.iter().copied().collect();
+
+    let ticket = query_as::<_, Ticket>(&format!(
+        "SELECT {TICKET_COLUMNS} FROM tickets WHERE id = $1 AND (assigned_to = $2 OR team_id = ANY($3))"
+    ))
+    .bind(ticket_id)
+    .bind(principal.id)
+    .bind(&teams)
+    .fetch_optional(&state.db)
     .await
     .map_err(|e| {
-        tracing::error!("DB error fetching tickets: {:?}", e);
+        tracing::error!("DB error fetching ticket by ID: {:?}", e);
         StatusCode::INTERNAL_SERVER_ERROR
     })?;
 
-    Ok(Json(tickets))
+    ticket.ok_or(StatusCode::NOT_FOUND)
 }
 
 // === Handler: GET /agent/tickets/{id} ===
 async fn get_ticket_by_id_for_agent(
     Path(ticket_id): Path<Uuid>,
     State(state): State<SharedState>,
-    user: AuthUser,
+    principal: Principal,
 ) -> Result<Json<Ticket>, StatusCode> {
-    let agent_id = user.0.id;
-
-    let ticket = query_as::<_, Ticket>(
-        r#"
-    SELECT id, subject, description,
-           customer_email, assigned_to,
-           priority, status,
-           created_at, updated_at,
-           user_id
-    FROM tickets
-    WHERE id = $1 AND assigned_to = $2
-    "#,
-    )
-    .bind(ticket_id)
-    .bind(agent_id)
-    .fetch_optional(&state.db)
-    .await
-    .map_err(|e| {
-        tracing::error!("DB error fetching ticket by ID: {:?}", e);
-        StatusCode::INTERNAL_SERVER_ERROR
-    })?;
-
-    match ticket {
-        Some(t) => Ok(Json(t)),
-        None => Err(StatusCode::NOT_FOUND),
-    }
+    Ok(Json(fetch_agent_ticket(&state, &principal, ticket_id).await?))
 }
 
 // === Handler: POST /agent/tickets/{id}/reply ===
@@ -101,11 +84,13 @@ struct ReplyResponse {
 
 async fn reply_to_ticket(
     State(state): State<SharedState>,
-    user: AuthUser,
+    principal: Principal,
     Path(ticket_id): Path<Uuid>,
     Json(payload): Json<ReplyInput>,
 ) -> Result<Json<ReplyResponse>, StatusCode> {
-    let author_id = user.0.id;
+    principal.require(Permission::NotesWrite)?;
+    fetch_agent_ticket(&state, &principal, ticket_id).await?;
+    let author_id = principal.id;
 
     let result = query!(
         r#"
@@ -119,6 +104,7 @@ async fn reply_to_ticket(
     .execute(&state.db)
     .await;
 
+    // An internal note never reaches the customer, so it isn't a first response
     match result {
         Ok(_) => Ok(Json(ReplyResponse {
             success: true,
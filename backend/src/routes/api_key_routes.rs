@@ -0,0 +1,37 @@
+use axum::{
+    middleware,
+    routing::{delete, get, post},
+    Router,
+};
+
+use crate::{
+    handlers::api_key_handler::{
+        create_api_key,
+        list_all_api_keys,
+        list_my_api_keys,
+        list_scopes,
+        revoke_any_api_key,
+        revoke_my_api_key,
+    },
+    middleware::permission::require_permission,
+    models::permission::Permission,
+    state::SharedState,
+};
+
+/// 🔒 Managing API keys. API keys themselves can't call these (see `require_auth`).
+pub fn routes(state: SharedState) -> Router {
+    let admin_routes = Router::new()
+        .route("/admin/api-keys", get(list_all_api_keys))
+        .route("/admin/api-keys/{id}", delete(revoke_any_api_key))
+        .route_layer(middleware::from_fn_with_state(
+            state.clone(),
+            |state, req, next| require_permission(state, req, next, Permission::UsersManage),
+        ));
+
+    Router::new()
+        .route("/api-keys", post(create_api_key).get(list_my_api_keys))
+        .route("/api-keys/scopes", get(list_scopes))
+        .route("/api-keys/{id}", delete(revoke_my_api_key))
+        .merge(admin_routes)
+        .with_state(state)
+}
@@ -1,10 +1,26 @@
 use axum::{Router, routing::post};
-use crate::handlers::auth_handler::{register, login};
+use crate::handlers::auth_handler::{
+    register, login, refresh, logout, forgot_password, reset_password, verify_email,
+    resend_verification,
+};
 use crate::state::SharedState;
 
-pub fn routes(state: SharedState) -> Router {
+/// 🆓 Registration, login and everything reachable from an emailed link
+pub fn public_auth_routes(state: SharedState) -> Router {
     Router::new()
         .route("/auth/register", post(register))
         .route("/auth/login", post(login))
+        .route("/auth/refresh", post(refresh))
+        .route("/auth/logout", post(logout))
+        .route("/auth/forgot-password", post(forgot_password))
+        .route("/auth/reset-password", post(reset_password))
+        .route("/auth/verify-email", post(verify_email))
         .with_state(state) // ✅ required for State<SharedState>
 }
+
+/// 🔒 Account actions for a signed-in user
+pub fn protected_auth_routes(state: SharedState) -> Router {
+    Router::new()
+        .route("/auth/verify-email/resend", post(resend_verification))
+        .with_state(state)
+}
@@ -0,0 +1,32 @@
+use axum::{
+    middleware,
+    routing::{delete, get, post},
+    Router,
+};
+
+use crate::{
+    handlers::business_calendar_handler::{
+        add_holiday, create_calendar, delete_calendar, get_calendar, list_calendars, remove_holiday,
+        update_calendar,
+    },
+    middleware::permission::require_permission,
+    models::permission::Permission,
+    state::SharedState,
+};
+
+/// 🔒 Business calendars need `settings.manage`
+pub fn routes(state: SharedState) -> Router {
+    Router::new()
+        .route("/admin/calendars", get(list_calendars).post(create_calendar))
+        .route(
+            "/admin/calendars/{id}",
+            get(get_calendar).put(update_calendar).delete(delete_calendar),
+        )
+        .route("/admin/calendars/{id}/holidays", post(add_holiday))
+        .route("/admin/calendars/{id}/holidays/{date}", delete(remove_holiday))
+        .route_layer(middleware::from_fn_with_state(
+            state.clone(),
+            |state, req, next| require_permission(state, req, next, Permission::SettingsManage),
+        ))
+        .with_state(state)
+}
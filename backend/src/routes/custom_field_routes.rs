@@ -0,0 +1,28 @@
+use axum::{
+    middleware,
+    routing::{get, post, put},
+    Router,
+};
+
+use crate::{
+    handlers::custom_field_handler::{create_field, delete_field, list_fields, update_field},
+    middleware::permission::require_permission,
+    models::permission::Permission,
+    state::SharedState,
+};
+
+pub fn routes(state: SharedState) -> Router {
+    // 🔒 Defining fields needs `settings.manage`
+    let admin_routes = Router::new()
+        .route("/admin/custom-fields", post(create_field))
+        .route("/admin/custom-fields/{id}", put(update_field).delete(delete_field))
+        .route_layer(middleware::from_fn_with_state(
+            state.clone(),
+            |state, req, next| require_permission(state, req, next, Permission::SettingsManage),
+        ));
+
+    Router::new()
+        .route("/custom-fields", get(list_fields))
+        .merge(admin_routes)
+        .with_state(state)
+}
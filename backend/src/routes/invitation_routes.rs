@@ -0,0 +1,36 @@
+use axum::{
+    middleware,
+    routing::{delete, post},
+    Router,
+};
+
+use crate::{
+    handlers::invitation_handler::{
+        accept_invitation,
+        create_invitation,
+        list_invitations,
+        revoke_invitation,
+    },
+    middleware::permission::require_permission,
+    models::permission::Permission,
+    state::SharedState,
+};
+
+/// 🆓 Accepting an invitation is how invited staff get an account, so no auth
+pub fn public_invitation_routes(state: SharedState) -> Router {
+    Router::new()
+        .route("/auth/invitations/accept", post(accept_invitation))
+        .with_state(state)
+}
+
+/// 🔒 Issuing and managing invitations — needs `users.manage`
+pub fn protected_invitation_routes(state: SharedState) -> Router {
+    Router::new()
+        .route("/admin/invitations", post(create_invitation).get(list_invitations))
+        .route("/admin/invitations/{id}", delete(revoke_invitation))
+        .route_layer(middleware::from_fn_with_state(
+            state.clone(),
+            |state, req, next| require_permission(state, req, next, Permission::UsersManage),
+        ))
+        .with_state(state)
+}
@@ -1,5 +1,6 @@
 use axum::{
-    routing::{get, post, put, delete},
+    middleware,
+    routing::{get, post, put},
     Router,
 };
 
@@ -16,6 +17,8 @@ use crate::{
         delete_article_by_id,
         get_all_tags, // Make sure this exists
     },
+    middleware::permission::require_permission,
+    models::permission::Permission,
     state::SharedState,
 };
 
@@ -31,7 +34,7 @@ pub fn public_kb_routes(state: SharedState) -> Router {
         .with_state(state)
 }
 
-/// 🔒 Protected KB routes — for admin/agent only
+/// 🔒 Protected KB routes — needs `kb.manage`
 pub fn protected_kb_routes(state: SharedState) -> Router {
     Router::new()
         .route("/kb/articles", post(create_article))
@@ -40,5 +43,9 @@ pub fn protected_kb_routes(state: SharedState) -> Router {
                 .delete(delete_article_by_id)
         })
         .route("/kb/categories", post(create_category))
+        .route_layer(middleware::from_fn_with_state(
+            state.clone(),
+            |state, req, next| require_permission(state, req, next, Permission::KbManage),
+        ))
         .with_state(state)
 }
@@ -0,0 +1,31 @@
+use axum::{
+    middleware,
+    routing::{delete, get},
+    Router,
+};
+
+use crate::{
+    handlers::lockout_handler::{
+        clear_account_lockout,
+        clear_ip_lockout,
+        list_lockouts,
+        list_login_attempts,
+    },
+    middleware::permission::require_permission,
+    models::permission::Permission,
+    state::SharedState,
+};
+
+/// 🔒 Viewing and lifting login lockouts — needs `users.manage`
+pub fn routes(state: SharedState) -> Router {
+    Router::new()
+        .route("/admin/lockouts", get(list_lockouts))
+        .route("/admin/lockouts/users/{id}", delete(clear_account_lockout))
+        .route("/admin/lockouts/ips/{ip}", delete(clear_ip_lockout))
+        .route("/admin/login-attempts", get(list_login_attempts))
+        .route_layer(middleware::from_fn_with_state(
+            state.clone(),
+            |state, req, next| require_permission(state, req, next, Permission::UsersManage),
+        ))
+        .with_state(state)
+}
@@ -0,0 +1,51 @@
+use axum::{
+    middleware,
+    routing::{get, post},
+    Router,
+};
+
+use crate::{
+    handlers::mfa_handler::{
+        begin_enrollment,
+        confirm_enrollment,
+        confirm_totp,
+        disable_totp,
+        get_mfa_settings,
+        get_status,
+        regenerate_recovery_codes,
+        setup_totp,
+        update_mfa_settings,
+        verify_login,
+    },
+    middleware::permission::require_permission,
+    models::permission::Permission,
+    state::SharedState,
+};
+
+/// 🆓 Second login step: authenticated by the interim `mfa_token`, not a bearer token
+pub fn public_mfa_routes(state: SharedState) -> Router {
+    Router::new()
+        .route("/auth/mfa/verify", post(verify_login))
+        .route("/auth/mfa/enroll", post(begin_enrollment))
+        .route("/auth/mfa/enroll/confirm", post(confirm_enrollment))
+        .with_state(state)
+}
+
+/// 🔒 Managing your own second factor, plus the MFA policy (`settings.manage`)
+pub fn protected_mfa_routes(state: SharedState) -> Router {
+    let admin_routes = Router::new()
+        .route("/admin/settings/mfa", get(get_mfa_settings).put(update_mfa_settings))
+        .route_layer(middleware::from_fn_with_state(
+            state.clone(),
+            |state, req, next| require_permission(state, req, next, Permission::SettingsManage),
+        ));
+
+    Router::new()
+        .route("/auth/mfa", get(get_status))
+        .route("/auth/mfa/totp/setup", post(setup_totp))
+        .route("/auth/mfa/totp/confirm", post(confirm_totp))
+        .route("/auth/mfa/totp/disable", post(disable_totp))
+        .route("/auth/mfa/recovery-codes", post(regenerate_recovery_codes))
+        .merge(admin_routes)
+        .with_state(state)
+}
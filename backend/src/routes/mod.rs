@@ -9,4 +9,19 @@ pub mod report_routes;
 pub mod ws_routes;
 pub mod user_routes;
 pub mod comment_routes;
-pub mod agent_ticket_routes;
\ No newline at end of file
+pub mod agent_ticket_routes;
+pub mod invitation_routes;
+pub mod mfa_routes;
+pub mod lockout_routes;
+pub mod api_key_routes;
+pub mod role_routes;
+pub mod oidc_routes;
+pub mod signing_key_routes;
+pub mod session_routes;
+pub mod search_routes;
+pub mod sla_routes;
+pub mod business_calendar_routes;
+pub mod routing_routes;
+pub mod team_routes;
+pub mod custom_field_routes;
+pub mod ticket_link_routes;
\ No newline at end of file
@@ -7,9 +7,16 @@ use crate::{
     state::SharedState,
 };
 
-pub fn routes(state: SharedState) -> Router {
+/// 🆓 Creating notifications (used by backend services)
+pub fn public_notification_routes(state: SharedState) -> Router {
     Router::new()
         .route("/notifications", post(create_notification))
+        .with_state(state)
+}
+
+/// 🔒 The caller's own notifications
+pub fn protected_notification_routes(state: SharedState) -> Router {
+    Router::new()
         .route("/notifications", get(get_notifications)) // ✅ fetch notifications for authenticated user
         .with_state(state)
 }
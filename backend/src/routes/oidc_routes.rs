@@ -0,0 +1,19 @@
+use axum::{
+    routing::{get, post},
+    Router,
+};
+
+use crate::{
+    handlers::oidc_handler::{callback, exchange, list_providers, login},
+    state::SharedState,
+};
+
+/// 🆓 OpenID Connect single sign-on; the provider does the authenticating
+pub fn routes(state: SharedState) -> Router {
+    Router::new()
+        .route("/auth/oidc/providers", get(list_providers))
+        .route("/auth/oidc/exchange", post(exchange))
+        .route("/auth/oidc/{provider}/login", get(login))
+        .route("/auth/oidc/{provider}/callback", get(callback))
+        .with_state(state)
+}
@@ -1,9 +1,15 @@
-use axum::{Router, routing::get};
+use axum::{Router, middleware, routing::get};
 use crate::handlers::analytics_handler::ticket_summary;
+use crate::middleware::permission::require_permission;
+use crate::models::permission::Permission;
 use crate::state::SharedState;
 
 pub fn routes(state: SharedState) -> Router {
     Router::new()
         .route("/analytics/summary", get(ticket_summary))
+        .route_layer(middleware::from_fn_with_state(
+            state.clone(),
+            |state, req, next| require_permission(state, req, next, Permission::ReportsView),
+        ))
         .with_state(state) // ✅ Required
 }
@@ -0,0 +1,43 @@
+use axum::{
+    middleware,
+    routing::{get, put},
+    Router,
+};
+
+use crate::{
+    handlers::role_handler::{
+        assign_role,
+        create_role,
+        delete_role,
+        list_permissions,
+        list_roles,
+        update_role,
+    },
+    middleware::permission::require_permission,
+    models::permission::Permission,
+    state::SharedState,
+};
+
+/// 🔒 Defining roles needs `roles.manage`, moving users between them `users.manage`
+pub fn routes(state: SharedState) -> Router {
+    let role_routes = Router::new()
+        .route("/admin/roles", get(list_roles).post(create_role))
+        .route("/admin/roles/{name}", put(update_role).delete(delete_role))
+        .route("/admin/permissions", get(list_permissions))
+        .route_layer(middleware::from_fn_with_state(
+            state.clone(),
+            |state, req, next| require_permission(state, req, next, Permission::RolesManage),
+        ));
+
+    let assignment_routes = Router::new()
+        .route("/admin/users/{id}/role", put(assign_role))
+        .route_layer(middleware::from_fn_with_state(
+            state.clone(),
+            |state, req, next| require_permission(state, req, next, Permission::UsersManage),
+        ));
+
+    Router::new()
+        .merge(role_routes)
+        .merge(assignment_routes)
+        .with_state(state)
+}
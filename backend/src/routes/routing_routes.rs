@@ -0,0 +1,47 @@
+use axum::{
+    middleware,
+    routing::{get, put},
+    Router,
+};
+
+use crate::{
+    handlers::routing_handler::{
+        delete_profile, get_settings, list_profiles, preview_routing, route_ticket, save_profile,
+        set_my_availability, update_settings,
+    },
+    middleware::permission::require_permission,
+    models::permission::Permission,
+    state::SharedState,
+};
+
+pub fn routes(state: SharedState) -> Router {
+    // 🔒 Strategy and agent profiles need `settings.manage`
+    let admin_routes = Router::new()
+        .route("/admin/routing", get(get_settings).put(update_settings))
+        .route("/admin/routing/agents", get(list_profiles))
+        .route("/admin/routing/agents/{user_id}", put(save_profile).delete(delete_profile))
+        .route_layer(middleware::from_fn_with_state(
+            state.clone(),
+            |state, req, next| require_permission(state, req, next, Permission::SettingsManage),
+        ));
+
+    let ticket_routes = Router::new()
+        .route("/tickets/{ticket_id}/routing", get(preview_routing).post(route_ticket))
+        .route_layer(middleware::from_fn_with_state(
+            state.clone(),
+            |state, req, next| require_permission(state, req, next, Permission::TicketsAssign),
+        ));
+
+    let agent_routes = Router::new()
+        .route("/agent/availability", put(set_my_availability))
+        .route_layer(middleware::from_fn_with_state(
+            state.clone(),
+            |state, req, next| require_permission(state, req, next, Permission::TicketsReadAssigned),
+        ));
+
+    Router::new()
+        .merge(admin_routes)
+        .merge(ticket_routes)
+        .merge(agent_routes)
+        .with_state(state)
+}
@@ -0,0 +1,11 @@
+use axum::{routing::get, Router};
+
+use crate::{handlers::search_handler::search, state::SharedState};
+
+/// 🔒 Full-text search over the tickets, messages and notes the caller can
+/// see; visibility is worked out per caller in the handler
+pub fn routes(state: SharedState) -> Router {
+    Router::new()
+        .route("/search", get(search))
+        .with_state(state)
+}
@@ -0,0 +1,36 @@
+use axum::{
+    middleware,
+    routing::{delete, get},
+    Router,
+};
+
+use crate::{
+    handlers::session_handler::{
+        list_my_sessions,
+        list_user_sessions,
+        revoke_my_other_sessions,
+        revoke_my_session,
+        revoke_user_session,
+    },
+    middleware::permission::require_permission,
+    models::permission::Permission,
+    state::SharedState,
+};
+
+/// 🔒 Devices you are logged in on, and other users' (`users.manage`).
+/// API keys can't call these (see `require_auth`).
+pub fn routes(state: SharedState) -> Router {
+    let admin_routes = Router::new()
+        .route("/admin/users/{id}/sessions", get(list_user_sessions))
+        .route("/admin/users/{id}/sessions/{session_id}", delete(revoke_user_session))
+        .route_layer(middleware::from_fn_with_state(
+            state.clone(),
+            |state, req, next| require_permission(state, req, next, Permission::UsersManage),
+        ));
+
+    Router::new()
+        .route("/me/sessions", get(list_my_sessions).delete(revoke_my_other_sessions))
+        .route("/me/sessions/{id}", delete(revoke_my_session))
+        .merge(admin_routes)
+        .with_state(state)
+}
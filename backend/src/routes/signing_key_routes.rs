@@ -0,0 +1,31 @@
+use axum::{
+    middleware,
+    routing::{get, post},
+    Router,
+};
+
+use crate::{
+    handlers::signing_key_handler::{jwks, list_signing_keys, rotate_signing_key},
+    middleware::permission::require_permission,
+    models::permission::Permission,
+    state::SharedState,
+};
+
+/// 🆓 Public keys for anyone verifying our access tokens
+pub fn public_signing_key_routes(state: SharedState) -> Router {
+    Router::new()
+        .route("/.well-known/jwks.json", get(jwks))
+        .with_state(state)
+}
+
+/// 🔒 Listing and rotating token signing keys — needs `settings.manage`
+pub fn protected_signing_key_routes(state: SharedState) -> Router {
+    Router::new()
+        .route("/admin/signing-keys", get(list_signing_keys))
+        .route("/admin/signing-keys/rotate", post(rotate_signing_key))
+        .route_layer(middleware::from_fn_with_state(
+            state.clone(),
+            |state, req, next| require_permission(state, req, next, Permission::SettingsManage),
+        ))
+        .with_state(state)
+}
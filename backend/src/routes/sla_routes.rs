@@ -0,0 +1,24 @@
+use axum::{
+    middleware,
+    routing::{get, put},
+    Router,
+};
+
+use crate::{
+    handlers::sla_handler::{create_policy, delete_policy, list_policies, update_policy},
+    middleware::permission::require_permission,
+    models::permission::Permission,
+    state::SharedState,
+};
+
+/// 🔒 SLA policies need `settings.manage`
+pub fn routes(state: SharedState) -> Router {
+    Router::new()
+        .route("/admin/sla-policies", get(list_policies).post(create_policy))
+        .route("/admin/sla-policies/{id}", put(update_policy).delete(delete_policy))
+        .route_layer(middleware::from_fn_with_state(
+            state.clone(),
+            |state, req, next| require_permission(state, req, next, Permission::SettingsManage),
+        ))
+        .with_state(state)
+}
@@ -0,0 +1,45 @@
+use axum::{
+    middleware,
+    routing::{get, put},
+    Router,
+};
+
+use crate::{
+    handlers::team_handler::{
+        create_team, delete_team, get_team, list_queue, list_teams, move_ticket_to_team,
+        remove_member, save_member, update_team,
+    },
+    middleware::permission::require_permission,
+    models::permission::Permission,
+    state::SharedState,
+};
+
+pub fn routes(state: SharedState) -> Router {
+    // 🔒 Creating and removing teams needs `users.manage`
+    let admin_routes = Router::new()
+        .route("/admin/teams", get(list_teams).post(create_team))
+        .route("/admin/teams/{team_id}", put(update_team).delete(delete_team))
+        .route_layer(middleware::from_fn_with_state(
+            state.clone(),
+            |state, req, next| require_permission(state, req, next, Permission::UsersManage),
+        ));
+
+    // Members and leads; each handler checks the caller against the team
+    let member_routes = Router::new()
+        .route("/teams/{team_id}", get(get_team))
+        .route("/teams/{team_id}/members/{user_id}", put(save_member).delete(remove_member))
+        .route("/queues/{team_id}", get(list_queue));
+
+    let assignment_routes = Router::new()
+        .route("/tickets/{ticket_id}/team/{team_id}", put(move_ticket_to_team))
+        .route_layer(middleware::from_fn_with_state(
+            state.clone(),
+            |state, req, next| require_permission(state, req, next, Permission::TicketsAssign),
+        ));
+
+    Router::new()
+        .merge(admin_routes)
+        .merge(member_routes)
+        .merge(assignment_routes)
+        .with_state(state)
+}
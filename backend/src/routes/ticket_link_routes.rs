@@ -0,0 +1,17 @@
+use axum::{
+    routing::{delete, get},
+    Router,
+};
+
+use crate::{
+    handlers::ticket_link_handler::{create_link, delete_link, list_links},
+    state::SharedState,
+};
+
+pub fn routes(state: SharedState) -> Router {
+    // Each handler checks the caller against both tickets
+    Router::new()
+        .route("/tickets/{ticket_id}/links", get(list_links).post(create_link))
+        .route("/tickets/{ticket_id}/links/{link_id}", delete(delete_link))
+        .with_state(state)
+}
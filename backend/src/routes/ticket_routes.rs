@@ -8,17 +8,27 @@ use crate::{
     handlers::ticket_handler::{
         create_ticket,
         get_ticket_by_id,
+        get_ticket_history,
+        set_ticket_fields,
+        set_ticket_tags,
+        merge_ticket,
+        split_ticket,
+        watch_ticket,
+        unwatch_ticket,
+        export_tickets,
         list_tickets,
         update_ticket,
         delete_ticket,
         admin_list_tickets,
         assign_ticket,
     },
-    middleware::role_guard::require_roles,
+    middleware::permission::require_permission,
+    models::permission::Permission,
     state::SharedState,
 };
 
 pub fn routes(state: SharedState) -> Router {
+    // Each handler checks the caller's permissions against the ticket itself
     let user_routes = Router::new()
         .route("/tickets", post(create_ticket).get(list_tickets))
         .route(
@@ -27,23 +37,26 @@ pub fn routes(state: SharedState) -> Router {
                 .put(update_ticket)
                 .delete(delete_ticket),
         )
-        .route_layer(middleware::from_fn_with_state(
-            state.clone(),
-            |req, next| require_roles(req, next, &["user"]),
-        ));
+        .route("/tickets/{ticket_id}/history", get(get_ticket_history))
+        .route("/tickets/{ticket_id}/tags", put(set_ticket_tags))
+        .route("/tickets/{ticket_id}/fields", put(set_ticket_fields))
+        .route("/tickets/{ticket_id}/merge/{target_id}", post(merge_ticket))
+        .route("/tickets/{ticket_id}/split", post(split_ticket))
+        .route("/tickets/{ticket_id}/watch", put(watch_ticket).delete(unwatch_ticket));
 
     let admin_routes = Router::new()
         .route("/admin/tickets", get(admin_list_tickets))
+        .route("/admin/tickets/export", get(export_tickets))
         .route_layer(middleware::from_fn_with_state(
             state.clone(),
-            |req, next| require_roles(req, next, &["admin"]),
+            |state, req, next| require_permission(state, req, next, Permission::TicketsReadAll),
         ));
 
     let assignment_routes = Router::new()
         .route("/tickets/assign/{ticket_id}/{agent_id}", put(assign_ticket))
         .route_layer(middleware::from_fn_with_state(
             state.clone(),
-            |req, next| require_roles(req, next, &["admin", "agent"]),
+            |state, req, next| require_permission(state, req, next, Permission::TicketsAssign),
         ));
 
     Router::new()
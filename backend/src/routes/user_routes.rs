@@ -1,6 +1,24 @@
-use axum::{routing::get, Router};
+use axum::{
+    middleware,
+    routing::{get, post, put},
+    Router,
+};
 use crate::{
-    handlers::user_handler::get_agents,
+    handlers::user_handler::{
+        anonymize_user,
+        change_password,
+        delete_user,
+        force_logout,
+        get_agents,
+        get_me,
+        get_user,
+        list_users,
+        set_user_status,
+        update_me,
+        update_user,
+    },
+    middleware::permission::require_permission,
+    models::permission::Permission,
     state::SharedState,
 };
 
@@ -9,8 +27,24 @@ pub fn user_routes(state: SharedState) -> Router {
         .route("/agents", get(get_agents))
         .with_state(state) // ✅ This adds the app state needed by get_agents
 }
+
+/// 🔒 Your own profile, plus user administration (`users.manage`)
 pub fn routes(state: SharedState) -> Router {
+    let admin_routes = Router::new()
+        .route("/admin/users", get(list_users))
+        .route("/admin/users/{id}", get(get_user).put(update_user).delete(delete_user))
+        .route("/admin/users/{id}/status", put(set_user_status))
+        .route("/admin/users/{id}/logout", post(force_logout))
+        .route("/admin/users/{id}/anonymize", post(anonymize_user))
+        .route_layer(middleware::from_fn_with_state(
+            state.clone(),
+            |state, req, next| require_permission(state, req, next, Permission::UsersManage),
+        ));
+
     Router::new()
         .route("/agents", get(get_agents))
+        .route("/me", get(get_me).put(update_me))
+        .route("/me/password", post(change_password))
+        .merge(admin_routes)
         .with_state(state)
 }
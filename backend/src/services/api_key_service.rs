@@ -0,0 +1,150 @@
+use chrono::{DateTime, Utc};
+use sqlx::PgPool;
+use uuid::Uuid;
+
+use crate::models::api_key::{ApiKey, API_KEY_PREFIX, API_KEY_SCOPES};
+use crate::utils::token::{generate_token, hash_token};
+
+#[derive(Debug, thiserror::Error)]
+pub enum ApiKeyError {
+    #[error("Database error: {0}")]
+    Database(#[from] sqlx::Error),
+    #[error("Unknown scope: {0}")]
+    InvalidScope(String),
+    #[error("An API key needs at least one scope")]
+    NoScopes,
+    #[error("API key not found")]
+    NotFound,
+}
+
+pub type Result<T> = std::result::Result<T, ApiKeyError>;
+
+/// Characters of the key kept in clear so users can tell their keys apart
+const DISPLAY_PREFIX_LEN: usize = 10;
+
+/// A valid API key, as seen by the auth middleware
+#[derive(Debug, Clone)]
+pub struct ApiKeyOwner {
+    pub key_id: Uuid,
+    pub user_id: Uuid,
+    pub scopes: Vec<String>,
+}
+
+/// Create a key for `user_id` and return it together with the plaintext key
+pub async fn create_api_key(
+    pool: &PgPool,
+    user_id: Uuid,
+    name: &str,
+    scopes: &[String],
+    expires_at: Option<DateTime<Utc>>,
+) -> Result<(ApiKey, String)> {
+    if scopes.is_empty() {
+        return Err(ApiKeyError::NoScopes);
+    }
+    if let Some(unknown) = scopes.iter().find(|s| !API_KEY_SCOPES.contains(&s.as_str())) {
+        return Err(ApiKeyError::InvalidScope(unknown.clone()));
+    }
+
+    let mut scopes = scopes.to_vec();
+    scopes.sort();
+    scopes.dedup();
+
+    let key = format!("{API_KEY_PREFIX}{}", generate_token());
+
+    let api_key = sqlx::query_as_unchecked!(
+        ApiKey,
+        r#"
+        INSERT INTO api_keys (user_id, name, prefix, key_hash, scopes, expires_at)
+        VALUES ($1, $2, $3, $4, $5, $6)
+        RETURNING id, user_id, name, prefix, scopes, created_at, expires_at, last_used_at, revoked_at
+        "#,
+        user_id,
+        name,
+        &key[..DISPLAY_PREFIX_LEN],
+        hash_token(&key),
+        &scopes,
+        expires_at
+    )
+    .fetch_one(pool)
+    .await?;
+
+    Ok((api_key, key))
+}
+
+/// Keys owned by a user, newest first
+pub async fn list_for_user(pool: &PgPool, user_id: Uuid) -> Result<Vec<ApiKey>> {
+    let keys = sqlx::query_as_unchecked!(
+        ApiKey,
+        r#"
+        SELECT id, user_id, name, prefix, scopes, created_at, expires_at, last_used_at, revoked_at
+        FROM api_keys
+        WHERE user_id = $1
+        ORDER BY created_at DESC
+        "#,
+        user_id
+    )
+    .fetch_all(pool)
+    .await?;
+
+    Ok(keys)
+}
+
+/// Every key in the system, newest first
+pub async fn list_all(pool: &PgPool) -> Result<Vec<ApiKey>> {
+    let keys = sqlx::query_as_unchecked!(
+        ApiKey,
+        r#"
+        SELECT id, user_id, name, prefix, scopes, created_at, expires_at, last_used_at, revoked_at
+        FROM api_keys
+        ORDER BY created_at DESC
+        "#
+    )
+    .fetch_all(pool)
+    .await?;
+
+    Ok(keys)
+}
+
+/// Revoke a key. With `owner` set, only that user's keys can be revoked.
+pub async fn revoke_api_key(pool: &PgPool, key_id: Uuid, owner: Option<Uuid>) -> Result<ApiKey> {
+    let key = sqlx::query_as_unchecked!(
+        ApiKey,
+        r#"
+        UPDATE api_keys
+        SET revoked_at = COALESCE(revoked_at, now())
+        WHERE id = $1 AND ($2::uuid IS NULL OR user_id = $2)
+        RETURNING id, user_id, name, prefix, scopes, created_at, expires_at, last_used_at, revoked_at
+        "#,
+        key_id,
+        owner
+    )
+    .fetch_optional(pool)
+    .await?
+    .ok_or(ApiKeyError::NotFound)?;
+
+    Ok(key)
+}
+
+/// Resolve a presented key to its owner and record the use.
+/// Unknown, revoked and expired keys, and keys of disabled users, give `None`.
+pub async fn authenticate(pool: &PgPool, key: &str) -> Result<Option<ApiKeyOwner>> {
+    let owner = sqlx::query_as_unchecked!(
+        ApiKeyOwner,
+        r#"
+        UPDATE api_keys k
+        SET last_used_at = now()
+        FROM users u
+        WHERE k.key_hash = $1
+          AND k.revoked_at IS NULL
+          AND (k.expires_at IS NULL OR k.expires_at > now())
+          AND u.id = k.user_id
+          AND COALESCE(u.is_active, true)
+        RETURNING k.id AS key_id, k.user_id, k.scopes
+        "#,
+        hash_token(key)
+    )
+    .fetch_optional(pool)
+    .await?;
+
+    Ok(owner)
+}
@@ -1,12 +1,14 @@
-use crate::models::user::{RegisterInput, LoginInput, User};
-use crate::utils::jwt::generate_jwt;
+use crate::models::{session::ClientInfo, user::{RegisterInput, LoginInput, User}};
+use crate::config::AppConfig;
+use crate::services::session_service::create_session;
+use crate::utils::hash::{hash_password, verify_password};
+use crate::utils::jwt::{generate_jwt, SigningKey};
 use sqlx::PgPool;
 use uuid::Uuid;
-use bcrypt::{hash, verify, DEFAULT_COST};
 use anyhow::{Result, anyhow};
 
 pub async fn register_user(pool: &PgPool, input: RegisterInput) -> Result<User> {
-    let hashed = hash(&input.password, DEFAULT_COST)?;
+    let hashed = hash_password(&input.password)?;
 
     let row = sqlx::query_as::<_, User>(r#"
         INSERT INTO users (id, name, email, password_hash, role)
@@ -23,8 +25,8 @@ pub async fn register_user(pool: &PgPool, input: RegisterInput) -> Result<User>
     Ok(row) // ✅ return the inserted user
 }
 
-// ✅ Updated to accept jwt_secret parameter
-pub async fn login_user(pool: &PgPool, input: LoginInput, jwt_secret: &str) -> Result<String> {
+// ✅ Updated to accept app config (secret + token lifetimes)
+pub async fn login_user(pool: &PgPool, input: LoginInput, config: &AppConfig, key: &SigningKey) -> Result<String> {
     let user = sqlx::query_as::<_, User>(r#"
         SELECT id, name, email, password_hash, role, created_at
         FROM users
@@ -36,11 +38,11 @@ pub async fn login_user(pool: &PgPool, input: LoginInput, jwt_secret: &str) -> R
 
     let user = user.ok_or_else(|| anyhow!("Invalid email or password"))?;
 
-    if !verify(&input.password, &user.password_hash)? {
+    if !verify_password(&input.password, &user.password_hash)? {
         return Err(anyhow!("Invalid email or password"));
     }
 
-    // ✅ Pass the jwt_secret to generate_jwt
-    let token = generate_jwt(&user, jwt_secret)?;
+    let session = create_session(pool, user.id, config.refresh_token_ttl(), &ClientInfo::default()).await?;
+    let token = generate_jwt(&user, session.session_id, key, config.access_token_ttl())?;
     Ok(token)
 }
\ No newline at end of file
@@ -0,0 +1,338 @@
+use std::collections::HashSet;
+
+use chrono::{NaiveDate, NaiveTime, Weekday};
+use sqlx::{FromRow, PgConnection, PgPool, Postgres, QueryBuilder};
+use uuid::Uuid;
+
+use crate::{
+    models::business_calendar::{BusinessCalendar, Holiday},
+    utils::business_time::{Schedule, Shift, WeeklySchedule},
+};
+
+#[derive(Debug, thiserror::Error)]
+pub enum CalendarError {
+    #[error("Database error: {0}")]
+    Database(#[from] sqlx::Error),
+    #[error("Calendar not found")]
+    NotFound,
+    #[error("Unknown time zone: {0}")]
+    InvalidTimezone(String),
+    #[error("{0} is listed as a holiday more than once")]
+    DuplicateHoliday(NaiveDate),
+    #[error("A calendar with this name already exists")]
+    AlreadyExists,
+    #[error("Calendar is used by an SLA policy")]
+    InUse,
+}
+
+pub type Result<T> = std::result::Result<T, CalendarError>;
+
+/// Everything an admin sets on a calendar
+#[derive(Debug, Clone)]
+pub struct CalendarInput {
+    pub name: String,
+    pub timezone: String,
+    pub hours: Vec<Shift>,
+    pub holidays: Vec<Holiday>,
+}
+
+#[derive(FromRow)]
+struct HoursRow {
+    calendar_id: Uuid,
+    weekday: i16,
+    opens_at: NaiveTime,
+    closes_at: NaiveTime,
+}
+
+#[derive(FromRow)]
+struct HolidayRow {
+    calendar_id: Uuid,
+    #[sqlx(flatten)]
+    holiday: Holiday,
+}
+
+fn parse_timezone(timezone: &str) -> Result<chrono_tz::Tz> {
+    timezone
+        .parse()
+        .map_err(|_| CalendarError::InvalidTimezone(timezone.to_string()))
+}
+
+fn validate(input: &CalendarInput) -> Result<()> {
+    parse_timezone(&input.timezone)?;
+
+    let mut dates = HashSet::new();
+    match input.holidays.iter().find(|holiday| !dates.insert(holiday.date)) {
+        Some(duplicate) => Err(CalendarError::DuplicateHoliday(duplicate.date)),
+        None => Ok(()),
+    }
+}
+
+fn conflict_or(err: sqlx::Error) -> CalendarError {
+    match err.as_database_error() {
+        Some(db) if db.is_unique_violation() => CalendarError::AlreadyExists,
+        _ => err.into(),
+    }
+}
+
+/// The calendar's hours and holidays, for timers to count with
+pub fn schedule_of(calendar: &BusinessCalendar) -> Schedule {
+    // Saved time zones are validated; fall back to UTC rather than fail a timer
+    let tz = calendar.timezone.parse().unwrap_or(chrono_tz::UTC);
+    let holidays = calendar.holidays.iter().map(|holiday| holiday.date);
+
+    Schedule::Weekly(Box::new(WeeklySchedule::new(tz, &calendar.hours, holidays)))
+}
+
+/// Fill in the hours and holidays of `calendars`
+async fn attach(conn: &mut PgConnection, calendars: &mut [BusinessCalendar]) -> std::result::Result<(), sqlx::Error> {
+    let ids: Vec<Uuid> = calendars.iter().map(|calendar| calendar.id).collect();
+
+    let hours = sqlx::query_as::<_, HoursRow>(
+        r#"
+        SELECT calendar_id, weekday, opens_at, closes_at FROM business_hours
+        WHERE calendar_id = ANY($1)
+        ORDER BY weekday, opens_at
+        "#,
+    )
+    .bind(&ids)
+    .fetch_all(&mut *conn)
+    .await?;
+
+    let holidays = sqlx::query_as::<_, HolidayRow>(
+        "SELECT calendar_id, date, name FROM business_holidays WHERE calendar_id = ANY($1) ORDER BY date",
+    )
+    .bind(&ids)
+    .fetch_all(&mut *conn)
+    .await?;
+
+    for calendar in calendars.iter_mut() {
+        calendar.hours = hours
+            .iter()
+            .filter(|row| row.calendar_id == calendar.id)
+            .filter_map(|row| {
+                Some(Shift {
+                    weekday: Weekday::try_from(u8::try_from(row.weekday).ok()?).ok()?,
+                    opens_at: row.opens_at,
+                    closes_at: row.closes_at,
+                })
+            })
+            .collect();
+        calendar.holidays = holidays
+            .iter()
+            .filter(|row| row.calendar_id == calendar.id)
+            .map(|row| row.holiday.clone())
+            .collect();
+    }
+
+    Ok(())
+}
+
+async fn fetch_calendar(conn: &mut PgConnection, calendar_id: Uuid) -> Result<BusinessCalendar> {
+    let calendar = sqlx::query_as::<_, BusinessCalendar>("SELECT * FROM business_calendars WHERE id = $1")
+        .bind(calendar_id)
+        .fetch_optional(&mut *conn)
+        .await?
+        .ok_or(CalendarError::NotFound)?;
+
+    let mut calendars = [calendar];
+    attach(conn, &mut calendars).await?;
+    let [calendar] = calendars;
+
+    Ok(calendar)
+}
+
+/// Replace a calendar's hours and holidays with those in `input`
+async fn save_hours(conn: &mut PgConnection, calendar_id: Uuid, input: &CalendarInput) -> std::result::Result<(), sqlx::Error> {
+    sqlx::query("DELETE FROM business_hours WHERE calendar_id = $1")
+        .bind(calendar_id)
+        .execute(&mut *conn)
+        .await?;
+    sqlx::query("DELETE FROM business_holidays WHERE calendar_id = $1")
+        .bind(calendar_id)
+        .execute(&mut *conn)
+        .await?;
+
+    if !input.hours.is_empty() {
+        let mut qb: QueryBuilder<Postgres> =
+            QueryBuilder::new("INSERT INTO business_hours (calendar_id, weekday, opens_at, closes_at) ");
+        qb.push_values(&input.hours, |mut row, shift| {
+            row.push_bind(calendar_id)
+                .push_bind(shift.weekday.num_days_from_monday() as i16)
+                .push_bind(shift.opens_at)
+                .push_bind(shift.closes_at);
+        });
+        qb.build().execute(&mut *conn).await?;
+    }
+
+    if !input.holidays.is_empty() {
+        let mut qb: QueryBuilder<Postgres> =
+            QueryBuilder::new("INSERT INTO business_holidays (calendar_id, date, name) ");
+        qb.push_values(&input.holidays, |mut row, holiday| {
+            row.push_bind(calendar_id)
+                .push_bind(holiday.date)
+                .push_bind(holiday.name.trim());
+        });
+        qb.build().execute(&mut *conn).await?;
+    }
+
+    Ok(())
+}
+
+/// Every calendar, by name
+pub async fn list_calendars(pool: &PgPool) -> Result<Vec<BusinessCalendar>> {
+    let mut conn = pool.acquire().await?;
+    let mut calendars = sqlx::query_as::<_, BusinessCalendar>("SELECT * FROM business_calendars ORDER BY name")
+        .fetch_all(&mut *conn)
+        .await?;
+    attach(&mut conn, &mut calendars).await?;
+
+    Ok(calendars)
+}
+
+pub async fn get_calendar(pool: &PgPool, calendar_id: Uuid) -> Result<BusinessCalendar> {
+    let mut conn = pool.acquire().await?;
+    fetch_calendar(&mut conn, calendar_id).await
+}
+
+pub async fn create_calendar(pool: &PgPool, input: &CalendarInput) -> Result<BusinessCalendar> {
+    validate(input)?;
+
+    let mut tx = pool.begin().await?;
+    let calendar_id: Uuid =
+        sqlx::query_scalar("INSERT INTO business_calendars (name, timezone) VALUES ($1, $2) RETURNING id")
+            .bind(input.name.trim())
+            .bind(&input.timezone)
+            .fetch_one(&mut *tx)
+            .await
+            .map_err(conflict_or)?;
+    save_hours(&mut tx, calendar_id, input).await?;
+    let calendar = fetch_calendar(&mut tx, calendar_id).await?;
+    tx.commit().await?;
+
+    Ok(calendar)
+}
+
+/// Replace all of a calendar's settings. Running timers keep the due times
+/// they already have.
+pub async fn update_calendar(pool: &PgPool, calendar_id: Uuid, input: &CalendarInput) -> Result<BusinessCalendar> {
+    validate(input)?;
+
+    let mut tx = pool.begin().await?;
+    let updated = sqlx::query(
+        "UPDATE business_calendars SET name = $1, timezone = $2, updated_at = now() WHERE id = $3",
+    )
+    .bind(input.name.trim())
+    .bind(&input.timezone)
+    .bind(calendar_id)
+    .execute(&mut *tx)
+    .await
+    .map_err(conflict_or)?;
+    if updated.rows_affected() == 0 {
+        return Err(CalendarError::NotFound);
+    }
+    save_hours(&mut tx, calendar_id, input).await?;
+    let calendar = fetch_calendar(&mut tx, calendar_id).await?;
+    tx.commit().await?;
+
+    Ok(calendar)
+}
+
+/// Remove a calendar no SLA policy uses any more
+pub async fn delete_calendar(pool: &PgPool, calendar_id: Uuid) -> Result<()> {
+    let deleted = sqlx::query("DELETE FROM business_calendars WHERE id = $1")
+        .bind(calendar_id)
+        .execute(pool)
+        .await
+        .map_err(|err| match err.as_database_error() {
+            Some(db) if db.is_foreign_key_violation() => CalendarError::InUse,
+            _ => err.into(),
+        })?;
+
+    if deleted.rows_affected() == 0 {
+        return Err(CalendarError::NotFound);
+    }
+
+    Ok(())
+}
+
+/// Add a holiday, or rename the one already on that date
+pub async fn add_holiday(pool: &PgPool, calendar_id: Uuid, holiday: &Holiday) -> Result<Holiday> {
+    sqlx::query_as::<_, Holiday>(
+        r#"
+        INSERT INTO business_holidays (calendar_id, date, name)
+        VALUES ($1, $2, $3)
+        ON CONFLICT (calendar_id, date) DO UPDATE SET name = EXCLUDED.name
+        RETURNING date, name
+        "#,
+    )
+    .bind(calendar_id)
+    .bind(holiday.date)
+    .bind(holiday.name.trim())
+    .fetch_one(pool)
+    .await
+    .map_err(|err| match err.as_database_error() {
+        Some(db) if db.is_foreign_key_violation() => CalendarError::NotFound,
+        _ => err.into(),
+    })
+}
+
+pub async fn remove_holiday(pool: &PgPool, calendar_id: Uuid, date: NaiveDate) -> Result<()> {
+    let deleted = sqlx::query("DELETE FROM business_holidays WHERE calendar_id = $1 AND date = $2")
+        .bind(calendar_id)
+        .bind(date)
+        .execute(pool)
+        .await?;
+
+    if deleted.rows_affected() == 0 {
+        return Err(CalendarError::NotFound);
+    }
+
+    Ok(())
+}
+
+/// The schedule a timer counts with: the calendar's, or around the clock
+/// without one
+pub async fn schedule(conn: &mut PgConnection, calendar_id: Option<Uuid>) -> std::result::Result<Schedule, sqlx::Error> {
+    let Some(calendar_id) = calendar_id else {
+        return Ok(Schedule::AlwaysOpen);
+    };
+
+    match fetch_calendar(conn, calendar_id).await {
+        Ok(calendar) => Ok(schedule_of(&calendar)),
+        Err(CalendarError::Database(err)) => Err(err),
+        Err(_) => Ok(Schedule::AlwaysOpen),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn input(timezone: &str, holidays: &[(u32, &str)]) -> CalendarInput {
+        CalendarInput {
+            name: "Support".into(),
+            timezone: timezone.into(),
+            hours: Vec::new(),
+            holidays: holidays
+                .iter()
+                .map(|&(day, name)| Holiday {
+                    date: NaiveDate::from_ymd_opt(2026, 12, day).unwrap(),
+                    name: name.into(),
+                })
+                .collect(),
+        }
+    }
+
+    #[test]
+    fn calendars_need_a_real_time_zone_and_distinct_holidays() {
+        assert!(validate(&input("Europe/Berlin", &[(24, "Christmas Eve"), (25, "Christmas")])).is_ok());
+        assert!(matches!(
+            validate(&input("Mars/Olympus_Mons", &[])),
+            Err(CalendarError::InvalidTimezone(tz)) if tz == "Mars/Olympus_Mons"
+        ));
+        assert!(matches!(
+            validate(&input("UTC", &[(25, "Christmas"), (25, "Also Christmas")])),
+            Err(CalendarError::DuplicateHoliday(date)) if date == NaiveDate::from_ymd_opt(2026, 12, 25).unwrap()
+        ));
+    }
+}
@@ -1,4 +1,5 @@
 use crate::models::message::{CreateMessageInput, Message, MessageWithSender};
+use crate::services::ticket_service::{self, ReplySide};
 use crate::state::SharedState;
 use chrono::Utc;
 use sqlx::PgPool;
@@ -7,10 +8,12 @@ use serde_json::json;
 use tracing::{info, warn};
 
 /// Adds a new message to a ticket and broadcasts it via WebSocket.
+/// `side` is where the sender stands on the ticket, as resolved by the caller.
 pub async fn add_message_to_ticket(
     pool: &PgPool,
     ticket_id: Uuid,
     sender_id: Uuid,
+    side: ReplySide,
     input: CreateMessageInput,
     state: Option<SharedState>, // Optional state for WebSocket broadcasting
 ) -> Result<Message, sqlx::Error> {
@@ -46,7 +49,7 @@ pub async fn add_message_to_ticket(
         ticket_id,
         sender_id,
         input.content,
-        input.is_from_customer,
+        side == ReplySide::Customer,
         input.channel,
         input.in_reply_to,
         input.subject,
@@ -59,6 +62,11 @@ pub async fn add_message_to_ticket(
     .fetch_one(pool)
     .await?;
 
+    // The message is saved either way
+    if let Err(e) = ticket_service::record_reply(pool, ticket_id, side, sender_id).await {
+        warn!("Failed to update ticket {} after reply: {:?}", ticket_id, e);
+    }
+
     // Broadcast the new message via WebSocket if state is provided
     if let Some(app_state) = state {
         broadcast_message_to_websocket(&app_state, &message).await;
@@ -102,6 +110,33 @@ async fn broadcast_message_to_websocket(state: &SharedState, message: &Message)
     }
 }
 
+/// Tells WebSocket clients of each ticket that messages moved between
+/// tickets, so open conversations can drop or pick them up
+pub async fn broadcast_messages_moved(
+    state: &SharedState,
+    event: &str,
+    source_id: Uuid,
+    target_id: Uuid,
+    message_ids: &[Uuid],
+) {
+    let ws_message = json!({
+        "event": event,
+        "source_ticket_id": source_id,
+        "target_ticket_id": target_id,
+        "message_ids": message_ids,
+    })
+    .to_string();
+
+    let channels = state.ws_channels.read().await;
+    for ticket_id in [source_id, target_id] {
+        if let Some(tx) = channels.get(&ticket_id) {
+            if let Err(e) = tx.send(ws_message.clone()) {
+                warn!("Failed to broadcast {} to WebSocket for ticket {}: {:?}", event, ticket_id, e);
+            }
+        }
+    }
+}
+
 /// Retrieves all messages for a specific ticket along with sender details.
 pub async fn get_messages_by_ticket(
     pool: &PgPool,
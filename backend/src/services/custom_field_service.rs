@@ -0,0 +1,469 @@
+use std::collections::{BTreeMap, HashMap};
+
+use chrono::NaiveDate;
+use serde_json::{Map, Value};
+use sqlx::{FromRow, PgConnection, PgPool};
+use uuid::Uuid;
+
+use crate::models::custom_field::{CustomField, CustomFieldType};
+
+#[derive(Debug, thiserror::Error)]
+pub enum CustomFieldError {
+    #[error("Database error: {0}")]
+    Database(#[from] sqlx::Error),
+    #[error("Custom field not found")]
+    NotFound,
+    #[error("A custom field with this key already exists")]
+    AlreadyExists,
+    #[error("Keys are 2-50 lower-case letters, digits or underscores, starting with a letter")]
+    InvalidKey,
+    #[error("Dropdowns need at least one option; other types take none")]
+    InvalidOptions,
+    #[error("Unknown custom field: {0}")]
+    UnknownField(String),
+    #[error("Invalid value for {key}: {reason}")]
+    InvalidValue { key: String, reason: &'static str },
+}
+
+pub type Result<T> = std::result::Result<T, CustomFieldError>;
+
+const MAX_TEXT_LEN: usize = 1000;
+
+/// Everything an admin sets when creating a field
+#[derive(Debug, Clone)]
+pub struct FieldInput {
+    pub key: String,
+    pub label: String,
+    pub field_type: CustomFieldType,
+    pub options: Vec<String>,
+    pub required_on_close: bool,
+    pub position: i32,
+}
+
+/// What can change once a field exists; key and type are fixed so stored
+/// values stay valid
+#[derive(Debug, Clone)]
+pub struct FieldUpdate {
+    pub label: String,
+    pub options: Vec<String>,
+    pub required_on_close: bool,
+    pub position: i32,
+}
+
+fn validate_key(key: &str) -> Result<()> {
+    let valid = (2..=50).contains(&key.len())
+        && key.starts_with(|c: char| c.is_ascii_lowercase())
+        && key
+            .bytes()
+            .all(|b| b.is_ascii_lowercase() || b.is_ascii_digit() || b == b'_');
+    if valid {
+        Ok(())
+    } else {
+        Err(CustomFieldError::InvalidKey)
+    }
+}
+
+/// Trimmed, without blanks or duplicates, in the order given
+fn validate_options(field_type: CustomFieldType, options: &[String]) -> Result<Vec<String>> {
+    let mut cleaned: Vec<String> = Vec::new();
+    for option in options.iter().map(|o| o.trim()).filter(|o| !o.is_empty()) {
+        if !cleaned.iter().any(|c| c == option) {
+            cleaned.push(option.to_string());
+        }
+    }
+
+    if (field_type == CustomFieldType::Dropdown) == cleaned.is_empty() {
+        return Err(CustomFieldError::InvalidOptions);
+    }
+
+    Ok(cleaned)
+}
+
+fn invalid(field: &CustomField, reason: &'static str) -> CustomFieldError {
+    CustomFieldError::InvalidValue { key: field.key.clone(), reason }
+}
+
+/// `value` in the field's canonical text form, `None` to clear it.
+/// Numbers and checkboxes also accept strings, so query strings can use this too.
+pub fn normalize_value(field: &CustomField, value: &Value) -> Result<Option<String>> {
+    let text = match value {
+        Value::Null => return Ok(None),
+        Value::String(s) => Some(s.trim()),
+        _ => None,
+    };
+    if text == Some("") {
+        return Ok(None);
+    }
+
+    let normalized = match field.kind() {
+        CustomFieldType::Text => {
+            let text = text.ok_or_else(|| invalid(field, "expected text"))?;
+            if text.chars().count() > MAX_TEXT_LEN {
+                return Err(invalid(field, "text is too long"));
+            }
+            text.to_string()
+        }
+        CustomFieldType::Number => {
+            let number = match (value, text) {
+                (Value::Number(n), _) => n.as_f64(),
+                (_, Some(text)) => text.parse::<f64>().ok(),
+                _ => None,
+            }
+            .filter(|n| n.is_finite())
+            .ok_or_else(|| invalid(field, "expected a number"))?;
+
+            // 5.0 and "5" are the same value
+            if number.fract() == 0.0 && number.abs() < 1e15 {
+                format!("{number:.0}")
+            } else {
+                number.to_string()
+            }
+        }
+        CustomFieldType::Dropdown => {
+            let choice = text.ok_or_else(|| invalid(field, "expected one of the options"))?;
+            if !field.options.iter().any(|o| o == choice) {
+                return Err(invalid(field, "not one of the options"));
+            }
+            choice.to_string()
+        }
+        CustomFieldType::Date => text
+            .and_then(|text| NaiveDate::parse_from_str(text, "%Y-%m-%d").ok())
+            .ok_or_else(|| invalid(field, "expected a YYYY-MM-DD date"))?
+            .to_string(),
+        CustomFieldType::Checkbox => match (value, text) {
+            (Value::Bool(checked), _) => checked.to_string(),
+            (_, Some("true")) => "true".to_string(),
+            (_, Some("false")) => "false".to_string(),
+            _ => return Err(invalid(field, "expected true or false")),
+        },
+    };
+
+    Ok(Some(normalized))
+}
+
+/// A stored value as JSON of the field's type
+pub fn typed_value(field_type: CustomFieldType, stored: &str) -> Value {
+    match field_type {
+        CustomFieldType::Number => stored
+            .parse::<f64>()
+            .ok()
+            .and_then(|n| {
+                if n.fract() == 0.0 && n.abs() < 1e15 {
+                    Some(Value::from(n as i64))
+                } else {
+                    serde_json::Number::from_f64(n).map(Value::Number)
+                }
+            })
+            .unwrap_or_else(|| Value::String(stored.to_string())),
+        CustomFieldType::Checkbox => Value::Bool(stored == "true"),
+        _ => Value::String(stored.to_string()),
+    }
+}
+
+fn conflict_or(err: sqlx::Error) -> CustomFieldError {
+    match err.as_database_error() {
+        Some(db) if db.is_unique_violation() => CustomFieldError::AlreadyExists,
+        _ => err.into(),
+    }
+}
+
+// ---------------------------------------------------------------------------
+// Field definitions
+// ---------------------------------------------------------------------------
+
+/// Every field, in display order
+pub async fn list_fields(pool: &PgPool) -> Result<Vec<CustomField>> {
+    let fields = sqlx::query_as::<_, CustomField>("SELECT * FROM custom_fields ORDER BY position, label")
+        .fetch_all(pool)
+        .await?;
+
+    Ok(fields)
+}
+
+pub async fn create_field(pool: &PgPool, input: &FieldInput) -> Result<CustomField> {
+    validate_key(&input.key)?;
+    let options = validate_options(input.field_type, &input.options)?;
+
+    sqlx::query_as::<_, CustomField>(
+        r#"
+        INSERT INTO custom_fields (key, label, field_type, options, required_on_close, position)
+        VALUES ($1, $2, $3, $4, $5, $6)
+        RETURNING *
+        "#,
+    )
+    .bind(&input.key)
+    .bind(input.label.trim())
+    .bind(input.field_type.as_str())
+    .bind(options)
+    .bind(input.required_on_close)
+    .bind(input.position)
+    .fetch_one(pool)
+    .await
+    .map_err(conflict_or)
+}
+
+/// Change a field's label, options or rules. Values already saved are kept,
+/// even if their dropdown option is gone.
+pub async fn update_field(pool: &PgPool, field_id: Uuid, input: &FieldUpdate) -> Result<CustomField> {
+    let field = sqlx::query_as::<_, CustomField>("SELECT * FROM custom_fields WHERE id = $1")
+        .bind(field_id)
+        .fetch_optional(pool)
+        .await?
+        .ok_or(CustomFieldError::NotFound)?;
+    let options = validate_options(field.kind(), &input.options)?;
+
+    let field = sqlx::query_as::<_, CustomField>(
+        r#"
+        UPDATE custom_fields
+        SET label = $1, options = $2, required_on_close = $3, position = $4, updated_at = now()
+        WHERE id = $5
+        RETURNING *
+        "#,
+    )
+    .bind(input.label.trim())
+    .bind(options)
+    .bind(input.required_on_close)
+    .bind(input.position)
+    .bind(field_id)
+    .fetch_one(pool)
+    .await?;
+
+    Ok(field)
+}
+
+/// Remove a field and every value saved for it
+pub async fn delete_field(pool: &PgPool, field_id: Uuid) -> Result<()> {
+    let deleted = sqlx::query("DELETE FROM custom_fields WHERE id = $1")
+        .bind(field_id)
+        .execute(pool)
+        .await?;
+
+    if deleted.rows_affected() == 0 {
+        return Err(CustomFieldError::NotFound);
+    }
+
+    Ok(())
+}
+
+// ---------------------------------------------------------------------------
+// Ticket values
+// ---------------------------------------------------------------------------
+
+/// Set the ticket's values for the fields named in `values`; `null` or an
+/// empty string clears one. Fields not named are left as they are.
+pub async fn set_values(conn: &mut PgConnection, ticket_id: Uuid, values: &Map<String, Value>) -> Result<()> {
+    if values.is_empty() {
+        return Ok(());
+    }
+
+    let fields: HashMap<String, CustomField> = sqlx::query_as::<_, CustomField>("SELECT * FROM custom_fields")
+        .fetch_all(&mut *conn)
+        .await?
+        .into_iter()
+        .map(|field| (field.key.clone(), field))
+        .collect();
+
+    for (key, value) in values {
+        let field = fields
+            .get(key)
+            .ok_or_else(|| CustomFieldError::UnknownField(key.clone()))?;
+
+        match normalize_value(field, value)? {
+            Some(value) => {
+                sqlx::query(
+                    r#"
+                    INSERT INTO ticket_field_values (ticket_id, field_id, value)
+                    VALUES ($1, $2, $3)
+                    ON CONFLICT (ticket_id, field_id) DO UPDATE
+                    SET value = EXCLUDED.value, updated_at = now()
+                    "#,
+                )
+                .bind(ticket_id)
+                .bind(field.id)
+                .bind(value)
+                .execute(&mut *conn)
+                .await?;
+            }
+            None => {
+                sqlx::query("DELETE FROM ticket_field_values WHERE ticket_id = $1 AND field_id = $2")
+                    .bind(ticket_id)
+                    .bind(field.id)
+                    .execute(&mut *conn)
+                    .await?;
+            }
+        }
+    }
+
+    Ok(())
+}
+
+#[derive(FromRow)]
+struct ValueRow {
+    ticket_id: Uuid,
+    key: String,
+    field_type: String,
+    value: String,
+}
+
+/// Each ticket's values by field key, typed; tickets without any are left out
+pub async fn values_for(
+    pool: &PgPool,
+    ticket_ids: &[Uuid],
+) -> Result<HashMap<Uuid, BTreeMap<String, Value>>> {
+    let rows = sqlx::query_as::<_, ValueRow>(
+        r#"
+        SELECT v.ticket_id, f.key, f.field_type, v.value
+        FROM ticket_field_values v
+        JOIN custom_fields f ON f.id = v.field_id
+        WHERE v.ticket_id = ANY($1)
+        "#,
+    )
+    .bind(ticket_ids)
+    .fetch_all(pool)
+    .await?;
+
+    let mut values: HashMap<Uuid, BTreeMap<String, Value>> = HashMap::new();
+    for row in rows {
+        let field_type = row.field_type.parse().unwrap_or(CustomFieldType::Text);
+        values
+            .entry(row.ticket_id)
+            .or_default()
+            .insert(row.key, typed_value(field_type, &row.value));
+    }
+
+    Ok(values)
+}
+
+/// Keys of the fields the ticket must fill in before it can be closed
+pub async fn missing_required(conn: &mut PgConnection, ticket_id: Uuid) -> std::result::Result<Vec<String>, sqlx::Error> {
+    sqlx::query_scalar(
+        r#"
+        SELECT f.key FROM custom_fields f
+        WHERE f.required_on_close
+          AND NOT EXISTS (
+              SELECT 1 FROM ticket_field_values v
+              WHERE v.field_id = f.id AND v.ticket_id = $1
+          )
+        ORDER BY f.position, f.key
+        "#,
+    )
+    .bind(ticket_id)
+    .fetch_all(conn)
+    .await
+}
+
+/// `(key, value)` list filters with each value in its field's canonical form
+pub async fn normalize_filters(pool: &PgPool, filters: &[(String, String)]) -> Result<Vec<(String, String)>> {
+    if filters.is_empty() {
+        return Ok(Vec::new());
+    }
+
+    let fields = list_fields(pool).await?;
+    filters
+        .iter()
+        .map(|(key, raw)| {
+            let field = fields
+                .iter()
+                .find(|field| &field.key == key)
+                .ok_or_else(|| CustomFieldError::UnknownField(key.clone()))?;
+            let value = normalize_value(field, &Value::String(raw.clone()))?
+                .ok_or_else(|| invalid(field, "filter value is empty"))?;
+            Ok((key.clone(), value))
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use chrono::Utc;
+    use serde_json::json;
+
+    use super::*;
+
+    fn field(field_type: CustomFieldType, options: &[&str]) -> CustomField {
+        CustomField {
+            id: Uuid::new_v4(),
+            key: "plan".into(),
+            label: "Plan".into(),
+            field_type: field_type.as_str().into(),
+            options: options.iter().map(|o| o.to_string()).collect(),
+            required_on_close: false,
+            position: 0,
+            created_at: Utc::now(),
+            updated_at: Utc::now(),
+        }
+    }
+
+    fn normalize(field: &CustomField, value: Value) -> Option<String> {
+        normalize_value(field, &value).unwrap()
+    }
+
+    #[test]
+    fn keys_are_lower_case_identifiers() {
+        assert!(validate_key("order_number").is_ok());
+        assert!(validate_key("tier2").is_ok());
+        assert!(validate_key("2tier").is_err());
+        assert!(validate_key("Order").is_err());
+        assert!(validate_key("order-number").is_err());
+        assert!(validate_key("x").is_err());
+    }
+
+    #[test]
+    fn only_dropdowns_take_options() {
+        let options = vec![" Pro ".to_string(), "Free".into(), "Pro".into(), "".into()];
+        assert_eq!(validate_options(CustomFieldType::Dropdown, &options).unwrap(), vec!["Pro", "Free"]);
+        assert!(validate_options(CustomFieldType::Dropdown, &[]).is_err());
+        assert!(validate_options(CustomFieldType::Number, &options).is_err());
+        assert!(validate_options(CustomFieldType::Date, &[]).unwrap().is_empty());
+    }
+
+    #[test]
+    fn values_are_stored_in_canonical_form() {
+        let number = field(CustomFieldType::Number, &[]);
+        assert_eq!(normalize(&number, json!(5.0)).as_deref(), Some("5"));
+        assert_eq!(normalize(&number, json!(" 5 ")).as_deref(), Some("5"));
+        assert_eq!(normalize(&number, json!(2.5)).as_deref(), Some("2.5"));
+
+        let date = field(CustomFieldType::Date, &[]);
+        assert_eq!(normalize(&date, json!("2026-02-28")).as_deref(), Some("2026-02-28"));
+
+        let checkbox = field(CustomFieldType::Checkbox, &[]);
+        assert_eq!(normalize(&checkbox, json!(true)).as_deref(), Some("true"));
+        assert_eq!(normalize(&checkbox, json!("false")).as_deref(), Some("false"));
+
+        let plan = field(CustomFieldType::Dropdown, &["Free", "Pro"]);
+        assert_eq!(normalize(&plan, json!("Pro")).as_deref(), Some("Pro"));
+
+        let text = field(CustomFieldType::Text, &[]);
+        assert_eq!(normalize(&text, json!("  A-123 ")).as_deref(), Some("A-123"));
+        assert_eq!(normalize(&text, json!("   ")), None);
+        assert_eq!(normalize(&text, Value::Null), None);
+    }
+
+    #[test]
+    fn values_of_the_wrong_type_are_rejected() {
+        let cases = [
+            (field(CustomFieldType::Number, &[]), json!("five")),
+            (field(CustomFieldType::Date, &[]), json!("28/02/2026")),
+            (field(CustomFieldType::Date, &[]), json!("2026-02-30")),
+            (field(CustomFieldType::Checkbox, &[]), json!("yes")),
+            (field(CustomFieldType::Dropdown, &["Free", "Pro"]), json!("Enterprise")),
+            (field(CustomFieldType::Text, &[]), json!(42)),
+        ];
+        for (field, value) in cases {
+            assert!(
+                matches!(normalize_value(&field, &value), Err(CustomFieldError::InvalidValue { .. })),
+                "{value} accepted for {}",
+                field.field_type
+            );
+        }
+    }
+
+    #[test]
+    fn stored_values_come_back_typed() {
+        assert_eq!(typed_value(CustomFieldType::Number, "5"), json!(5));
+        assert_eq!(typed_value(CustomFieldType::Number, "2.5"), json!(2.5));
+        assert_eq!(typed_value(CustomFieldType::Checkbox, "true"), json!(true));
+        assert_eq!(typed_value(CustomFieldType::Date, "2026-02-28"), json!("2026-02-28"));
+    }
+}
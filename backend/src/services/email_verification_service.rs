@@ -0,0 +1,136 @@
+use chrono::{Duration, Utc};
+use sqlx::PgPool;
+use uuid::Uuid;
+
+use crate::utils::token::{generate_token, hash_token};
+
+#[derive(Debug, thiserror::Error)]
+pub enum EmailVerificationError {
+    #[error("Database error: {0}")]
+    Database(#[from] sqlx::Error),
+    #[error("Verification link is invalid, expired or already used")]
+    InvalidToken,
+    #[error("Email address is already verified")]
+    AlreadyVerified,
+    #[error("A verification email was sent moments ago")]
+    TooSoon,
+    #[error("User not found")]
+    UserNotFound,
+}
+
+pub type Result<T> = std::result::Result<T, EmailVerificationError>;
+
+/// How long a verification link stays valid
+pub const VERIFICATION_TOKEN_TTL_HOURS: i64 = 24;
+/// Minimum gap between two verification emails to the same user
+pub const RESEND_COOLDOWN_SECONDS: i64 = 60;
+
+/// Who to mail a freshly created verification token to
+#[derive(Debug)]
+pub struct VerificationRequest {
+    pub email: String,
+    pub name: String,
+    pub token: String,
+}
+
+/// Create a verification token for the user's current email address
+pub async fn request_verification(pool: &PgPool, user_id: Uuid) -> Result<VerificationRequest> {
+    let user = sqlx::query!(
+        r#"
+        SELECT name, email, email_verified_at,
+               (SELECT MAX(created_at) FROM email_verification_tokens WHERE user_id = u.id) AS last_sent_at
+        FROM users u
+        WHERE id = $1
+        "#,
+        user_id
+    )
+    .fetch_optional(pool)
+    .await?
+    .ok_or(EmailVerificationError::UserNotFound)?;
+
+    if user.email_verified_at.is_some() {
+        return Err(EmailVerificationError::AlreadyVerified);
+    }
+    if user
+        .last_sent_at
+        .is_some_and(|sent| sent > Utc::now() - Duration::seconds(RESEND_COOLDOWN_SECONDS))
+    {
+        return Err(EmailVerificationError::TooSoon);
+    }
+
+    let token = generate_token();
+
+    sqlx::query!(
+        r#"
+        INSERT INTO email_verification_tokens (user_id, email, token_hash, expires_at)
+        VALUES ($1, $2, $3, $4)
+        "#,
+        user_id,
+        user.email,
+        hash_token(&token),
+        Utc::now() + Duration::hours(VERIFICATION_TOKEN_TTL_HOURS)
+    )
+    .execute(pool)
+    .await?;
+
+    Ok(VerificationRequest {
+        email: user.email,
+        name: user.name,
+        token,
+    })
+}
+
+/// Consume a verification token and mark the address it was sent to as
+/// verified. Burns the user's other outstanding links as well.
+pub async fn verify_email(pool: &PgPool, token: &str) -> Result<Uuid> {
+    let mut tx = pool.begin().await?;
+
+    let verification = sqlx::query!(
+        r#"
+        SELECT id, user_id, email, expires_at, used_at
+        FROM email_verification_tokens
+        WHERE token_hash = $1
+        FOR UPDATE
+        "#,
+        hash_token(token)
+    )
+    .fetch_optional(&mut *tx)
+    .await?
+    .ok_or(EmailVerificationError::InvalidToken)?;
+
+    if verification.used_at.is_some() || verification.expires_at <= Utc::now() {
+        return Err(EmailVerificationError::InvalidToken);
+    }
+
+    // Only if the account still has the address the link was mailed to
+    let updated = sqlx::query!(
+        r#"
+        UPDATE users
+        SET email_verified_at = COALESCE(email_verified_at, now()), updated_at = now()
+        WHERE id = $1 AND email = $2
+        "#,
+        verification.user_id,
+        verification.email
+    )
+    .execute(&mut *tx)
+    .await?;
+
+    if updated.rows_affected() == 0 {
+        return Err(EmailVerificationError::InvalidToken);
+    }
+
+    sqlx::query!(
+        r#"
+        UPDATE email_verification_tokens
+        SET used_at = now()
+        WHERE user_id = $1 AND used_at IS NULL
+        "#,
+        verification.user_id
+    )
+    .execute(&mut *tx)
+    .await?;
+
+    tx.commit().await?;
+
+    Ok(verification.user_id)
+}
@@ -0,0 +1,177 @@
+use chrono::{Duration, Utc};
+use sqlx::PgPool;
+use uuid::Uuid;
+
+use crate::models::{
+    invitation::Invitation,
+    role::DEFAULT_ROLE,
+    user::User,
+};
+use crate::utils::token::{generate_token, hash_token};
+
+#[derive(Debug, thiserror::Error)]
+pub enum InvitationError {
+    #[error("Database error: {0}")]
+    Database(#[from] sqlx::Error),
+    #[error("Role cannot be granted by invitation")]
+    InvalidRole,
+    #[error("A user with this email already exists")]
+    EmailTaken,
+    #[error("Invitation not found")]
+    NotFound,
+    #[error("Invitation is invalid, expired or already used")]
+    InvalidToken,
+}
+
+pub type Result<T> = std::result::Result<T, InvitationError>;
+
+/// Default lifetime of an invitation link
+pub const DEFAULT_EXPIRY_HOURS: i64 = 72;
+
+/// Create an invitation and return it together with the plaintext token
+pub async fn create_invitation(
+    pool: &PgPool,
+    email: &str,
+    role: &str,
+    invited_by: Uuid,
+    expires_in: Duration,
+) -> Result<(Invitation, String)> {
+    // Any staff role, built-in or custom; customers sign up on their own
+    let invitable = role != DEFAULT_ROLE
+        && sqlx::query_scalar!(
+            r#"SELECT EXISTS (SELECT 1 FROM roles WHERE name = $1) AS "exists!""#,
+            role
+        )
+        .fetch_one(pool)
+        .await?;
+
+    if !invitable {
+        return Err(InvitationError::InvalidRole);
+    }
+
+    let taken = sqlx::query_scalar!(
+        r#"SELECT EXISTS (SELECT 1 FROM users WHERE email = $1) AS "taken!""#,
+        email
+    )
+    .fetch_one(pool)
+    .await?;
+
+    if taken {
+        return Err(InvitationError::EmailTaken);
+    }
+
+    let token = generate_token();
+
+    let invitation = sqlx::query_as_unchecked!(
+        Invitation,
+        r#"
+        INSERT INTO invitations (email, role, token_hash, invited_by, expires_at)
+        VALUES ($1, $2, $3, $4, $5)
+        RETURNING id, email, role, invited_by, created_at, expires_at, accepted_at, revoked_at
+        "#,
+        email,
+        role,
+        hash_token(&token),
+        invited_by,
+        Utc::now() + expires_in
+    )
+    .fetch_one(pool)
+    .await?;
+
+    Ok((invitation, token))
+}
+
+/// All invitations, newest first
+pub async fn list_invitations(pool: &PgPool) -> Result<Vec<Invitation>> {
+    let invitations = sqlx::query_as_unchecked!(
+        Invitation,
+        r#"
+        SELECT id, email, role, invited_by, created_at, expires_at, accepted_at, revoked_at
+        FROM invitations
+        ORDER BY created_at DESC
+        "#
+    )
+    .fetch_all(pool)
+    .await?;
+
+    Ok(invitations)
+}
+
+/// Revoke a pending invitation so its token can no longer be accepted
+pub async fn revoke_invitation(pool: &PgPool, invitation_id: Uuid) -> Result<Invitation> {
+    sqlx::query_as_unchecked!(
+        Invitation,
+        r#"
+        UPDATE invitations
+        SET revoked_at = now()
+        WHERE id = $1 AND accepted_at IS NULL AND revoked_at IS NULL
+        RETURNING id, email, role, invited_by, created_at, expires_at, accepted_at, revoked_at
+        "#,
+        invitation_id
+    )
+    .fetch_optional(pool)
+    .await?
+    .ok_or(InvitationError::NotFound)
+}
+
+/// Redeem an invitation token: create the user with the invited role and
+/// mark the invitation as used, atomically.
+pub async fn accept_invitation(
+    pool: &PgPool,
+    token: &str,
+    name: &str,
+    password_hash: &str,
+) -> Result<User> {
+    let mut tx = pool.begin().await?;
+
+    let invitation = sqlx::query_as_unchecked!(
+        Invitation,
+        r#"
+        SELECT id, email, role, invited_by, created_at, expires_at, accepted_at, revoked_at
+        FROM invitations
+        WHERE token_hash = $1
+        FOR UPDATE
+        "#,
+        hash_token(token)
+    )
+    .fetch_optional(&mut *tx)
+    .await?
+    .ok_or(InvitationError::InvalidToken)?;
+
+    if invitation.accepted_at.is_some()
+        || invitation.revoked_at.is_some()
+        || invitation.expires_at <= Utc::now()
+    {
+        return Err(InvitationError::InvalidToken);
+    }
+
+    // The link was mailed to this address, so using it proves ownership
+    let user = sqlx::query_as_unchecked!(
+        User,
+        r#"
+        INSERT INTO users (id, name, email, password_hash, role, email_verified_at)
+        VALUES ($1, $2, $3, $4, $5, now())
+        ON CONFLICT (email) DO NOTHING
+        RETURNING *
+        "#,
+        Uuid::new_v4(),
+        name,
+        invitation.email,
+        password_hash,
+        invitation.role
+    )
+    .fetch_optional(&mut *tx)
+    .await?
+    .ok_or(InvitationError::EmailTaken)?;
+
+    sqlx::query!(
+        "UPDATE invitations SET accepted_at = now() WHERE id = $1",
+        invitation.id
+    )
+    .execute(&mut *tx)
+    .await?;
+
+    tx.commit().await?;
+
+    Ok(user)
+}
@@ -0,0 +1,340 @@
+use chrono::{DateTime, Duration, Utc};
+use sqlx::PgPool;
+use uuid::Uuid;
+
+use crate::models::login_attempt::{LockedAccount, LoginAttempt, LoginOutcome, ThrottledIp};
+
+#[derive(Debug, thiserror::Error)]
+pub enum LoginThrottleError {
+    #[error("Database error: {0}")]
+    Database(#[from] sqlx::Error),
+    #[error("User not found")]
+    UserNotFound,
+}
+
+pub type Result<T> = std::result::Result<T, LoginThrottleError>;
+
+/// Consecutive failures before an account is locked
+const ACCOUNT_THRESHOLD: i64 = 5;
+const ACCOUNT_BASE_LOCK_SECONDS: i64 = 30;
+const ACCOUNT_MAX_LOCK_SECONDS: i64 = 60 * 60;
+
+/// Emails without an account are locked like accounts are, from their
+/// failures in the audit log within this window, so a lockout doesn't
+/// reveal whether an account exists
+const UNKNOWN_EMAIL_WINDOW_MINUTES: i64 = 60;
+
+/// Failures from one IP (any account) within the window before it is throttled.
+/// Higher than the account threshold since offices and NATs share addresses.
+const IP_THRESHOLD: i64 = 20;
+const IP_WINDOW_MINUTES: i64 = 15;
+const IP_BASE_LOCK_SECONDS: i64 = 30;
+const IP_MAX_LOCK_SECONDS: i64 = 15 * 60;
+
+/// Exponential backoff: nothing below `threshold` failures, then
+/// `base`, `2 * base`, `4 * base`, ... capped at `max` (all in seconds)
+fn backoff(failures: i64, threshold: i64, base: i64, max: i64) -> Option<Duration> {
+    if failures < threshold {
+        return None;
+    }
+    let doublings = (failures - threshold).min(32) as u32;
+    let seconds = base.saturating_mul(1i64 << doublings).min(max);
+    Some(Duration::seconds(seconds))
+}
+
+/// How long an account with `failures` consecutive failures stays locked
+fn account_lock_duration(failures: i64) -> Option<Duration> {
+    backoff(failures, ACCOUNT_THRESHOLD, ACCOUNT_BASE_LOCK_SECONDS, ACCOUNT_MAX_LOCK_SECONDS)
+}
+
+fn ip_lock_duration(failures: i64) -> Option<Duration> {
+    backoff(failures, IP_THRESHOLD, IP_BASE_LOCK_SECONDS, IP_MAX_LOCK_SECONDS)
+}
+
+/// Emails are logged and counted case-insensitively, without surrounding whitespace
+pub fn normalize_email(email: &str) -> String {
+    email.trim().to_lowercase()
+}
+
+/// Write an entry to the login audit log
+pub async fn record_attempt(
+    pool: &PgPool,
+    email: &str,
+    user_id: Option<Uuid>,
+    ip: &str,
+    outcome: LoginOutcome,
+) -> Result<()> {
+    sqlx::query!(
+        r#"
+        INSERT INTO login_attempts (email, user_id, ip, outcome)
+        VALUES ($1, $2, $3, $4)
+        "#,
+        normalize_email(email),
+        user_id,
+        ip,
+        outcome.as_str()
+    )
+    .execute(pool)
+    .await?;
+
+    Ok(())
+}
+
+/// If the IP is currently throttled, until when
+pub async fn ip_blocked_until(pool: &PgPool, ip: &str) -> Result<Option<DateTime<Utc>>> {
+    let row = sqlx::query!(
+        r#"
+        SELECT COUNT(*) AS "failures!", MAX(created_at) AS last_failure
+        FROM login_attempts
+        WHERE ip = $1 AND outcome <> 'success' AND NOT cleared AND created_at > $2
+        "#,
+        ip,
+        Utc::now() - Duration::minutes(IP_WINDOW_MINUTES)
+    )
+    .fetch_one(pool)
+    .await?;
+
+    Ok(blocked_until(row.failures, row.last_failure))
+}
+
+fn blocked_until(failures: i64, last_failure: Option<DateTime<Utc>>) -> Option<DateTime<Utc>> {
+    let until = last_failure? + ip_lock_duration(failures)?;
+    (until > Utc::now()).then_some(until)
+}
+
+/// If an email nobody has an account for is currently locked, until when
+pub async fn unknown_email_blocked_until(pool: &PgPool, email: &str) -> Result<Option<DateTime<Utc>>> {
+    let row = sqlx::query!(
+        r#"
+        SELECT COUNT(*) AS "failures!", MAX(created_at) AS last_failure
+        FROM login_attempts
+        WHERE email = $1 AND outcome = 'unknown_user' AND NOT cleared AND created_at > $2
+        "#,
+        normalize_email(email),
+        Utc::now() - Duration::minutes(UNKNOWN_EMAIL_WINDOW_MINUTES)
+    )
+    .fetch_one(pool)
+    .await?;
+
+    Ok(unknown_email_locked_until(row.failures, row.last_failure))
+}
+
+fn unknown_email_locked_until(failures: i64, last_failure: Option<DateTime<Utc>>) -> Option<DateTime<Utc>> {
+    let until = last_failure? + account_lock_duration(failures)?;
+    (until > Utc::now()).then_some(until)
+}
+
+/// Count a failed password for the account and lock it if it crossed the threshold
+pub async fn register_failure(pool: &PgPool, user_id: Uuid) -> Result<Option<DateTime<Utc>>> {
+    let mut tx = pool.begin().await?;
+
+    let failures = sqlx::query_scalar!(
+        r#"
+        UPDATE users
+        SET failed_login_count = failed_login_count + 1, last_failed_login_at = now()
+        WHERE id = $1
+        RETURNING failed_login_count
+        "#,
+        user_id
+    )
+    .fetch_one(&mut *tx)
+    .await?;
+
+    let locked_until = account_lock_duration(failures as i64).map(|lock| Utc::now() + lock);
+
+    if let Some(until) = locked_until {
+        sqlx::query!(
+            "UPDATE users SET locked_until = $1 WHERE id = $2",
+            until,
+            user_id
+        )
+        .execute(&mut *tx)
+        .await?;
+    }
+
+    tx.commit().await?;
+    Ok(locked_until)
+}
+
+/// Successful login: the failure streak starts over
+pub async fn register_success(pool: &PgPool, user_id: Uuid) -> Result<()> {
+    sqlx::query!(
+        r#"
+        UPDATE users
+        SET failed_login_count = 0, locked_until = NULL
+        WHERE id = $1 AND (failed_login_count <> 0 OR locked_until IS NOT NULL)
+        "#,
+        user_id
+    )
+    .execute(pool)
+    .await?;
+
+    Ok(())
+}
+
+/// Accounts locked right now
+pub async fn locked_accounts(pool: &PgPool) -> Result<Vec<LockedAccount>> {
+    let accounts = sqlx::query_as_unchecked!(
+        LockedAccount,
+        r#"
+        SELECT id AS user_id, email, failed_login_count, last_failed_login_at, locked_until
+        FROM users
+        WHERE locked_until > now()
+        ORDER BY locked_until DESC
+        "#
+    )
+    .fetch_all(pool)
+    .await?;
+
+    Ok(accounts)
+}
+
+/// IPs throttled right now
+pub async fn throttled_ips(pool: &PgPool) -> Result<Vec<ThrottledIp>> {
+    let rows = sqlx::query!(
+        r#"
+        SELECT ip, COUNT(*) AS "failures!", MAX(created_at) AS "last_failure!"
+        FROM login_attempts
+        WHERE outcome <> 'success' AND NOT cleared AND created_at > $1
+        GROUP BY ip
+        HAVING COUNT(*) >= $2
+        "#,
+        Utc::now() - Duration::minutes(IP_WINDOW_MINUTES),
+        IP_THRESHOLD
+    )
+    .fetch_all(pool)
+    .await?;
+
+    let mut ips: Vec<ThrottledIp> = rows
+        .into_iter()
+        .filter_map(|row| {
+            blocked_until(row.failures, Some(row.last_failure)).map(|blocked_until| ThrottledIp {
+                ip: row.ip,
+                recent_failures: row.failures,
+                blocked_until,
+            })
+        })
+        .collect();
+    ips.sort_by_key(|ip| std::cmp::Reverse(ip.blocked_until));
+
+    Ok(ips)
+}
+
+/// Most recent audit log entries, optionally filtered by email and/or IP
+pub async fn recent_attempts(
+    pool: &PgPool,
+    email: Option<&str>,
+    ip: Option<&str>,
+    limit: i64,
+) -> Result<Vec<LoginAttempt>> {
+    let email = email.map(normalize_email);
+    let attempts = sqlx::query_as_unchecked!(
+        LoginAttempt,
+        r#"
+        SELECT id, email, user_id, ip, outcome, cleared, created_at
+        FROM login_attempts
+        WHERE ($1::text IS NULL OR email = $1)
+          AND ($2::text IS NULL OR ip = $2)
+        ORDER BY created_at DESC
+        LIMIT $3
+        "#,
+        email.as_deref(),
+        ip,
+        limit
+    )
+    .fetch_all(pool)
+    .await?;
+
+    Ok(attempts)
+}
+
+/// Lift an account lockout and reset its failure streak
+pub async fn clear_account(pool: &PgPool, user_id: Uuid) -> Result<()> {
+    let result = sqlx::query!(
+        "UPDATE users SET failed_login_count = 0, locked_until = NULL WHERE id = $1",
+        user_id
+    )
+    .execute(pool)
+    .await?;
+
+    if result.rows_affected() == 0 {
+        return Err(LoginThrottleError::UserNotFound);
+    }
+
+    sqlx::query!(
+        "UPDATE login_attempts SET cleared = TRUE WHERE user_id = $1 AND NOT cleared",
+        user_id
+    )
+    .execute(pool)
+    .await?;
+
+    Ok(())
+}
+
+/// Forget recent failures from an IP so it is no longer throttled
+pub async fn clear_ip(pool: &PgPool, ip: &str) -> Result<u64> {
+    let result = sqlx::query!(
+        "UPDATE login_attempts SET cleared = TRUE WHERE ip = $1 AND NOT cleared",
+        ip
+    )
+    .execute(pool)
+    .await?;
+
+    Ok(result.rows_affected())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn no_lock_below_threshold() {
+        assert_eq!(account_lock_duration(0), None);
+        assert_eq!(account_lock_duration(ACCOUNT_THRESHOLD - 1), None);
+    }
+
+    #[test]
+    fn lock_doubles_after_threshold() {
+        assert_eq!(account_lock_duration(ACCOUNT_THRESHOLD), Some(Duration::seconds(30)));
+        assert_eq!(account_lock_duration(ACCOUNT_THRESHOLD + 1), Some(Duration::seconds(60)));
+        assert_eq!(account_lock_duration(ACCOUNT_THRESHOLD + 3), Some(Duration::seconds(240)));
+    }
+
+    #[test]
+    fn lock_is_capped() {
+        assert_eq!(
+            account_lock_duration(ACCOUNT_THRESHOLD + 10),
+            Some(Duration::seconds(ACCOUNT_MAX_LOCK_SECONDS))
+        );
+        assert_eq!(
+            account_lock_duration(i32::MAX as i64),
+            Some(Duration::seconds(ACCOUNT_MAX_LOCK_SECONDS))
+        );
+    }
+
+    #[test]
+    fn ip_block_expires() {
+        let long_ago = Utc::now() - Duration::hours(1);
+        assert_eq!(blocked_until(IP_THRESHOLD, Some(long_ago)), None);
+
+        let just_now = Utc::now();
+        assert!(blocked_until(IP_THRESHOLD, Some(just_now)).is_some());
+        assert_eq!(blocked_until(IP_THRESHOLD - 1, Some(just_now)), None);
+    }
+
+    #[test]
+    fn unknown_emails_lock_like_accounts() {
+        let just_now = Utc::now();
+        assert_eq!(unknown_email_locked_until(ACCOUNT_THRESHOLD - 1, Some(just_now)), None);
+        assert!(unknown_email_locked_until(ACCOUNT_THRESHOLD, Some(just_now)).is_some());
+        assert_eq!(unknown_email_locked_until(ACCOUNT_THRESHOLD, None), None);
+
+        let long_ago = Utc::now() - Duration::hours(1);
+        assert_eq!(unknown_email_locked_until(ACCOUNT_THRESHOLD, Some(long_ago)), None);
+    }
+
+    #[test]
+    fn emails_are_normalized() {
+        assert_eq!(normalize_email("  Alice@Example.COM "), "alice@example.com");
+    }
+}
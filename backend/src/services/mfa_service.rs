@@ -0,0 +1,462 @@
+use chrono::{Duration, Utc};
+use rand::{distributions::Alphanumeric, Rng};
+use sqlx::{PgPool, Postgres, Transaction};
+use uuid::Uuid;
+
+use crate::utils::{
+    token::{generate_token, hash_token},
+    totp,
+};
+
+#[derive(Debug, thiserror::Error)]
+pub enum MfaError {
+    #[error("Database error: {0}")]
+    Database(#[from] sqlx::Error),
+    #[error("Two-factor authentication is already enabled")]
+    AlreadyEnabled,
+    #[error("Two-factor authentication is not enabled")]
+    NotEnabled,
+    #[error("No pending two-factor setup")]
+    SetupNotStarted,
+    #[error("Invalid verification code")]
+    InvalidCode,
+    #[error("MFA token is invalid, expired or already used")]
+    InvalidChallenge,
+}
+
+pub type Result<T> = std::result::Result<T, MfaError>;
+
+/// How long the interim login token stays valid
+pub const CHALLENGE_TTL_MINUTES: i64 = 5;
+/// Wrong codes allowed per interim token before it is burned
+const MAX_CHALLENGE_ATTEMPTS: i32 = 5;
+/// Wrong codes allowed per user across all interim tokens in the last
+/// `FAILURE_WINDOW_MINUTES`, so logging in again doesn't reset the budget
+const MAX_RECENT_FAILURES: i64 = 10;
+const FAILURE_WINDOW_MINUTES: i64 = 15;
+/// Recovery codes handed out per batch
+const RECOVERY_CODE_COUNT: usize = 10;
+
+/// What an interim login token can be exchanged for
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ChallengePurpose {
+    /// User has TOTP enabled and must enter a code
+    Verify,
+    /// User's role requires TOTP but none is set up yet
+    Enroll,
+}
+
+impl ChallengePurpose {
+    fn as_str(self) -> &'static str {
+        match self {
+            ChallengePurpose::Verify => "verify",
+            ChallengePurpose::Enroll => "enroll",
+        }
+    }
+}
+
+/// A pending TOTP secret, shown to the user once so they can add it to an app
+#[derive(Debug)]
+pub struct TotpSetup {
+    pub secret: String,
+    pub otpauth_uri: String,
+}
+
+/// Whether the user has a confirmed TOTP secret
+pub async fn is_enabled(pool: &PgPool, user_id: Uuid) -> Result<bool> {
+    let enabled = sqlx::query_scalar!(
+        r#"
+        SELECT EXISTS (
+            SELECT 1 FROM user_totp WHERE user_id = $1 AND confirmed_at IS NOT NULL
+        ) AS "enabled!"
+        "#,
+        user_id
+    )
+    .fetch_one(pool)
+    .await?;
+
+    Ok(enabled)
+}
+
+/// Number of recovery codes the user has not used yet
+pub async fn remaining_recovery_codes(pool: &PgPool, user_id: Uuid) -> Result<i64> {
+    let remaining = sqlx::query_scalar!(
+        r#"
+        SELECT COUNT(*) AS "count!"
+        FROM user_recovery_codes
+        WHERE user_id = $1 AND used_at IS NULL
+        "#,
+        user_id
+    )
+    .fetch_one(pool)
+    .await?;
+
+    Ok(remaining)
+}
+
+/// Generate a new (unconfirmed) TOTP secret for the user.
+/// Calling it again before confirming replaces the pending secret.
+pub async fn begin_setup(
+    pool: &PgPool,
+    user_id: Uuid,
+    account: &str,
+    issuer: &str,
+) -> Result<TotpSetup> {
+    let secret = totp::generate_secret();
+
+    let updated = sqlx::query!(
+        r#"
+        INSERT INTO user_totp (user_id, secret)
+        VALUES ($1, $2)
+        ON CONFLICT (user_id) DO UPDATE
+        SET secret = EXCLUDED.secret, created_at = now(), last_used_step = NULL
+        WHERE user_totp.confirmed_at IS NULL
+        "#,
+        user_id,
+        secret
+    )
+    .execute(pool)
+    .await?;
+
+    if updated.rows_affected() == 0 {
+        return Err(MfaError::AlreadyEnabled);
+    }
+
+    Ok(TotpSetup {
+        otpauth_uri: totp::otpauth_uri(&secret, account, issuer),
+        secret,
+    })
+}
+
+/// Confirm the pending secret with a code from the app.
+/// Returns a fresh set of recovery codes (plaintext, shown once).
+pub async fn confirm_setup(pool: &PgPool, user_id: Uuid, code: &str) -> Result<Vec<String>> {
+    let mut tx = pool.begin().await?;
+    let codes = confirm_setup_in(&mut tx, user_id, code).await?;
+    tx.commit().await?;
+    Ok(codes)
+}
+
+/// Turn TOTP off. Requires a current TOTP or recovery code.
+pub async fn disable(pool: &PgPool, user_id: Uuid, code: &str) -> Result<()> {
+    let mut tx = pool.begin().await?;
+
+    if !check_second_factor(&mut tx, user_id, code).await? {
+        return Err(MfaError::InvalidCode);
+    }
+
+    sqlx::query!("DELETE FROM user_totp WHERE user_id = $1", user_id)
+        .execute(&mut *tx)
+        .await?;
+    sqlx::query!("DELETE FROM user_recovery_codes WHERE user_id = $1", user_id)
+        .execute(&mut *tx)
+        .await?;
+
+    tx.commit().await?;
+    Ok(())
+}
+
+/// Replace all recovery codes. Requires a current TOTP code.
+pub async fn regenerate_recovery_codes(
+    pool: &PgPool,
+    user_id: Uuid,
+    code: &str,
+) -> Result<Vec<String>> {
+    let mut tx = pool.begin().await?;
+
+    if !check_totp(&mut tx, user_id, code).await? {
+        return Err(MfaError::InvalidCode);
+    }
+
+    let codes = replace_recovery_codes(&mut tx, user_id).await?;
+    tx.commit().await?;
+    Ok(codes)
+}
+
+/// Hand out an interim login token for a user who passed the password check
+pub async fn create_challenge(
+    pool: &PgPool,
+    user_id: Uuid,
+    purpose: ChallengePurpose,
+) -> Result<String> {
+    let token = generate_token();
+
+    sqlx::query!(
+        r#"
+        INSERT INTO mfa_challenges (user_id, token_hash, purpose, expires_at)
+        VALUES ($1, $2, $3, $4)
+        "#,
+        user_id,
+        hash_token(&token),
+        purpose.as_str(),
+        Utc::now() + Duration::minutes(CHALLENGE_TTL_MINUTES)
+    )
+    .execute(pool)
+    .await?;
+
+    Ok(token)
+}
+
+/// Start TOTP enrollment for the user behind an `Enroll` interim token.
+/// The token is not consumed; it is needed again to confirm.
+pub async fn begin_enrollment(
+    pool: &PgPool,
+    challenge_token: &str,
+    issuer: &str,
+) -> Result<TotpSetup> {
+    let challenge = sqlx::query!(
+        r#"
+        SELECT c.user_id, u.email
+        FROM mfa_challenges c
+        JOIN users u ON u.id = c.user_id
+        WHERE c.token_hash = $1 AND c.purpose = $2
+          AND c.consumed_at IS NULL AND c.expires_at > now()
+        "#,
+        hash_token(challenge_token),
+        ChallengePurpose::Enroll.as_str()
+    )
+    .fetch_optional(pool)
+    .await?
+    .ok_or(MfaError::InvalidChallenge)?;
+
+    begin_setup(pool, challenge.user_id, &challenge.email, issuer).await
+}
+
+/// Exchange an interim token plus a second-factor code for the user id.
+///
+/// For `Verify` the code is a TOTP or recovery code; for `Enroll` it confirms
+/// the pending secret and the new recovery codes are returned as well.
+/// Each token allows a handful of wrong codes before it stops working.
+pub async fn complete_challenge(
+    pool: &PgPool,
+    challenge_token: &str,
+    purpose: ChallengePurpose,
+    code: &str,
+) -> Result<(Uuid, Option<Vec<String>>)> {
+    let mut tx = pool.begin().await?;
+
+    let challenge = sqlx::query!(
+        r#"
+        SELECT id, user_id, attempts
+        FROM mfa_challenges
+        WHERE token_hash = $1 AND purpose = $2
+          AND consumed_at IS NULL AND expires_at > now()
+        FOR UPDATE
+        "#,
+        hash_token(challenge_token),
+        purpose.as_str()
+    )
+    .fetch_optional(&mut *tx)
+    .await?
+    .ok_or(MfaError::InvalidChallenge)?;
+
+    if challenge.attempts >= MAX_CHALLENGE_ATTEMPTS {
+        return Err(MfaError::InvalidChallenge);
+    }
+
+    let recent_failures = sqlx::query_scalar!(
+        r#"
+        SELECT COALESCE(SUM(attempts), 0) AS "failures!"
+        FROM mfa_challenges
+        WHERE user_id = $1 AND created_at > $2
+        "#,
+        challenge.user_id,
+        Utc::now() - Duration::minutes(FAILURE_WINDOW_MINUTES)
+    )
+    .fetch_one(&mut *tx)
+    .await?;
+
+    if recent_failures >= MAX_RECENT_FAILURES {
+        return Err(MfaError::InvalidChallenge);
+    }
+
+    let outcome = match purpose {
+        ChallengePurpose::Verify => {
+            if check_second_factor(&mut tx, challenge.user_id, code).await? {
+                Ok(None)
+            } else {
+                Err(MfaError::InvalidCode)
+            }
+        }
+        ChallengePurpose::Enroll => confirm_setup_in(&mut tx, challenge.user_id, code)
+            .await
+            .map(Some),
+    };
+
+    match outcome {
+        Ok(recovery_codes) => {
+            sqlx::query!(
+                "UPDATE mfa_challenges SET consumed_at = now() WHERE id = $1",
+                challenge.id
+            )
+            .execute(&mut *tx)
+            .await?;
+            tx.commit().await?;
+            Ok((challenge.user_id, recovery_codes))
+        }
+        Err(MfaError::InvalidCode) => {
+            // Roll back whatever the check touched, but keep the failed attempt
+            tx.rollback().await?;
+            sqlx::query!(
+                "UPDATE mfa_challenges SET attempts = attempts + 1 WHERE id = $1",
+                challenge.id
+            )
+            .execute(pool)
+            .await?;
+            Err(MfaError::InvalidCode)
+        }
+        Err(err) => Err(err),
+    }
+}
+
+async fn confirm_setup_in(
+    tx: &mut Transaction<'_, Postgres>,
+    user_id: Uuid,
+    code: &str,
+) -> Result<Vec<String>> {
+    let pending = sqlx::query!(
+        r#"
+        SELECT secret, confirmed_at
+        FROM user_totp
+        WHERE user_id = $1
+        FOR UPDATE
+        "#,
+        user_id
+    )
+    .fetch_optional(&mut **tx)
+    .await?
+    .ok_or(MfaError::SetupNotStarted)?;
+
+    if pending.confirmed_at.is_some() {
+        return Err(MfaError::AlreadyEnabled);
+    }
+
+    let step = totp::verify(&pending.secret, code, Utc::now().timestamp(), None)
+        .ok_or(MfaError::InvalidCode)?;
+
+    sqlx::query!(
+        r#"
+        UPDATE user_totp
+        SET confirmed_at = now(), last_used_step = $2
+        WHERE user_id = $1
+        "#,
+        user_id,
+        step
+    )
+    .execute(&mut **tx)
+    .await?;
+
+    replace_recovery_codes(tx, user_id).await
+}
+
+/// TOTP code first, then recovery code (which is burned on use)
+async fn check_second_factor(
+    tx: &mut Transaction<'_, Postgres>,
+    user_id: Uuid,
+    code: &str,
+) -> Result<bool> {
+    if check_totp(tx, user_id, code).await? {
+        return Ok(true);
+    }
+
+    let used = sqlx::query!(
+        r#"
+        UPDATE user_recovery_codes
+        SET used_at = now()
+        WHERE id = (
+            SELECT id FROM user_recovery_codes
+            WHERE user_id = $1 AND code_hash = $2 AND used_at IS NULL
+            LIMIT 1
+            FOR UPDATE
+        )
+        "#,
+        user_id,
+        hash_token(&normalize_recovery_code(code))
+    )
+    .execute(&mut **tx)
+    .await?;
+
+    Ok(used.rows_affected() > 0)
+}
+
+/// Verify a TOTP code against the confirmed secret, refusing replays of a
+/// time step that was already used
+async fn check_totp(
+    tx: &mut Transaction<'_, Postgres>,
+    user_id: Uuid,
+    code: &str,
+) -> Result<bool> {
+    let row = sqlx::query!(
+        r#"
+        SELECT secret, last_used_step
+        FROM user_totp
+        WHERE user_id = $1 AND confirmed_at IS NOT NULL
+        FOR UPDATE
+        "#,
+        user_id
+    )
+    .fetch_optional(&mut **tx)
+    .await?
+    .ok_or(MfaError::NotEnabled)?;
+
+    let Some(step) = totp::verify(&row.secret, code, Utc::now().timestamp(), row.last_used_step)
+    else {
+        return Ok(false);
+    };
+
+    sqlx::query!(
+        "UPDATE user_totp SET last_used_step = $2 WHERE user_id = $1",
+        user_id,
+        step
+    )
+    .execute(&mut **tx)
+    .await?;
+
+    Ok(true)
+}
+
+async fn replace_recovery_codes(
+    tx: &mut Transaction<'_, Postgres>,
+    user_id: Uuid,
+) -> Result<Vec<String>> {
+    sqlx::query!("DELETE FROM user_recovery_codes WHERE user_id = $1", user_id)
+        .execute(&mut **tx)
+        .await?;
+
+    let codes: Vec<String> = (0..RECOVERY_CODE_COUNT).map(|_| generate_recovery_code()).collect();
+    let hashes: Vec<String> = codes
+        .iter()
+        .map(|code| hash_token(&normalize_recovery_code(code)))
+        .collect();
+
+    sqlx::query!(
+        r#"
+        INSERT INTO user_recovery_codes (user_id, code_hash)
+        SELECT $1, UNNEST($2::text[])
+        "#,
+        user_id,
+        &hashes
+    )
+    .execute(&mut **tx)
+    .await?;
+
+    Ok(codes)
+}
+
+/// `xxxxx-xxxxx`, lowercase letters and digits
+fn generate_recovery_code() -> String {
+    let raw: String = rand::thread_rng()
+        .sample_iter(&Alphanumeric)
+        .take(10)
+        .map(|b| char::from(b).to_ascii_lowercase())
+        .collect();
+    format!("{}-{}", &raw[..5], &raw[5..])
+}
+
+/// Users may type recovery codes with or without the dash, in any case
+fn normalize_recovery_code(code: &str) -> String {
+    code.chars()
+        .filter(|c| c.is_ascii_alphanumeric())
+        .map(|c| c.to_ascii_lowercase())
+        .collect()
+}
@@ -4,4 +4,25 @@ pub mod note_service;
 pub mod collaboration_service;
 pub mod knowledge_base_service;
 pub mod report_service;
-pub mod notification_services;
\ No newline at end of file
+pub mod notification_services;
+pub mod session_service;
+pub mod invitation_service;
+pub mod password_reset_service;
+pub mod settings_service;
+pub mod mfa_service;
+pub mod login_throttle_service;
+pub mod api_key_service;
+
+pub mod role_service;
+pub mod oidc_service;
+pub mod email_verification_service;
+pub mod signing_key_service;
+pub mod user_service;
+pub mod search_service;
+pub mod ticket_history_service;
+pub mod sla_service;
+pub mod business_calendar_service;
+pub mod routing_service;
+pub mod team_service;
+pub mod custom_field_service;
+pub mod ticket_link_service;
\ No newline at end of file
@@ -0,0 +1,278 @@
+use chrono::{Duration, Utc};
+use sqlx::PgPool;
+use uuid::Uuid;
+
+use crate::{
+    config::OidcProviderConfig,
+    services::role_service::{self, RoleError},
+    utils::{
+        hash::{hash_password, HashError},
+        oidc::{map_role, IdTokenClaims, OidcClient, OidcError},
+        token::{generate_token, hash_token},
+    },
+};
+
+#[derive(Debug, thiserror::Error)]
+pub enum OidcLoginError {
+    #[error("Database error: {0}")]
+    Database(#[from] sqlx::Error),
+    #[error(transparent)]
+    Provider(#[from] OidcError),
+    #[error("Login state is invalid, expired or already used")]
+    InvalidState,
+    #[error("Handoff code is invalid, expired or already used")]
+    InvalidHandoff,
+    #[error("Identity provider did not return an email address")]
+    MissingEmail,
+    #[error("No role is mapped for this identity")]
+    NoRole,
+    #[error("Mapped role does not exist: {0}")]
+    UnknownRole(String),
+    #[error("A local account uses this email and the provider has not verified it")]
+    EmailNotVerified,
+    #[error("Account is disabled")]
+    AccountDisabled,
+    #[error("Password hashing failed: {0}")]
+    Hash(#[from] HashError),
+}
+
+pub type Result<T> = std::result::Result<T, OidcLoginError>;
+
+/// Time the user has to finish signing in at the provider
+pub const LOGIN_TTL_MINUTES: i64 = 10;
+/// Time the frontend has to redeem the handoff code after the callback
+pub const HANDOFF_TTL_SECONDS: i64 = 120;
+
+/// Start an authorization-code + PKCE login and return the URL to send the
+/// browser to
+pub async fn begin_login(
+    pool: &PgPool,
+    client: &OidcClient,
+    provider: &OidcProviderConfig,
+    redirect_uri: &str,
+) -> Result<String> {
+    let state = generate_token();
+    let nonce = generate_token();
+    let code_verifier = generate_token();
+
+    let url = client
+        .authorization_url(provider, redirect_uri, &state, &nonce, &code_verifier)
+        .await?;
+
+    sqlx::query!(
+        r#"
+        INSERT INTO oidc_logins (provider, state_hash, nonce, code_verifier, expires_at)
+        VALUES ($1, $2, $3, $4, $5)
+        "#,
+        provider.name,
+        hash_token(&state),
+        nonce,
+        code_verifier,
+        Utc::now() + Duration::minutes(LOGIN_TTL_MINUTES)
+    )
+    .execute(pool)
+    .await?;
+
+    Ok(url)
+}
+
+/// Handle the provider's redirect back: redeem the code, validate the ID
+/// token, find or provision the user, and return a single-use handoff code
+pub async fn complete_login(
+    pool: &PgPool,
+    client: &OidcClient,
+    provider: &OidcProviderConfig,
+    redirect_uri: &str,
+    code: &str,
+    state: &str,
+) -> Result<String> {
+    // Burn the state first so a replayed callback can't get this far twice
+    let login = sqlx::query!(
+        r#"
+        UPDATE oidc_logins
+        SET expires_at = now()
+        WHERE state_hash = $1 AND provider = $2 AND expires_at > now() AND user_id IS NULL
+        RETURNING id, nonce, code_verifier
+        "#,
+        hash_token(state),
+        provider.name
+    )
+    .fetch_optional(pool)
+    .await?
+    .ok_or(OidcLoginError::InvalidState)?;
+
+    let claims = client
+        .exchange_code(provider, redirect_uri, code, &login.code_verifier, &login.nonce)
+        .await?;
+
+    let user_id = provision_user(pool, provider, &claims).await?;
+
+    let handoff = generate_token();
+    sqlx::query!(
+        r#"
+        UPDATE oidc_logins
+        SET user_id = $2, handoff_hash = $3, handoff_expires_at = $4
+        WHERE id = $1
+        "#,
+        login.id,
+        user_id,
+        hash_token(&handoff),
+        Utc::now() + Duration::seconds(HANDOFF_TTL_SECONDS)
+    )
+    .execute(pool)
+    .await?;
+
+    Ok(handoff)
+}
+
+/// Exchange a handoff code for the user it was issued to (single use)
+pub async fn redeem_handoff(pool: &PgPool, code: &str) -> Result<Uuid> {
+    let user_id = sqlx::query_scalar!(
+        r#"
+        UPDATE oidc_logins
+        SET redeemed_at = now()
+        WHERE handoff_hash = $1 AND redeemed_at IS NULL AND handoff_expires_at > now()
+        RETURNING user_id AS "user_id!"
+        "#,
+        hash_token(code)
+    )
+    .fetch_optional(pool)
+    .await?
+    .ok_or(OidcLoginError::InvalidHandoff)?;
+
+    Ok(user_id)
+}
+
+/// Find the local user for an identity, linking by verified email or
+/// creating them on first login, and bring their role in line with the
+/// provider's claims
+async fn provision_user(
+    pool: &PgPool,
+    provider: &OidcProviderConfig,
+    claims: &IdTokenClaims,
+) -> Result<Uuid> {
+    let role = map_role(provider, claims).ok_or(OidcLoginError::NoRole)?;
+    let email = claims
+        .email
+        .as_deref()
+        .map(str::trim)
+        .filter(|email| !email.is_empty())
+        .ok_or(OidcLoginError::MissingEmail)?;
+
+    let mut tx = pool.begin().await?;
+
+    let linked = sqlx::query_scalar!(
+        "SELECT user_id FROM user_identities WHERE provider = $1 AND subject = $2",
+        provider.name,
+        claims.sub
+    )
+    .fetch_optional(&mut *tx)
+    .await?;
+
+    let user_id = match linked {
+        Some(user_id) => user_id,
+        None => {
+            let existing = sqlx::query_scalar!("SELECT id FROM users WHERE email = $1", email)
+                .fetch_optional(&mut *tx)
+                .await?;
+
+            let user_id = match existing {
+                // Only trust the provider's word on email ownership if it says so
+                Some(_) if !claims.email_verified() => return Err(OidcLoginError::EmailNotVerified),
+                Some(user_id) => user_id,
+                None => {
+                    let role_exists = sqlx::query_scalar!(
+                        r#"SELECT EXISTS (SELECT 1 FROM roles WHERE name = $1) AS "exists!""#,
+                        role
+                    )
+                    .fetch_one(&mut *tx)
+                    .await?;
+                    if !role_exists {
+                        return Err(OidcLoginError::UnknownRole(role));
+                    }
+
+                    // SSO users sign in at the provider; nobody knows this password
+                    let password_hash = hash_password(&generate_token())?;
+                    sqlx::query_scalar!(
+                        r#"
+                        INSERT INTO users (name, email, password_hash, role)
+                        VALUES ($1, $2, $3, $4)
+                        RETURNING id
+                        "#,
+                        claims.name.as_deref().unwrap_or(email),
+                        email,
+                        password_hash,
+                        role
+                    )
+                    .fetch_one(&mut *tx)
+                    .await?
+                }
+            };
+
+            sqlx::query!(
+                "INSERT INTO user_identities (provider, subject, user_id) VALUES ($1, $2, $3)",
+                provider.name,
+                claims.sub,
+                user_id
+            )
+            .execute(&mut *tx)
+            .await?;
+
+            user_id
+        }
+    };
+
+    sqlx::query!(
+        r#"
+        UPDATE user_identities
+        SET email = $3, last_login_at = now()
+        WHERE provider = $1 AND subject = $2
+        "#,
+        provider.name,
+        claims.sub,
+        email
+    )
+    .execute(&mut *tx)
+    .await?;
+
+    if claims.email_verified() {
+        sqlx::query!(
+            r#"
+            UPDATE users SET email_verified_at = now()
+            WHERE id = $1 AND email = $2 AND email_verified_at IS NULL
+            "#,
+            user_id,
+            email
+        )
+        .execute(&mut *tx)
+        .await?;
+    }
+
+    tx.commit().await?;
+
+    let user = sqlx::query!(
+        r#"SELECT role, COALESCE(is_active, true) AS "is_active!" FROM users WHERE id = $1"#,
+        user_id
+    )
+    .fetch_one(pool)
+    .await?;
+
+    if !user.is_active {
+        return Err(OidcLoginError::AccountDisabled);
+    }
+
+    // The provider is the source of truth for staff roles
+    if user.role != role {
+        match role_service::assign_role(pool, user_id, &role).await {
+            Ok(()) => tracing::info!("🔑 SSO moved user {} from {} to {}", user_id, user.role, role),
+            Err(RoleError::LastAdmin) => {
+                tracing::warn!("SSO left user {} as the last admin instead of {}", user_id, role)
+            }
+            Err(RoleError::NotFound) => return Err(OidcLoginError::UnknownRole(role)),
+            Err(RoleError::Database(err)) => return Err(err.into()),
+            Err(err) => tracing::error!("SSO role sync for {} failed: {}", user_id, err),
+        }
+    }
+
+    Ok(user_id)
+}
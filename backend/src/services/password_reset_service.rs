@@ -0,0 +1,126 @@
+use chrono::{Duration, Utc};
+use sqlx::PgPool;
+use uuid::Uuid;
+
+use crate::services::session_service::{self, SessionError};
+use crate::utils::token::{generate_token, hash_token};
+
+#[derive(Debug, thiserror::Error)]
+pub enum PasswordResetError {
+    #[error("Database error: {0}")]
+    Database(#[from] sqlx::Error),
+    #[error("Reset token is invalid, expired or already used")]
+    InvalidToken,
+}
+
+impl From<SessionError> for PasswordResetError {
+    fn from(err: SessionError) -> Self {
+        match err {
+            SessionError::Database(err) => PasswordResetError::Database(err),
+            _ => PasswordResetError::InvalidToken,
+        }
+    }
+}
+
+pub type Result<T> = std::result::Result<T, PasswordResetError>;
+
+/// How long a reset link stays valid
+pub const RESET_TOKEN_TTL_MINUTES: i64 = 30;
+
+/// Who to mail a freshly created reset token to
+#[derive(Debug)]
+pub struct PasswordResetRequest {
+    pub email: String,
+    pub name: String,
+    pub token: String,
+}
+
+/// Create a reset token for the account behind `email`.
+/// Returns `None` for unknown or disabled accounts so the caller can
+/// answer the same way in every case.
+pub async fn request_reset(pool: &PgPool, email: &str) -> Result<Option<PasswordResetRequest>> {
+    let user = sqlx::query!(
+        r#"
+        SELECT id, name, email
+        FROM users
+        WHERE email = $1 AND COALESCE(is_active, true)
+        "#,
+        email
+    )
+    .fetch_optional(pool)
+    .await?;
+
+    let Some(user) = user else {
+        return Ok(None);
+    };
+
+    let token = generate_token();
+
+    sqlx::query!(
+        r#"
+        INSERT INTO password_reset_tokens (user_id, token_hash, expires_at)
+        VALUES ($1, $2, $3)
+        "#,
+        user.id,
+        hash_token(&token),
+        Utc::now() + Duration::minutes(RESET_TOKEN_TTL_MINUTES)
+    )
+    .execute(pool)
+    .await?;
+
+    Ok(Some(PasswordResetRequest {
+        email: user.email,
+        name: user.name,
+        token,
+    }))
+}
+
+/// Consume a reset token and set a new password hash.
+///
+/// Every other outstanding reset token of the user is burned as well and
+/// all sessions are revoked, so whoever knew the old password is logged out.
+pub async fn reset_password(pool: &PgPool, token: &str, password_hash: &str) -> Result<Uuid> {
+    let mut tx = pool.begin().await?;
+
+    let reset = sqlx::query!(
+        r#"
+        SELECT id, user_id, expires_at, used_at
+        FROM password_reset_tokens
+        WHERE token_hash = $1
+        FOR UPDATE
+        "#,
+        hash_token(token)
+    )
+    .fetch_optional(&mut *tx)
+    .await?
+    .ok_or(PasswordResetError::InvalidToken)?;
+
+    if reset.used_at.is_some() || reset.expires_at <= Utc::now() {
+        return Err(PasswordResetError::InvalidToken);
+    }
+
+    sqlx::query!(
+        "UPDATE users SET password_hash = $1, updated_at = now() WHERE id = $2",
+        password_hash,
+        reset.user_id
+    )
+    .execute(&mut *tx)
+    .await?;
+
+    sqlx::query!(
+        r#"
+        UPDATE password_reset_tokens
+        SET used_at = now()
+        WHERE user_id = $1 AND used_at IS NULL
+        "#,
+        reset.user_id
+    )
+    .execute(&mut *tx)
+    .await?;
+
+    session_service::revoke_all_for_user(&mut *tx, reset.user_id, "password_reset").await?;
+
+    tx.commit().await?;
+
+    Ok(reset.user_id)
+}
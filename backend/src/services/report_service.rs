@@ -1,14 +1,8 @@
-use sqlx::{PgPool, query_as_unchecked};
+use sqlx::{PgPool, Row, query_as_unchecked};
 use serde::Serialize;
 use uuid::Uuid;
 
-#[derive(Serialize)]
-pub struct TicketOverview {
-    pub total: i64,
-    pub open: i64,
-    pub closed: i64,
-    pub in_progress: i64,
-}
+use crate::models::analytics::TicketStats;
 
 #[derive(Serialize)]
 pub struct TicketsByAgent {
@@ -23,29 +17,32 @@ pub struct TicketStatusCount {
     pub count: i64,
 }
 
-pub async fn get_ticket_overview(pool: &PgPool) -> Result<TicketOverview, sqlx::Error> {
-    let total: (i64,) = sqlx::query_as("SELECT COUNT(*) FROM tickets")
-        .fetch_one(pool)
-        .await?;
-
-    let open: (i64,) = sqlx::query_as("SELECT COUNT(*) FROM tickets WHERE status = 'open'")
-        .fetch_one(pool)
-        .await?;
-
-    let closed: (i64,) = sqlx::query_as("SELECT COUNT(*) FROM tickets WHERE status = 'closed'")
-        .fetch_one(pool)
-        .await?;
-
-    let in_progress: (i64,) =
-        sqlx::query_as("SELECT COUNT(*) FROM tickets WHERE status = 'in_progress'")
-            .fetch_one(pool)
-            .await?;
+/// Ticket counts per lifecycle status
+pub async fn get_ticket_stats(pool: &PgPool) -> Result<TicketStats, sqlx::Error> {
+    let row = sqlx::query(
+        r#"
+        SELECT
+            COUNT(*) AS total,
+            COUNT(*) FILTER (WHERE status = 'New') AS new,
+            COUNT(*) FILTER (WHERE status = 'Open') AS open,
+            COUNT(*) FILTER (WHERE status = 'Pending') AS pending,
+            COUNT(*) FILTER (WHERE status = 'OnHold') AS on_hold,
+            COUNT(*) FILTER (WHERE status = 'Resolved') AS resolved,
+            COUNT(*) FILTER (WHERE status = 'Closed') AS closed
+        FROM tickets
+        "#,
+    )
+    .fetch_one(pool)
+    .await?;
 
-    Ok(TicketOverview {
-        total: total.0,
-        open: open.0,
-        closed: closed.0,
-        in_progress: in_progress.0,
+    Ok(TicketStats {
+        total: row.try_get("total")?,
+        new: row.try_get("new")?,
+        open: row.try_get("open")?,
+        pending: row.try_get("pending")?,
+        on_hold: row.try_get("on_hold")?,
+        resolved: row.try_get("resolved")?,
+        closed: row.try_get("closed")?,
     })
 }
 
@@ -0,0 +1,390 @@
+use sqlx::PgPool;
+use uuid::Uuid;
+
+use crate::models::{
+    permission::Permission,
+    role::{PermissionInfo, Role, ADMIN_ROLE},
+};
+
+#[derive(Debug, thiserror::Error)]
+pub enum RoleError {
+    #[error("Database error: {0}")]
+    Database(#[from] sqlx::Error),
+    #[error("Role not found")]
+    NotFound,
+    #[error("User not found")]
+    UserNotFound,
+    #[error("Role names are 2-32 characters of a-z, 0-9, '-' or '_'")]
+    InvalidName,
+    #[error("Unknown permission: {0}")]
+    UnknownPermission(String),
+    #[error("A role with this name already exists")]
+    AlreadyExists,
+    #[error("Built-in roles cannot be changed this way")]
+    Protected,
+    #[error("Role is still assigned to users or invitations")]
+    InUse,
+    #[error("At least one active admin must remain")]
+    LastAdmin,
+}
+
+pub type Result<T> = std::result::Result<T, RoleError>;
+
+fn validate_name(name: &str) -> Result<()> {
+    let valid = (2..=32).contains(&name.len())
+        && name
+            .bytes()
+            .all(|b| b.is_ascii_lowercase() || b.is_ascii_digit() || b == b'-' || b == b'_');
+    if valid {
+        Ok(())
+    } else {
+        Err(RoleError::InvalidName)
+    }
+}
+
+fn validate_permissions(permissions: &[String]) -> Result<Vec<String>> {
+    if let Some(unknown) = permissions.iter().find(|p| p.parse::<Permission>().is_err()) {
+        return Err(RoleError::UnknownPermission(unknown.clone()));
+    }
+    let mut permissions = permissions.to_vec();
+    permissions.sort();
+    permissions.dedup();
+    Ok(permissions)
+}
+
+/// All roles with their permissions
+pub async fn list_roles(pool: &PgPool) -> Result<Vec<Role>> {
+    let roles = sqlx::query_as_unchecked!(
+        Role,
+        r#"
+        SELECT r.name, r.description, r.is_system, r.created_at,
+               COALESCE(array_agg(rp.permission ORDER BY rp.permission)
+                        FILTER (WHERE rp.permission IS NOT NULL), '{}') AS permissions
+        FROM roles r
+        LEFT JOIN role_permissions rp ON rp.role = r.name
+        GROUP BY r.name
+        ORDER BY r.is_system DESC, r.name
+        "#
+    )
+    .fetch_all(pool)
+    .await?;
+
+    Ok(roles)
+}
+
+pub async fn get_role(pool: &PgPool, name: &str) -> Result<Role> {
+    let role = sqlx::query_as_unchecked!(
+        Role,
+        r#"
+        SELECT r.name, r.description, r.is_system, r.created_at,
+               COALESCE(array_agg(rp.permission ORDER BY rp.permission)
+                        FILTER (WHERE rp.permission IS NOT NULL), '{}') AS permissions
+        FROM roles r
+        LEFT JOIN role_permissions rp ON rp.role = r.name
+        WHERE r.name = $1
+        GROUP BY r.name
+        "#,
+        name
+    )
+    .fetch_optional(pool)
+    .await?
+    .ok_or(RoleError::NotFound)?;
+
+    Ok(role)
+}
+
+/// Every permission a role can be given
+pub async fn list_permissions(pool: &PgPool) -> Result<Vec<PermissionInfo>> {
+    let permissions = sqlx::query_as_unchecked!(
+        PermissionInfo,
+        "SELECT name, description FROM permissions ORDER BY name"
+    )
+    .fetch_all(pool)
+    .await?;
+
+    Ok(permissions)
+}
+
+/// Define a custom role
+pub async fn create_role(
+    pool: &PgPool,
+    name: &str,
+    description: &str,
+    permissions: &[String],
+) -> Result<Role> {
+    validate_name(name)?;
+    let permissions = validate_permissions(permissions)?;
+
+    let mut tx = pool.begin().await?;
+
+    let inserted = sqlx::query!(
+        r#"
+        INSERT INTO roles (name, description)
+        VALUES ($1, $2)
+        ON CONFLICT (name) DO NOTHING
+        "#,
+        name,
+        description
+    )
+    .execute(&mut *tx)
+    .await?;
+
+    if inserted.rows_affected() == 0 {
+        return Err(RoleError::AlreadyExists);
+    }
+
+    sqlx::query!(
+        "INSERT INTO role_permissions (role, permission) SELECT $1, UNNEST($2::text[])",
+        name,
+        &permissions
+    )
+    .execute(&mut *tx)
+    .await?;
+
+    tx.commit().await?;
+    get_role(pool, name).await
+}
+
+/// Change a role's description and/or replace its permission set.
+/// `admin` always keeps every permission and cannot be edited.
+pub async fn update_role(
+    pool: &PgPool,
+    name: &str,
+    description: Option<&str>,
+    permissions: Option<&[String]>,
+) -> Result<Role> {
+    if name == ADMIN_ROLE {
+        return Err(RoleError::Protected);
+    }
+    let permissions = permissions.map(validate_permissions).transpose()?;
+
+    let mut tx = pool.begin().await?;
+
+    let updated = sqlx::query!(
+        "UPDATE roles SET description = COALESCE($2, description) WHERE name = $1",
+        name,
+        description
+    )
+    .execute(&mut *tx)
+    .await?;
+
+    if updated.rows_affected() == 0 {
+        return Err(RoleError::NotFound);
+    }
+
+    if let Some(permissions) = permissions {
+        sqlx::query!("DELETE FROM role_permissions WHERE role = $1", name)
+            .execute(&mut *tx)
+            .await?;
+        sqlx::query!(
+            "INSERT INTO role_permissions (role, permission) SELECT $1, UNNEST($2::text[])",
+            name,
+            &permissions
+        )
+        .execute(&mut *tx)
+        .await?;
+    }
+
+    tx.commit().await?;
+    get_role(pool, name).await
+}
+
+/// Delete a custom role that nobody has
+pub async fn delete_role(pool: &PgPool, name: &str) -> Result<()> {
+    let mut tx = pool.begin().await?;
+
+    let role = sqlx::query!(
+        "SELECT is_system FROM roles WHERE name = $1 FOR UPDATE",
+        name
+    )
+    .fetch_optional(&mut *tx)
+    .await?
+    .ok_or(RoleError::NotFound)?;
+
+    if role.is_system {
+        return Err(RoleError::Protected);
+    }
+
+    let in_use = sqlx::query_scalar!(
+        r#"
+        SELECT EXISTS (SELECT 1 FROM users WHERE role = $1)
+            OR EXISTS (SELECT 1 FROM invitations
+                       WHERE role = $1 AND accepted_at IS NULL AND revoked_at IS NULL)
+            AS "in_use!"
+        "#,
+        name
+    )
+    .fetch_one(&mut *tx)
+    .await?;
+
+    if in_use {
+        return Err(RoleError::InUse);
+    }
+
+    // Old, finished invitations only keep the name for history
+    sqlx::query!(
+        "DELETE FROM invitations WHERE role = $1 AND (accepted_at IS NOT NULL OR revoked_at IS NOT NULL)",
+        name
+    )
+    .execute(&mut *tx)
+    .await?;
+
+    sqlx::query!(
+        r#"
+        UPDATE settings
+        SET value = value - $1::text, updated_at = now()
+        WHERE key = 'mfa_required_roles'
+        "#,
+        name
+    )
+    .execute(&mut *tx)
+    .await?;
+
+    sqlx::query!("DELETE FROM roles WHERE name = $1", name)
+        .execute(&mut *tx)
+        .await?;
+
+    tx.commit().await?;
+    Ok(())
+}
+
+/// Give a user a different role. Refuses to demote the last active admin.
+pub async fn assign_role(pool: &PgPool, user_id: Uuid, role: &str) -> Result<()> {
+    let mut tx = pool.begin().await?;
+
+    if !role_exists_in(&mut tx, role).await? {
+        return Err(RoleError::NotFound);
+    }
+
+    lock_users(&mut tx).await?;
+
+    let current = sqlx::query_scalar!("SELECT role FROM users WHERE id = $1", user_id)
+        .fetch_optional(&mut *tx)
+        .await?
+        .ok_or(RoleError::UserNotFound)?;
+
+    if current == ADMIN_ROLE && role != ADMIN_ROLE {
+        ensure_other_admin(&mut tx, user_id).await?;
+    }
+
+    sqlx::query!(
+        "UPDATE users SET role = $1, updated_at = now() WHERE id = $2",
+        role,
+        user_id
+    )
+    .execute(&mut *tx)
+    .await?;
+
+    tx.commit().await?;
+    Ok(())
+}
+
+/// Serialize changes that could leave no admin, so two admins can't demote
+/// or deactivate each other at once
+pub async fn lock_users(tx: &mut sqlx::Transaction<'_, sqlx::Postgres>) -> Result<()> {
+    sqlx::query!("LOCK TABLE users IN SHARE ROW EXCLUSIVE MODE")
+        .execute(&mut **tx)
+        .await?;
+
+    Ok(())
+}
+
+/// `Err(LastAdmin)` unless an active admin other than `user_id` exists.
+/// Call after `lock_users` in the same transaction.
+pub async fn ensure_other_admin(
+    tx: &mut sqlx::Transaction<'_, sqlx::Postgres>,
+    user_id: Uuid,
+) -> Result<()> {
+    let other_admins = sqlx::query_scalar!(
+        r#"
+        SELECT COUNT(*) AS "count!" FROM users
+        WHERE role = $1 AND id <> $2 AND COALESCE(is_active, true)
+        "#,
+        ADMIN_ROLE,
+        user_id
+    )
+    .fetch_one(&mut **tx)
+    .await?;
+
+    if other_admins == 0 {
+        return Err(RoleError::LastAdmin);
+    }
+    Ok(())
+}
+
+async fn role_exists_in(
+    tx: &mut sqlx::Transaction<'_, sqlx::Postgres>,
+    name: &str,
+) -> Result<bool> {
+    let exists = sqlx::query_scalar!(
+        r#"SELECT EXISTS (SELECT 1 FROM roles WHERE name = $1) AS "exists!""#,
+        name
+    )
+    .fetch_one(&mut **tx)
+    .await?;
+
+    Ok(exists)
+}
+
+/// Who an active user is and what their role allows right now
+#[derive(Debug, Clone)]
+pub struct UserAccess {
+    pub user_id: Uuid,
+    pub email: String,
+    pub name: String,
+    pub role: String,
+    pub email_verified: bool,
+    pub permissions: Vec<String>,
+    pub team_ids: Vec<Uuid>,
+}
+
+/// `None` if the user is gone or disabled
+pub async fn user_access(pool: &PgPool, user_id: Uuid) -> Result<Option<UserAccess>> {
+    let access = sqlx::query_as_unchecked!(
+        UserAccess,
+        r#"
+        SELECT u.id AS user_id, u.email, u.name, u.role,
+               u.email_verified_at IS NOT NULL AS email_verified,
+               COALESCE(array_agg(rp.permission)
+                        FILTER (WHERE rp.permission IS NOT NULL), '{}') AS permissions,
+               ARRAY(SELECT tm.team_id FROM team_members tm WHERE tm.user_id = u.id) AS team_ids
+        FROM users u
+        LEFT JOIN role_permissions rp ON rp.role = u.role
+        WHERE u.id = $1 AND COALESCE(u.is_active, true)
+        GROUP BY u.id
+        "#,
+        user_id
+    )
+    .fetch_optional(pool)
+    .await?;
+
+    Ok(access)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn role_names_are_slugs() {
+        assert!(validate_name("supervisor").is_ok());
+        assert!(validate_name("read-only_auditor2").is_ok());
+        assert!(validate_name("a").is_err());
+        assert!(validate_name("Supervisor").is_err());
+        assert!(validate_name("has space").is_err());
+        assert!(validate_name(&"x".repeat(33)).is_err());
+    }
+
+    #[test]
+    fn permissions_are_checked_and_deduplicated() {
+        let perms = vec!["notes.read".to_string(), "kb.manage".into(), "notes.read".into()];
+        assert_eq!(
+            validate_permissions(&perms).unwrap(),
+            vec!["kb.manage".to_string(), "notes.read".into()]
+        );
+        assert!(matches!(
+            validate_permissions(&["tickets.nuke".to_string()]),
+            Err(RoleError::UnknownPermission(p)) if p == "tickets.nuke"
+        ));
+    }
+}
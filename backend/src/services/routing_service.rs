@@ -0,0 +1,510 @@
+use std::cmp::{Ordering, Reverse};
+
+use sqlx::{types::Json, PgConnection, PgPool};
+use uuid::Uuid;
+
+use crate::{
+    models::{
+        permission::Permission,
+        routing::{
+            AgentRoutingProfile, CandidateReport, RoutingCandidate, RoutingDecision, RoutingSettings,
+            RoutingStrategy,
+        },
+        ticket::{Ticket, TicketStatus},
+    },
+    services::ticket_service::{self, TicketError},
+};
+
+#[derive(Debug, thiserror::Error)]
+pub enum RoutingError {
+    #[error("Database error: {0}")]
+    Database(#[from] sqlx::Error),
+    #[error(transparent)]
+    Ticket(#[from] TicketError),
+    #[error("Agent has no routing profile")]
+    NotFound,
+    #[error("Only users who can work assigned tickets can be routed to")]
+    NotAnAgent,
+    #[error("Capacity can't be negative")]
+    InvalidCapacity,
+}
+
+pub type Result<T> = std::result::Result<T, RoutingError>;
+
+const ROUTING_SETTINGS: &str = "routing";
+
+/// Routing only ever touches one ticket at a time, so round-robin and
+/// capacity see every earlier assignment
+const ROUTING_LOCK: i64 = 0x7469_636b_6574;
+
+// ---------------------------------------------------------------------------
+// Settings and agent profiles
+// ---------------------------------------------------------------------------
+
+/// Whether new tickets are routed and with which strategy; defaults until an admin saves some
+pub async fn settings(pool: &PgPool) -> Result<RoutingSettings> {
+    let saved = sqlx::query_scalar::<_, Json<RoutingSettings>>("SELECT value FROM settings WHERE key = $1")
+        .bind(ROUTING_SETTINGS)
+        .fetch_optional(pool)
+        .await?;
+
+    Ok(saved.map(|Json(settings)| settings).unwrap_or_default())
+}
+
+pub async fn set_settings(pool: &PgPool, settings: RoutingSettings, updated_by: Uuid) -> Result<RoutingSettings> {
+    sqlx::query(
+        r#"
+        INSERT INTO settings (key, value, updated_by)
+        VALUES ($1, $2, $3)
+        ON CONFLICT (key) DO UPDATE
+        SET value = EXCLUDED.value, updated_at = now(), updated_by = EXCLUDED.updated_by
+        "#,
+    )
+    .bind(ROUTING_SETTINGS)
+    .bind(Json(settings))
+    .bind(updated_by)
+    .execute(pool)
+    .await?;
+
+    Ok(settings)
+}
+
+/// Everything an admin sets on an agent's profile
+#[derive(Debug, Clone)]
+pub struct ProfileInput {
+    pub skills: Vec<String>,
+    pub max_open_tickets: Option<i32>,
+    pub available: bool,
+}
+
+/// Trimmed, lower-case and without duplicates, so matching tags is case-insensitive
+fn normalize_skills(skills: &[String]) -> Vec<String> {
+    let mut skills: Vec<String> = skills
+        .iter()
+        .map(|skill| skill.trim().to_lowercase())
+        .filter(|skill| !skill.is_empty())
+        .collect();
+    skills.sort();
+    skills.dedup();
+    skills
+}
+
+/// Every agent the engine can route to
+pub async fn list_profiles(pool: &PgPool) -> Result<Vec<AgentRoutingProfile>> {
+    let profiles = sqlx::query_as::<_, AgentRoutingProfile>(
+        "SELECT * FROM agent_routing_profiles ORDER BY user_id",
+    )
+    .fetch_all(pool)
+    .await?;
+
+    Ok(profiles)
+}
+
+/// Create or replace an agent's profile, which makes them routable
+pub async fn save_profile(pool: &PgPool, user_id: Uuid, input: &ProfileInput) -> Result<AgentRoutingProfile> {
+    if input.max_open_tickets.is_some_and(|max| max < 0) {
+        return Err(RoutingError::InvalidCapacity);
+    }
+
+    let role = sqlx::query_scalar::<_, bool>(
+        r#"
+        SELECT EXISTS (
+            SELECT 1 FROM role_permissions rp
+            WHERE rp.role = u.role AND rp.permission = $2
+        )
+        FROM users u
+        WHERE u.id = $1
+        "#,
+    )
+    .bind(user_id)
+    .bind(Permission::TicketsReadAssigned.as_str())
+    .fetch_optional(pool)
+    .await?;
+
+    match role {
+        None => return Err(RoutingError::NotFound),
+        Some(false) => return Err(RoutingError::NotAnAgent),
+        Some(true) => {}
+    }
+
+    let profile = sqlx::query_as::<_, AgentRoutingProfile>(
+        r#"
+        INSERT INTO agent_routing_profiles (user_id, skills, max_open_tickets, available)
+        VALUES ($1, $2, $3, $4)
+        ON CONFLICT (user_id) DO UPDATE
+        SET skills = EXCLUDED.skills, max_open_tickets = EXCLUDED.max_open_tickets,
+            available = EXCLUDED.available, updated_at = now()
+        RETURNING *
+        "#,
+    )
+    .bind(user_id)
+    .bind(normalize_skills(&input.skills))
+    .bind(input.max_open_tickets)
+    .bind(input.available)
+    .fetch_one(pool)
+    .await?;
+
+    Ok(profile)
+}
+
+/// Stop routing tickets to an agent. Tickets they already have stay theirs.
+pub async fn delete_profile(pool: &PgPool, user_id: Uuid) -> Result<()> {
+    let deleted = sqlx::query("DELETE FROM agent_routing_profiles WHERE user_id = $1")
+        .bind(user_id)
+        .execute(pool)
+        .await?;
+
+    if deleted.rows_affected() == 0 {
+        return Err(RoutingError::NotFound);
+    }
+
+    Ok(())
+}
+
+/// An agent going on or off shift
+pub async fn set_availability(pool: &PgPool, user_id: Uuid, available: bool) -> Result<AgentRoutingProfile> {
+    sqlx::query_as::<_, AgentRoutingProfile>(
+        r#"
+        UPDATE agent_routing_profiles
+        SET available = $1, updated_at = now()
+        WHERE user_id = $2
+        RETURNING *
+        "#,
+    )
+    .bind(available)
+    .bind(user_id)
+    .fetch_optional(pool)
+    .await?
+    .ok_or(RoutingError::NotFound)
+}
+
+// ---------------------------------------------------------------------------
+// Choosing an agent
+// ---------------------------------------------------------------------------
+
+/// Active agents with a profile, how many open tickets each has and
+/// which teams they are on
+async fn candidates(conn: &mut PgConnection) -> std::result::Result<Vec<RoutingCandidate>, sqlx::Error> {
+    sqlx::query_as::<_, RoutingCandidate>(
+        r#"
+        SELECT p.user_id, u.name, p.skills, p.max_open_tickets, p.available, p.last_assigned_at,
+               (SELECT COUNT(*) FROM tickets t
+                WHERE t.assigned_to = p.user_id AND t.status NOT IN ($2, $3)) AS open_tickets,
+               ARRAY(SELECT tm.team_id FROM team_members tm WHERE tm.user_id = p.user_id) AS team_ids
+        FROM agent_routing_profiles p
+        JOIN users u ON u.id = p.user_id
+        WHERE COALESCE(u.is_active, true)
+          AND EXISTS (
+              SELECT 1 FROM role_permissions rp
+              WHERE rp.role = u.role AND rp.permission = $1
+          )
+        ORDER BY p.user_id
+        "#,
+    )
+    .bind(Permission::TicketsReadAssigned.as_str())
+    .bind(TicketStatus::Resolved)
+    .bind(TicketStatus::Closed)
+    .fetch_all(conn)
+    .await
+}
+
+/// Order in which eligible agents are preferred; the smallest wins.
+/// Ties always fall back to round-robin, then to the agent id.
+fn preference(strategy: RoutingStrategy, a: (&RoutingCandidate, usize), b: (&RoutingCandidate, usize)) -> Ordering {
+    let (a, a_matches) = a;
+    let (b, b_matches) = b;
+    let round_robin = |a: &RoutingCandidate, b: &RoutingCandidate| {
+        // Never assigned (`None`) sorts first
+        (a.last_assigned_at, a.user_id).cmp(&(b.last_assigned_at, b.user_id))
+    };
+
+    match strategy {
+        RoutingStrategy::RoundRobin => round_robin(a, b),
+        RoutingStrategy::LeastOpen => a.open_tickets.cmp(&b.open_tickets).then_with(|| round_robin(a, b)),
+        RoutingStrategy::Skills => Reverse(a_matches)
+            .cmp(&Reverse(b_matches))
+            .then_with(|| a.open_tickets.cmp(&b.open_tickets))
+            .then_with(|| round_robin(a, b)),
+    }
+}
+
+fn chosen_reason(strategy: RoutingStrategy, chosen: &RoutingCandidate, matched: &[String]) -> String {
+    let last = match chosen.last_assigned_at {
+        Some(at) => format!("last routed a ticket at {}", at.to_rfc3339()),
+        None => "never routed a ticket before".to_string(),
+    };
+
+    match strategy {
+        RoutingStrategy::RoundRobin => format!("Longest wait since their last ticket ({last})"),
+        RoutingStrategy::LeastOpen => format!("Fewest open tickets ({})", chosen.open_tickets),
+        RoutingStrategy::Skills if matched.is_empty() => format!(
+            "Ticket has no tags; fewest open tickets ({})",
+            chosen.open_tickets
+        ),
+        RoutingStrategy::Skills => format!(
+            "Most matching skills ({}); fewest open tickets among them ({})",
+            matched.join(", "),
+            chosen.open_tickets
+        ),
+    }
+}
+
+/// Pick an agent for a ticket tagged `tags` from `candidates`, never
+/// `exclude`, and explain the choice for every candidate. A ticket in a
+/// team's queue only goes to members of `team_id`; other agents are left out.
+pub fn decide(
+    ticket_id: Uuid,
+    strategy: RoutingStrategy,
+    tags: &[String],
+    team_id: Option<Uuid>,
+    candidates: &[RoutingCandidate],
+    exclude: Option<Uuid>,
+) -> RoutingDecision {
+    let tags = normalize_skills(tags);
+
+    let mut eligible = Vec::new();
+    let mut ineligible = Vec::new();
+    let on_team = |candidate: &&RoutingCandidate| team_id.is_none_or(|team_id| candidate.team_ids.contains(&team_id));
+    for candidate in candidates.iter().filter(on_team) {
+        let matched: Vec<String> = candidate
+            .skills
+            .iter()
+            .filter(|skill| tags.contains(skill))
+            .cloned()
+            .collect();
+
+        let refused = if exclude == Some(candidate.user_id) {
+            Some("Already assigned to this ticket".to_string())
+        } else if !candidate.available {
+            Some("Unavailable".to_string())
+        } else if let Some(max) = candidate.max_open_tickets.filter(|max| candidate.open_tickets >= i64::from(*max)) {
+            Some(format!("At capacity ({} of {max} open tickets)", candidate.open_tickets))
+        } else if strategy == RoutingStrategy::Skills && !tags.is_empty() && matched.is_empty() {
+            Some("No skill matches the ticket's tags".to_string())
+        } else {
+            None
+        };
+
+        match refused {
+            Some(reason) => ineligible.push(report(candidate, matched, false, reason)),
+            None => eligible.push((candidate, matched)),
+        }
+    }
+
+    eligible.sort_by(|(a, a_matched), (b, b_matched)| {
+        preference(strategy, (*a, a_matched.len()), (*b, b_matched.len()))
+    });
+
+    let (agent_id, reason) = match eligible.first() {
+        Some((chosen, matched)) => (Some(chosen.user_id), chosen_reason(strategy, chosen, matched)),
+        None => (None, "No agent is eligible".to_string()),
+    };
+
+    let mut reports: Vec<CandidateReport> = eligible
+        .into_iter()
+        .enumerate()
+        .map(|(rank, (candidate, matched))| {
+            let reason = if rank == 0 { "Chosen".to_string() } else { format!("Eligible, ranked #{}", rank + 1) };
+            report(candidate, matched, true, reason)
+        })
+        .collect();
+    reports.extend(ineligible);
+
+    RoutingDecision { ticket_id, strategy, agent_id, reason, candidates: reports }
+}
+
+fn report(candidate: &RoutingCandidate, matched_skills: Vec<String>, eligible: bool, reason: String) -> CandidateReport {
+    CandidateReport {
+        agent_id: candidate.user_id,
+        name: candidate.name.clone(),
+        eligible,
+        open_tickets: candidate.open_tickets,
+        matched_skills,
+        reason,
+    }
+}
+
+/// Dry run: who `strategy` would pick for the ticket now, changing nothing
+pub async fn preview(pool: &PgPool, ticket_id: Uuid, strategy: RoutingStrategy) -> Result<RoutingDecision> {
+    let ticket = ticket_service::get_ticket_by_id(pool, ticket_id)
+        .await
+        .map_err(|err| match err {
+            sqlx::Error::RowNotFound => RoutingError::Ticket(TicketError::NotFound),
+            err => err.into(),
+        })?;
+
+    let mut conn = pool.acquire().await?;
+    let tags = ticket_service::ticket_tags(&mut conn, ticket_id).await?;
+    let candidates = candidates(&mut conn).await?;
+
+    Ok(decide(ticket_id, strategy, &tags, ticket.team_id, &candidates, ticket.assigned_to))
+}
+
+/// Assign the ticket to whoever `strategy` picks, other than its current
+/// assignee. The ticket is left alone when nobody is eligible.
+pub async fn route_ticket(
+    pool: &PgPool,
+    ticket_id: Uuid,
+    strategy: RoutingStrategy,
+    actor: Option<Uuid>,
+) -> Result<(RoutingDecision, Option<Ticket>)> {
+    let mut tx = pool.begin().await?;
+
+    sqlx::query("SELECT pg_advisory_xact_lock($1)")
+        .bind(ROUTING_LOCK)
+        .execute(&mut *tx)
+        .await?;
+
+    let current = ticket_service::lock_ticket(&mut tx, ticket_id).await?;
+    let tags = ticket_service::ticket_tags(&mut tx, ticket_id).await?;
+    let candidates = candidates(&mut tx).await?;
+    let decision = decide(ticket_id, strategy, &tags, current.team_id, &candidates, current.assigned_to);
+
+    let Some(agent_id) = decision.agent_id else {
+        return Ok((decision, None));
+    };
+
+    let ticket = ticket_service::assign_in(&mut tx, ticket_id, agent_id, actor).await?;
+    sqlx::query("UPDATE agent_routing_profiles SET last_assigned_at = now() WHERE user_id = $1")
+        .bind(agent_id)
+        .execute(&mut *tx)
+        .await?;
+
+    tx.commit().await?;
+    Ok((decision, Some(ticket)))
+}
+
+/// Route a just-created ticket if auto-assignment is on. `None` when it's
+/// off or nobody is eligible.
+pub async fn route_new_ticket(pool: &PgPool, ticket_id: Uuid) -> Result<Option<Ticket>> {
+    let settings = settings(pool).await?;
+    if !settings.auto_assign {
+        return Ok(None);
+    }
+
+    let (decision, ticket) = route_ticket(pool, ticket_id, settings.strategy, None).await?;
+    tracing::info!(
+        "🧭 Ticket {} routed by {}: {}",
+        ticket_id,
+        settings.strategy.as_str(),
+        decision.reason
+    );
+
+    Ok(ticket)
+}
+
+#[cfg(test)]
+mod tests {
+    use chrono::{Duration, Utc};
+
+    use super::*;
+
+    fn agent(name: &str, skills: &[&str], open_tickets: i64, minutes_since_last: Option<i64>) -> RoutingCandidate {
+        RoutingCandidate {
+            user_id: Uuid::new_v4(),
+            name: name.into(),
+            skills: skills.iter().map(|s| s.to_string()).collect(),
+            max_open_tickets: None,
+            available: true,
+            last_assigned_at: minutes_since_last.map(|m| Utc::now() - Duration::minutes(m)),
+            open_tickets,
+            team_ids: Vec::new(),
+        }
+    }
+
+    fn pick(strategy: RoutingStrategy, tags: &[&str], agents: &[RoutingCandidate]) -> Option<String> {
+        let tags: Vec<String> = tags.iter().map(|t| t.to_string()).collect();
+        let decision = decide(Uuid::new_v4(), strategy, &tags, None, agents, None);
+        decision
+            .agent_id
+            .map(|id| agents.iter().find(|a| a.user_id == id).unwrap().name.clone())
+    }
+
+    #[test]
+    fn round_robin_prefers_the_longest_wait() {
+        let agents = [agent("ann", &[], 0, Some(5)), agent("bob", &[], 9, Some(60)), agent("cat", &[], 3, Some(30))];
+        assert_eq!(pick(RoutingStrategy::RoundRobin, &[], &agents).as_deref(), Some("bob"));
+
+        let agents = [agent("ann", &[], 0, Some(5)), agent("new", &[], 0, None)];
+        assert_eq!(pick(RoutingStrategy::RoundRobin, &[], &agents).as_deref(), Some("new"));
+    }
+
+    #[test]
+    fn least_open_prefers_the_lightest_load() {
+        let agents = [agent("ann", &[], 4, None), agent("bob", &[], 1, Some(1)), agent("cat", &[], 2, None)];
+        assert_eq!(pick(RoutingStrategy::LeastOpen, &[], &agents).as_deref(), Some("bob"));
+    }
+
+    #[test]
+    fn skills_prefer_the_most_matches_then_the_lightest_load() {
+        let agents = [
+            agent("ann", &["billing"], 0, None),
+            agent("bob", &["billing", "refund"], 5, None),
+            agent("cat", &["billing", "refund"], 2, None),
+            agent("dan", &["hardware"], 1, None),
+        ];
+        assert_eq!(pick(RoutingStrategy::Skills, &["Refund", "billing"], &agents).as_deref(), Some("cat"));
+        assert_eq!(pick(RoutingStrategy::Skills, &["shipping"], &agents), None);
+        // Untagged tickets can go to anyone
+        assert_eq!(pick(RoutingStrategy::Skills, &[], &agents).as_deref(), Some("ann"));
+    }
+
+    #[test]
+    fn unavailable_full_and_current_agents_are_skipped_with_a_reason() {
+        let mut away = agent("away", &[], 0, None);
+        away.available = false;
+        let mut full = agent("full", &[], 3, None);
+        full.max_open_tickets = Some(3);
+        let current = agent("current", &[], 0, None);
+        let busy = agent("busy", &[], 7, Some(1));
+
+        let agents = [away, full, current.clone(), busy.clone()];
+        let decision = decide(Uuid::new_v4(), RoutingStrategy::LeastOpen, &[], None, &agents, Some(current.user_id));
+
+        assert_eq!(decision.agent_id, Some(busy.user_id));
+        assert_eq!(decision.candidates[0].agent_id, busy.user_id);
+        let reasons: Vec<&str> = decision.candidates[1..].iter().map(|c| c.reason.as_str()).collect();
+        assert_eq!(reasons, ["Unavailable", "At capacity (3 of 3 open tickets)", "Already assigned to this ticket"]);
+        assert!(decision.candidates[1..].iter().all(|c| !c.eligible));
+    }
+
+    #[test]
+    fn nobody_eligible_means_no_agent() {
+        let mut away = agent("away", &[], 0, None);
+        away.available = false;
+        let decision = decide(Uuid::new_v4(), RoutingStrategy::RoundRobin, &[], None, &[away], None);
+
+        assert_eq!(decision.agent_id, None);
+        assert_eq!(decision.reason, "No agent is eligible");
+    }
+
+    #[test]
+    fn team_tickets_only_go_to_team_members() {
+        let billing = Uuid::new_v4();
+        let mut member = agent("member", &[], 5, Some(1));
+        member.team_ids = vec![billing];
+        let outsider = agent("outsider", &[], 0, None);
+        let agents = [member.clone(), outsider.clone()];
+
+        let decision = decide(Uuid::new_v4(), RoutingStrategy::LeastOpen, &[], Some(billing), &agents, None);
+        assert_eq!(decision.agent_id, Some(member.user_id));
+        assert!(decision.candidates.iter().all(|c| c.agent_id != outsider.user_id));
+
+        // Nobody on the team: the ticket waits in the queue
+        let decision = decide(Uuid::new_v4(), RoutingStrategy::LeastOpen, &[], Some(Uuid::new_v4()), &agents, None);
+        assert_eq!(decision.agent_id, None);
+        assert!(decision.candidates.is_empty());
+
+        // Tickets outside any queue can go to anyone
+        let decision = decide(Uuid::new_v4(), RoutingStrategy::LeastOpen, &[], None, &agents, None);
+        assert_eq!(decision.agent_id, Some(outsider.user_id));
+    }
+
+    #[test]
+    fn strategy_names_round_trip() {
+        for strategy in [RoutingStrategy::RoundRobin, RoutingStrategy::LeastOpen, RoutingStrategy::Skills] {
+            assert_eq!(strategy.as_str().parse(), Ok(strategy));
+        }
+        assert!("random".parse::<RoutingStrategy>().is_err());
+    }
+}
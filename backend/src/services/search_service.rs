@@ -0,0 +1,257 @@
+use std::collections::HashMap;
+
+use chrono::{DateTime, Utc};
+use sqlx::{FromRow, PgPool};
+use uuid::Uuid;
+
+use crate::{
+    models::search::{SearchHit, SearchHitKind, SearchResult},
+    services::ticket_service,
+};
+
+/// Best hits shown per ticket
+const HITS_PER_TICKET: i64 = 3;
+
+// These must match the expressions indexed in the full-text search migration
+const TICKET_VECTOR: &str = "(setweight(to_tsvector('english'::regconfig, t.subject), 'A') \
+     || to_tsvector('english'::regconfig, COALESCE(t.description, '')))";
+const MESSAGE_VECTOR: &str = "to_tsvector('english'::regconfig, m.content)";
+const NOTE_VECTOR: &str = "to_tsvector('english'::regconfig, n.content)";
+
+/// What the caller may find. Tickets match if any of the ticket rules
+/// apply; notes are internal and only searched with `include_notes`.
+#[derive(Debug, Clone, Default)]
+pub struct SearchScope {
+    pub all_tickets: bool,
+    /// Tickets assigned to this agent
+    pub assigned_to: Option<Uuid>,
+    /// Tickets in these teams' queues
+    pub teams: Vec<Uuid>,
+    /// Tickets filed under this (verified) customer email
+    pub customer_email: Option<String>,
+    pub include_notes: bool,
+}
+
+impl SearchScope {
+    fn is_empty(&self) -> bool {
+        !self.all_tickets && self.assigned_to.is_none() && self.teams.is_empty() && self.customer_email.is_none()
+    }
+}
+
+#[derive(FromRow)]
+struct RankedTicket {
+    ticket_id: Uuid,
+    rank: f32,
+}
+
+#[derive(FromRow)]
+struct HitRow {
+    kind: String,
+    ticket_id: Uuid,
+    id: Uuid,
+    rank: f32,
+    created_at: Option<DateTime<Utc>>,
+    snippet: String,
+}
+
+/// `(ticket_id, rank)` of every visible match; binds `$1` query text,
+/// `$2` all tickets, `$3` assignee, `$4` customer email, `$5` include notes,
+/// `$6` teams
+fn visible_hits_sql() -> String {
+    let visible = "($2 OR t.assigned_to = $3 OR t.customer_email = $4 OR t.team_id = ANY($6))";
+    format!(
+        r#"
+        WITH q AS (SELECT websearch_to_tsquery('english'::regconfig, $1) AS query),
+        hits AS (
+            SELECT t.id AS ticket_id, ts_rank({TICKET_VECTOR}, q.query) AS rank
+            FROM tickets t, q
+            WHERE {visible} AND {TICKET_VECTOR} @@ q.query
+            UNION ALL
+            SELECT m.ticket_id, ts_rank({MESSAGE_VECTOR}, q.query)
+            FROM messages m JOIN tickets t ON t.id = m.ticket_id, q
+            WHERE {visible} AND {MESSAGE_VECTOR} @@ q.query
+            UNION ALL
+            SELECT n.ticket_id, ts_rank({NOTE_VECTOR}, q.query)
+            FROM notes n JOIN tickets t ON t.id = n.ticket_id, q
+            WHERE $5 AND {visible} AND {NOTE_VECTOR} @@ q.query
+        )
+        "#
+    )
+}
+
+/// Search tickets, their messages and (if allowed) notes for `query`
+/// (web search syntax: `"exact phrase"`, `or`, `-exclude`). Results are
+/// grouped by ticket, best first, `limit` at a time; also returns the
+/// number of matching tickets.
+pub async fn search(
+    pool: &PgPool,
+    query: &str,
+    scope: &SearchScope,
+    limit: i64,
+    offset: i64,
+) -> Result<(Vec<SearchResult>, i64), sqlx::Error> {
+    if scope.is_empty() {
+        return Ok((Vec::new(), 0));
+    }
+
+    let hits = visible_hits_sql();
+
+    let total: i64 = sqlx::query_scalar(&format!("{hits} SELECT COUNT(DISTINCT ticket_id) FROM hits"))
+        .bind(query)
+        .bind(scope.all_tickets)
+        .bind(scope.assigned_to)
+        .bind(scope.customer_email.as_deref())
+        .bind(scope.include_notes)
+        .bind(&scope.teams)
+        .fetch_one(pool)
+        .await?;
+
+    let page_sql = format!(
+        "{hits} SELECT ticket_id, MAX(rank) AS rank FROM hits GROUP BY ticket_id \
+         ORDER BY rank DESC, ticket_id LIMIT $7 OFFSET $8"
+    );
+    let ranked = sqlx::query_as::<_, RankedTicket>(&page_sql)
+        .bind(query)
+        .bind(scope.all_tickets)
+        .bind(scope.assigned_to)
+        .bind(scope.customer_email.as_deref())
+        .bind(scope.include_notes)
+        .bind(&scope.teams)
+        .bind(limit)
+        .bind(offset)
+        .fetch_all(pool)
+        .await?;
+    if ranked.is_empty() {
+        return Ok((Vec::new(), total));
+    }
+
+    let ids: Vec<Uuid> = ranked.iter().map(|r| r.ticket_id).collect();
+    let mut tickets: HashMap<Uuid, _> = ticket_service::get_tickets_by_ids(pool, &ids)
+        .await?
+        .into_iter()
+        .map(|ticket| (ticket.id, ticket))
+        .collect();
+    let mut hits_by_ticket = page_hits(pool, query, &ids, scope.include_notes).await?;
+
+    let results = ranked
+        .into_iter()
+        .filter_map(|r| {
+            Some(SearchResult {
+                // Deleted since the ranking query; just leave it out
+                ticket: tickets.remove(&r.ticket_id)?,
+                rank: r.rank,
+                hits: hits_by_ticket.remove(&r.ticket_id).unwrap_or_default(),
+            })
+        })
+        .collect();
+
+    Ok((results, total))
+}
+
+/// The best few hits of each ticket in `ticket_ids`, with highlighted snippets
+async fn page_hits(
+    pool: &PgPool,
+    query: &str,
+    ticket_ids: &[Uuid],
+    include_notes: bool,
+) -> Result<HashMap<Uuid, Vec<SearchHit>>, sqlx::Error> {
+    // Control characters mark matches so the text can be escaped before
+    // they become <mark> tags
+    let sql = format!(
+        r#"
+        WITH q AS (SELECT websearch_to_tsquery('english'::regconfig, $1) AS query),
+        hits AS (
+            SELECT 'ticket' AS kind, t.id AS ticket_id, t.id, t.created_at,
+                   ts_rank({TICKET_VECTOR}, q.query) AS rank,
+                   t.subject || E'\n' || COALESCE(t.description, '') AS body
+            FROM tickets t, q
+            WHERE t.id = ANY($2) AND {TICKET_VECTOR} @@ q.query
+            UNION ALL
+            SELECT 'message', m.ticket_id, m.id, m.created_at,
+                   ts_rank({MESSAGE_VECTOR}, q.query), m.content
+            FROM messages m, q
+            WHERE m.ticket_id = ANY($2) AND {MESSAGE_VECTOR} @@ q.query
+            UNION ALL
+            SELECT 'note', n.ticket_id, n.id, n.created_at,
+                   ts_rank({NOTE_VECTOR}, q.query), n.content
+            FROM notes n, q
+            WHERE $3 AND n.ticket_id = ANY($2) AND {NOTE_VECTOR} @@ q.query
+        ),
+        best AS (
+            SELECT *, row_number() OVER (PARTITION BY ticket_id ORDER BY rank DESC, created_at DESC) AS position
+            FROM hits
+        )
+        SELECT kind, ticket_id, id, rank, created_at,
+               ts_headline('english'::regconfig, body, q.query,
+                   'StartSel=' || chr(2) || ', StopSel=' || chr(3)
+                   || ', MaxFragments=2, MaxWords=30, MinWords=10, FragmentDelimiter=" … "') AS snippet
+        FROM best, q
+        WHERE position <= $4
+        ORDER BY ticket_id, position
+        "#
+    );
+
+    let rows = sqlx::query_as::<_, HitRow>(&sql)
+        .bind(query)
+        .bind(ticket_ids)
+        .bind(include_notes)
+        .bind(HITS_PER_TICKET)
+        .fetch_all(pool)
+        .await?;
+
+    let mut hits: HashMap<Uuid, Vec<SearchHit>> = HashMap::new();
+    for row in rows {
+        let kind = match row.kind.as_str() {
+            "ticket" => SearchHitKind::Ticket,
+            "message" => SearchHitKind::Message,
+            _ => SearchHitKind::Note,
+        };
+        hits.entry(row.ticket_id).or_default().push(SearchHit {
+            kind,
+            id: row.id,
+            snippet: highlight(&row.snippet),
+            rank: row.rank,
+            created_at: row.created_at,
+        });
+    }
+
+    Ok(hits)
+}
+
+/// Escape a `ts_headline` excerpt for HTML and turn its match markers into `<mark>` tags
+fn highlight(raw: &str) -> String {
+    let mut html = String::with_capacity(raw.len() + 32);
+    for c in raw.chars() {
+        match c {
+            '\u{2}' => html.push_str("<mark>"),
+            '\u{3}' => html.push_str("</mark>"),
+            '&' => html.push_str("&amp;"),
+            '<' => html.push_str("&lt;"),
+            '>' => html.push_str("&gt;"),
+            '"' => html.push_str("&quot;"),
+            '\'' => html.push_str("&#39;"),
+            c => html.push(c),
+        }
+    }
+    html
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn snippets_are_escaped_before_marking() {
+        assert_eq!(
+            highlight("<b>Printer</b> is \u{2}jammed\u{3} & \"stuck\""),
+            "&lt;b&gt;Printer&lt;/b&gt; is <mark>jammed</mark> &amp; &quot;stuck&quot;"
+        );
+    }
+
+    #[test]
+    fn scope_without_ticket_rules_finds_nothing() {
+        assert!(SearchScope { include_notes: true, ..Default::default() }.is_empty());
+        assert!(!SearchScope { assigned_to: Some(Uuid::nil()), ..Default::default() }.is_empty());
+        assert!(!SearchScope { teams: vec![Uuid::nil()], ..Default::default() }.is_empty());
+    }
+}
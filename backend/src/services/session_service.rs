@@ -0,0 +1,320 @@
+use chrono::{Duration, Utc};
+use sqlx::PgPool;
+use uuid::Uuid;
+
+use crate::{
+    models::session::{ClientInfo, Session},
+    utils::token::{generate_token, hash_token},
+};
+
+#[derive(Debug, thiserror::Error)]
+pub enum SessionError {
+    #[error("Database error: {0}")]
+    Database(#[from] sqlx::Error),
+    #[error("Refresh token is invalid or expired")]
+    InvalidToken,
+    #[error("Refresh token was already used; session revoked")]
+    TokenReused,
+    #[error("Session not found")]
+    NotFound,
+}
+
+pub type Result<T> = std::result::Result<T, SessionError>;
+
+/// A freshly minted refresh token and the session it belongs to.
+/// `refresh_token` is the only copy of the plaintext token.
+#[derive(Debug)]
+pub struct IssuedRefreshToken {
+    pub session_id: Uuid,
+    pub user_id: Uuid,
+    pub refresh_token: String,
+}
+
+/// Bump `last_seen_at` at most this often, so busy clients don't write on every request
+const LAST_SEEN_RESOLUTION_SECONDS: f64 = 60.0;
+
+/// Start a new session for a user on `client`'s device and issue its first refresh token
+pub async fn create_session(
+    pool: &PgPool,
+    user_id: Uuid,
+    ttl: Duration,
+    client: &ClientInfo,
+) -> Result<IssuedRefreshToken> {
+    let expires_at = Utc::now() + ttl;
+    let refresh_token = generate_token();
+
+    let mut tx = pool.begin().await?;
+
+    let session_id = sqlx::query_scalar!(
+        r#"
+        INSERT INTO sessions (user_id, expires_at, user_agent, ip_address)
+        VALUES ($1, $2, $3, $4)
+        RETURNING id
+        "#,
+        user_id,
+        expires_at,
+        client.user_agent,
+        client.ip_address
+    )
+    .fetch_one(&mut *tx)
+    .await?;
+
+    sqlx::query!(
+        r#"
+        INSERT INTO refresh_tokens (session_id, token_hash, expires_at)
+        VALUES ($1, $2, $3)
+        "#,
+        session_id,
+        hash_token(&refresh_token),
+        expires_at
+    )
+    .execute(&mut *tx)
+    .await?;
+
+    tx.commit().await?;
+
+    Ok(IssuedRefreshToken {
+        session_id,
+        user_id,
+        refresh_token,
+    })
+}
+
+/// Exchange a refresh token for a new one within the same session.
+///
+/// Presenting a token that was already rotated means it leaked (either the
+/// legitimate client or an attacker is replaying it), so the whole session
+/// is revoked and every token in the family stops working.
+pub async fn rotate_refresh_token(
+    pool: &PgPool,
+    refresh_token: &str,
+    ttl: Duration,
+) -> Result<IssuedRefreshToken> {
+    let mut tx = pool.begin().await?;
+
+    let current = sqlx::query!(
+        r#"
+        SELECT rt.id, rt.session_id, rt.expires_at, rt.rotated_at,
+               s.user_id, s.revoked_at
+        FROM refresh_tokens rt
+        JOIN sessions s ON s.id = rt.session_id
+        WHERE rt.token_hash = $1
+        FOR UPDATE OF rt, s
+        "#,
+        hash_token(refresh_token)
+    )
+    .fetch_optional(&mut *tx)
+    .await?
+    .ok_or(SessionError::InvalidToken)?;
+
+    if current.revoked_at.is_some() {
+        return Err(SessionError::InvalidToken);
+    }
+
+    if current.rotated_at.is_some() {
+        revoke_session_in(&mut tx, current.session_id, "refresh_token_reuse").await?;
+        tx.commit().await?;
+        tracing::warn!(
+            "Refresh token reuse detected; revoked session {}",
+            current.session_id
+        );
+        return Err(SessionError::TokenReused);
+    }
+
+    let now = Utc::now();
+    if current.expires_at <= now {
+        return Err(SessionError::InvalidToken);
+    }
+
+    let expires_at = now + ttl;
+    let new_token = generate_token();
+
+    sqlx::query!(
+        "UPDATE refresh_tokens SET rotated_at = $1 WHERE id = $2",
+        now,
+        current.id
+    )
+    .execute(&mut *tx)
+    .await?;
+
+    sqlx::query!(
+        r#"
+        INSERT INTO refresh_tokens (session_id, token_hash, expires_at)
+        VALUES ($1, $2, $3)
+        "#,
+        current.session_id,
+        hash_token(&new_token),
+        expires_at
+    )
+    .execute(&mut *tx)
+    .await?;
+
+    sqlx::query!(
+        "UPDATE sessions SET expires_at = $1, last_seen_at = now() WHERE id = $2",
+        expires_at,
+        current.session_id
+    )
+    .execute(&mut *tx)
+    .await?;
+
+    tx.commit().await?;
+
+    Ok(IssuedRefreshToken {
+        session_id: current.session_id,
+        user_id: current.user_id,
+        refresh_token: new_token,
+    })
+}
+
+/// Revoke the session a refresh token belongs to (logout).
+/// Returns `false` if the token is unknown.
+pub async fn revoke_by_refresh_token(pool: &PgPool, refresh_token: &str) -> Result<bool> {
+    let result = sqlx::query!(
+        r#"
+        UPDATE sessions
+        SET revoked_at = now(), revoked_reason = 'logout'
+        WHERE revoked_at IS NULL
+          AND id = (SELECT session_id FROM refresh_tokens WHERE token_hash = $1)
+        "#,
+        hash_token(refresh_token)
+    )
+    .execute(pool)
+    .await?;
+
+    Ok(result.rows_affected() > 0)
+}
+
+/// Whether access tokens minted for this session are still honoured.
+/// Also records that the session is in use.
+pub async fn touch_session(pool: &PgPool, session_id: Uuid) -> Result<bool> {
+    let active = sqlx::query_scalar!(
+        r#"
+        WITH active AS (
+            SELECT id, last_seen_at FROM sessions
+            WHERE id = $1 AND revoked_at IS NULL AND expires_at > now()
+        ), touched AS (
+            UPDATE sessions SET last_seen_at = now()
+            WHERE id IN (
+                SELECT id FROM active
+                WHERE last_seen_at < now() - make_interval(secs => $2)
+            )
+        )
+        SELECT EXISTS (SELECT 1 FROM active) AS "active!"
+        "#,
+        session_id,
+        LAST_SEEN_RESOLUTION_SECONDS
+    )
+    .fetch_one(pool)
+    .await?;
+
+    Ok(active)
+}
+
+/// A user's sessions that are still usable, most recently used first
+pub async fn list_active(pool: &PgPool, user_id: Uuid) -> Result<Vec<Session>> {
+    let sessions = sqlx::query_as_unchecked!(
+        Session,
+        r#"
+        SELECT id, user_id, user_agent, ip_address, created_at, last_seen_at, expires_at, revoked_at
+        FROM sessions
+        WHERE user_id = $1 AND revoked_at IS NULL AND expires_at > now()
+        ORDER BY last_seen_at DESC
+        "#,
+        user_id
+    )
+    .fetch_all(pool)
+    .await?;
+
+    Ok(sessions)
+}
+
+/// Revoke one session. With `owner` set, only that user's sessions match.
+pub async fn revoke_session(
+    pool: &PgPool,
+    session_id: Uuid,
+    owner: Option<Uuid>,
+    reason: &str,
+) -> Result<Session> {
+    let session = sqlx::query_as_unchecked!(
+        Session,
+        r#"
+        UPDATE sessions
+        SET revoked_at = COALESCE(revoked_at, now()),
+            revoked_reason = COALESCE(revoked_reason, $3)
+        WHERE id = $1 AND ($2::uuid IS NULL OR user_id = $2)
+        RETURNING id, user_id, user_agent, ip_address, created_at, last_seen_at, expires_at, revoked_at
+        "#,
+        session_id,
+        owner,
+        reason
+    )
+    .fetch_optional(pool)
+    .await?
+    .ok_or(SessionError::NotFound)?;
+
+    Ok(session)
+}
+
+async fn revoke_session_in(
+    tx: &mut sqlx::Transaction<'_, sqlx::Postgres>,
+    session_id: Uuid,
+    reason: &str,
+) -> Result<()> {
+    sqlx::query!(
+        r#"
+        UPDATE sessions
+        SET revoked_at = now(), revoked_reason = $2
+        WHERE id = $1 AND revoked_at IS NULL
+        "#,
+        session_id,
+        reason
+    )
+    .execute(&mut **tx)
+    .await?;
+
+    Ok(())
+}
+
+/// Revoke every active session of a user (password reset, account changes)
+pub async fn revoke_all_for_user(
+    executor: impl sqlx::PgExecutor<'_>,
+    user_id: Uuid,
+    reason: &str,
+) -> Result<u64> {
+    let result = sqlx::query!(
+        r#"
+        UPDATE sessions
+        SET revoked_at = now(), revoked_reason = $2
+        WHERE user_id = $1 AND revoked_at IS NULL
+        "#,
+        user_id,
+        reason
+    )
+    .execute(executor)
+    .await?;
+
+    Ok(result.rows_affected())
+}
+
+/// Revoke every active session of a user except `keep` (the caller's own)
+pub async fn revoke_other_sessions(
+    executor: impl sqlx::PgExecutor<'_>,
+    user_id: Uuid,
+    keep: Uuid,
+    reason: &str,
+) -> Result<u64> {
+    let result = sqlx::query!(
+        r#"
+        UPDATE sessions
+        SET revoked_at = now(), revoked_reason = $3
+        WHERE user_id = $1 AND id <> $2 AND revoked_at IS NULL
+        "#,
+        user_id,
+        keep,
+        reason
+    )
+    .execute(executor)
+    .await?;
+
+    Ok(result.rows_affected())
+}
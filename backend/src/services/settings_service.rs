@@ -0,0 +1,77 @@
+use sqlx::PgPool;
+use uuid::Uuid;
+
+#[derive(Debug, thiserror::Error)]
+pub enum SettingsError {
+    #[error("Database error: {0}")]
+    Database(#[from] sqlx::Error),
+    #[error("Unknown role: {0}")]
+    UnknownRole(String),
+}
+
+pub type Result<T> = std::result::Result<T, SettingsError>;
+
+const MFA_REQUIRED_ROLES: &str = "mfa_required_roles";
+
+/// Roles whose members must have a second factor before they can log in
+pub async fn mfa_required_roles(pool: &PgPool) -> Result<Vec<String>> {
+    let roles = sqlx::query_scalar!(
+        r#"
+        SELECT ARRAY(SELECT jsonb_array_elements_text(value)) AS "roles!"
+        FROM settings
+        WHERE key = $1
+        "#,
+        MFA_REQUIRED_ROLES
+    )
+    .fetch_optional(pool)
+    .await?;
+
+    Ok(roles.unwrap_or_default())
+}
+
+/// Whether `role` has mandatory two-factor authentication
+pub async fn is_mfa_required_for(pool: &PgPool, role: &str) -> Result<bool> {
+    Ok(mfa_required_roles(pool).await?.iter().any(|r| r == role))
+}
+
+/// Replace the list of roles with mandatory two-factor authentication
+pub async fn set_mfa_required_roles(
+    pool: &PgPool,
+    roles: &[String],
+    updated_by: Uuid,
+) -> Result<Vec<String>> {
+    let mut roles = roles.to_vec();
+    roles.sort();
+    roles.dedup();
+
+    let unknown = sqlx::query_scalar!(
+        r#"
+        SELECT r AS "role!" FROM UNNEST($1::text[]) AS r
+        WHERE r NOT IN (SELECT name FROM roles)
+        LIMIT 1
+        "#,
+        &roles
+    )
+    .fetch_optional(pool)
+    .await?;
+
+    if let Some(unknown) = unknown {
+        return Err(SettingsError::UnknownRole(unknown));
+    }
+
+    sqlx::query!(
+        r#"
+        INSERT INTO settings (key, value, updated_by)
+        VALUES ($1, to_jsonb($2::text[]), $3)
+        ON CONFLICT (key) DO UPDATE
+        SET value = EXCLUDED.value, updated_at = now(), updated_by = EXCLUDED.updated_by
+        "#,
+        MFA_REQUIRED_ROLES,
+        &roles,
+        updated_by
+    )
+    .execute(pool)
+    .await?;
+
+    Ok(roles)
+}
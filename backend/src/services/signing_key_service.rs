@@ -0,0 +1,258 @@
+use std::{
+    sync::Arc,
+    time::{Duration as StdDuration, Instant},
+};
+
+use chrono::{Duration, Utc};
+use jsonwebtoken::{decode_header, jwk::{Jwk, JwkSet}, Algorithm};
+use sqlx::PgPool;
+use tokio::sync::RwLock;
+
+use crate::{
+    models::signing_key::{KeyAlgorithm, SigningKeyInfo},
+    utils::jwt::{self, Claims, SigningKey, VerificationKey},
+};
+
+#[derive(Debug, thiserror::Error)]
+pub enum SigningKeyError {
+    #[error("Database error: {0}")]
+    Database(#[from] sqlx::Error),
+    #[error("Invalid token: {0}")]
+    Token(#[from] jsonwebtoken::errors::Error),
+    #[error("Token is not signed by a known key")]
+    UnknownKey,
+    #[error("Stored key {0} is unusable")]
+    CorruptKey(String),
+    #[error("Key generation failed: {0}")]
+    Generation(String),
+}
+
+pub type Result<T> = std::result::Result<T, SigningKeyError>;
+
+/// How long keys loaded from the database are used before rereading them,
+/// so a rotation on another instance is picked up
+const RELOAD_INTERVAL: StdDuration = StdDuration::from_secs(60);
+/// An unknown `kid` triggers an early reload at most this often
+const MIN_RELOAD_INTERVAL: StdDuration = StdDuration::from_secs(5);
+
+/// Current and recently retired keys
+struct KeyRing {
+    signing: Arc<SigningKey>,
+    verifying: Vec<VerificationKey>,
+    jwks: JwkSet,
+    loaded_at: Instant,
+}
+
+impl KeyRing {
+    fn find(&self, kid: &str) -> Option<&VerificationKey> {
+        self.verifying.iter().find(|key| key.kid == kid)
+    }
+}
+
+/// Signs and verifies access tokens with the keys in `signing_keys`.
+/// One key signs; retired keys keep verifying for `grace`, long enough for
+/// every token they signed to expire.
+pub struct KeyStore {
+    algorithm: KeyAlgorithm,
+    grace: Duration,
+    legacy_secret: Option<String>,
+    ring: RwLock<Option<Arc<KeyRing>>>,
+}
+
+impl KeyStore {
+    /// `algorithm` is used for keys created from now on, `grace` should be
+    /// the access token lifetime plus some clock skew. `legacy_secret`
+    /// keeps HS256 tokens from before key rotation working.
+    pub fn new(algorithm: KeyAlgorithm, grace: Duration, legacy_secret: Option<String>) -> Self {
+        Self {
+            algorithm,
+            grace,
+            legacy_secret,
+            ring: RwLock::new(None),
+        }
+    }
+
+    async fn ring(&self, pool: &PgPool) -> Result<Arc<KeyRing>> {
+        if let Some(ring) = self.ring.read().await.as_ref() {
+            if ring.loaded_at.elapsed() < RELOAD_INTERVAL {
+                return Ok(ring.clone());
+            }
+        }
+        self.reload(pool).await
+    }
+
+    async fn reload(&self, pool: &PgPool) -> Result<Arc<KeyRing>> {
+        let ring = Arc::new(load_ring(pool, self.algorithm, self.grace).await?);
+        *self.ring.write().await = Some(ring.clone());
+        Ok(ring)
+    }
+
+    /// The key new tokens are signed with
+    pub async fn signing_key(&self, pool: &PgPool) -> Result<Arc<SigningKey>> {
+        Ok(self.ring(pool).await?.signing.clone())
+    }
+
+    /// Public keys other services can verify our tokens with
+    pub async fn jwks(&self, pool: &PgPool) -> Result<JwkSet> {
+        Ok(self.ring(pool).await?.jwks.clone())
+    }
+
+    /// Check an access token's signature and expiry against the key named
+    /// by its `kid`
+    pub async fn verify(&self, pool: &PgPool, token: &str) -> Result<Claims> {
+        let header = decode_header(token)?;
+
+        let Some(kid) = header.kid else {
+            return match (&self.legacy_secret, header.alg) {
+                (Some(secret), Algorithm::HS256) => Ok(jwt::decode_legacy_token(token, secret)?),
+                _ => Err(SigningKeyError::UnknownKey),
+            };
+        };
+
+        let mut ring = self.ring(pool).await?;
+        // Possibly rotated on another instance since we last looked
+        if ring.find(&kid).is_none() && ring.loaded_at.elapsed() >= MIN_RELOAD_INTERVAL {
+            ring = self.reload(pool).await?;
+        }
+        let key = ring.find(&kid).ok_or(SigningKeyError::UnknownKey)?;
+
+        Ok(jwt::decode_token(token, key)?)
+    }
+
+    /// Retire the signing key and start signing with a new one
+    pub async fn rotate(
+        &self,
+        pool: &PgPool,
+        algorithm: Option<KeyAlgorithm>,
+    ) -> Result<SigningKeyInfo> {
+        let key = rotate_key(pool, algorithm.unwrap_or(self.algorithm), self.grace).await?;
+        self.reload(pool).await?;
+        Ok(key)
+    }
+
+    /// Keys that are signing or still accepted, newest first
+    pub async fn list(&self, pool: &PgPool) -> Result<Vec<SigningKeyInfo>> {
+        let keys = sqlx::query_as_unchecked!(
+            SigningKeyInfo,
+            r#"
+            SELECT kid, algorithm, created_at, retired_at
+            FROM signing_keys
+            WHERE retired_at IS NULL OR retired_at > $1
+            ORDER BY created_at DESC
+            "#,
+            Utc::now() - self.grace
+        )
+        .fetch_all(pool)
+        .await?;
+
+        Ok(keys)
+    }
+}
+
+async fn generate(algorithm: KeyAlgorithm) -> Result<jwt::GeneratedKey> {
+    tokio::task::spawn_blocking(move || jwt::generate_key(algorithm))
+        .await
+        .map_err(|err| SigningKeyError::Generation(err.to_string()))?
+        .map_err(|err| SigningKeyError::Generation(err.to_string()))
+}
+
+/// Load every key still inside its verification window, creating the
+/// first signing key if there is none yet
+async fn load_ring(pool: &PgPool, algorithm: KeyAlgorithm, grace: Duration) -> Result<KeyRing> {
+    loop {
+        let rows = sqlx::query!(
+            r#"
+            SELECT kid, algorithm, private_key, public_jwk, retired_at
+            FROM signing_keys
+            WHERE retired_at IS NULL OR retired_at > $1
+            ORDER BY created_at DESC
+            "#,
+            Utc::now() - grace
+        )
+        .fetch_all(pool)
+        .await?;
+
+        let Some(active) = rows.iter().find(|row| row.retired_at.is_none()) else {
+            let key = generate(algorithm).await?;
+            // Another instance may get there first; either way, read again
+            insert_key(pool, algorithm, &key).await?;
+            continue;
+        };
+
+        let corrupt = |kid: &str| SigningKeyError::CorruptKey(kid.to_string());
+        let active_algorithm = active.algorithm.parse().map_err(|_| corrupt(&active.kid))?;
+        let signing = SigningKey::from_pem(&active.kid, active_algorithm, &active.private_key)
+            .map_err(|_| corrupt(&active.kid))?;
+
+        let mut verifying = Vec::with_capacity(rows.len());
+        let mut jwks = JwkSet { keys: Vec::with_capacity(rows.len()) };
+        for row in &rows {
+            let algorithm = row.algorithm.parse().map_err(|_| corrupt(&row.kid))?;
+            let jwk: Jwk = serde_json::from_str(&row.public_jwk).map_err(|_| corrupt(&row.kid))?;
+            verifying.push(VerificationKey::from_jwk(algorithm, &jwk).map_err(|_| corrupt(&row.kid))?);
+            jwks.keys.push(jwk);
+        }
+
+        return Ok(KeyRing {
+            signing: Arc::new(signing),
+            verifying,
+            jwks,
+            loaded_at: Instant::now(),
+        });
+    }
+}
+
+async fn insert_key(pool: &PgPool, algorithm: KeyAlgorithm, key: &jwt::GeneratedKey) -> Result<()> {
+    sqlx::query!(
+        r#"
+        INSERT INTO signing_keys (kid, algorithm, private_key, public_jwk)
+        VALUES ($1, $2, $3, $4)
+        ON CONFLICT DO NOTHING
+        "#,
+        key.kid,
+        algorithm.as_str(),
+        key.private_pem,
+        serde_json::to_string(&key.public_jwk).expect("JWK serializes")
+    )
+    .execute(pool)
+    .await?;
+
+    Ok(())
+}
+
+/// Retire the current signing key, add a new one and drop keys whose
+/// tokens have all expired
+async fn rotate_key(pool: &PgPool, algorithm: KeyAlgorithm, grace: Duration) -> Result<SigningKeyInfo> {
+    let key = generate(algorithm).await?;
+
+    let mut tx = pool.begin().await?;
+
+    sqlx::query!("UPDATE signing_keys SET retired_at = now() WHERE retired_at IS NULL")
+        .execute(&mut *tx)
+        .await?;
+
+    let info = sqlx::query_as_unchecked!(
+        SigningKeyInfo,
+        r#"
+        INSERT INTO signing_keys (kid, algorithm, private_key, public_jwk)
+        VALUES ($1, $2, $3, $4)
+        RETURNING kid, algorithm, created_at, retired_at
+        "#,
+        key.kid,
+        algorithm.as_str(),
+        key.private_pem,
+        serde_json::to_string(&key.public_jwk).expect("JWK serializes")
+    )
+    .fetch_one(&mut *tx)
+    .await?;
+
+    sqlx::query!(
+        "DELETE FROM signing_keys WHERE retired_at < $1",
+        Utc::now() - grace
+    )
+    .execute(&mut *tx)
+    .await?;
+
+    tx.commit().await?;
+    Ok(info)
+}
@@ -0,0 +1,586 @@
+use std::time::Duration as StdDuration;
+
+use chrono::{DateTime, Duration, Utc};
+use sqlx::{PgPool, Postgres, Transaction};
+use uuid::Uuid;
+
+use crate::{
+    models::{
+        permission::Permission,
+        sla::{SlaPolicy, TicketSla},
+        ticket::{Ticket, TicketPriority, TicketStatus},
+    },
+    services::{business_calendar_service, notification_services::notify_user, ticket_service::TICKET_COLUMNS},
+    utils::business_time::Schedule,
+};
+
+#[derive(Debug, thiserror::Error)]
+pub enum SlaError {
+    #[error("Database error: {0}")]
+    Database(#[from] sqlx::Error),
+    #[error("SLA policy not found")]
+    NotFound,
+    #[error("Customer domain must look like example.com")]
+    InvalidDomain,
+    #[error("Targets must be at least one minute")]
+    InvalidTarget,
+    #[error("A policy for this priority and customer domain already exists")]
+    AlreadyExists,
+    #[error("Business calendar not found")]
+    UnknownCalendar,
+}
+
+pub type Result<T> = std::result::Result<T, SlaError>;
+
+// ---------------------------------------------------------------------------
+// Policies
+// ---------------------------------------------------------------------------
+
+/// Everything an admin sets on a policy
+#[derive(Debug, Clone)]
+pub struct PolicyInput {
+    pub name: String,
+    pub priority: TicketPriority,
+    pub customer_domain: Option<String>,
+    pub first_response_minutes: i32,
+    pub resolution_minutes: i32,
+    pub calendar_id: Option<Uuid>,
+}
+
+/// `"@Example.COM "` -> `"example.com"`; anything that isn't a plausible
+/// domain is rejected
+fn normalize_domain(raw: &str) -> Result<String> {
+    let domain = raw.trim().trim_start_matches('@').to_lowercase();
+    let valid = domain.contains('.')
+        && !domain.starts_with(['.', '-'])
+        && !domain.ends_with(['.', '-'])
+        && !domain.contains("..")
+        && domain
+            .chars()
+            .all(|c| c.is_ascii_alphanumeric() || c == '.' || c == '-');
+
+    if valid {
+        Ok(domain)
+    } else {
+        Err(SlaError::InvalidDomain)
+    }
+}
+
+/// The organization a customer belongs to, by email domain
+fn customer_domain(email: &str) -> Option<String> {
+    email
+        .rsplit_once('@')
+        .map(|(_, domain)| domain.trim().to_lowercase())
+        .filter(|domain| !domain.is_empty())
+}
+
+fn validate(input: &PolicyInput) -> Result<Option<String>> {
+    if input.first_response_minutes < 1 || input.resolution_minutes < 1 {
+        return Err(SlaError::InvalidTarget);
+    }
+
+    input
+        .customer_domain
+        .as_deref()
+        .filter(|domain| !domain.trim().is_empty())
+        .map(normalize_domain)
+        .transpose()
+}
+
+fn conflict_or(err: sqlx::Error) -> SlaError {
+    match err.as_database_error() {
+        Some(db) if db.is_unique_violation() => SlaError::AlreadyExists,
+        Some(db) if db.is_foreign_key_violation() => SlaError::UnknownCalendar,
+        _ => err.into(),
+    }
+}
+
+/// Every policy, defaults before organization overrides
+pub async fn list_policies(pool: &PgPool) -> Result<Vec<SlaPolicy>> {
+    let policies = sqlx::query_as::<_, SlaPolicy>(
+        "SELECT * FROM sla_policies ORDER BY priority DESC, customer_domain NULLS FIRST",
+    )
+    .fetch_all(pool)
+    .await?;
+
+    Ok(policies)
+}
+
+/// Add a policy. It applies to tickets created (or re-prioritized) from now on.
+pub async fn create_policy(pool: &PgPool, input: &PolicyInput) -> Result<SlaPolicy> {
+    let domain = validate(input)?;
+
+    sqlx::query_as::<_, SlaPolicy>(
+        r#"
+        INSERT INTO sla_policies (name, priority, customer_domain, first_response_minutes, resolution_minutes, calendar_id)
+        VALUES ($1, $2, $3, $4, $5, $6)
+        RETURNING *
+        "#,
+    )
+    .bind(input.name.trim())
+    .bind(&input.priority)
+    .bind(domain)
+    .bind(input.first_response_minutes)
+    .bind(input.resolution_minutes)
+    .bind(input.calendar_id)
+    .fetch_one(pool)
+    .await
+    .map_err(conflict_or)
+}
+
+/// Replace a policy's settings. Tickets keep the due times they already have.
+pub async fn update_policy(pool: &PgPool, policy_id: Uuid, input: &PolicyInput) -> Result<SlaPolicy> {
+    let domain = validate(input)?;
+
+    sqlx::query_as::<_, SlaPolicy>(
+        r#"
+        UPDATE sla_policies
+        SET name = $1, priority = $2, customer_domain = $3,
+            first_response_minutes = $4, resolution_minutes = $5, calendar_id = $6, updated_at = now()
+        WHERE id = $7
+        RETURNING *
+        "#,
+    )
+    .bind(input.name.trim())
+    .bind(&input.priority)
+    .bind(domain)
+    .bind(input.first_response_minutes)
+    .bind(input.resolution_minutes)
+    .bind(input.calendar_id)
+    .bind(policy_id)
+    .fetch_optional(pool)
+    .await
+    .map_err(conflict_or)?
+    .ok_or(SlaError::NotFound)
+}
+
+/// Remove a policy. Tickets keep the due times they already have.
+pub async fn delete_policy(pool: &PgPool, policy_id: Uuid) -> Result<()> {
+    let deleted = sqlx::query("DELETE FROM sla_policies WHERE id = $1")
+        .bind(policy_id)
+        .execute(pool)
+        .await?;
+
+    if deleted.rows_affected() == 0 {
+        return Err(SlaError::NotFound);
+    }
+
+    Ok(())
+}
+
+/// The policy for a ticket: its organization's if there is one, else the default
+async fn find_policy(
+    tx: &mut Transaction<'_, Postgres>,
+    priority: &TicketPriority,
+    customer_email: Option<&str>,
+) -> std::result::Result<Option<SlaPolicy>, sqlx::Error> {
+    sqlx::query_as::<_, SlaPolicy>(
+        r#"
+        SELECT * FROM sla_policies
+        WHERE priority = $1 AND (customer_domain IS NULL OR customer_domain = $2)
+        ORDER BY customer_domain IS NULL
+        LIMIT 1
+        "#,
+    )
+    .bind(priority)
+    .bind(customer_email.and_then(customer_domain))
+    .fetch_optional(&mut **tx)
+    .await
+}
+
+// ---------------------------------------------------------------------------
+// Ticket clocks
+// ---------------------------------------------------------------------------
+
+/// The clock stops while we're waiting on the customer or the work is done
+fn clock_running(status: TicketStatus) -> bool {
+    !matches!(status, TicketStatus::Pending | TicketStatus::Resolved | TicketStatus::Closed)
+}
+
+/// Due times from `policy`, counted in `schedule`'s open time from
+/// `created_at`, plus the time the clock has been stopped. Targets already met
+/// or missed keep their due time; with no policy the ticket has no targets.
+fn retarget(
+    sla: &TicketSla,
+    created_at: DateTime<Utc>,
+    policy: Option<&SlaPolicy>,
+    schedule: &Schedule,
+    now: DateTime<Utc>,
+) -> TicketSla {
+    let mut sla = sla.clone();
+    sla.policy_id = policy.map(|p| p.id);
+
+    let paused = Duration::seconds(sla.paused_seconds);
+    let due = |minutes: Option<i32>| {
+        minutes.and_then(|m| schedule.due_at(created_at, Duration::minutes(m.into()) + paused))
+    };
+
+    if sla.first_responded_at.is_none() {
+        sla.first_response_due_at = due(policy.map(|p| p.first_response_minutes));
+        // A later target can un-breach a ticket the old one had already failed
+        if sla.first_response_due_at.is_none_or(|due| due > now) {
+            sla.first_response_breached_at = None;
+        }
+    }
+    if sla.resolved_at.is_none() {
+        sla.resolution_due_at = due(policy.map(|p| p.resolution_minutes));
+        if sla.resolution_due_at.is_none_or(|due| due > now) {
+            sla.resolution_breached_at = None;
+        }
+    }
+
+    sla
+}
+
+/// Stop, restart or leave the clock as the ticket moves from `from` to `to`.
+/// Restarting pushes the open targets back by however much of `schedule`'s
+/// open time it was stopped for.
+fn follow_status(
+    sla: &TicketSla,
+    from: TicketStatus,
+    to: TicketStatus,
+    schedule: &Schedule,
+    now: DateTime<Utc>,
+) -> TicketSla {
+    let mut sla = sla.clone();
+
+    match (clock_running(from), clock_running(to)) {
+        (true, false) => sla.paused_at = Some(now),
+        (false, true) => {
+            if let Some(paused_at) = sla.paused_at.take() {
+                let stopped = schedule.elapsed(paused_at, now);
+                sla.paused_seconds += stopped.num_seconds();
+                if sla.first_responded_at.is_none() {
+                    sla.first_response_due_at = sla.first_response_due_at.and_then(|due| schedule.due_at(due, stopped));
+                }
+                sla.resolution_due_at = sla.resolution_due_at.and_then(|due| schedule.due_at(due, stopped));
+            }
+        }
+        _ => {}
+    }
+
+    match to {
+        TicketStatus::Resolved | TicketStatus::Closed => {
+            sla.resolved_at.get_or_insert(now);
+        }
+        _ => sla.resolved_at = None,
+    }
+
+    sla
+}
+
+/// The schedule the ticket's current policy counts in
+async fn policy_schedule(
+    tx: &mut Transaction<'_, Postgres>,
+    policy_id: Option<Uuid>,
+) -> std::result::Result<Schedule, sqlx::Error> {
+    let Some(policy_id) = policy_id else {
+        return Ok(Schedule::AlwaysOpen);
+    };
+
+    let calendar_id: Option<Uuid> = sqlx::query_scalar("SELECT calendar_id FROM sla_policies WHERE id = $1")
+        .bind(policy_id)
+        .fetch_optional(&mut **tx)
+        .await?
+        .flatten();
+
+    business_calendar_service::schedule(tx, calendar_id).await
+}
+
+async fn save(
+    tx: &mut Transaction<'_, Postgres>,
+    ticket: Ticket,
+    sla: TicketSla,
+) -> std::result::Result<Ticket, sqlx::Error> {
+    if sla == ticket.sla {
+        return Ok(ticket);
+    }
+
+    sqlx::query_as::<_, Ticket>(&format!(
+        r#"
+        UPDATE tickets
+        SET sla_policy_id = $1,
+            first_response_due_at = $2, first_responded_at = $3, first_response_breached_at = $4,
+            resolution_due_at = $5, resolved_at = $6, resolution_breached_at = $7,
+            sla_paused_at = $8, sla_paused_seconds = $9
+        WHERE id = $10
+        RETURNING {TICKET_COLUMNS}
+        "#
+    ))
+    .bind(sla.policy_id)
+    .bind(sla.first_response_due_at)
+    .bind(sla.first_responded_at)
+    .bind(sla.first_response_breached_at)
+    .bind(sla.resolution_due_at)
+    .bind(sla.resolved_at)
+    .bind(sla.resolution_breached_at)
+    .bind(sla.paused_at)
+    .bind(sla.paused_seconds)
+    .bind(ticket.id)
+    .fetch_one(&mut **tx)
+    .await
+}
+
+/// Set a new ticket's due times from the matching policy
+pub async fn start_clock(
+    tx: &mut Transaction<'_, Postgres>,
+    ticket: Ticket,
+) -> std::result::Result<Ticket, sqlx::Error> {
+    let now = Utc::now();
+    let policy = find_policy(tx, &ticket.priority, ticket.customer_email.as_deref()).await?;
+    let schedule = business_calendar_service::schedule(tx, policy.as_ref().and_then(|p| p.calendar_id)).await?;
+    let sla = retarget(&ticket.sla, ticket.created_at.unwrap_or(now), policy.as_ref(), &schedule, now);
+
+    save(tx, ticket, sla).await
+}
+
+/// Keep the SLA in step with a change from `before` to `after`: the clock
+/// follows the status, and a new priority brings new targets
+pub async fn track_change(
+    tx: &mut Transaction<'_, Postgres>,
+    before: &Ticket,
+    after: Ticket,
+) -> std::result::Result<Ticket, sqlx::Error> {
+    let now = Utc::now();
+    let schedule = policy_schedule(tx, after.sla.policy_id).await?;
+    let mut sla = follow_status(&after.sla, before.status, after.status, &schedule, now);
+
+    if before.priority != after.priority {
+        let policy = find_policy(tx, &after.priority, after.customer_email.as_deref()).await?;
+        let schedule = business_calendar_service::schedule(tx, policy.as_ref().and_then(|p| p.calendar_id)).await?;
+        sla = retarget(&sla, after.created_at.unwrap_or(now), policy.as_ref(), &schedule, now);
+    }
+
+    save(tx, after, sla).await
+}
+
+/// Staff answered the customer; the first time counts for the SLA
+pub async fn record_first_response(pool: &PgPool, ticket_id: Uuid) -> std::result::Result<(), sqlx::Error> {
+    sqlx::query("UPDATE tickets SET first_responded_at = now() WHERE id = $1 AND first_responded_at IS NULL")
+        .bind(ticket_id)
+        .execute(pool)
+        .await?;
+
+    Ok(())
+}
+
+// ---------------------------------------------------------------------------
+// Breach checker
+// ---------------------------------------------------------------------------
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SlaTarget {
+    FirstResponse,
+    Resolution,
+}
+
+impl SlaTarget {
+    fn label(self) -> &'static str {
+        match self {
+            SlaTarget::FirstResponse => "first response",
+            SlaTarget::Resolution => "resolution",
+        }
+    }
+}
+
+#[derive(Debug, sqlx::FromRow)]
+struct BreachedTicket {
+    id: Uuid,
+    subject: String,
+    assigned_to: Option<Uuid>,
+}
+
+/// Mark running tickets past a due time as breached. Each breach is returned
+/// exactly once, even with several instances checking.
+async fn mark_breaches(pool: &PgPool, target: SlaTarget) -> std::result::Result<Vec<BreachedTicket>, sqlx::Error> {
+    let sql = match target {
+        SlaTarget::FirstResponse => {
+            r#"
+            UPDATE tickets SET first_response_breached_at = now()
+            WHERE first_responded_at IS NULL AND first_response_breached_at IS NULL
+              AND first_response_due_at <= now() AND sla_paused_at IS NULL
+            RETURNING id, subject, assigned_to
+            "#
+        }
+        SlaTarget::Resolution => {
+            r#"
+            UPDATE tickets SET resolution_breached_at = now()
+            WHERE resolved_at IS NULL AND resolution_breached_at IS NULL
+              AND resolution_due_at <= now() AND sla_paused_at IS NULL
+            RETURNING id, subject, assigned_to
+            "#
+        }
+    };
+
+    sqlx::query_as::<_, BreachedTicket>(sql).fetch_all(pool).await
+}
+
+/// Tell the assignee and everyone who oversees all tickets
+async fn notify_breach(pool: &PgPool, ticket: &BreachedTicket, target: SlaTarget) -> std::result::Result<(), sqlx::Error> {
+    let mut recipients = sqlx::query_scalar::<_, Uuid>(
+        r#"
+        SELECT u.id FROM users u
+        JOIN role_permissions rp ON rp.role = u.role
+        WHERE rp.permission = $1 AND COALESCE(u.is_active, true)
+        "#,
+    )
+    .bind(Permission::TicketsReadAll.as_str())
+    .fetch_all(pool)
+    .await?;
+    recipients.extend(ticket.assigned_to);
+    recipients.sort();
+    recipients.dedup();
+
+    let message = format!("⏰ SLA breached ({}): {}", target.label(), ticket.subject);
+    for user_id in recipients {
+        notify_user(pool, user_id, &message, Some(format!("/dashboard/ticket/{}", ticket.id))).await?;
+    }
+
+    Ok(())
+}
+
+/// One pass of the checker
+pub async fn check_breaches(pool: &PgPool) -> std::result::Result<usize, sqlx::Error> {
+    let mut breaches = 0;
+
+    for target in [SlaTarget::FirstResponse, SlaTarget::Resolution] {
+        for ticket in mark_breaches(pool, target).await? {
+            tracing::warn!("⏰ Ticket {} breached its {} target", ticket.id, target.label());
+            if let Err(err) = notify_breach(pool, &ticket, target).await {
+                tracing::error!("Failed to send SLA breach notifications: {:?}", err);
+            }
+            breaches += 1;
+        }
+    }
+
+    Ok(breaches)
+}
+
+/// Check for breaches every `every` in the background, for the life of the process
+pub fn spawn_breach_checker(pool: PgPool, every: StdDuration) {
+    tokio::spawn(async move {
+        let mut interval = tokio::time::interval(every);
+        interval.set_missed_tick_behavior(tokio::time::MissedTickBehavior::Delay);
+
+        loop {
+            interval.tick().await;
+            if let Err(err) = check_breaches(&pool).await {
+                tracing::error!("SLA breach check failed: {:?}", err);
+            }
+        }
+    });
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn policy() -> SlaPolicy {
+        SlaPolicy {
+            id: Uuid::new_v4(),
+            name: "High".into(),
+            priority: TicketPriority::High,
+            customer_domain: None,
+            first_response_minutes: 60,
+            resolution_minutes: 480,
+            calendar_id: None,
+            created_at: Utc::now(),
+            updated_at: Utc::now(),
+        }
+    }
+
+    #[test]
+    fn domains_are_normalized() {
+        assert_eq!(normalize_domain(" @Example.COM ").unwrap(), "example.com");
+        assert!(normalize_domain("localhost").is_err());
+        assert!(normalize_domain("exa mple.com").is_err());
+        assert!(normalize_domain(".example.com").is_err());
+        assert_eq!(customer_domain("Jane@Sub.Example.com").as_deref(), Some("sub.example.com"));
+        assert_eq!(customer_domain("nobody"), None);
+    }
+
+    #[test]
+    fn targets_count_from_creation_plus_paused_time() {
+        let created = Utc::now() - Duration::hours(2);
+        let policy = policy();
+        let sla = TicketSla { paused_seconds: 600, ..Default::default() };
+
+        let sla = retarget(&sla, created, Some(&policy), &Schedule::AlwaysOpen, Utc::now());
+        assert_eq!(sla.policy_id, Some(policy.id));
+        assert_eq!(sla.first_response_due_at, Some(created + Duration::minutes(70)));
+        assert_eq!(sla.resolution_due_at, Some(created + Duration::minutes(490)));
+
+        let none = retarget(&sla, created, None, &Schedule::AlwaysOpen, Utc::now());
+        assert_eq!(none, TicketSla { paused_seconds: 600, ..Default::default() });
+    }
+
+    #[test]
+    fn business_hours_targets_skip_closed_time() {
+        use chrono::{NaiveTime, TimeZone, Weekday};
+
+        use crate::utils::business_time::{Shift, WeeklySchedule};
+
+        let hours: Vec<Shift> = [Weekday::Mon, Weekday::Tue, Weekday::Wed, Weekday::Thu, Weekday::Fri]
+            .into_iter()
+            .map(|weekday| Shift {
+                weekday,
+                opens_at: NaiveTime::from_hms_opt(9, 0, 0).unwrap(),
+                closes_at: NaiveTime::from_hms_opt(17, 0, 0).unwrap(),
+            })
+            .collect();
+        let office = Schedule::Weekly(Box::new(WeeklySchedule::new(chrono_tz::UTC, &hours, [])));
+        let at = |day: u32, hour: u32, minute: u32| Utc.with_ymd_and_hms(2026, 1, day, hour, minute, 0).unwrap();
+
+        // Friday afternoon: an hour left that day, the rest on Monday
+        let created = at(2, 16, 0);
+        let sla = retarget(&TicketSla::default(), created, Some(&policy()), &office, created);
+        assert_eq!(sla.first_response_due_at, Some(at(2, 17, 0)));
+        assert_eq!(sla.resolution_due_at, Some(at(5, 16, 0)));
+
+        // Waiting on the customer over the weekend only stops 90 open minutes
+        let paused = follow_status(&sla, TicketStatus::Open, TicketStatus::Pending, &office, at(2, 16, 30));
+        let resumed = follow_status(&paused, TicketStatus::Pending, TicketStatus::Open, &office, at(5, 10, 0));
+        assert_eq!(resumed.paused_seconds, 5400);
+        assert_eq!(resumed.first_response_due_at, Some(at(5, 10, 30)));
+        assert_eq!(resumed.resolution_due_at, Some(at(6, 9, 30)));
+    }
+
+    #[test]
+    fn pending_stops_the_clock_and_pushes_open_targets_back() {
+        let now = Utc::now();
+        let due = now + Duration::hours(1);
+        let sla = TicketSla {
+            first_response_due_at: Some(due),
+            resolution_due_at: Some(due),
+            ..Default::default()
+        };
+
+        let paused = follow_status(&sla, TicketStatus::Open, TicketStatus::Pending, &Schedule::AlwaysOpen, now);
+        assert_eq!(paused.paused_at, Some(now));
+
+        let later = now + Duration::minutes(30);
+        let resumed = follow_status(&paused, TicketStatus::Pending, TicketStatus::Open, &Schedule::AlwaysOpen, later);
+        assert_eq!(resumed.paused_at, None);
+        assert_eq!(resumed.paused_seconds, 1800);
+        assert_eq!(resumed.first_response_due_at, Some(due + Duration::minutes(30)));
+        assert_eq!(resumed.resolution_due_at, Some(due + Duration::minutes(30)));
+
+        // Moving between running states changes nothing
+        assert_eq!(follow_status(&resumed, TicketStatus::Open, TicketStatus::OnHold, &Schedule::AlwaysOpen, later), resumed);
+    }
+
+    #[test]
+    fn resolving_meets_the_target_until_reopened() {
+        let now = Utc::now();
+        let sla = TicketSla { resolution_due_at: Some(now), ..Default::default() };
+
+        let resolved = follow_status(&sla, TicketStatus::Open, TicketStatus::Resolved, &Schedule::AlwaysOpen, now);
+        assert_eq!(resolved.resolved_at, Some(now));
+        let closed = follow_status(&resolved, TicketStatus::Resolved, TicketStatus::Closed, &Schedule::AlwaysOpen, now + Duration::hours(1));
+        assert_eq!(closed.resolved_at, Some(now));
+
+        let reopened = follow_status(&resolved, TicketStatus::Resolved, TicketStatus::Open, &Schedule::AlwaysOpen, now + Duration::hours(1));
+        assert_eq!(reopened.resolved_at, None);
+        assert_eq!(reopened.resolution_due_at, Some(now + Duration::hours(1)));
+    }
+}
@@ -0,0 +1,210 @@
+use sqlx::PgPool;
+use uuid::Uuid;
+
+use crate::{
+    models::{
+        permission::Permission,
+        team::{Team, TeamDetail, TeamMember},
+    },
+    services::notification_services::notify_user,
+};
+
+#[derive(Debug, thiserror::Error)]
+pub enum TeamError {
+    #[error("Database error: {0}")]
+    Database(#[from] sqlx::Error),
+    #[error("Team not found")]
+    NotFound,
+    #[error("A team with this name already exists")]
+    AlreadyExists,
+    #[error("User not found")]
+    UnknownUser,
+    #[error("Only users who can work assigned tickets can join a team")]
+    NotStaff,
+}
+
+pub type Result<T> = std::result::Result<T, TeamError>;
+
+/// Everything an admin sets on a team
+#[derive(Debug, Clone)]
+pub struct TeamInput {
+    pub name: String,
+    pub description: Option<String>,
+}
+
+fn conflict_or(err: sqlx::Error) -> TeamError {
+    match err.as_database_error() {
+        Some(db) if db.is_unique_violation() => TeamError::AlreadyExists,
+        _ => err.into(),
+    }
+}
+
+pub async fn list_teams(pool: &PgPool) -> Result<Vec<Team>> {
+    let teams = sqlx::query_as::<_, Team>("SELECT * FROM teams ORDER BY name")
+        .fetch_all(pool)
+        .await?;
+
+    Ok(teams)
+}
+
+/// A team and its members, leads first
+pub async fn get_team(pool: &PgPool, team_id: Uuid) -> Result<TeamDetail> {
+    let team = sqlx::query_as::<_, Team>("SELECT * FROM teams WHERE id = $1")
+        .bind(team_id)
+        .fetch_optional(pool)
+        .await?
+        .ok_or(TeamError::NotFound)?;
+
+    let members = sqlx::query_as::<_, TeamMember>(
+        r#"
+        SELECT u.id AS user_id, u.name, u.email, tm.is_lead, tm.added_at
+        FROM team_members tm
+        JOIN users u ON u.id = tm.user_id
+        WHERE tm.team_id = $1
+        ORDER BY tm.is_lead DESC, u.name
+        "#,
+    )
+    .bind(team_id)
+    .fetch_all(pool)
+    .await?;
+
+    Ok(TeamDetail { team, members })
+}
+
+pub async fn create_team(pool: &PgPool, input: &TeamInput) -> Result<Team> {
+    sqlx::query_as::<_, Team>(
+        "INSERT INTO teams (name, description) VALUES ($1, $2) RETURNING *",
+    )
+    .bind(input.name.trim())
+    .bind(&input.description)
+    .fetch_one(pool)
+    .await
+    .map_err(conflict_or)
+}
+
+pub async fn update_team(pool: &PgPool, team_id: Uuid, input: &TeamInput) -> Result<Team> {
+    sqlx::query_as::<_, Team>(
+        r#"
+        UPDATE teams
+        SET name = $1, description = $2, updated_at = now()
+        WHERE id = $3
+        RETURNING *
+        "#,
+    )
+    .bind(input.name.trim())
+    .bind(&input.description)
+    .bind(team_id)
+    .fetch_optional(pool)
+    .await
+    .map_err(conflict_or)?
+    .ok_or(TeamError::NotFound)
+}
+
+/// Remove a team. Its tickets leave the queue but keep their assignees.
+pub async fn delete_team(pool: &PgPool, team_id: Uuid) -> Result<()> {
+    let deleted = sqlx::query("DELETE FROM teams WHERE id = $1")
+        .bind(team_id)
+        .execute(pool)
+        .await?;
+
+    if deleted.rows_affected() == 0 {
+        return Err(TeamError::NotFound);
+    }
+
+    Ok(())
+}
+
+/// Add `user_id` to the team, or change whether they lead it
+pub async fn save_member(pool: &PgPool, team_id: Uuid, user_id: Uuid, is_lead: bool) -> Result<()> {
+    let staff = sqlx::query_scalar::<_, bool>(
+        r#"
+        SELECT EXISTS (
+            SELECT 1 FROM role_permissions rp
+            WHERE rp.role = u.role AND rp.permission = $2
+        )
+        FROM users u
+        WHERE u.id = $1
+        "#,
+    )
+    .bind(user_id)
+    .bind(Permission::TicketsReadAssigned.as_str())
+    .fetch_optional(pool)
+    .await?;
+
+    match staff {
+        None => return Err(TeamError::UnknownUser),
+        Some(false) => return Err(TeamError::NotStaff),
+        Some(true) => {}
+    }
+
+    sqlx::query(
+        r#"
+        INSERT INTO team_members (team_id, user_id, is_lead)
+        VALUES ($1, $2, $3)
+        ON CONFLICT (team_id, user_id) DO UPDATE SET is_lead = EXCLUDED.is_lead
+        "#,
+    )
+    .bind(team_id)
+    .bind(user_id)
+    .bind(is_lead)
+    .execute(pool)
+    .await
+    .map_err(|err| match err.as_database_error() {
+        Some(db) if db.is_foreign_key_violation() => TeamError::NotFound,
+        _ => err.into(),
+    })?;
+
+    Ok(())
+}
+
+pub async fn remove_member(pool: &PgPool, team_id: Uuid, user_id: Uuid) -> Result<()> {
+    let deleted = sqlx::query("DELETE FROM team_members WHERE team_id = $1 AND user_id = $2")
+        .bind(team_id)
+        .bind(user_id)
+        .execute(pool)
+        .await?;
+
+    if deleted.rows_affected() == 0 {
+        return Err(TeamError::NotFound);
+    }
+
+    Ok(())
+}
+
+/// Whether `user_id` is a member of the team, and if so whether they lead it
+pub async fn membership(pool: &PgPool, team_id: Uuid, user_id: Uuid) -> Result<Option<bool>> {
+    let is_lead = sqlx::query_scalar::<_, bool>(
+        "SELECT is_lead FROM team_members WHERE team_id = $1 AND user_id = $2",
+    )
+    .bind(team_id)
+    .bind(user_id)
+    .fetch_optional(pool)
+    .await?;
+
+    Ok(is_lead)
+}
+
+/// Notify every active member of the team
+pub async fn notify_members(
+    pool: &PgPool,
+    team_id: Uuid,
+    message: &str,
+    link: Option<String>,
+) -> Result<()> {
+    let members = sqlx::query_scalar::<_, Uuid>(
+        r#"
+        SELECT u.id FROM team_members tm
+        JOIN users u ON u.id = tm.user_id
+        WHERE tm.team_id = $1 AND COALESCE(u.is_active, true)
+        "#,
+    )
+    .bind(team_id)
+    .fetch_all(pool)
+    .await?;
+
+    for user_id in members {
+        notify_user(pool, user_id, message, link.clone()).await?;
+    }
+
+    Ok(())
+}
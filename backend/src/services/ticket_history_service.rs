@@ -0,0 +1,166 @@
+use sqlx::{PgPool, Postgres, QueryBuilder, Transaction};
+use uuid::Uuid;
+
+use crate::models::{
+    ticket::Ticket,
+    ticket_event::{TicketEvent, TicketField},
+};
+
+/// A field that differs between two versions of a ticket
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct FieldChange {
+    pub field: TicketField,
+    pub old_value: Option<String>,
+    pub new_value: Option<String>,
+}
+
+/// Every recorded field that differs between `before` and `after`
+pub fn changes_between(before: &Ticket, after: &Ticket) -> Vec<FieldChange> {
+    fn text(value: &str) -> Option<String> {
+        (!value.is_empty()).then(|| value.to_string())
+    }
+
+    let fields = [
+        (
+            TicketField::Status,
+            Some(before.status.as_str().to_string()),
+            Some(after.status.as_str().to_string()),
+        ),
+        (
+            TicketField::Priority,
+            Some(before.priority.as_str().to_string()),
+            Some(after.priority.as_str().to_string()),
+        ),
+        (
+            TicketField::Assignee,
+            before.assigned_to.map(|id| id.to_string()),
+            after.assigned_to.map(|id| id.to_string()),
+        ),
+        (
+            TicketField::Team,
+            before.team_id.map(|id| id.to_string()),
+            after.team_id.map(|id| id.to_string()),
+        ),
+        (TicketField::Subject, text(&before.subject), text(&after.subject)),
+        (TicketField::Description, text(&before.description), text(&after.description)),
+        (
+            TicketField::MergedInto,
+            before.merged_into_id.map(|id| id.to_string()),
+            after.merged_into_id.map(|id| id.to_string()),
+        ),
+    ];
+
+    fields
+        .into_iter()
+        .filter(|(_, old_value, new_value)| old_value != new_value)
+        .map(|(field, old_value, new_value)| FieldChange { field, old_value, new_value })
+        .collect()
+}
+
+/// Record `changes` made by `actor` as part of `tx`, so history and ticket
+/// are saved (or rolled back) together
+pub async fn record(
+    tx: &mut Transaction<'_, Postgres>,
+    ticket_id: Uuid,
+    actor: Option<Uuid>,
+    changes: &[FieldChange],
+) -> Result<(), sqlx::Error> {
+    if changes.is_empty() {
+        return Ok(());
+    }
+
+    let mut qb: QueryBuilder<Postgres> =
+        QueryBuilder::new("INSERT INTO ticket_events (ticket_id, actor_id, field, old_value, new_value) ");
+    qb.push_values(changes, |mut row, change| {
+        row.push_bind(ticket_id)
+            .push_bind(actor)
+            .push_bind(change.field.as_str())
+            .push_bind(&change.old_value)
+            .push_bind(&change.new_value);
+    });
+    qb.build().execute(&mut **tx).await?;
+
+    Ok(())
+}
+
+/// A ticket's history, oldest first. Fields changed together share a
+/// timestamp and are listed in a fixed order.
+pub async fn list_for_ticket(pool: &PgPool, ticket_id: Uuid) -> Result<Vec<TicketEvent>, sqlx::Error> {
+    sqlx::query_as::<_, TicketEvent>(
+        r#"
+        SELECT e.id, e.ticket_id, e.actor_id, u.name AS actor_name,
+               e.field, e.old_value, e.new_value, e.created_at
+        FROM ticket_events e
+        LEFT JOIN users u ON u.id = e.actor_id
+        WHERE e.ticket_id = $1
+        ORDER BY e.created_at,
+                 array_position(
+                     ARRAY['status', 'priority', 'assignee', 'team', 'tags', 'subject', 'description',
+                           'merged_into', 'merged_from', 'split_from', 'split_into'],
+                     e.field
+                 )
+        "#,
+    )
+    .bind(ticket_id)
+    .fetch_all(pool)
+    .await
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::models::ticket::TicketStatus;
+
+    fn ticket() -> Ticket {
+        Ticket { status: TicketStatus::New, ..Ticket::for_test() }
+    }
+
+    #[test]
+    fn only_changed_fields_are_recorded() {
+        let before = ticket();
+        assert!(changes_between(&before, &before.clone()).is_empty());
+
+        let agent = Uuid::new_v4();
+        let after = Ticket {
+            status: TicketStatus::Open,
+            assigned_to: Some(agent),
+            description: "Smoke everywhere".into(),
+            ..before.clone()
+        };
+
+        assert_eq!(
+            changes_between(&before, &after),
+            vec![
+                FieldChange {
+                    field: TicketField::Status,
+                    old_value: Some("New".into()),
+                    new_value: Some("Open".into()),
+                },
+                FieldChange {
+                    field: TicketField::Assignee,
+                    old_value: None,
+                    new_value: Some(agent.to_string()),
+                },
+                FieldChange {
+                    field: TicketField::Description,
+                    old_value: None,
+                    new_value: Some("Smoke everywhere".into()),
+                },
+            ]
+        );
+    }
+
+    #[test]
+    fn merging_records_the_pointer() {
+        let before = ticket();
+        let target = Uuid::new_v4();
+        let after = Ticket {
+            status: TicketStatus::Closed,
+            merged_into_id: Some(target),
+            ..before.clone()
+        };
+
+        let fields: Vec<_> = changes_between(&before, &after).into_iter().map(|c| c.field).collect();
+        assert_eq!(fields, vec![TicketField::Status, TicketField::MergedInto]);
+    }
+}
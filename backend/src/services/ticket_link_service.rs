@@ -0,0 +1,190 @@
+use sqlx::{PgConnection, PgPool};
+use uuid::Uuid;
+
+use crate::models::ticket_link::{LinkKind, TicketLinkRow};
+
+#[derive(Debug, thiserror::Error)]
+pub enum LinkError {
+    #[error("Database error: {0}")]
+    Database(#[from] sqlx::Error),
+    #[error("Link not found")]
+    NotFound,
+    #[error("A ticket can't be linked to itself")]
+    SelfLink,
+    #[error("The tickets are already linked this way")]
+    AlreadyLinked,
+    #[error("The ticket already has a parent")]
+    AlreadyHasParent,
+    #[error("The link would make a loop")]
+    Cycle,
+}
+
+pub type Result<T> = std::result::Result<T, LinkError>;
+
+/// Parent and blocked-by links form chains that must not loop back; new ones
+/// are checked one at a time
+const LINK_LOCK: i64 = 0x6c69_6e6b_7321;
+
+/// The row storing `ticket_id` has `kind` `other_id`: the kind read from the
+/// stored side and the ticket on that side first
+pub fn stored(kind: LinkKind, ticket_id: Uuid, other_id: Uuid) -> (LinkKind, Uuid, Uuid) {
+    match kind {
+        LinkKind::Related => (kind, ticket_id.min(other_id), ticket_id.max(other_id)),
+        kind if kind.is_stored() => (kind, ticket_id, other_id),
+        kind => (kind.inverse(), other_id, ticket_id),
+    }
+}
+
+/// Links in both directions, oldest first
+pub async fn links_for(pool: &PgPool, ticket_id: Uuid) -> Result<Vec<TicketLinkRow>> {
+    let links = sqlx::query_as::<_, TicketLinkRow>(
+        r#"
+        SELECT * FROM ticket_links
+        WHERE ticket_id = $1 OR linked_ticket_id = $1
+        ORDER BY created_at, id
+        "#,
+    )
+    .bind(ticket_id)
+    .fetch_all(pool)
+    .await?;
+
+    Ok(links)
+}
+
+/// Link `ticket_id` to `other_id`, e.g. `LinkKind::Child` makes `other_id`
+/// one of its children
+pub async fn create_link(
+    pool: &PgPool,
+    ticket_id: Uuid,
+    kind: LinkKind,
+    other_id: Uuid,
+    actor: Option<Uuid>,
+) -> Result<TicketLinkRow> {
+    if ticket_id == other_id {
+        return Err(LinkError::SelfLink);
+    }
+    let (kind, from, to) = stored(kind, ticket_id, other_id);
+
+    let mut tx = pool.begin().await?;
+
+    if matches!(kind, LinkKind::Parent | LinkKind::BlockedBy) {
+        sqlx::query("SELECT pg_advisory_xact_lock($1)")
+            .bind(LINK_LOCK)
+            .execute(&mut *tx)
+            .await?;
+
+        // `from` -> `to` loops if `from` is already reached by following `to`'s links
+        let loops = sqlx::query_scalar::<_, bool>(
+            r#"
+            WITH RECURSIVE chain(id) AS (
+                SELECT $2::uuid
+                UNION
+                SELECT l.linked_ticket_id FROM ticket_links l
+                JOIN chain c ON l.ticket_id = c.id
+                WHERE l.kind = $3
+            )
+            SELECT EXISTS (SELECT 1 FROM chain WHERE id = $1)
+            "#,
+        )
+        .bind(from)
+        .bind(to)
+        .bind(kind.as_str())
+        .fetch_one(&mut *tx)
+        .await?;
+        if loops {
+            return Err(LinkError::Cycle);
+        }
+    }
+
+    let link = sqlx::query_as::<_, TicketLinkRow>(
+        r#"
+        INSERT INTO ticket_links (ticket_id, linked_ticket_id, kind, created_by)
+        VALUES ($1, $2, $3, $4)
+        RETURNING *
+        "#,
+    )
+    .bind(from)
+    .bind(to)
+    .bind(kind.as_str())
+    .bind(actor)
+    .fetch_one(&mut *tx)
+    .await
+    .map_err(|err| match err.as_database_error() {
+        Some(db) if db.constraint() == Some("idx_ticket_links_one_parent") => LinkError::AlreadyHasParent,
+        Some(db) if db.is_unique_violation() => LinkError::AlreadyLinked,
+        Some(db) if db.is_foreign_key_violation() => LinkError::NotFound,
+        _ => err.into(),
+    })?;
+
+    tx.commit().await?;
+    Ok(link)
+}
+
+/// Remove a link at either end of which is `ticket_id`
+pub async fn delete_link(pool: &PgPool, ticket_id: Uuid, link_id: Uuid) -> Result<()> {
+    let deleted = sqlx::query(
+        "DELETE FROM ticket_links WHERE id = $1 AND $2 IN (ticket_id, linked_ticket_id)",
+    )
+    .bind(link_id)
+    .bind(ticket_id)
+    .execute(pool)
+    .await?;
+
+    if deleted.rows_affected() == 0 {
+        return Err(LinkError::NotFound);
+    }
+
+    Ok(())
+}
+
+/// The ticket's children, their children and so on
+pub async fn descendants(conn: &mut PgConnection, ticket_id: Uuid) -> std::result::Result<Vec<Uuid>, sqlx::Error> {
+    sqlx::query_scalar(
+        r#"
+        WITH RECURSIVE tree(id) AS (
+            SELECT ticket_id FROM ticket_links WHERE linked_ticket_id = $1 AND kind = 'parent'
+            UNION
+            SELECT l.ticket_id FROM ticket_links l
+            JOIN tree t ON l.linked_ticket_id = t.id
+            WHERE l.kind = 'parent'
+        )
+        SELECT id FROM tree
+        "#,
+    )
+    .bind(ticket_id)
+    .fetch_all(conn)
+    .await
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn links_are_stored_from_one_side() {
+        let (a, b) = (Uuid::new_v4(), Uuid::new_v4());
+
+        assert_eq!(stored(LinkKind::Parent, a, b), (LinkKind::Parent, a, b));
+        assert_eq!(stored(LinkKind::Child, a, b), (LinkKind::Parent, b, a));
+        assert_eq!(stored(LinkKind::Blocks, a, b), (LinkKind::BlockedBy, b, a));
+        assert_eq!(stored(LinkKind::DuplicatedBy, a, b), (LinkKind::DuplicateOf, b, a));
+        assert_eq!(stored(LinkKind::Related, a, b), stored(LinkKind::Related, b, a));
+    }
+
+    #[test]
+    fn rows_read_from_either_end() {
+        let (child, parent) = (Uuid::new_v4(), Uuid::new_v4());
+        let row = TicketLinkRow {
+            id: Uuid::new_v4(),
+            ticket_id: child,
+            linked_ticket_id: parent,
+            kind: "parent".into(),
+            created_by: None,
+            created_at: chrono::Utc::now(),
+        };
+
+        assert_eq!(row.seen_from(child), Some((LinkKind::Parent, parent)));
+        assert_eq!(row.seen_from(parent), Some((LinkKind::Child, child)));
+        assert_eq!(row.seen_from(Uuid::new_v4()), None);
+    }
+}
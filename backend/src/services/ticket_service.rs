@@ -1,85 +1,223 @@
+use std::collections::{HashMap, HashSet};
+use std::str::FromStr;
+
+use crate::models::permission::Permission;
 use crate::models::ticket::{
     CreateTicketInput, Ticket, TicketPriority, TicketStatus, UpdateTicketInput,
 };
-use chrono::Utc;
-use sqlx::PgPool;
+use crate::models::ticket_event::TicketField;
+use crate::services::{
+    custom_field_service::{self, CustomFieldError},
+    sla_service, ticket_link_service,
+    ticket_history_service::{self, changes_between, FieldChange},
+};
+use base64::{engine::general_purpose::URL_SAFE_NO_PAD, Engine};
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use sqlx::{PgConnection, PgPool, Postgres, QueryBuilder, Transaction};
 use uuid::Uuid;
 
-/// Create a new ticket
-pub async fn create_ticket(
-    pool: &PgPool,
-    input: CreateTicketInput,
-    agent_id: Uuid,
-    user_id: Uuid,
-) -> Result<Ticket, sqlx::Error> {
-    let now = Utc::now();
-    let ticket = sqlx::query_as_unchecked!(
-        Ticket,
+#[derive(Debug, thiserror::Error)]
+pub enum TicketError {
+    #[error("Database error: {0}")]
+    Database(#[from] sqlx::Error),
+    #[error("Ticket not found")]
+    NotFound,
+    #[error("A {from:?} ticket can't be moved to {to:?}")]
+    InvalidTransition { from: TicketStatus, to: TicketStatus },
+    #[error("Team not found")]
+    UnknownTeam,
+    #[error("Fill in {} before closing the ticket", .0.join(", "))]
+    MissingRequiredFields(Vec<String>),
+    #[error(transparent)]
+    Field(#[from] CustomFieldError),
+    #[error("A ticket can't be merged into itself")]
+    SameTicket,
+    #[error("The ticket was already merged into another")]
+    AlreadyMerged,
+    #[error("Tickets can't be merged into a closed ticket")]
+    MergeIntoClosed,
+    #[error("Some messages don't belong to the ticket")]
+    UnknownMessages,
+    #[error("Not allowed to close child ticket {0}")]
+    ChildNotAllowed(Uuid),
+    #[error("Assignee not found")]
+    UnknownAssignee,
+    #[error("Only agents, and for a team's ticket only its members, can be assigned")]
+    NotAssignable,
+}
+
+/// The only foreign key callers choose freely is the team
+fn unknown_team_or(err: sqlx::Error) -> TicketError {
+    match err.as_database_error() {
+        Some(db) if db.is_foreign_key_violation() => TicketError::UnknownTeam,
+        _ => err.into(),
+    }
+}
+
+/// Columns for `Ticket`, SLA included; `description` is optional when
+/// creating a ticket but not in the model
+pub(crate) const TICKET_COLUMNS: &str = "id, subject, COALESCE(description, '') AS description, status, priority, \
+     assigned_to, created_at, updated_at, customer_email, user_id, team_id, merged_into_id, \
+     sla_policy_id, first_response_due_at, first_responded_at, first_response_breached_at, \
+     resolution_due_at, resolved_at, resolution_breached_at, sla_paused_at, sla_paused_seconds";
+
+/// Open a new ticket and start its SLA clock
+pub async fn create_ticket(pool: &PgPool, input: CreateTicketInput) -> Result<Ticket, TicketError> {
+    let mut tx = pool.begin().await?;
+
+    let ticket = sqlx::query_as::<_, Ticket>(&format!(
         r#"
-        INSERT INTO tickets (
-            id, subject, description, status, priority,
-            assigned_to, created_at, updated_at,
-            customer_email, user_id
-        )
-        VALUES (
-            $1, $2, $3, $4, $5,
-            $6, $7, $7,
-            $8, $9
-        )
-        RETURNING id, subject, description, status as "status: _", priority as "priority: _",
-                  assigned_to, created_at, updated_at, customer_email, user_id
+        INSERT INTO tickets (subject, description, status, priority, customer_email, team_id)
+        VALUES ($1, $2, $3, $4, $5, $6)
+        RETURNING {TICKET_COLUMNS}
+        "#
+    ))
+    .bind(&input.subject)
+    .bind(&input.description)
+    .bind(TicketStatus::New)
+    .bind(&input.priority)
+    .bind(&input.customer_email)
+    .bind(input.team_id)
+    .fetch_one(&mut *tx)
+    .await
+    .map_err(unknown_team_or)?;
+
+    add_tags(&mut tx, ticket.id, &input.tags).await?;
+    custom_field_service::set_values(&mut tx, ticket.id, &input.custom_fields).await?;
+    let ticket = sla_service::start_clock(&mut tx, ticket).await?;
+
+    tx.commit().await?;
+    Ok(ticket)
+}
+
+/// Tag the ticket with `tags`, creating tag names nobody has used yet.
+/// Names are trimmed; blanks are skipped.
+async fn add_tags(
+    tx: &mut Transaction<'_, Postgres>,
+    ticket_id: Uuid,
+    tags: &[String],
+) -> Result<(), sqlx::Error> {
+    let mut names: Vec<String> = tags
+        .iter()
+        .map(|tag| tag.trim().to_string())
+        .filter(|tag| !tag.is_empty())
+        .collect();
+    names.sort();
+    names.dedup();
+    if names.is_empty() {
+        return Ok(());
+    }
+
+    sqlx::query("INSERT INTO tags (name) SELECT UNNEST($1::text[]) ON CONFLICT (name) DO NOTHING")
+        .bind(&names)
+        .execute(&mut **tx)
+        .await?;
+    sqlx::query(
+        r#"
+        INSERT INTO ticket_tags (ticket_id, tag_id)
+        SELECT $1, id FROM tags WHERE name = ANY($2)
+        ON CONFLICT DO NOTHING
         "#,
-        Uuid::new_v4(),
-        input.subject,
-        input.description,
-        TicketStatus::Open as TicketStatus,
-        input.priority as TicketPriority,
-        agent_id,
-        now,
-        input.customer_email,
-        user_id
     )
-    .fetch_one(pool)
+    .bind(ticket_id)
+    .bind(&names)
+    .execute(&mut **tx)
     .await?;
 
-    Ok(ticket)
+    Ok(())
 }
 
-/// Get all tickets
-pub async fn get_all_tickets(pool: &PgPool) -> Result<Vec<Ticket>, sqlx::Error> {
-    let tickets = sqlx::query_as_unchecked!(
-        Ticket,
+/// Names of the ticket's tags, alphabetically
+pub async fn ticket_tags(
+    conn: &mut PgConnection,
+    ticket_id: Uuid,
+) -> Result<Vec<String>, sqlx::Error> {
+    sqlx::query_scalar(
         r#"
-        SELECT id, subject, description,
-               status as "status: _", priority as "priority: _",
-               assigned_to, created_at, updated_at,
-               customer_email, user_id
-        FROM tickets
-        ORDER BY created_at DESC
-        "#
+        SELECT t.name FROM tags t
+        JOIN ticket_tags tt ON tt.tag_id = t.id
+        WHERE tt.ticket_id = $1
+        ORDER BY t.name
+        "#,
+    )
+    .bind(ticket_id)
+    .fetch_all(conn)
+    .await
+}
+
+/// Replace the ticket's tags, recording the change in its history
+pub async fn set_tags(
+    pool: &PgPool,
+    ticket_id: Uuid,
+    tags: &[String],
+    actor: Option<Uuid>,
+) -> Result<Vec<String>, TicketError> {
+    let mut tx = pool.begin().await?;
+
+    lock_ticket(&mut tx, ticket_id).await?;
+    let before = ticket_tags(&mut tx, ticket_id).await?;
+
+    sqlx::query("DELETE FROM ticket_tags WHERE ticket_id = $1")
+        .bind(ticket_id)
+        .execute(&mut *tx)
+        .await?;
+    add_tags(&mut tx, ticket_id, tags).await?;
+    let after = ticket_tags(&mut tx, ticket_id).await?;
+
+    if before != after {
+        let joined = |tags: &[String]| (!tags.is_empty()).then(|| tags.join(", "));
+        let change = FieldChange {
+            field: TicketField::Tags,
+            old_value: joined(&before),
+            new_value: joined(&after),
+        };
+        ticket_history_service::record(&mut tx, ticket_id, actor, &[change]).await?;
+        sqlx::query("UPDATE tickets SET updated_at = now() WHERE id = $1")
+            .bind(ticket_id)
+            .execute(&mut *tx)
+            .await?;
+    }
+
+    tx.commit().await?;
+    Ok(after)
+}
+
+/// Each ticket's tag names, alphabetically; untagged tickets are left out
+pub async fn tags_for(pool: &PgPool, ticket_ids: &[Uuid]) -> Result<HashMap<Uuid, Vec<String>>, sqlx::Error> {
+    let rows: Vec<(Uuid, String)> = sqlx::query_as(
+        r#"
+        SELECT tt.ticket_id, t.name FROM tags t
+        JOIN ticket_tags tt ON tt.tag_id = t.id
+        WHERE tt.ticket_id = ANY($1)
+        ORDER BY t.name
+        "#,
     )
+    .bind(ticket_ids)
     .fetch_all(pool)
     .await?;
 
-    Ok(tickets)
+    let mut tags: HashMap<Uuid, Vec<String>> = HashMap::new();
+    for (ticket_id, name) in rows {
+        tags.entry(ticket_id).or_default().push(name);
+    }
+
+    Ok(tags)
+}
+
+/// Get all tickets
+pub async fn get_all_tickets(pool: &PgPool) -> Result<Vec<Ticket>, sqlx::Error> {
+    sqlx::query_as::<_, Ticket>(&format!("SELECT {TICKET_COLUMNS} FROM tickets ORDER BY created_at DESC"))
+        .fetch_all(pool)
+        .await
 }
 
 /// Get a ticket by its ID
 pub async fn get_ticket_by_id(pool: &PgPool, ticket_id: Uuid) -> Result<Ticket, sqlx::Error> {
-    sqlx::query_as_unchecked!(
-        Ticket,
-        r#"
-        SELECT id, subject, description,
-               status as "status: _", priority as "priority: _",
-               assigned_to, created_at, updated_at,
-               customer_email, user_id
-        FROM tickets
-        WHERE id = $1
-        "#,
-        ticket_id
-    )
-    .fetch_one(pool)
-    .await
+    sqlx::query_as::<_, Ticket>(&format!("SELECT {TICKET_COLUMNS} FROM tickets WHERE id = $1"))
+        .bind(ticket_id)
+        .fetch_one(pool)
+        .await
 }
 
 /// Update a ticket's status
@@ -87,28 +225,560 @@ pub async fn update_ticket_status(
     pool: &PgPool,
     ticket_id: Uuid,
     input: UpdateTicketInput,
-) -> Result<Ticket, sqlx::Error> {
-    let now = Utc::now();
+    actor: Option<Uuid>,
+) -> Result<Ticket, TicketError> {
+    let changes = TicketChanges {
+        status: Some(input.status),
+        ..Default::default()
+    };
+
+    update_ticket(pool, ticket_id, &changes, actor).await
+}
+
+/// Fields to change on a ticket; `None` leaves a field as it is
+#[derive(Debug, Clone, Default)]
+pub struct TicketChanges {
+    pub subject: Option<String>,
+    pub description: Option<String>,
+    pub status: Option<TicketStatus>,
+    pub priority: Option<TicketPriority>,
+    pub assigned_to: Option<Uuid>,
+    /// When closing, close the ticket's children (and theirs) too. Holds the
+    /// tickets the caller may close; any other descendant refuses the cascade.
+    pub close_children: Option<HashSet<Uuid>>,
+}
+
+/// Lock the ticket's row for the rest of `tx` and return it as it is now
+pub(crate) async fn lock_ticket(
+    tx: &mut Transaction<'_, Postgres>,
+    ticket_id: Uuid,
+) -> Result<Ticket, TicketError> {
+    sqlx::query_as::<_, Ticket>(&format!("SELECT {TICKET_COLUMNS} FROM tickets WHERE id = $1 FOR UPDATE"))
+        .bind(ticket_id)
+        .fetch_optional(&mut **tx)
+        .await?
+        .ok_or(TicketError::NotFound)
+}
+
+fn check_transition(from: TicketStatus, to: TicketStatus) -> Result<(), TicketError> {
+    if from.can_transition_to(to) {
+        Ok(())
+    } else {
+        Err(TicketError::InvalidTransition { from, to })
+    }
+}
+
+/// Apply `changes` on behalf of `actor`, refusing status changes the
+/// lifecycle doesn't allow. Every changed field goes into the ticket's history
+/// and the SLA clock follows status and priority.
+pub async fn update_ticket(
+    pool: &PgPool,
+    ticket_id: Uuid,
+    changes: &TicketChanges,
+    actor: Option<Uuid>,
+) -> Result<Ticket, TicketError> {
+    let mut tx = pool.begin().await?;
 
-    let updated = sqlx::query_as_unchecked!(
-        Ticket,
+    let before = lock_ticket(&mut tx, ticket_id).await?;
+    if let Some(next) = changes.status {
+        check_transition(before.status, next)?;
+
+        if next == TicketStatus::Closed && before.status != TicketStatus::Closed {
+            check_required_fields(&mut tx, ticket_id).await?;
+        }
+    }
+    if let Some(agent_id) = changes.assigned_to.filter(|&id| before.assigned_to != Some(id)) {
+        check_assignee(&mut tx, agent_id, before.team_id).await?;
+    }
+
+    let after = sqlx::query_as::<_, Ticket>(&format!(
         r#"
         UPDATE tickets
-        SET status = $1, updated_at = $2
+        SET
+            subject = COALESCE($1, subject),
+            description = COALESCE($2, description),
+            status = COALESCE($3, status),
+            priority = COALESCE($4, priority),
+            assigned_to = COALESCE($5, assigned_to),
+            updated_at = now()
+        WHERE id = $6
+        RETURNING {TICKET_COLUMNS}
+        "#
+    ))
+    .bind(&changes.subject)
+    .bind(&changes.description)
+    .bind(changes.status)
+    .bind(&changes.priority)
+    .bind(changes.assigned_to)
+    .bind(ticket_id)
+    .fetch_one(&mut *tx)
+    .await?;
+
+    ticket_history_service::record(&mut tx, ticket_id, actor, &changes_between(&before, &after)).await?;
+    let after = sla_service::track_change(&mut tx, &before, after).await?;
+
+    if let Some(allowed) = changes.close_children.as_ref().filter(|_| after.status == TicketStatus::Closed) {
+        let children = ticket_link_service::descendants(&mut tx, ticket_id).await?;
+        check_cascade(&children, allowed)?;
+        for child_id in children {
+            close_in(&mut tx, child_id, actor).await?;
+        }
+    }
+
+    tx.commit().await?;
+    Ok(after)
+}
+
+/// Every ticket a cascade would close must be one the caller may close
+fn check_cascade(children: &[Uuid], allowed: &HashSet<Uuid>) -> Result<(), TicketError> {
+    match children.iter().find(|child_id| !allowed.contains(child_id)) {
+        Some(child_id) => Err(TicketError::ChildNotAllowed(*child_id)),
+        None => Ok(()),
+    }
+}
+
+async fn check_required_fields(tx: &mut Transaction<'_, Postgres>, ticket_id: Uuid) -> Result<(), TicketError> {
+    let missing = custom_field_service::missing_required(tx, ticket_id).await?;
+    if !missing.is_empty() {
+        return Err(TicketError::MissingRequiredFields(missing));
+    }
+
+    Ok(())
+}
+
+/// Close the ticket as part of `tx` unless it already is
+async fn close_in(
+    tx: &mut Transaction<'_, Postgres>,
+    ticket_id: Uuid,
+    actor: Option<Uuid>,
+) -> Result<(), TicketError> {
+    let before = lock_ticket(tx, ticket_id).await?;
+    if before.status == TicketStatus::Closed {
+        return Ok(());
+    }
+    check_transition(before.status, TicketStatus::Closed)?;
+    check_required_fields(tx, ticket_id).await?;
+
+    let after = sqlx::query_as::<_, Ticket>(&format!(
+        "UPDATE tickets SET status = $1, updated_at = now() WHERE id = $2 RETURNING {TICKET_COLUMNS}"
+    ))
+    .bind(TicketStatus::Closed)
+    .bind(ticket_id)
+    .fetch_one(&mut **tx)
+    .await?;
+
+    ticket_history_service::record(tx, ticket_id, actor, &changes_between(&before, &after)).await?;
+    sla_service::track_change(tx, &before, after).await?;
+
+    Ok(())
+}
+
+/// Hand the ticket to `agent_id`, which (re)opens it. Closed tickets stay closed.
+pub async fn assign_ticket(
+    pool: &PgPool,
+    ticket_id: Uuid,
+    agent_id: Uuid,
+    actor: Option<Uuid>,
+) -> Result<Ticket, TicketError> {
+    let mut tx = pool.begin().await?;
+    let after = assign_in(&mut tx, ticket_id, agent_id, actor).await?;
+    tx.commit().await?;
+
+    Ok(after)
+}
+
+/// `assign_ticket` as part of a larger transaction
+pub(crate) async fn assign_in(
+    tx: &mut Transaction<'_, Postgres>,
+    ticket_id: Uuid,
+    agent_id: Uuid,
+    actor: Option<Uuid>,
+) -> Result<Ticket, TicketError> {
+    let before = lock_ticket(tx, ticket_id).await?;
+    check_transition(before.status, TicketStatus::Open)?;
+    check_assignee(tx, agent_id, before.team_id).await?;
+
+    let after = sqlx::query_as::<_, Ticket>(&format!(
+        r#"
+        UPDATE tickets
+        SET assigned_to = $1, status = $2, updated_at = now()
         WHERE id = $3
-        RETURNING id, subject, description,
-                  status as "status: _", priority as "priority: _",
-                  assigned_to, created_at, updated_at,
-                  customer_email, user_id
+        RETURNING {TICKET_COLUMNS}
+        "#
+    ))
+    .bind(agent_id)
+    .bind(TicketStatus::Open)
+    .bind(ticket_id)
+    .fetch_one(&mut **tx)
+    .await?;
+
+    ticket_history_service::record(tx, ticket_id, actor, &changes_between(&before, &after)).await?;
+    let after = sla_service::track_change(tx, &before, after).await?;
+
+    Ok(after)
+}
+
+/// Tickets go to users who can work assigned tickets, and a ticket in a
+/// team's queue only to that team's members
+async fn check_assignee(
+    tx: &mut Transaction<'_, Postgres>,
+    agent_id: Uuid,
+    team_id: Option<Uuid>,
+) -> Result<(), TicketError> {
+    let assignable = sqlx::query_scalar::<_, bool>(
+        r#"
+        SELECT EXISTS (
+                   SELECT 1 FROM role_permissions rp
+                   WHERE rp.role = u.role AND rp.permission = $2
+               )
+               AND ($3::uuid IS NULL OR EXISTS (
+                   SELECT 1 FROM team_members tm WHERE tm.team_id = $3 AND tm.user_id = u.id
+               ))
+        FROM users u
+        WHERE u.id = $1
         "#,
-        input.status as TicketStatus,
-        now,
-        ticket_id
     )
-    .fetch_one(pool)
+    .bind(agent_id)
+    .bind(Permission::TicketsReadAssigned.as_str())
+    .bind(team_id)
+    .fetch_optional(&mut **tx)
+    .await?;
+
+    match assignable {
+        None => Err(TicketError::UnknownAssignee),
+        Some(false) => Err(TicketError::NotAssignable),
+        Some(true) => Ok(()),
+    }
+}
+
+/// Put the ticket in `team_id`'s queue. An assignee who isn't on that team
+/// is taken off the ticket so the team can pick it up.
+pub async fn move_to_team(
+    pool: &PgPool,
+    ticket_id: Uuid,
+    team_id: Uuid,
+    actor: Option<Uuid>,
+) -> Result<Ticket, TicketError> {
+    let mut tx = pool.begin().await?;
+
+    let before = lock_ticket(&mut tx, ticket_id).await?;
+
+    let after = sqlx::query_as::<_, Ticket>(&format!(
+        r#"
+        UPDATE tickets
+        SET team_id = $1,
+            assigned_to = CASE
+                WHEN EXISTS (SELECT 1 FROM team_members WHERE team_id = $1 AND user_id = assigned_to)
+                THEN assigned_to
+            END,
+            updated_at = now()
+        WHERE id = $2
+        RETURNING {TICKET_COLUMNS}
+        "#
+    ))
+    .bind(team_id)
+    .bind(ticket_id)
+    .fetch_one(&mut *tx)
+    .await
+    .map_err(unknown_team_or)?;
+
+    ticket_history_service::record(&mut tx, ticket_id, actor, &changes_between(&before, &after)).await?;
+
+    tx.commit().await?;
+    Ok(after)
+}
+
+/// `actor` replied as the customer: move a `Pending` or `Resolved` ticket back
+/// to `Open`. Returns whether the ticket was reopened.
+async fn reopen_on_customer_reply(
+    pool: &PgPool,
+    ticket_id: Uuid,
+    actor: Option<Uuid>,
+) -> Result<bool, TicketError> {
+    let mut tx = pool.begin().await?;
+
+    let before = lock_ticket(&mut tx, ticket_id).await?;
+    if !before.status.reopens_on_customer_reply() {
+        return Ok(false);
+    }
+
+    let after = sqlx::query_as::<_, Ticket>(&format!(
+        "UPDATE tickets SET status = $1, updated_at = now() WHERE id = $2 RETURNING {TICKET_COLUMNS}"
+    ))
+    .bind(TicketStatus::Open)
+    .bind(ticket_id)
+    .fetch_one(&mut *tx)
     .await?;
 
-    Ok(updated)
+    ticket_history_service::record(&mut tx, ticket_id, actor, &changes_between(&before, &after)).await?;
+    sla_service::track_change(&mut tx, &before, after).await?;
+
+    tx.commit().await?;
+    Ok(true)
+}
+
+/// Which side of the conversation a message on a ticket came from. Worked
+/// out from the caller, never taken from the client.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ReplySide {
+    Customer,
+    Staff,
+}
+
+/// Bookkeeping after `actor` added a message the customer can see: a
+/// customer answering reopens a ticket that was waiting on them or
+/// resolved, and staff answering counts toward the first-response SLA
+pub async fn record_reply(
+    pool: &PgPool,
+    ticket_id: Uuid,
+    side: ReplySide,
+    actor: Uuid,
+) -> Result<(), TicketError> {
+    match side {
+        ReplySide::Customer => reopen_on_customer_reply(pool, ticket_id, Some(actor)).await.map(|_| ()),
+        ReplySide::Staff => Ok(sla_service::record_first_response(pool, ticket_id).await?),
+    }
+}
+
+/// Messages moved from `source` to `target` by a merge or split
+#[derive(Debug, Clone, Serialize)]
+pub struct MovedMessages {
+    pub source: Ticket,
+    pub target: Ticket,
+    pub message_ids: Vec<Uuid>,
+}
+
+/// Add `user_id` to the ticket's watchers
+pub async fn watch(pool: &PgPool, ticket_id: Uuid, user_id: Uuid) -> Result<(), TicketError> {
+    sqlx::query("INSERT INTO ticket_watchers (ticket_id, user_id) VALUES ($1, $2) ON CONFLICT DO NOTHING")
+        .bind(ticket_id)
+        .bind(user_id)
+        .execute(pool)
+        .await
+        .map_err(|err| match err.as_database_error() {
+            Some(db) if db.is_foreign_key_violation() => TicketError::NotFound,
+            _ => err.into(),
+        })?;
+
+    Ok(())
+}
+
+pub async fn unwatch(pool: &PgPool, ticket_id: Uuid, user_id: Uuid) -> Result<(), sqlx::Error> {
+    sqlx::query("DELETE FROM ticket_watchers WHERE ticket_id = $1 AND user_id = $2")
+        .bind(ticket_id)
+        .bind(user_id)
+        .execute(pool)
+        .await?;
+
+    Ok(())
+}
+
+/// Give `target` the watchers of `source` it doesn't have yet
+async fn copy_watchers(
+    tx: &mut Transaction<'_, Postgres>,
+    source_id: Uuid,
+    target_id: Uuid,
+) -> Result<(), sqlx::Error> {
+    sqlx::query(
+        r#"
+        INSERT INTO ticket_watchers (ticket_id, user_id)
+        SELECT $2, user_id FROM ticket_watchers WHERE ticket_id = $1
+        ON CONFLICT DO NOTHING
+        "#,
+    )
+    .bind(source_id)
+    .bind(target_id)
+    .execute(&mut **tx)
+    .await?;
+
+    Ok(())
+}
+
+/// Whether `source` may be merged into `target`, both as they are now
+fn check_merge(source: &Ticket, target: &Ticket) -> Result<(), TicketError> {
+    if source.id == target.id {
+        return Err(TicketError::SameTicket);
+    }
+    if source.merged_into_id.is_some() {
+        return Err(TicketError::AlreadyMerged);
+    }
+    if target.status == TicketStatus::Closed {
+        return Err(TicketError::MergeIntoClosed);
+    }
+
+    check_transition(source.status, TicketStatus::Closed)
+}
+
+/// A split takes at least one message, and only the ticket's own (`owned`)
+fn check_split(message_ids: &[Uuid], owned: &[Uuid]) -> Result<(), TicketError> {
+    if message_ids.is_empty() || !message_ids.iter().all(|id| owned.contains(id)) {
+        return Err(TicketError::UnknownMessages);
+    }
+
+    Ok(())
+}
+
+/// Merge the duplicate `source_id` into `target_id`: its messages (and with
+/// them their attachments), notes and watchers move over, and it is closed
+/// pointing at the target, subject to the usual rules for closing. Both
+/// tickets' histories record the merge, as do those of earlier duplicates
+/// re-pointed from the source to the target.
+pub async fn merge_tickets(
+    pool: &PgPool,
+    source_id: Uuid,
+    target_id: Uuid,
+    actor: Option<Uuid>,
+) -> Result<MovedMessages, TicketError> {
+    let mut tx = pool.begin().await?;
+
+    // Lock in a fixed order so two merges of the same pair can't deadlock
+    let (source, target) = if source_id < target_id {
+        let source = lock_ticket(&mut tx, source_id).await?;
+        (source, lock_ticket(&mut tx, target_id).await?)
+    } else {
+        let target = lock_ticket(&mut tx, target_id).await?;
+        (lock_ticket(&mut tx, source_id).await?, target)
+    };
+    check_merge(&source, &target)?;
+    if source.status != TicketStatus::Closed {
+        check_required_fields(&mut tx, source_id).await?;
+    }
+
+    let message_ids = sqlx::query_scalar::<_, Uuid>(
+        "UPDATE messages SET ticket_id = $2 WHERE ticket_id = $1 RETURNING id",
+    )
+    .bind(source_id)
+    .bind(target_id)
+    .fetch_all(&mut *tx)
+    .await?;
+    sqlx::query("UPDATE notes SET ticket_id = $2 WHERE ticket_id = $1")
+        .bind(source_id)
+        .bind(target_id)
+        .execute(&mut *tx)
+        .await?;
+    copy_watchers(&mut tx, source_id, target_id).await?;
+    sqlx::query("DELETE FROM ticket_watchers WHERE ticket_id = $1")
+        .bind(source_id)
+        .execute(&mut *tx)
+        .await?;
+    // Earlier duplicates of the source now point at where its conversation went
+    let repointed = sqlx::query_scalar::<_, Uuid>(
+        "UPDATE tickets SET merged_into_id = $2 WHERE merged_into_id = $1 RETURNING id",
+    )
+    .bind(source_id)
+    .bind(target_id)
+    .fetch_all(&mut *tx)
+    .await?;
+    let repoint = FieldChange {
+        field: TicketField::MergedInto,
+        old_value: Some(source_id.to_string()),
+        new_value: Some(target_id.to_string()),
+    };
+    for duplicate_id in repointed {
+        ticket_history_service::record(&mut tx, duplicate_id, actor, std::slice::from_ref(&repoint)).await?;
+    }
+
+    let closed = sqlx::query_as::<_, Ticket>(&format!(
+        r#"
+        UPDATE tickets
+        SET status = $1, merged_into_id = $2, updated_at = now()
+        WHERE id = $3
+        RETURNING {TICKET_COLUMNS}
+        "#
+    ))
+    .bind(TicketStatus::Closed)
+    .bind(target_id)
+    .bind(source_id)
+    .fetch_one(&mut *tx)
+    .await?;
+    ticket_history_service::record(&mut tx, source_id, actor, &changes_between(&source, &closed)).await?;
+    let closed = sla_service::track_change(&mut tx, &source, closed).await?;
+
+    let target = sqlx::query_as::<_, Ticket>(&format!(
+        "UPDATE tickets SET updated_at = now() WHERE id = $1 RETURNING {TICKET_COLUMNS}"
+    ))
+    .bind(target_id)
+    .fetch_one(&mut *tx)
+    .await?;
+    let merged_from = FieldChange {
+        field: TicketField::MergedFrom,
+        old_value: None,
+        new_value: Some(source_id.to_string()),
+    };
+    ticket_history_service::record(&mut tx, target_id, actor, &[merged_from]).await?;
+
+    tx.commit().await?;
+    Ok(MovedMessages { source: closed, target, message_ids })
+}
+
+/// Move `message_ids` off `source_id` into a new ticket for the same
+/// customer, team and priority, which also gets the source's watchers.
+/// `subject` defaults to the source's.
+pub async fn split_ticket(
+    pool: &PgPool,
+    source_id: Uuid,
+    message_ids: &[Uuid],
+    subject: Option<&str>,
+    actor: Option<Uuid>,
+) -> Result<MovedMessages, TicketError> {
+    let mut tx = pool.begin().await?;
+
+    let source = lock_ticket(&mut tx, source_id).await?;
+    let mut message_ids = message_ids.to_vec();
+    message_ids.sort();
+    message_ids.dedup();
+
+    let owned = sqlx::query_scalar::<_, Uuid>("SELECT id FROM messages WHERE ticket_id = $1 AND id = ANY($2)")
+        .bind(source_id)
+        .bind(&message_ids)
+        .fetch_all(&mut *tx)
+        .await?;
+    check_split(&message_ids, &owned)?;
+
+    let split = sqlx::query_as::<_, Ticket>(&format!(
+        r#"
+        INSERT INTO tickets (subject, description, status, priority, customer_email, team_id)
+        VALUES ($1, '', $2, $3, $4, $5)
+        RETURNING {TICKET_COLUMNS}
+        "#
+    ))
+    .bind(subject.unwrap_or(source.subject.as_str()))
+    .bind(TicketStatus::New)
+    .bind(&source.priority)
+    .bind(&source.customer_email)
+    .bind(source.team_id)
+    .fetch_one(&mut *tx)
+    .await?;
+    let split = sla_service::start_clock(&mut tx, split).await?;
+
+    sqlx::query("UPDATE messages SET ticket_id = $1 WHERE id = ANY($2)")
+        .bind(split.id)
+        .bind(&message_ids)
+        .execute(&mut *tx)
+        .await?;
+    copy_watchers(&mut tx, source_id, split.id).await?;
+
+    let source = sqlx::query_as::<_, Ticket>(&format!(
+        "UPDATE tickets SET updated_at = now() WHERE id = $1 RETURNING {TICKET_COLUMNS}"
+    ))
+    .bind(source_id)
+    .fetch_one(&mut *tx)
+    .await?;
+    let split_into = FieldChange {
+        field: TicketField::SplitInto,
+        old_value: None,
+        new_value: Some(split.id.to_string()),
+    };
+    ticket_history_service::record(&mut tx, source_id, actor, &[split_into]).await?;
+    let split_from = FieldChange {
+        field: TicketField::SplitFrom,
+        old_value: None,
+        new_value: Some(source_id.to_string()),
+    };
+    ticket_history_service::record(&mut tx, split.id, actor, &[split_from]).await?;
+
+    tx.commit().await?;
+    Ok(MovedMessages { source, target: split, message_ids })
 }
 
 /// Delete a ticket
@@ -119,3 +789,355 @@ pub async fn delete_ticket(pool: &PgPool, ticket_id: Uuid) -> Result<(), sqlx::E
 
     Ok(())
 }
+
+// ---------------------------------------------------------------------------
+// Listing: filters, sorting and keyset pagination shared by every ticket list
+// ---------------------------------------------------------------------------
+
+/// Tickets a list endpoint is limited to, whatever the filters say
+#[derive(Debug, Clone)]
+pub enum TicketScope {
+    All,
+    /// Tickets filed under this (verified) customer email
+    Customer(String),
+    /// Tickets assigned to this agent
+    Assignee(Uuid),
+    /// Tickets in this team's queue
+    Team(Uuid),
+}
+
+/// Optional filters; empty lists and `None` match everything
+#[derive(Debug, Clone, Default)]
+pub struct TicketFilter {
+    pub statuses: Vec<TicketStatus>,
+    pub priorities: Vec<TicketPriority>,
+    pub assigned_to: Option<Uuid>,
+    pub unassigned: bool,
+    pub customer_email: Option<String>,
+    /// Tickets must have every one of these tags (case-insensitive)
+    pub tags: Vec<String>,
+    /// `(key, value)` custom field values, canonical form; all must match
+    pub fields: Vec<(String, String)>,
+    /// Inclusive lower and exclusive upper bounds
+    pub created_after: Option<DateTime<Utc>>,
+    pub created_before: Option<DateTime<Utc>>,
+    pub updated_after: Option<DateTime<Utc>>,
+    pub updated_before: Option<DateTime<Utc>>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum TicketSortField {
+    CreatedAt,
+    UpdatedAt,
+    Priority,
+}
+
+impl TicketSortField {
+    fn column(self) -> &'static str {
+        match self {
+            TicketSortField::CreatedAt => "created_at",
+            TicketSortField::UpdatedAt => "updated_at",
+            TicketSortField::Priority => "priority",
+        }
+    }
+}
+
+/// Sort order; ties are broken by ticket id in the same direction so
+/// every ticket has a stable position for cursors
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct TicketSort {
+    pub field: TicketSortField,
+    pub descending: bool,
+}
+
+impl Default for TicketSort {
+    /// Newest first
+    fn default() -> Self {
+        Self { field: TicketSortField::CreatedAt, descending: true }
+    }
+}
+
+impl FromStr for TicketSort {
+    type Err = ();
+
+    /// `created_at`, `updated_at` or `priority`; a leading `-` sorts descending
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let (descending, name) = match s.strip_prefix('-') {
+            Some(name) => (true, name),
+            None => (false, s),
+        };
+        let field = match name {
+            "created_at" => TicketSortField::CreatedAt,
+            "updated_at" => TicketSortField::UpdatedAt,
+            "priority" => TicketSortField::Priority,
+            _ => return Err(()),
+        };
+
+        Ok(Self { field, descending })
+    }
+}
+
+/// Sort key of the last ticket on a page
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(untagged)]
+enum CursorKey {
+    Time(DateTime<Utc>),
+    Priority(TicketPriority),
+}
+
+/// Where the next page starts: just after the ticket with this sort key and id.
+/// Opaque to clients; only valid with the sort order it was issued for.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct TicketCursor {
+    #[serde(rename = "s")]
+    sort: String,
+    #[serde(rename = "k")]
+    key: CursorKey,
+    id: Uuid,
+}
+
+impl TicketCursor {
+    fn after(ticket: &Ticket, sort: TicketSort) -> Self {
+        let key = match sort.field {
+            TicketSortField::CreatedAt => CursorKey::Time(ticket.created_at.unwrap_or_default()),
+            TicketSortField::UpdatedAt => CursorKey::Time(ticket.updated_at.unwrap_or_default()),
+            TicketSortField::Priority => CursorKey::Priority(ticket.priority.clone()),
+        };
+
+        Self { sort: sort_name(sort), key, id: ticket.id }
+    }
+
+    pub fn encode(&self) -> String {
+        URL_SAFE_NO_PAD.encode(serde_json::to_vec(self).expect("cursor serializes"))
+    }
+
+    /// `None` if the cursor is malformed or was issued for another sort order
+    pub fn decode(raw: &str, sort: TicketSort) -> Option<Self> {
+        let bytes = URL_SAFE_NO_PAD.decode(raw).ok()?;
+        let cursor: Self = serde_json::from_slice(&bytes).ok()?;
+
+        let key_matches = matches!(
+            (&cursor.key, sort.field),
+            (CursorKey::Time(_), TicketSortField::CreatedAt | TicketSortField::UpdatedAt)
+                | (CursorKey::Priority(_), TicketSortField::Priority)
+        );
+        (key_matches && cursor.sort == sort_name(sort)).then_some(cursor)
+    }
+}
+
+fn sort_name(sort: TicketSort) -> String {
+    format!("{}{}", if sort.descending { "-" } else { "" }, sort.field.column())
+}
+
+/// Tickets with these ids, in no particular order
+pub async fn get_tickets_by_ids(pool: &PgPool, ids: &[Uuid]) -> Result<Vec<Ticket>, sqlx::Error> {
+    sqlx::query_as::<_, Ticket>(&format!("SELECT {TICKET_COLUMNS} FROM tickets WHERE id = ANY($1)"))
+        .bind(ids)
+        .fetch_all(pool)
+        .await
+}
+
+/// One page of a ticket list
+#[derive(Debug)]
+pub struct TicketPage {
+    pub tickets: Vec<Ticket>,
+    /// Tickets matching scope and filters, across all pages
+    pub total: i64,
+    pub next_cursor: Option<TicketCursor>,
+}
+
+fn push_conditions<'a>(qb: &mut QueryBuilder<'a, Postgres>, scope: &'a TicketScope, filter: &'a TicketFilter) {
+    qb.push(" WHERE TRUE");
+
+    match scope {
+        TicketScope::All => {}
+        TicketScope::Customer(email) => {
+            qb.push(" AND customer_email = ").push_bind(email);
+        }
+        TicketScope::Assignee(agent_id) => {
+            qb.push(" AND assigned_to = ").push_bind(agent_id);
+        }
+        TicketScope::Team(team_id) => {
+            qb.push(" AND team_id = ").push_bind(team_id);
+        }
+    }
+
+    if !filter.statuses.is_empty() {
+        qb.push(" AND status IN (");
+        let mut list = qb.separated(", ");
+        for status in &filter.statuses {
+            list.push_bind(status);
+        }
+        list.push_unseparated(")");
+    }
+    if !filter.priorities.is_empty() {
+        qb.push(" AND priority IN (");
+        let mut list = qb.separated(", ");
+        for priority in &filter.priorities {
+            list.push_bind(priority);
+        }
+        list.push_unseparated(")");
+    }
+    if let Some(agent_id) = filter.assigned_to {
+        qb.push(" AND assigned_to = ").push_bind(agent_id);
+    }
+    if filter.unassigned {
+        qb.push(" AND assigned_to IS NULL");
+    }
+    if let Some(email) = &filter.customer_email {
+        qb.push(" AND customer_email = ").push_bind(email);
+    }
+    for tag in &filter.tags {
+        qb.push(
+            " AND EXISTS (SELECT 1 FROM ticket_tags tt JOIN tags tg ON tg.id = tt.tag_id \
+             WHERE tt.ticket_id = tickets.id AND lower(tg.name) = lower(",
+        )
+        .push_bind(tag)
+        .push("))");
+    }
+    for (key, value) in &filter.fields {
+        qb.push(
+            " AND EXISTS (SELECT 1 FROM ticket_field_values fv JOIN custom_fields cf ON cf.id = fv.field_id \
+             WHERE fv.ticket_id = tickets.id AND cf.key = ",
+        )
+        .push_bind(key)
+        .push(" AND fv.value = ")
+        .push_bind(value)
+        .push(")");
+    }
+    if let Some(at) = filter.created_after {
+        qb.push(" AND created_at >= ").push_bind(at);
+    }
+    if let Some(at) = filter.created_before {
+        qb.push(" AND created_at < ").push_bind(at);
+    }
+    if let Some(at) = filter.updated_after {
+        qb.push(" AND updated_at >= ").push_bind(at);
+    }
+    if let Some(at) = filter.updated_before {
+        qb.push(" AND updated_at < ").push_bind(at);
+    }
+}
+
+/// List tickets in `scope` matching `filter`, `limit` at a time, starting
+/// after `cursor`
+pub async fn list_tickets(
+    pool: &PgPool,
+    scope: &TicketScope,
+    filter: &TicketFilter,
+    sort: TicketSort,
+    cursor: Option<&TicketCursor>,
+    limit: i64,
+) -> Result<TicketPage, sqlx::Error> {
+    let mut count = QueryBuilder::new("SELECT COUNT(*) FROM tickets");
+    push_conditions(&mut count, scope, filter);
+    let total: i64 = count.build_query_scalar().fetch_one(pool).await?;
+
+    let column = sort.field.column();
+    let direction = if sort.descending { "DESC" } else { "ASC" };
+
+    let mut page = QueryBuilder::new(format!("SELECT {TICKET_COLUMNS} FROM tickets"));
+    push_conditions(&mut page, scope, filter);
+    if let Some(cursor) = cursor {
+        // Row comparison: strictly after the cursor in sort order
+        page.push(format_args!(" AND ({column}, id) {} (", if sort.descending { "<" } else { ">" }));
+        match &cursor.key {
+            CursorKey::Time(at) => page.push_bind(*at),
+            CursorKey::Priority(priority) => page.push_bind(priority.clone()),
+        };
+        page.push(", ").push_bind(cursor.id).push(")");
+    }
+    page.push(format_args!(" ORDER BY {column} {direction}, id {direction} LIMIT "))
+        .push_bind(limit + 1);
+
+    let mut tickets: Vec<Ticket> = page.build_query_as().fetch_all(pool).await?;
+
+    // The extra row only tells us there is another page
+    let next_cursor = if tickets.len() as i64 > limit {
+        tickets.truncate(limit as usize);
+        tickets.last().map(|ticket| TicketCursor::after(ticket, sort))
+    } else {
+        None
+    };
+
+    Ok(TicketPage { tickets, total, next_cursor })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn ticket() -> Ticket {
+        Ticket { priority: TicketPriority::High, ..Ticket::for_test() }
+    }
+
+    #[test]
+    fn sort_parses_direction_and_field() {
+        assert_eq!("created_at".parse(), Ok(TicketSort { field: TicketSortField::CreatedAt, descending: false }));
+        assert_eq!("-priority".parse(), Ok(TicketSort { field: TicketSortField::Priority, descending: true }));
+        assert_eq!("subject".parse::<TicketSort>(), Err(()));
+        assert_eq!(sort_name(TicketSort::default()), "-created_at");
+    }
+
+    #[test]
+    fn cursors_round_trip_for_their_own_sort_only() {
+        let ticket = ticket();
+        let newest = TicketSort::default();
+        let by_priority: TicketSort = "priority".parse().unwrap();
+
+        let cursor = TicketCursor::after(&ticket, newest);
+        assert_eq!(TicketCursor::decode(&cursor.encode(), newest), Some(cursor.clone()));
+        assert_eq!(TicketCursor::decode(&cursor.encode(), by_priority), None);
+        assert_eq!(TicketCursor::decode(&cursor.encode(), "created_at".parse().unwrap()), None);
+
+        let cursor = TicketCursor::after(&ticket, by_priority);
+        assert_eq!(cursor.key, CursorKey::Priority(TicketPriority::High));
+        assert_eq!(TicketCursor::decode(&cursor.encode(), by_priority), Some(cursor));
+    }
+
+    #[test]
+    fn garbage_cursors_are_rejected() {
+        assert_eq!(TicketCursor::decode("not a cursor", TicketSort::default()), None);
+        let forged = URL_SAFE_NO_PAD.encode(br#"{"s":"-created_at","k":"High","id":"00000000-0000-0000-0000-000000000000"}"#);
+        assert_eq!(TicketCursor::decode(&forged, TicketSort::default()), None);
+    }
+
+    #[test]
+    fn cascades_stop_at_children_the_caller_may_not_close() {
+        let (seen, hidden) = (Uuid::new_v4(), Uuid::new_v4());
+        let allowed = HashSet::from([seen]);
+
+        assert!(check_cascade(&[], &allowed).is_ok());
+        assert!(check_cascade(&[seen], &allowed).is_ok());
+        assert!(matches!(
+            check_cascade(&[seen, hidden], &allowed),
+            Err(TicketError::ChildNotAllowed(id)) if id == hidden
+        ));
+    }
+
+    #[test]
+    fn merges_need_two_tickets_and_an_open_target() {
+        let source = ticket();
+        let target = ticket();
+        assert!(check_merge(&source, &target).is_ok());
+
+        assert!(matches!(check_merge(&source, &source), Err(TicketError::SameTicket)));
+
+        let closed = Ticket { status: TicketStatus::Closed, ..target.clone() };
+        assert!(matches!(check_merge(&source, &closed), Err(TicketError::MergeIntoClosed)));
+
+        let merged = Ticket { status: TicketStatus::Closed, merged_into_id: Some(target.id), ..source.clone() };
+        assert!(matches!(check_merge(&merged, &ticket()), Err(TicketError::AlreadyMerged)));
+    }
+
+    #[test]
+    fn splits_take_only_the_tickets_own_messages() {
+        let (mine, also_mine, theirs) = (Uuid::new_v4(), Uuid::new_v4(), Uuid::new_v4());
+        let owned = [mine, also_mine];
+
+        assert!(check_split(&[mine], &owned).is_ok());
+        assert!(check_split(&[mine, also_mine], &owned).is_ok());
+        assert!(matches!(check_split(&[mine, theirs], &owned), Err(TicketError::UnknownMessages)));
+        assert!(matches!(check_split(&[], &owned), Err(TicketError::UnknownMessages)));
+    }
+}
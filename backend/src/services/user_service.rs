@@ -0,0 +1,488 @@
+use sqlx::PgPool;
+use uuid::Uuid;
+
+use crate::{
+    models::{
+        role::ADMIN_ROLE,
+        user::{User, UserSummary},
+    },
+    services::{
+        role_service::{self, RoleError},
+        session_service::{self, SessionError},
+    },
+    utils::{hash::{hash_password, HashError}, token::generate_token},
+};
+
+#[derive(Debug, thiserror::Error)]
+pub enum UserError {
+    #[error("Database error: {0}")]
+    Database(#[from] sqlx::Error),
+    #[error("User not found")]
+    NotFound,
+    #[error("Unknown time zone: {0}")]
+    InvalidTimezone(String),
+    #[error("Avatar must be an http(s) URL of at most 2048 characters")]
+    InvalidAvatarUrl,
+    #[error("Another account already uses this email")]
+    EmailTaken,
+    #[error("At least one active admin must remain")]
+    LastAdmin,
+    #[error("User has tickets, notes or messages; anonymize instead")]
+    HasActivity,
+    #[error(transparent)]
+    Session(#[from] SessionError),
+    #[error(transparent)]
+    Role(RoleError),
+    #[error("Password hashing failed: {0}")]
+    Hash(#[from] HashError),
+}
+
+impl From<RoleError> for UserError {
+    fn from(err: RoleError) -> Self {
+        match err {
+            RoleError::Database(err) => UserError::Database(err),
+            RoleError::LastAdmin => UserError::LastAdmin,
+            err => UserError::Role(err),
+        }
+    }
+}
+
+pub type Result<T> = std::result::Result<T, UserError>;
+
+const MAX_AVATAR_URL_LENGTH: usize = 2048;
+
+fn validate_timezone(timezone: &str) -> Result<()> {
+    timezone
+        .parse::<chrono_tz::Tz>()
+        .map(|_| ())
+        .map_err(|_| UserError::InvalidTimezone(timezone.to_string()))
+}
+
+/// `""` clears the avatar; anything else must be an http(s) URL
+fn normalize_avatar_url(raw: &str) -> Result<Option<String>> {
+    let url = raw.trim();
+    if url.is_empty() {
+        return Ok(None);
+    }
+
+    let valid = url.len() <= MAX_AVATAR_URL_LENGTH
+        && (url.starts_with("https://") || url.starts_with("http://"))
+        && !url.chars().any(char::is_whitespace);
+    if valid {
+        Ok(Some(url.to_string()))
+    } else {
+        Err(UserError::InvalidAvatarUrl)
+    }
+}
+
+fn is_unique_violation(err: &sqlx::Error) -> bool {
+    matches!(err, sqlx::Error::Database(db) if db.code().as_deref() == Some("23505"))
+}
+
+pub async fn get_user(pool: &PgPool, user_id: Uuid) -> Result<User> {
+    let user = sqlx::query_as_unchecked!(User, "SELECT * FROM users WHERE id = $1", user_id)
+        .fetch_optional(pool)
+        .await?
+        .ok_or(UserError::NotFound)?;
+
+    Ok(user)
+}
+
+/// Change your own profile. `None` leaves a field unchanged.
+pub async fn update_profile(
+    pool: &PgPool,
+    user_id: Uuid,
+    name: Option<&str>,
+    timezone: Option<&str>,
+    avatar_url: Option<&str>,
+) -> Result<User> {
+    if let Some(timezone) = timezone {
+        validate_timezone(timezone)?;
+    }
+    let avatar_url = avatar_url.map(normalize_avatar_url).transpose()?;
+
+    let user = sqlx::query_as_unchecked!(
+        User,
+        r#"
+        UPDATE users
+        SET name = COALESCE($2, name),
+            timezone = COALESCE($3, timezone),
+            avatar_url = CASE WHEN $4 THEN $5 ELSE avatar_url END,
+            updated_at = now()
+        WHERE id = $1
+        RETURNING *
+        "#,
+        user_id,
+        name,
+        timezone,
+        avatar_url.is_some(),
+        avatar_url.flatten()
+    )
+    .fetch_optional(pool)
+    .await?
+    .ok_or(UserError::NotFound)?;
+
+    Ok(user)
+}
+
+/// Set a new password hash and log out every other session of the user
+pub async fn change_password(
+    pool: &PgPool,
+    user_id: Uuid,
+    password_hash: &str,
+    keep_session: Option<Uuid>,
+) -> Result<()> {
+    let mut tx = pool.begin().await?;
+
+    sqlx::query!(
+        "UPDATE users SET password_hash = $1, updated_at = now() WHERE id = $2",
+        password_hash,
+        user_id
+    )
+    .execute(&mut *tx)
+    .await?;
+
+    match keep_session {
+        Some(session_id) => {
+            session_service::revoke_other_sessions(&mut *tx, user_id, session_id, "password_changed")
+                .await?
+        }
+        None => session_service::revoke_all_for_user(&mut *tx, user_id, "password_changed").await?,
+    };
+
+    tx.commit().await?;
+    Ok(())
+}
+
+/// Replace a password hash made with outdated settings after a successful
+/// login. Skipped if the password changed since `old_hash` was read.
+pub async fn upgrade_password_hash(
+    pool: &PgPool,
+    user_id: Uuid,
+    old_hash: &str,
+    new_hash: &str,
+) -> Result<()> {
+    sqlx::query!(
+        "UPDATE users SET password_hash = $1 WHERE id = $2 AND password_hash = $3",
+        new_hash,
+        user_id,
+        old_hash
+    )
+    .execute(pool)
+    .await?;
+
+    Ok(())
+}
+
+/// Filters for the admin user list
+#[derive(Debug, Default)]
+pub struct UserFilter<'a> {
+    /// Matched against name and email, case-insensitively
+    pub search: Option<&'a str>,
+    pub role: Option<&'a str>,
+    pub is_active: Option<bool>,
+}
+
+/// One page of users, oldest first, plus the total matching count
+pub async fn list_users(
+    pool: &PgPool,
+    filter: &UserFilter<'_>,
+    limit: i64,
+    offset: i64,
+) -> Result<(Vec<UserSummary>, i64)> {
+    let pattern = filter.search.map(|search| {
+        let escaped = search
+            .replace('\\', "\\\\")
+            .replace('%', "\\%")
+            .replace('_', "\\_");
+        format!("%{escaped}%")
+    });
+
+    let users = sqlx::query_as_unchecked!(
+        UserSummary,
+        r#"
+        SELECT id, name, email, role, COALESCE(is_active, true) AS is_active,
+               email_verified_at, timezone, avatar_url, locked_until, anonymized_at,
+               created_at, updated_at
+        FROM users
+        WHERE ($1::text IS NULL OR name ILIKE $1 OR email ILIKE $1)
+          AND ($2::text IS NULL OR role = $2)
+          AND ($3::bool IS NULL OR COALESCE(is_active, true) = $3)
+        ORDER BY created_at, id
+        LIMIT $4 OFFSET $5
+        "#,
+        pattern,
+        filter.role,
+        filter.is_active,
+        limit,
+        offset
+    )
+    .fetch_all(pool)
+    .await?;
+
+    let total = sqlx::query_scalar!(
+        r#"
+        SELECT COUNT(*) AS "count!"
+        FROM users
+        WHERE ($1::text IS NULL OR name ILIKE $1 OR email ILIKE $1)
+          AND ($2::text IS NULL OR role = $2)
+          AND ($3::bool IS NULL OR COALESCE(is_active, true) = $3)
+        "#,
+        pattern,
+        filter.role,
+        filter.is_active
+    )
+    .fetch_one(pool)
+    .await?;
+
+    Ok((users, total))
+}
+
+pub async fn get_user_summary(pool: &PgPool, user_id: Uuid) -> Result<UserSummary> {
+    let user = sqlx::query_as_unchecked!(
+        UserSummary,
+        r#"
+        SELECT id, name, email, role, COALESCE(is_active, true) AS is_active,
+               email_verified_at, timezone, avatar_url, locked_until, anonymized_at,
+               created_at, updated_at
+        FROM users
+        WHERE id = $1
+        "#,
+        user_id
+    )
+    .fetch_optional(pool)
+    .await?
+    .ok_or(UserError::NotFound)?;
+
+    Ok(user)
+}
+
+/// Edit another user's details. A new email address has to be verified again.
+pub async fn update_user(
+    pool: &PgPool,
+    user_id: Uuid,
+    name: Option<&str>,
+    email: Option<&str>,
+    timezone: Option<&str>,
+) -> Result<UserSummary> {
+    if let Some(timezone) = timezone {
+        validate_timezone(timezone)?;
+    }
+
+    sqlx::query!(
+        r#"
+        UPDATE users
+        SET name = COALESCE($2, name),
+            email_verified_at = CASE WHEN $3::text IS NOT NULL AND $3 <> email
+                                     THEN NULL ELSE email_verified_at END,
+            email = COALESCE($3, email),
+            timezone = COALESCE($4, timezone),
+            updated_at = now()
+        WHERE id = $1
+        "#,
+        user_id,
+        name,
+        email,
+        timezone
+    )
+    .execute(pool)
+    .await
+    .map_err(|err| {
+        if is_unique_violation(&err) {
+            UserError::EmailTaken
+        } else {
+            err.into()
+        }
+    })?;
+
+    get_user_summary(pool, user_id).await
+}
+
+/// Enable or disable an account. Disabling revokes all of its sessions;
+/// `user_access` already refuses the access tokens and API keys of
+/// disabled users.
+pub async fn set_active(pool: &PgPool, user_id: Uuid, active: bool) -> Result<UserSummary> {
+    let mut tx = pool.begin().await?;
+    role_service::lock_users(&mut tx).await?;
+
+    let role = sqlx::query_scalar!("SELECT role FROM users WHERE id = $1", user_id)
+        .fetch_optional(&mut *tx)
+        .await?
+        .ok_or(UserError::NotFound)?;
+
+    if !active && role == ADMIN_ROLE {
+        role_service::ensure_other_admin(&mut tx, user_id).await?;
+    }
+
+    sqlx::query!(
+        "UPDATE users SET is_active = $2, updated_at = now() WHERE id = $1",
+        user_id,
+        active
+    )
+    .execute(&mut *tx)
+    .await?;
+
+    if !active {
+        session_service::revoke_all_for_user(&mut *tx, user_id, "account_disabled").await?;
+    }
+
+    tx.commit().await?;
+    get_user_summary(pool, user_id).await
+}
+
+/// Log a user out everywhere. Returns how many sessions were revoked.
+pub async fn force_logout(pool: &PgPool, user_id: Uuid) -> Result<u64> {
+    let exists = sqlx::query_scalar!(
+        r#"SELECT EXISTS (SELECT 1 FROM users WHERE id = $1) AS "exists!""#,
+        user_id
+    )
+    .fetch_one(pool)
+    .await?;
+    if !exists {
+        return Err(UserError::NotFound);
+    }
+
+    Ok(session_service::revoke_all_for_user(pool, user_id, "admin_logout").await?)
+}
+
+/// Lock the user row and refuse if it is the last active admin
+async fn lock_for_removal(
+    tx: &mut sqlx::Transaction<'_, sqlx::Postgres>,
+    user_id: Uuid,
+) -> Result<User> {
+    role_service::lock_users(tx).await?;
+
+    let user = sqlx::query_as_unchecked!(User, "SELECT * FROM users WHERE id = $1", user_id)
+        .fetch_optional(&mut **tx)
+        .await?
+        .ok_or(UserError::NotFound)?;
+
+    if user.role == ADMIN_ROLE && user.is_active {
+        role_service::ensure_other_admin(tx, user_id).await?;
+    }
+
+    Ok(user)
+}
+
+/// Delete a user who never took part in any ticket. Everyone else has to
+/// be anonymized so the history keeps its authors.
+pub async fn delete_user(pool: &PgPool, user_id: Uuid) -> Result<()> {
+    let mut tx = pool.begin().await?;
+    lock_for_removal(&mut tx, user_id).await?;
+
+    let has_activity = sqlx::query_scalar!(
+        r#"
+        SELECT EXISTS (SELECT 1 FROM tickets WHERE user_id = $1 OR assigned_to = $1)
+            OR EXISTS (SELECT 1 FROM notes WHERE author_id = $1)
+            OR EXISTS (SELECT 1 FROM comments WHERE author_id = $1)
+            OR EXISTS (SELECT 1 FROM messages WHERE sender_id = $1)
+            OR EXISTS (SELECT 1 FROM attachments WHERE uploaded_by = $1)
+            OR EXISTS (SELECT 1 FROM kb_articles WHERE author_id = $1)
+            AS "has_activity!"
+        "#,
+        user_id
+    )
+    .fetch_one(&mut *tx)
+    .await?;
+
+    if has_activity {
+        return Err(UserError::HasActivity);
+    }
+
+    sqlx::query!("DELETE FROM users WHERE id = $1", user_id)
+        .execute(&mut *tx)
+        .await?;
+
+    tx.commit().await?;
+    Ok(())
+}
+
+/// Scrub a user's personal data and disable the account for good. The row
+/// stays so tickets, notes and messages keep pointing at it; the email is
+/// also replaced on tickets they filed and in the login audit log.
+pub async fn anonymize_user(pool: &PgPool, user_id: Uuid) -> Result<()> {
+    // Nobody knows this password, and nobody ever will
+    let password_hash = hash_password(&generate_token())?;
+
+    let mut tx = pool.begin().await?;
+    let user = lock_for_removal(&mut tx, user_id).await?;
+    let placeholder = format!("deleted-{}@anonymized.invalid", user.id);
+
+    sqlx::query!(
+        "UPDATE tickets SET customer_email = $2 WHERE customer_email = $1",
+        user.email,
+        placeholder
+    )
+    .execute(&mut *tx)
+    .await?;
+
+    sqlx::query!(
+        "UPDATE login_attempts SET email = $3 WHERE user_id = $1 OR email = $2",
+        user.id,
+        user.email,
+        placeholder
+    )
+    .execute(&mut *tx)
+    .await?;
+
+    // Credentials, second factors and anything else tied to the person
+    for statement in [
+        "DELETE FROM sessions WHERE user_id = $1",
+        "DELETE FROM api_keys WHERE user_id = $1",
+        "DELETE FROM user_identities WHERE user_id = $1",
+        "DELETE FROM user_totp WHERE user_id = $1",
+        "DELETE FROM user_recovery_codes WHERE user_id = $1",
+        "DELETE FROM mfa_challenges WHERE user_id = $1",
+        "DELETE FROM password_reset_tokens WHERE user_id = $1",
+        "DELETE FROM email_verification_tokens WHERE user_id = $1",
+        "DELETE FROM oidc_logins WHERE user_id = $1",
+        "DELETE FROM notifications WHERE user_id = $1",
+    ] {
+        sqlx::query(statement).bind(user.id).execute(&mut *tx).await?;
+    }
+
+    sqlx::query!(
+        r#"
+        UPDATE users
+        SET name = 'Deleted user', email = $2, password_hash = $3,
+            is_active = false, email_verified_at = NULL, avatar_url = NULL,
+            timezone = 'UTC', failed_login_count = 0, last_failed_login_at = NULL,
+            locked_until = NULL, anonymized_at = now(), updated_at = now()
+        WHERE id = $1
+        "#,
+        user.id,
+        placeholder,
+        password_hash
+    )
+    .execute(&mut *tx)
+    .await?;
+
+    tx.commit().await?;
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn timezones_must_be_iana_names() {
+        assert!(validate_timezone("UTC").is_ok());
+        assert!(validate_timezone("Europe/Berlin").is_ok());
+        assert!(validate_timezone("America/Argentina/Buenos_Aires").is_ok());
+        assert!(matches!(validate_timezone("Mars/Olympus"), Err(UserError::InvalidTimezone(_))));
+        assert!(validate_timezone("").is_err());
+    }
+
+    #[test]
+    fn avatar_urls_are_http_or_cleared() {
+        assert_eq!(normalize_avatar_url("  ").unwrap(), None);
+        assert_eq!(
+            normalize_avatar_url(" https://cdn.example/a.png ").unwrap().as_deref(),
+            Some("https://cdn.example/a.png")
+        );
+        assert!(normalize_avatar_url("javascript:alert(1)").is_err());
+        assert!(normalize_avatar_url("https://cdn.example/a b.png").is_err());
+        assert!(normalize_avatar_url(&format!("https://x/{}", "a".repeat(2048))).is_err());
+    }
+}
@@ -5,12 +5,19 @@ use uuid::Uuid;
 
 use sqlx::PgPool;
 use crate::config::AppConfig;
+use crate::mailer::SharedMailer;
+use crate::services::signing_key_service::KeyStore;
+use crate::utils::{oidc::OidcClient, password_policy::PasswordPolicy};
 
 #[derive(Clone)]
 pub struct AppState {
     pub db: PgPool,
     pub config: AppConfig,
     pub ws_channels: Arc<RwLock<HashMap<Uuid, broadcast::Sender<String>>>>,
+    pub mailer: SharedMailer,
+    pub oidc: Arc<OidcClient>,
+    pub keys: Arc<KeyStore>,
+    pub password_policy: Arc<PasswordPolicy>,
 }
 
 pub type SharedState = Arc<AppState>;
@@ -0,0 +1,447 @@
+use std::collections::HashSet;
+
+use chrono::{DateTime, Datelike, Duration, LocalResult, NaiveDate, NaiveDateTime, NaiveTime, TimeZone, Utc, Weekday};
+use chrono_tz::Tz;
+use serde::{Deserialize, Serialize};
+
+/// How far ahead `due_at` looks for enough open time before giving up
+const MAX_LOOKAHEAD_DAYS: i64 = 2 * 366;
+
+/// One block of opening hours, in the schedule's local time. A shift that
+/// closes at or before it opens runs past midnight (e.g. 22:00-06:00); equal
+/// times mean 24 hours from then.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Shift {
+    pub weekday: Weekday,
+    pub opens_at: NaiveTime,
+    pub closes_at: NaiveTime,
+}
+
+/// When the support desk is open, for timers that only run during business hours
+#[derive(Debug, Clone)]
+pub enum Schedule {
+    /// Around the clock, every day
+    AlwaysOpen,
+    Weekly(Box<WeeklySchedule>),
+}
+
+impl Schedule {
+    /// Open time between `from` and `to`
+    pub fn elapsed(&self, from: DateTime<Utc>, to: DateTime<Utc>) -> Duration {
+        if to <= from {
+            return Duration::zero();
+        }
+
+        match self {
+            Schedule::AlwaysOpen => to - from,
+            Schedule::Weekly(weekly) => weekly.elapsed(from, to),
+        }
+    }
+
+    /// When `open_time` of open time will have passed since `start`; `None`
+    /// if the desk is (practically) never open
+    pub fn due_at(&self, start: DateTime<Utc>, open_time: Duration) -> Option<DateTime<Utc>> {
+        if open_time <= Duration::zero() {
+            return Some(start);
+        }
+
+        match self {
+            Schedule::AlwaysOpen => Some(start + open_time),
+            Schedule::Weekly(weekly) => weekly.due_at(start, open_time),
+        }
+    }
+}
+
+/// Which end of a shift a local time is
+#[derive(Clone, Copy)]
+enum Edge {
+    Opens,
+    Closes,
+}
+
+/// Weekly shifts in a time zone, minus holidays
+#[derive(Debug, Clone)]
+pub struct WeeklySchedule {
+    tz: Tz,
+    /// Indexed by `Weekday::num_days_from_monday`, in opening order
+    shifts: [Vec<(NaiveTime, NaiveTime)>; 7],
+    /// Local dates whose shifts don't happen. A shift belongs to the day it
+    /// opens on, so the tail of the night before a holiday still counts.
+    holidays: HashSet<NaiveDate>,
+}
+
+impl WeeklySchedule {
+    pub fn new(tz: Tz, shifts: &[Shift], holidays: impl IntoIterator<Item = NaiveDate>) -> Self {
+        let mut by_day: [Vec<(NaiveTime, NaiveTime)>; 7] = Default::default();
+        for shift in shifts {
+            by_day[shift.weekday.num_days_from_monday() as usize].push((shift.opens_at, shift.closes_at));
+        }
+        for day in &mut by_day {
+            day.sort();
+        }
+
+        Self {
+            tz,
+            shifts: by_day,
+            holidays: holidays.into_iter().collect(),
+        }
+    }
+
+    fn is_never_open(&self) -> bool {
+        self.shifts.iter().all(Vec::is_empty)
+    }
+
+    /// The shifts opening on local `date`, as UTC periods
+    fn shifts_on(&self, date: NaiveDate) -> impl Iterator<Item = (DateTime<Utc>, DateTime<Utc>)> + '_ {
+        let shifts: &[(NaiveTime, NaiveTime)] = if self.holidays.contains(&date) {
+            &[]
+        } else {
+            &self.shifts[date.weekday().num_days_from_monday() as usize]
+        };
+
+        shifts.iter().map(move |&(opens, closes)| {
+            let closing_date = if closes <= opens { date + Duration::days(1) } else { date };
+            (
+                self.instant(date.and_time(opens), Edge::Opens),
+                self.instant(closing_date.and_time(closes), Edge::Closes),
+            )
+        })
+    }
+
+    /// When a local opening or closing time happens. A time repeated as the
+    /// clocks go back opens at its first and closes at its last occurrence,
+    /// so the desk is open for all of it; a time skipped as they go forward
+    /// happens when the clocks jump past it.
+    fn instant(&self, local: NaiveDateTime, edge: Edge) -> DateTime<Utc> {
+        match (self.tz.from_local_datetime(&local), edge) {
+            (LocalResult::Single(at), _) => at.with_timezone(&Utc),
+            (LocalResult::Ambiguous(first, _), Edge::Opens) => first.with_timezone(&Utc),
+            (LocalResult::Ambiguous(_, last), Edge::Closes) => last.with_timezone(&Utc),
+            (LocalResult::None, _) => self.first_instant_at_or_after(local),
+        }
+    }
+
+    /// The first second whose local time is at least `local`
+    fn first_instant_at_or_after(&self, local: NaiveDateTime) -> DateTime<Utc> {
+        let at = |seconds: i64| DateTime::from_timestamp(seconds, 0).unwrap_or_default();
+
+        // UTC offsets are within -12h..+14h, so local time is before `local`
+        // at `lo` and after it at `hi`
+        let as_utc = local.and_utc().timestamp();
+        let (mut lo, mut hi) = (as_utc - 15 * 3600, as_utc + 13 * 3600);
+        while hi - lo > 1 {
+            let mid = lo + (hi - lo) / 2;
+            if at(mid).with_timezone(&self.tz).naive_local() >= local {
+                hi = mid;
+            } else {
+                lo = mid;
+            }
+        }
+
+        at(hi)
+    }
+
+    /// Open periods from `from` on, in order and without overlaps (shifts
+    /// may overlap, e.g. a night shift running into the morning's), looking
+    /// `days` ahead
+    fn periods_from(
+        &self,
+        from: DateTime<Utc>,
+        days: i64,
+    ) -> impl Iterator<Item = (DateTime<Utc>, DateTime<Utc>)> + '_ {
+        // A night shift from the day before may still be running
+        let first_day = from.with_timezone(&self.tz).date_naive() - Duration::days(1);
+        let mut cursor = from;
+
+        (0..=days)
+            .flat_map(move |offset| self.shifts_on(first_day + Duration::days(offset)))
+            .filter_map(move |(opens, closes)| {
+                let opens = opens.max(cursor);
+                if opens >= closes {
+                    return None;
+                }
+                cursor = closes;
+                Some((opens, closes))
+            })
+    }
+
+    fn elapsed(&self, from: DateTime<Utc>, to: DateTime<Utc>) -> Duration {
+        let days = (to - from).num_days() + 2;
+
+        self.periods_from(from, days)
+            .take_while(|&(opens, _)| opens < to)
+            .fold(Duration::zero(), |total, (opens, closes)| total + (closes.min(to) - opens))
+    }
+
+    fn due_at(&self, start: DateTime<Utc>, open_time: Duration) -> Option<DateTime<Utc>> {
+        if self.is_never_open() {
+            return None;
+        }
+
+        let mut remaining = open_time;
+        for (opens, closes) in self.periods_from(start, MAX_LOOKAHEAD_DAYS) {
+            if closes - opens >= remaining {
+                return Some(opens + remaining);
+            }
+            remaining -= closes - opens;
+        }
+
+        None
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use chrono_tz::{America::New_York, Europe::Berlin};
+
+    use super::*;
+
+    fn time(hour: u32, minute: u32) -> NaiveTime {
+        NaiveTime::from_hms_opt(hour, minute, 0).unwrap()
+    }
+
+    fn shift(weekday: Weekday, opens: (u32, u32), closes: (u32, u32)) -> Shift {
+        Shift {
+            weekday,
+            opens_at: time(opens.0, opens.1),
+            closes_at: time(closes.0, closes.1),
+        }
+    }
+
+    fn weekdays(opens: (u32, u32), closes: (u32, u32)) -> Vec<Shift> {
+        [Weekday::Mon, Weekday::Tue, Weekday::Wed, Weekday::Thu, Weekday::Fri]
+            .into_iter()
+            .map(|day| shift(day, opens, closes))
+            .collect()
+    }
+
+    fn date(year: i32, month: u32, day: u32) -> NaiveDate {
+        NaiveDate::from_ymd_opt(year, month, day).unwrap()
+    }
+
+    /// An unambiguous local time in `tz`
+    fn at(tz: Tz, (year, month, day): (i32, u32, u32), hour: u32, minute: u32) -> DateTime<Utc> {
+        tz.with_ymd_and_hms(year, month, day, hour, minute, 0)
+            .single()
+            .unwrap()
+            .with_timezone(&Utc)
+    }
+
+    fn utc(ymd: (i32, u32, u32), hour: u32, minute: u32) -> DateTime<Utc> {
+        at(Tz::UTC, ymd, hour, minute)
+    }
+
+    fn schedule(tz: Tz, shifts: &[Shift], holidays: &[NaiveDate]) -> Schedule {
+        Schedule::Weekly(Box::new(WeeklySchedule::new(tz, shifts, holidays.iter().copied())))
+    }
+
+    // 2026-01-02 is a Friday, 2026-01-05 a Monday
+
+    #[test]
+    fn always_open_counts_wall_clock_time() {
+        let start = utc((2026, 1, 3), 10, 0);
+        assert_eq!(Schedule::AlwaysOpen.due_at(start, Duration::hours(30)), Some(start + Duration::hours(30)));
+        assert_eq!(Schedule::AlwaysOpen.elapsed(start, start + Duration::minutes(90)), Duration::minutes(90));
+        assert_eq!(Schedule::AlwaysOpen.elapsed(start, start - Duration::minutes(90)), Duration::zero());
+    }
+
+    #[test]
+    fn office_hours_skip_nights_and_weekends() {
+        let office = schedule(Tz::UTC, &weekdays((9, 0), (17, 0)), &[]);
+
+        let friday = utc((2026, 1, 2), 16, 0);
+        assert_eq!(office.due_at(friday, Duration::hours(1)), Some(utc((2026, 1, 2), 17, 0)));
+        assert_eq!(office.due_at(friday, Duration::hours(3)), Some(utc((2026, 1, 5), 11, 0)));
+        assert_eq!(office.elapsed(friday, utc((2026, 1, 5), 10, 0)), Duration::hours(2));
+        assert_eq!(office.elapsed(friday, utc((2026, 1, 9), 17, 0)), Duration::hours(41));
+    }
+
+    #[test]
+    fn timers_started_while_closed_wait_for_opening() {
+        let office = schedule(Tz::UTC, &weekdays((9, 0), (17, 0)), &[]);
+
+        assert_eq!(office.due_at(utc((2026, 1, 5), 7, 0), Duration::hours(1)), Some(utc((2026, 1, 5), 10, 0)));
+        assert_eq!(office.due_at(utc((2026, 1, 3), 12, 0), Duration::minutes(30)), Some(utc((2026, 1, 5), 9, 30)));
+        assert_eq!(office.elapsed(utc((2026, 1, 3), 0, 0), utc((2026, 1, 4), 23, 59)), Duration::zero());
+    }
+
+    #[test]
+    fn zero_open_time_is_due_immediately() {
+        let office = schedule(Tz::UTC, &weekdays((9, 0), (17, 0)), &[]);
+        let saturday = utc((2026, 1, 3), 12, 0);
+        assert_eq!(office.due_at(saturday, Duration::zero()), Some(saturday));
+    }
+
+    #[test]
+    fn holidays_close_the_whole_day() {
+        let office = schedule(Tz::UTC, &weekdays((9, 0), (17, 0)), &[date(2026, 1, 5)]);
+
+        let friday = utc((2026, 1, 2), 16, 0);
+        assert_eq!(office.due_at(friday, Duration::hours(3)), Some(utc((2026, 1, 6), 11, 0)));
+        assert_eq!(office.elapsed(friday, utc((2026, 1, 6), 9, 0)), Duration::hours(1));
+    }
+
+    #[test]
+    fn overnight_shifts_run_past_midnight() {
+        let nights = schedule(Tz::UTC, &weekdays((22, 0), (6, 0)), &[]);
+
+        let monday_evening = utc((2026, 1, 5), 21, 0);
+        assert_eq!(nights.due_at(monday_evening, Duration::hours(2)), Some(utc((2026, 1, 6), 0, 0)));
+        assert_eq!(nights.due_at(utc((2026, 1, 6), 2, 0), Duration::hours(3)), Some(utc((2026, 1, 6), 5, 0)));
+        assert_eq!(nights.elapsed(utc((2026, 1, 5), 23, 0), utc((2026, 1, 6), 5, 0)), Duration::hours(6));
+
+        // Friday night's shift runs into Saturday; nothing opens again until Monday
+        assert_eq!(nights.due_at(utc((2026, 1, 10), 5, 0), Duration::hours(2)), Some(utc((2026, 1, 12), 23, 0)));
+    }
+
+    #[test]
+    fn holiday_cancels_the_night_starting_on_it_only() {
+        let nights = schedule(Tz::UTC, &weekdays((22, 0), (6, 0)), &[date(2026, 1, 6)]);
+
+        // Monday night (into the holiday) counts, Tuesday night doesn't
+        let monday_night = utc((2026, 1, 5), 22, 0);
+        assert_eq!(nights.elapsed(monday_night, utc((2026, 1, 7), 22, 0)), Duration::hours(8));
+        assert_eq!(nights.due_at(monday_night, Duration::hours(9)), Some(utc((2026, 1, 7), 23, 0)));
+    }
+
+    #[test]
+    fn overlapping_shifts_are_not_counted_twice() {
+        let split = schedule(
+            Tz::UTC,
+            &[shift(Weekday::Mon, (9, 0), (13, 0)), shift(Weekday::Mon, (12, 0), (17, 0))],
+            &[],
+        );
+        assert_eq!(split.elapsed(utc((2026, 1, 5), 0, 0), utc((2026, 1, 6), 0, 0)), Duration::hours(8));
+
+        let nested = schedule(
+            Tz::UTC,
+            &[shift(Weekday::Mon, (8, 0), (20, 0)), shift(Weekday::Mon, (9, 0), (17, 0))],
+            &[],
+        );
+        assert_eq!(nested.elapsed(utc((2026, 1, 5), 0, 0), utc((2026, 1, 6), 0, 0)), Duration::hours(12));
+
+        let night_into_day = schedule(
+            Tz::UTC,
+            &[shift(Weekday::Mon, (22, 0), (10, 0)), shift(Weekday::Tue, (9, 0), (17, 0))],
+            &[],
+        );
+        let monday_night = utc((2026, 1, 5), 22, 0);
+        assert_eq!(night_into_day.elapsed(monday_night, utc((2026, 1, 6), 17, 0)), Duration::hours(19));
+        assert_eq!(night_into_day.due_at(monday_night, Duration::hours(13)), Some(utc((2026, 1, 6), 11, 0)));
+    }
+
+    #[test]
+    fn equal_opening_and_closing_means_all_day() {
+        let weekend = schedule(
+            Tz::UTC,
+            &[shift(Weekday::Sat, (0, 0), (0, 0)), shift(Weekday::Sun, (0, 0), (0, 0))],
+            &[],
+        );
+        assert_eq!(weekend.elapsed(utc((2026, 1, 2), 12, 0), utc((2026, 1, 5), 12, 0)), Duration::hours(48));
+        assert_eq!(weekend.due_at(utc((2026, 1, 2), 17, 0), Duration::hours(30)), Some(utc((2026, 1, 4), 6, 0)));
+    }
+
+    #[test]
+    fn hours_are_local_to_the_time_zone() {
+        let office = schedule(New_York, &weekdays((9, 0), (17, 0)), &[]);
+
+        // 9:00 in New York is 14:00 UTC in winter
+        let monday = at(New_York, (2026, 1, 5), 9, 0);
+        assert_eq!(monday, utc((2026, 1, 5), 14, 0));
+        assert_eq!(office.due_at(utc((2026, 1, 5), 12, 0), Duration::hours(1)), Some(utc((2026, 1, 5), 15, 0)));
+        assert_eq!(office.elapsed(utc((2026, 1, 5), 0, 0), utc((2026, 1, 6), 0, 0)), Duration::hours(8));
+    }
+
+    #[test]
+    fn office_hours_stay_local_across_daylight_saving_changes() {
+        // New York springs forward on Sunday 2026-03-08: opening moves from 14:00 to 13:00 UTC
+        let office = schedule(New_York, &weekdays((9, 0), (17, 0)), &[]);
+
+        let friday = at(New_York, (2026, 3, 6), 16, 0);
+        assert_eq!(office.due_at(friday, Duration::hours(2)), Some(at(New_York, (2026, 3, 9), 10, 0)));
+        assert_eq!(office.due_at(friday, Duration::hours(2)), Some(utc((2026, 3, 9), 14, 0)));
+        assert_eq!(office.elapsed(friday, at(New_York, (2026, 3, 9), 10, 0)), Duration::hours(2));
+
+        // ...and falls back on Sunday 2026-11-01
+        let friday = at(New_York, (2026, 10, 30), 16, 0);
+        assert_eq!(office.due_at(friday, Duration::hours(2)), Some(utc((2026, 11, 2), 15, 0)));
+    }
+
+    #[test]
+    fn night_shift_is_an_hour_shorter_when_clocks_go_forward() {
+        // Berlin skips 02:00-03:00 on Sunday 2026-03-29
+        let nights = schedule(Berlin, &[shift(Weekday::Sat, (22, 0), (6, 0))], &[]);
+
+        let opens = at(Berlin, (2026, 3, 28), 22, 0);
+        let closes = at(Berlin, (2026, 3, 29), 6, 0);
+        assert_eq!(nights.elapsed(opens, closes), Duration::hours(7));
+        assert_eq!(nights.elapsed(opens - Duration::days(1), closes + Duration::days(1)), Duration::hours(7));
+        assert_eq!(nights.due_at(opens, Duration::hours(7)), Some(closes));
+        assert_eq!(nights.due_at(opens, Duration::hours(4)), Some(at(Berlin, (2026, 3, 29), 3, 0)));
+    }
+
+    #[test]
+    fn night_shift_is_an_hour_longer_when_clocks_go_back() {
+        // Berlin repeats 02:00-03:00 on Sunday 2026-10-25
+        let nights = schedule(Berlin, &[shift(Weekday::Sat, (22, 0), (6, 0))], &[]);
+
+        let opens = at(Berlin, (2026, 10, 24), 22, 0);
+        let closes = at(Berlin, (2026, 10, 25), 6, 0);
+        assert_eq!(nights.elapsed(opens, closes), Duration::hours(9));
+        assert_eq!(nights.due_at(opens, Duration::hours(9)), Some(closes));
+        // 02:00 happens twice; five hours in is the second one
+        assert_eq!(nights.due_at(opens, Duration::hours(5)), Some(utc((2026, 10, 25), 1, 0)));
+    }
+
+    #[test]
+    fn shifts_starting_in_a_skipped_hour_open_when_the_clocks_jump() {
+        // New York skips 02:00-03:00 on Sunday 2026-03-08
+        let early = schedule(New_York, &[shift(Weekday::Sun, (2, 30), (10, 0))], &[]);
+        let sunday = (at(New_York, (2026, 3, 7), 12, 0), at(New_York, (2026, 3, 8), 12, 0));
+        assert_eq!(early.elapsed(sunday.0, sunday.1), Duration::hours(7));
+        assert_eq!(early.due_at(sunday.0, Duration::minutes(1)), Some(utc((2026, 3, 8), 7, 1)));
+
+        let late = schedule(New_York, &[shift(Weekday::Sat, (22, 0), (2, 30))], &[]);
+        assert_eq!(late.elapsed(sunday.0, sunday.1), Duration::hours(4));
+    }
+
+    #[test]
+    fn repeated_times_open_first_and_close_last() {
+        // Berlin's 02:30 is 00:30 UTC, then 01:30 UTC on 2026-10-25
+        let opens_in_repeat = schedule(Berlin, &[shift(Weekday::Sun, (2, 30), (4, 0))], &[]);
+        let sunday = (utc((2026, 10, 24), 12, 0), utc((2026, 10, 25), 12, 0));
+        assert_eq!(opens_in_repeat.elapsed(sunday.0, sunday.1), Duration::minutes(150));
+        assert_eq!(opens_in_repeat.due_at(sunday.0, Duration::minutes(1)), Some(utc((2026, 10, 25), 0, 31)));
+
+        let closes_in_repeat = schedule(Berlin, &[shift(Weekday::Sat, (22, 0), (2, 30))], &[]);
+        assert_eq!(closes_in_repeat.elapsed(sunday.0, sunday.1), Duration::minutes(330));
+    }
+
+    #[test]
+    fn due_at_and_elapsed_agree() {
+        let office = schedule(
+            Berlin,
+            &[weekdays((8, 0), (12, 0)), weekdays((13, 0), (17, 30)), vec![shift(Weekday::Sat, (22, 0), (6, 0))]]
+                .concat(),
+            &[date(2026, 3, 30)],
+        );
+
+        let start = at(Berlin, (2026, 3, 26), 15, 45);
+        for minutes in [1, 59, 60, 135, 510, 1440, 2000, 6000] {
+            let due = office.due_at(start, Duration::minutes(minutes)).unwrap();
+            assert_eq!(office.elapsed(start, due), Duration::minutes(minutes), "{minutes} minutes");
+        }
+    }
+
+    #[test]
+    fn schedule_without_hours_is_never_due() {
+        let closed = schedule(Tz::UTC, &[], &[]);
+        let start = utc((2026, 1, 5), 9, 0);
+        assert_eq!(closed.due_at(start, Duration::minutes(1)), None);
+        assert_eq!(closed.elapsed(start, start + Duration::days(30)), Duration::zero());
+
+        let all_holidays: Vec<_> = (0..800).map(|day| date(2026, 1, 5) + Duration::days(day)).collect();
+        let on_leave = schedule(Tz::UTC, &weekdays((9, 0), (17, 0)), &all_holidays);
+        assert_eq!(on_leave.due_at(start, Duration::minutes(1)), None);
+    }
+}
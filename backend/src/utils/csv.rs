@@ -0,0 +1,58 @@
+/// One CSV record (RFC 4180), ending in CRLF. Fields with commas, quotes or
+/// line breaks are quoted; fields a spreadsheet would run as a formula (a
+/// leading `=`, `+`, `-`, `@`, tab or carriage return) get a leading `'`
+/// (negative numbers are just numbers).
+pub fn record<I, S>(fields: I) -> String
+where
+    I: IntoIterator<Item = S>,
+    S: AsRef<str>,
+{
+    let mut line = fields
+        .into_iter()
+        .map(|field| escape(field.as_ref()))
+        .collect::<Vec<_>>()
+        .join(",");
+    line.push_str("\r\n");
+    line
+}
+
+fn escape(field: &str) -> String {
+    let formula = field.starts_with(['=', '+', '-', '@', '\t', '\r']) && field.parse::<f64>().is_err();
+    let field = if formula {
+        format!("'{field}")
+    } else {
+        field.to_string()
+    };
+
+    if field.contains([',', '"', '\n', '\r']) {
+        format!("\"{}\"", field.replace('"', "\"\""))
+    } else {
+        field
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn plain_fields_are_left_alone() {
+        assert_eq!(record(["a", "b c", ""]), "a,b c,\r\n");
+    }
+
+    #[test]
+    fn separators_and_quotes_are_quoted() {
+        assert_eq!(record(["x,y", "say \"hi\"", "two\nlines"]), "\"x,y\",\"say \"\"hi\"\"\",\"two\nlines\"\r\n");
+    }
+
+    #[test]
+    fn formulas_are_defused() {
+        assert_eq!(record(["=SUM(A1)", "-2+3", "@cmd"]), "'=SUM(A1),'-2+3,'@cmd\r\n");
+        assert_eq!(record(["-2", "-0.5"]), "-2,-0.5\r\n");
+    }
+
+    #[test]
+    fn leading_tabs_and_carriage_returns_are_defused() {
+        assert_eq!(record(["\t=1+1", "\r=1+1"]), "'\t=1+1,\"'\r=1+1\"\r\n");
+    }
+}
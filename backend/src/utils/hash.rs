@@ -1,9 +1,111 @@
-use bcrypt::{hash, verify, DEFAULT_COST};
+use std::sync::OnceLock;
 
-pub fn hash_password(password: &str) -> Result<String, bcrypt::BcryptError> {
-    hash(password, DEFAULT_COST)
+use argon2::{
+    password_hash::{rand_core::OsRng, PasswordHash, PasswordHasher, PasswordVerifier, SaltString},
+    Argon2, Params,
+};
+
+#[derive(Debug, thiserror::Error)]
+pub enum HashError {
+    #[error("Argon2 error: {0}")]
+    Argon2(#[from] argon2::password_hash::Error),
+    #[error("bcrypt error: {0}")]
+    Bcrypt(#[from] bcrypt::BcryptError),
+}
+
+/// Argon2id with the crate's default (OWASP recommended) cost parameters
+fn argon2() -> Argon2<'static> {
+    Argon2::default()
+}
+
+/// Hash a password for storage. New hashes are always Argon2id.
+pub fn hash_password(password: &str) -> Result<String, HashError> {
+    let salt = SaltString::generate(&mut OsRng);
+    Ok(argon2().hash_password(password.as_bytes(), &salt)?.to_string())
+}
+
+/// Check a password against a stored Argon2id or (older) bcrypt hash
+pub fn verify_password(password: &str, hashed: &str) -> Result<bool, HashError> {
+    if is_bcrypt(hashed) {
+        return Ok(bcrypt::verify(password, hashed)?);
+    }
+
+    let parsed = PasswordHash::new(hashed)?;
+    match argon2().verify_password(password.as_bytes(), &parsed) {
+        Ok(()) => Ok(true),
+        Err(argon2::password_hash::Error::Password) => Ok(false),
+        Err(err) => Err(err.into()),
+    }
+}
+
+/// Verify against a throwaway hash and ignore the result, so a login that
+/// never reaches a real hash takes as long as a wrong password does
+pub fn dummy_verify(password: &str) {
+    static DUMMY: OnceLock<String> = OnceLock::new();
+    let hashed = DUMMY.get_or_init(|| hash_password("not a real password").unwrap_or_default());
+    let _ = verify_password(password, hashed);
 }
 
-pub fn verify_password(password: &str, hashed: &str) -> Result<bool, bcrypt::BcryptError> {
-    verify(password, hashed)
+/// Whether a stored hash should be replaced after the next successful
+/// login: bcrypt hashes, and Argon2 hashes made with other parameters
+pub fn needs_rehash(hashed: &str) -> bool {
+    if is_bcrypt(hashed) {
+        return true;
+    }
+
+    let Ok(parsed) = PasswordHash::new(hashed) else {
+        return true;
+    };
+    if parsed.algorithm != argon2::Algorithm::Argon2id.ident() {
+        return true;
+    }
+
+    let current = argon2().params().clone();
+    Params::try_from(&parsed).map_or(true, |params| {
+        params.m_cost() != current.m_cost()
+            || params.t_cost() != current.t_cost()
+            || params.p_cost() != current.p_cost()
+    })
+}
+
+fn is_bcrypt(hashed: &str) -> bool {
+    ["$2a$", "$2b$", "$2x$", "$2y$"]
+        .iter()
+        .any(|prefix| hashed.starts_with(prefix))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn new_hashes_are_argon2id_and_verify() {
+        let hashed = hash_password("correct horse").unwrap();
+        assert!(hashed.starts_with("$argon2id$"));
+        assert!(verify_password("correct horse", &hashed).unwrap());
+        assert!(!verify_password("wrong horse", &hashed).unwrap());
+        assert!(!needs_rehash(&hashed));
+    }
+
+    #[test]
+    fn bcrypt_hashes_still_verify_but_need_rehash() {
+        let hashed = bcrypt::hash("correct horse", 4).unwrap();
+        assert!(verify_password("correct horse", &hashed).unwrap());
+        assert!(!verify_password("wrong horse", &hashed).unwrap());
+        assert!(needs_rehash(&hashed));
+    }
+
+    #[test]
+    fn weaker_argon2_parameters_need_rehash() {
+        let weak = Argon2::new(
+            argon2::Algorithm::Argon2id,
+            argon2::Version::V0x13,
+            Params::new(8 * 1024, 1, 1, None).unwrap(),
+        );
+        let salt = SaltString::generate(&mut OsRng);
+        let hashed = weak.hash_password(b"correct horse", &salt).unwrap().to_string();
+
+        assert!(verify_password("correct horse", &hashed).unwrap());
+        assert!(needs_rehash(&hashed));
+    }
 }
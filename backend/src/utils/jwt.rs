@@ -1,8 +1,11 @@
 use jsonwebtoken::{encode, decode, Header, EncodingKey, DecodingKey, Validation, Algorithm};
+use jsonwebtoken::jwk::Jwk;
+use base64::{engine::general_purpose::URL_SAFE_NO_PAD, Engine};
 use serde::{Serialize, Deserialize};
 use chrono::{Utc, Duration};
 use uuid::Uuid;
-use crate::models::user::User;
+use crate::models::{signing_key::KeyAlgorithm, user::User};
+use crate::utils::token::generate_token;
 use anyhow::{Result, anyhow};
 
 #[derive(Debug, Serialize, Deserialize, Clone)]
@@ -10,45 +13,121 @@ pub struct Claims {
     pub sub: Uuid,         // user ID
     pub email: String,
     pub role: String,      // user/agent/admin
+    pub sid: Uuid,         // session the token was minted for
     pub exp: usize,        // expiration timestamp
     pub iat: usize,        // issued at timestamp
 }
 
-/// Used during auth service - NOW TAKES SECRET AS PARAMETER
-pub fn generate_jwt(user: &User, secret: &str) -> Result<String> {
-    let now = Utc::now();
-    let expiration = now
-        .checked_add_signed(Duration::days(7))
-        .ok_or_else(|| anyhow!("invalid timestamp"))?
-        .timestamp() as usize;
+/// Private key access tokens are signed with; `kid` goes in the token header
+#[derive(Clone)]
+pub struct SigningKey {
+    pub kid: String,
+    pub algorithm: KeyAlgorithm,
+    encoding: EncodingKey,
+}
 
-    let claims = Claims {
-        sub: user.id,
-        email: user.email.clone(),
-        role: user.role.clone(),
-        iat: now.timestamp() as usize,
-        exp: expiration,
+impl SigningKey {
+    /// Load a PKCS#8 PEM private key
+    pub fn from_pem(kid: &str, algorithm: KeyAlgorithm, pem: &str) -> Result<Self, jsonwebtoken::errors::Error> {
+        let encoding = match algorithm {
+            KeyAlgorithm::Rs256 => EncodingKey::from_rsa_pem(pem.as_bytes())?,
+            KeyAlgorithm::EdDsa => EncodingKey::from_ed_pem(pem.as_bytes())?,
+        };
+
+        Ok(Self { kid: kid.to_string(), algorithm, encoding })
+    }
+}
+
+/// Public half of a signing key, current or retired
+#[derive(Clone)]
+pub struct VerificationKey {
+    pub kid: String,
+    pub algorithm: KeyAlgorithm,
+    decoding: DecodingKey,
+}
+
+impl VerificationKey {
+    pub fn from_jwk(algorithm: KeyAlgorithm, jwk: &Jwk) -> Result<Self, jsonwebtoken::errors::Error> {
+        Ok(Self {
+            kid: jwk.common.key_id.clone().unwrap_or_default(),
+            algorithm,
+            decoding: DecodingKey::from_jwk(jwk)?,
+        })
+    }
+}
+
+/// A new key pair: the PKCS#8 PEM private key and the public JWK to publish
+pub struct GeneratedKey {
+    pub kid: String,
+    pub private_pem: String,
+    pub public_jwk: Jwk,
+}
+
+/// Generate a signing key pair. RSA keys are 2048 bits; this is slow, so
+/// call it off the async runtime.
+pub fn generate_key(algorithm: KeyAlgorithm) -> Result<GeneratedKey> {
+    // Date first so admins can tell keys apart at a glance
+    let kid = format!("{}-{}", Utc::now().format("%Y%m%d"), &generate_token()[..8]);
+
+    let (private_pem, public_jwk) = match algorithm {
+        KeyAlgorithm::Rs256 => {
+            use rsa::{pkcs8::{EncodePrivateKey, LineEnding}, traits::PublicKeyParts, RsaPrivateKey};
+
+            let private = RsaPrivateKey::new(&mut rand::thread_rng(), 2048)?;
+            let pem = private.to_pkcs8_pem(LineEnding::LF)?.to_string();
+            let jwk = serde_json::json!({
+                "kty": "RSA",
+                "n": URL_SAFE_NO_PAD.encode(private.n().to_bytes_be()),
+                "e": URL_SAFE_NO_PAD.encode(private.e().to_bytes_be()),
+            });
+            (pem, jwk)
+        }
+        KeyAlgorithm::EdDsa => {
+            use ring::signature::{Ed25519KeyPair, KeyPair};
+
+            let pkcs8 = Ed25519KeyPair::generate_pkcs8(&ring::rand::SystemRandom::new())
+                .map_err(|_| anyhow!("Ed25519 key generation failed"))?;
+            let pair = Ed25519KeyPair::from_pkcs8(pkcs8.as_ref())
+                .map_err(|err| anyhow!("generated Ed25519 key is unusable: {err}"))?;
+            let pem = pem::encode(&pem::Pem::new("PRIVATE KEY", pkcs8.as_ref()));
+            let jwk = serde_json::json!({
+                "kty": "OKP",
+                "crv": "Ed25519",
+                "x": URL_SAFE_NO_PAD.encode(pair.public_key().as_ref()),
+            });
+            (pem, jwk)
+        }
     };
 
-    let token = encode(
-        &Header::default(),
-        &claims,
-        &EncodingKey::from_secret(secret.as_bytes()), // ✅ Use provided secret
-    )?;
+    let mut public_jwk: Jwk = serde_json::from_value(public_jwk)?;
+    public_jwk.common.key_id = Some(kid.clone());
+    public_jwk.common.public_key_use = Some(jsonwebtoken::jwk::PublicKeyUse::Signature);
+    public_jwk.common.key_algorithm = Some(match algorithm {
+        KeyAlgorithm::Rs256 => jsonwebtoken::jwk::KeyAlgorithm::RS256,
+        KeyAlgorithm::EdDsa => jsonwebtoken::jwk::KeyAlgorithm::EdDSA,
+    });
+
+    Ok(GeneratedKey { kid, private_pem, public_jwk })
+}
 
-    Ok(token)
+/// Used during auth service
+pub fn generate_jwt(user: &User, session_id: Uuid, key: &SigningKey, ttl: Duration) -> Result<String> {
+    Ok(create_token(user.id, user.email.clone(), user.role.clone(), session_id, key, ttl)?)
 }
 
-/// Token creator for custom use
+/// Token creator for custom use. Access tokens are short-lived (`ttl`);
+/// clients renew them with the session's refresh token.
 pub fn create_token(
     user_id: Uuid,
     email: String,
     role: String,
-    secret: &str,
+    session_id: Uuid,
+    key: &SigningKey,
+    ttl: Duration,
 ) -> Result<String, jsonwebtoken::errors::Error> {
     let now = Utc::now();
     let expiration = now
-        .checked_add_signed(Duration::days(7))
+        .checked_add_signed(ttl)
         .expect("valid timestamp")
         .timestamp() as usize;
 
@@ -56,26 +135,92 @@ pub fn create_token(
         sub: user_id,
         email,
         role,
+        sid: session_id,
         iat: now.timestamp() as usize,
         exp: expiration,
     };
 
-    encode(&Header::default(), &claims, &EncodingKey::from_secret(secret.as_bytes()))
+    let mut header = Header::new(key.algorithm.jwt_algorithm());
+    header.kid = Some(key.kid.clone());
+
+    encode(&header, &claims, &key.encoding)
 }
 
-/// Token decoder
+/// Token decoder. The caller picks `key` by the token's `kid`.
 pub fn decode_token(
+    token: &str,
+    key: &VerificationKey,
+) -> Result<Claims, jsonwebtoken::errors::Error> {
+    let token_data = decode::<Claims>(
+        token,
+        &key.decoding,
+        &Validation::new(key.algorithm.jwt_algorithm()),
+    )?;
+
+    Ok(token_data.claims)
+}
+
+/// Decoder for HS256 tokens minted with the shared `JWT_SECRET` before
+/// signing keys existed
+pub fn decode_legacy_token(
     token: &str,
     secret: &str,
 ) -> Result<Claims, jsonwebtoken::errors::Error> {
-    println!("🔓 Decoding token with secret: '{}'", secret); // Debug log
-    
     let token_data = decode::<Claims>(
         token,
         &DecodingKey::from_secret(secret.as_bytes()),
         &Validation::new(Algorithm::HS256),
     )?;
-    
-    println!("✅ Token decoded successfully for user: {}", token_data.claims.sub);
+
     Ok(token_data.claims)
-}
\ No newline at end of file
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn key_pair(algorithm: KeyAlgorithm) -> (SigningKey, VerificationKey) {
+        let generated = generate_key(algorithm).unwrap();
+        (
+            SigningKey::from_pem(&generated.kid, algorithm, &generated.private_pem).unwrap(),
+            VerificationKey::from_jwk(algorithm, &generated.public_jwk).unwrap(),
+        )
+    }
+
+    fn token(key: &SigningKey, ttl: Duration) -> String {
+        create_token(Uuid::new_v4(), "a@b.c".into(), "user".into(), Uuid::new_v4(), key, ttl).unwrap()
+    }
+
+    #[test]
+    fn generated_keys_round_trip() {
+        for algorithm in [KeyAlgorithm::EdDsa, KeyAlgorithm::Rs256] {
+            let (signing, verification) = key_pair(algorithm);
+            let token = token(&signing, Duration::minutes(5));
+
+            let header = jsonwebtoken::decode_header(&token).unwrap();
+            assert_eq!(header.kid.as_deref(), Some(signing.kid.as_str()));
+            assert_eq!(header.alg, algorithm.jwt_algorithm());
+            assert_eq!(verification.kid, signing.kid);
+            assert_eq!(decode_token(&token, &verification).unwrap().email, "a@b.c");
+        }
+    }
+
+    #[test]
+    fn other_keys_and_expired_tokens_are_rejected() {
+        let (signing, _) = key_pair(KeyAlgorithm::EdDsa);
+        let (_, other) = key_pair(KeyAlgorithm::EdDsa);
+        assert!(decode_token(&token(&signing, Duration::minutes(5)), &other).is_err());
+
+        let (signing, verification) = key_pair(KeyAlgorithm::EdDsa);
+        assert!(decode_token(&token(&signing, Duration::minutes(-5)), &verification).is_err());
+    }
+
+    #[test]
+    fn published_jwk_has_no_private_material() {
+        let generated = generate_key(KeyAlgorithm::Rs256).unwrap();
+        let jwk = serde_json::to_value(&generated.public_jwk).unwrap();
+        assert_eq!(jwk["kid"], generated.kid.as_str());
+        assert_eq!(jwk["alg"], "RS256");
+        assert!(jwk.get("d").is_none());
+    }
+}
@@ -1,3 +1,9 @@
 pub mod jwt;
 pub mod hash;
 pub mod slug;
+pub mod token;
+pub mod totp;
+pub mod oidc;
+pub mod password_policy;
+pub mod business_time;
+pub mod csv;
\ No newline at end of file
@@ -0,0 +1,117 @@
+use std::collections::HashSet;
+
+use anyhow::{bail, Context};
+
+use crate::config::AppConfig;
+
+#[derive(Debug, Clone, PartialEq, Eq, thiserror::Error)]
+pub enum PasswordPolicyError {
+    #[error("Password must be at least {0} characters long")]
+    TooShort(usize),
+    #[error("Password must be at most {0} characters long")]
+    TooLong(usize),
+    #[error("This password is too common; please choose another one")]
+    Banned,
+}
+
+impl PasswordPolicyError {
+    /// Stable identifier clients can switch on
+    pub fn code(&self) -> &'static str {
+        match self {
+            PasswordPolicyError::TooShort(_) => "password_too_short",
+            PasswordPolicyError::TooLong(_) => "password_too_long",
+            PasswordPolicyError::Banned => "password_banned",
+        }
+    }
+}
+
+/// What a new password must satisfy, wherever one is set
+/// (registration, invitations, reset and change)
+#[derive(Debug, Clone)]
+pub struct PasswordPolicy {
+    min_length: usize,
+    max_length: usize,
+    /// Lower-cased; matching ignores case
+    banned: HashSet<String>,
+}
+
+impl PasswordPolicy {
+    pub fn new(min_length: usize, max_length: usize, banned: HashSet<String>) -> Self {
+        Self { min_length, max_length, banned }
+    }
+
+    /// Build the policy from `PASSWORD_*` settings, reading the banned list
+    /// file if one is configured
+    pub fn from_config(config: &AppConfig) -> anyhow::Result<Self> {
+        if config.password_min_length == 0 || config.password_min_length > config.password_max_length {
+            bail!("PASSWORD_MIN_LENGTH must be between 1 and PASSWORD_MAX_LENGTH");
+        }
+
+        let banned = match &config.password_banned_list {
+            Some(path) => {
+                let contents = std::fs::read_to_string(path)
+                    .with_context(|| format!("reading PASSWORD_BANNED_LIST {path}"))?;
+                parse_banned_list(&contents)
+            }
+            None => HashSet::new(),
+        };
+        tracing::info!(
+            "🔐 Password policy: {}-{} characters, {} banned passwords",
+            config.password_min_length,
+            config.password_max_length,
+            banned.len()
+        );
+
+        Ok(Self::new(config.password_min_length, config.password_max_length, banned))
+    }
+
+    pub fn check(&self, password: &str) -> Result<(), PasswordPolicyError> {
+        let length = password.chars().count();
+        if length < self.min_length {
+            return Err(PasswordPolicyError::TooShort(self.min_length));
+        }
+        if length > self.max_length {
+            return Err(PasswordPolicyError::TooLong(self.max_length));
+        }
+        if self.banned.contains(&password.to_lowercase()) {
+            return Err(PasswordPolicyError::Banned);
+        }
+
+        Ok(())
+    }
+}
+
+/// One password per line; blank lines and `#` comments are skipped
+fn parse_banned_list(contents: &str) -> HashSet<String> {
+    contents
+        .lines()
+        .map(str::trim)
+        .filter(|line| !line.is_empty() && !line.starts_with('#'))
+        .map(str::to_lowercase)
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn policy() -> PasswordPolicy {
+        PasswordPolicy::new(10, 20, parse_banned_list("# common\nPassword123!\n\n  qwertyuiop  \n"))
+    }
+
+    #[test]
+    fn length_is_counted_in_characters() {
+        assert_eq!(policy().check(""), Err(PasswordPolicyError::TooShort(10)));
+        assert_eq!(policy().check("short"), Err(PasswordPolicyError::TooShort(10)));
+        assert_eq!(policy().check("ääääääääää"), Ok(()));
+        assert_eq!(policy().check(&"x".repeat(21)), Err(PasswordPolicyError::TooLong(20)));
+    }
+
+    #[test]
+    fn banned_passwords_match_case_insensitively() {
+        assert_eq!(policy().check("password123!"), Err(PasswordPolicyError::Banned));
+        assert_eq!(policy().check("QWERTYUIOP"), Err(PasswordPolicyError::Banned));
+        assert_eq!(policy().check("correct horse battery"), Err(PasswordPolicyError::TooLong(20)));
+        assert_eq!(policy().check("correct horse"), Ok(()));
+    }
+}
@@ -0,0 +1,137 @@
+use base32::Alphabet;
+use hmac::{Hmac, Mac};
+use rand::RngCore;
+use sha1::Sha1;
+
+/// Seconds per TOTP time step (RFC 6238 default)
+pub const STEP_SECONDS: i64 = 30;
+/// Digits in a generated code
+pub const DIGITS: u32 = 6;
+/// Accept codes from one step before/after to absorb clock drift
+const ALLOWED_DRIFT_STEPS: i64 = 1;
+
+const BASE32: Alphabet = Alphabet::RFC4648 { padding: false };
+
+/// New random 160-bit shared secret, base32 encoded (what authenticator apps expect)
+pub fn generate_secret() -> String {
+    let mut bytes = [0u8; 20];
+    rand::thread_rng().fill_bytes(&mut bytes);
+    base32::encode(BASE32, &bytes)
+}
+
+/// `otpauth://` URI for QR codes / manual entry in authenticator apps
+pub fn otpauth_uri(secret: &str, account: &str, issuer: &str) -> String {
+    format!(
+        "otpauth://totp/{}:{}?secret={}&issuer={}&algorithm=SHA1&digits={}&period={}",
+        urlencoding::encode(issuer),
+        urlencoding::encode(account),
+        secret,
+        urlencoding::encode(issuer),
+        DIGITS,
+        STEP_SECONDS
+    )
+}
+
+/// RFC 4226 HOTP value for `counter`
+fn hotp(key: &[u8], counter: u64, digits: u32) -> u32 {
+    let mut mac = Hmac::<Sha1>::new_from_slice(key).expect("HMAC accepts keys of any length");
+    mac.update(&counter.to_be_bytes());
+    let hash = mac.finalize().into_bytes();
+
+    let offset = (hash[hash.len() - 1] & 0x0f) as usize;
+    let binary = u32::from_be_bytes([
+        hash[offset] & 0x7f,
+        hash[offset + 1],
+        hash[offset + 2],
+        hash[offset + 3],
+    ]);
+
+    binary % 10u32.pow(digits)
+}
+
+/// Time step a unix timestamp falls into
+pub fn time_step(unix_time: i64) -> i64 {
+    unix_time.div_euclid(STEP_SECONDS)
+}
+
+/// Check `code` against the secret at `unix_time`.
+///
+/// Returns the matched time step so callers can refuse to accept the same
+/// step twice (replay protection); steps `<= last_used_step` never match.
+pub fn verify(secret: &str, code: &str, unix_time: i64, last_used_step: Option<i64>) -> Option<i64> {
+    let code = code.trim();
+    if code.len() != DIGITS as usize || !code.bytes().all(|b| b.is_ascii_digit()) {
+        return None;
+    }
+    let code: u32 = code.parse().ok()?;
+    let key = base32::decode(BASE32, secret)?;
+
+    let current = time_step(unix_time);
+    (current - ALLOWED_DRIFT_STEPS..=current + ALLOWED_DRIFT_STEPS)
+        .filter(|step| *step >= 0 && last_used_step.is_none_or(|last| *step > last))
+        .find(|step| hotp(&key, *step as u64, DIGITS) == code)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const RFC_SECRET: &[u8] = b"12345678901234567890";
+
+    fn code_at(key: &[u8], unix_time: i64, digits: u32) -> u32 {
+        hotp(key, time_step(unix_time) as u64, digits)
+    }
+
+    #[test]
+    fn matches_rfc6238_sha1_vectors() {
+        let vectors = [
+            (59, 94287082),
+            (1111111109, 7081804),
+            (1111111111, 14050471),
+            (1234567890, 89005924),
+            (2000000000, 69279037),
+            (20000000000, 65353130),
+        ];
+
+        for (time, expected) in vectors {
+            assert_eq!(code_at(RFC_SECRET, time, 8), expected, "T = {time}");
+        }
+    }
+
+    #[test]
+    fn verify_accepts_current_and_adjacent_steps() {
+        let secret = base32::encode(BASE32, RFC_SECRET);
+        let now = 1234567890;
+        let code = format!("{:06}", code_at(RFC_SECRET, now, DIGITS));
+
+        assert_eq!(verify(&secret, &code, now, None), Some(time_step(now)));
+        assert_eq!(verify(&secret, &code, now + STEP_SECONDS, None), Some(time_step(now)));
+        assert_eq!(verify(&secret, &code, now + 3 * STEP_SECONDS, None), None);
+    }
+
+    #[test]
+    fn verify_rejects_replayed_step() {
+        let secret = base32::encode(BASE32, RFC_SECRET);
+        let now = 1234567890;
+        let code = format!("{:06}", code_at(RFC_SECRET, now, DIGITS));
+
+        assert_eq!(verify(&secret, &code, now, Some(time_step(now))), None);
+    }
+
+    #[test]
+    fn verify_rejects_malformed_codes() {
+        let secret = generate_secret();
+        assert_eq!(verify(&secret, "12345", 0, None), None);
+        assert_eq!(verify(&secret, "abcdef", 0, None), None);
+        assert_eq!(verify("not base32!", "123456", 0, None), None);
+    }
+
+    #[test]
+    fn otpauth_uri_contains_secret_and_issuer() {
+        let uri = otpauth_uri("ABC", "jane@example.com", "Support Desk");
+        assert_eq!(
+            uri,
+            "otpauth://totp/Support%20Desk:jane%40example.com?secret=ABC&issuer=Support%20Desk&algorithm=SHA1&digits=6&period=30"
+        );
+    }
+}